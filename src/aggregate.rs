@@ -0,0 +1,112 @@
+//! Rows returned by aggregate queries
+//!
+//! `GROUP BY` queries return `AggregateResult` records whose fields are the
+//! grouped fields plus one field per aggregate, named by its alias or, when
+//! unaliased, `expr0`, `expr1`, ... in SELECT order.
+
+use serde::{Deserialize, Serialize};
+
+/// One `AggregateResult` record
+///
+/// # Example
+/// ```
+/// use salesforce_client::AggregateRow;
+///
+/// let row: AggregateRow = serde_json::from_str(
+///     r#"{"attributes":{"type":"AggregateResult"},"Industry":"Energy","cnt":42,"expr0":1.5e6}"#,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(row.get_str("Industry"), Some("Energy"));
+/// assert_eq!(row.get_i64("cnt"), Some(42));
+/// assert_eq!(row.expr_f64(0), Some(1.5e6));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct AggregateRow {
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl AggregateRow {
+    /// Raw value of a field, `None` if the row has no such field
+    pub fn get(&self, field: &str) -> Option<&serde_json::Value> {
+        self.fields.get(field)
+    }
+
+    /// String value of a field; `None` if missing, null or not a string
+    pub fn get_str(&self, field: &str) -> Option<&str> {
+        self.get(field).and_then(serde_json::Value::as_str)
+    }
+
+    /// Integer value of a field; `None` if missing, null or fractional
+    ///
+    /// Whole floats such as `SUM()` results of `1500.0` are accepted.
+    pub fn get_i64(&self, field: &str) -> Option<i64> {
+        let value = self.get(field)?;
+        value.as_i64().or_else(|| {
+            value
+                .as_f64()
+                .filter(|number| number.fract() == 0.0)
+                .map(|number| number as i64)
+        })
+    }
+
+    /// Numeric value of a field; `None` if missing, null or not a number
+    pub fn get_f64(&self, field: &str) -> Option<f64> {
+        self.get(field).and_then(serde_json::Value::as_f64)
+    }
+
+    /// Whether a field is present with a null value, e.g. a null group key
+    pub fn is_null(&self, field: &str) -> bool {
+        self.get(field).is_some_and(serde_json::Value::is_null)
+    }
+
+    /// Integer value of the `index`th unaliased aggregate (`expr{index}`)
+    pub fn expr_i64(&self, index: usize) -> Option<i64> {
+        self.get_i64(&format!("expr{}", index))
+    }
+
+    /// Numeric value of the `index`th unaliased aggregate (`expr{index}`)
+    pub fn expr_f64(&self, index: usize) -> Option<f64> {
+        self.get_f64(&format!("expr{}", index))
+    }
+
+    /// Field names in the row, excluding `attributes`
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.fields
+            .keys()
+            .map(String::as_str)
+            .filter(|name| *name != "attributes")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_row_getters() {
+        let rows: Vec<AggregateRow> = serde_json::from_str(
+            r#"[
+                {"attributes":{"type":"AggregateResult"},"Industry":"Energy","cnt":12,"expr0":1500.0,"expr1":250.75},
+                {"attributes":{"type":"AggregateResult"},"Industry":null,"cnt":3,"expr0":null,"expr1":null}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(rows[0].get_str("Industry"), Some("Energy"));
+        assert_eq!(rows[0].get_i64("cnt"), Some(12));
+        assert_eq!(rows[0].expr_i64(0), Some(1500));
+        assert_eq!(rows[0].expr_i64(1), None);
+        assert_eq!(rows[0].expr_f64(1), Some(250.75));
+        let mut names: Vec<_> = rows[0].field_names().collect();
+        names.sort_unstable();
+        assert_eq!(names, vec!["Industry", "cnt", "expr0", "expr1"]);
+
+        // Null group key and aggregates over no values
+        assert!(rows[1].is_null("Industry"));
+        assert_eq!(rows[1].get_str("Industry"), None);
+        assert_eq!(rows[1].expr_f64(0), None);
+        assert!(!rows[1].is_null("Missing"));
+    }
+}
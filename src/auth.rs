@@ -2,15 +2,105 @@
 //!
 //! Handles OAuth flows, token refresh, and credential management.
 
-use crate::error::SfError;
+pub mod pkce;
+pub mod profiles;
+#[cfg(feature = "soap-login")]
+pub mod soap;
+pub mod store;
+
+use crate::error::{ErrorCode, SfError, SfResult};
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64_STANDARD;
+use base64::Engine;
 use chrono::{DateTime, Duration, Utc};
+use hmac::{Hmac, Mac};
+use pkce::PkceChallenge;
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration as StdDuration;
+use store::TokenStore;
 use tokio::sync::RwLock;
 use tracing::{debug, info, warn};
 
+pub use profiles::{AuthFlow, Profile, Profiles};
+#[cfg(feature = "soap-login")]
+pub use soap::soap_login;
+
+/// Extra delay added to the polling interval on a `slow_down` response (RFC 8628)
+const DEVICE_SLOW_DOWN_STEP: StdDuration = StdDuration::from_secs(5);
+
+/// Device code lifetime assumed when the server does not send `expires_in`
+const DEFAULT_DEVICE_CODE_LIFETIME: StdDuration = StdDuration::from_secs(600);
+
+/// Grant type for the SAML 2.0 bearer assertion flow
+const SAML_BEARER_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:saml2-bearer";
+
+/// Identity of the authenticated user from the `userinfo` endpoint
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UserIdentity {
+    /// 18-character ID of the user
+    pub user_id: String,
+
+    /// 18-character ID of the org
+    pub organization_id: String,
+
+    /// Login username
+    #[serde(rename = "preferred_username")]
+    pub username: String,
+
+    /// Full display name
+    #[serde(rename = "name")]
+    pub display_name: String,
+
+    /// Email address
+    #[serde(default)]
+    pub email: Option<String>,
+
+    /// Locale, e.g. `en_US`
+    #[serde(default)]
+    pub locale: Option<String>,
+
+    /// Time zone, e.g. `America/Los_Angeles`
+    #[serde(rename = "zoneinfo", default)]
+    pub timezone: Option<String>,
+
+    /// API endpoint URLs keyed by name (`rest`, `sobjects`, `query`, ...)
+    ///
+    /// Values may contain a `{version}` placeholder.
+    #[serde(default)]
+    pub urls: HashMap<String, String>,
+}
+
+/// Salesforce environment to authenticate against
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Environment {
+    /// Production and developer orgs (login.salesforce.com)
+    #[default]
+    Production,
+
+    /// Sandbox orgs (test.salesforce.com)
+    Sandbox,
+
+    /// Custom login endpoint, e.g. a My Domain URL
+    Custom(String),
+}
+
+impl Environment {
+    /// Get the login endpoint for this environment
+    pub fn auth_url(&self) -> &str {
+        match self {
+            Environment::Production => "https://login.salesforce.com",
+            Environment::Sandbox => "https://test.salesforce.com",
+            Environment::Custom(url) => url,
+        }
+    }
+}
+
 /// OAuth 2.0 credentials for Salesforce
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct OAuthCredentials {
     /// OAuth client ID (Consumer Key)
     pub client_id: String,
@@ -24,8 +114,38 @@ pub struct OAuthCredentials {
     /// Username for password flow
     pub username: Option<String>,
 
-    /// Password + security token for password flow
+    /// Password for password flow
     pub password: Option<String>,
+
+    /// Security token appended to the password in the password flow
+    ///
+    /// Required when logging in from outside the org's trusted IP ranges.
+    pub security_token: Option<String>,
+}
+
+impl OAuthCredentials {
+    /// Credentials for the client credentials flow (server-to-server)
+    ///
+    /// The connected app must have a run-as user configured for this flow.
+    pub fn client_credentials(
+        client_id: impl Into<String>,
+        client_secret: impl Into<String>,
+    ) -> Self {
+        Self {
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Check if only the consumer key and secret are available
+    fn is_client_credentials_only(&self) -> bool {
+        !self.client_id.is_empty()
+            && !self.client_secret.is_empty()
+            && self.refresh_token.is_none()
+            && self.username.is_none()
+            && self.password.is_none()
+    }
 }
 
 /// Response from OAuth token endpoint
@@ -40,16 +160,188 @@ struct TokenResponse {
 
     token_type: String,
 
+    /// Milliseconds since the Unix epoch
     #[serde(default)]
     issued_at: Option<String>,
+
+    /// Identity URL of the user
+    #[serde(default)]
+    id: Option<String>,
+
+    /// Base64 HMAC-SHA256 of `id + issued_at` keyed with the consumer secret
+    #[serde(default)]
+    signature: Option<String>,
+
+    /// Space-separated scopes granted to the token
+    #[serde(default)]
+    scope: Option<String>,
+}
+
+impl TokenResponse {
+    fn into_access_token(self) -> AccessToken {
+        let scopes = self.scopes();
+        let mut token = AccessToken::new(self.access_token, self.instance_url, self.expires_in);
+        token.refresh_token = self.refresh_token;
+        token.issued_at = self
+            .issued_at
+            .and_then(|ms| ms.parse().ok())
+            .and_then(DateTime::from_timestamp_millis);
+        token.scopes = scopes;
+        token
+    }
+
+    fn scopes(&self) -> Option<HashSet<String>> {
+        self.scope
+            .as_ref()
+            .map(|scope| scope.split_whitespace().map(str::to_string).collect())
+    }
+
+    /// Check that every scope in `required` was granted
+    ///
+    /// Responses without a `scope` field pass, since the grant is unknown.
+    fn check_scopes(&self, required: &[String]) -> SfResult<()> {
+        let Some(granted) = self.scopes() else {
+            return Ok(());
+        };
+
+        let missing: Vec<&str> = required
+            .iter()
+            .filter(|scope| !granted.contains(scope.as_str()))
+            .map(String::as_str)
+            .collect();
+
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(SfError::Auth(format!(
+                "Token is missing required scopes: {}",
+                missing.join(", ")
+            )))
+        }
+    }
+
+    /// Check `signature` against the consumer secret
+    fn verify_signature(&self, client_secret: &str) -> SfResult<()> {
+        let (Some(id), Some(issued_at), Some(signature)) =
+            (&self.id, &self.issued_at, &self.signature)
+        else {
+            return Err(SfError::Auth(
+                "signature mismatch: token response is not signed".to_string(),
+            ));
+        };
+
+        let signature = BASE64_STANDARD
+            .decode(signature)
+            .map_err(|_| SfError::Auth("signature mismatch".to_string()))?;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(client_secret.as_bytes())
+            .map_err(|_| SfError::Auth("signature mismatch".to_string()))?;
+        mac.update(id.as_bytes());
+        mac.update(issued_at.as_bytes());
+
+        // verify_slice compares in constant time
+        mac.verify_slice(&signature)
+            .map_err(|_| SfError::Auth("signature mismatch".to_string()))
+    }
+}
+
+/// Error body returned by the OAuth token endpoint
+#[derive(Debug, Deserialize)]
+struct OAuthErrorResponse {
+    error: String,
+
+    #[serde(default)]
+    error_description: Option<String>,
+}
+
+/// Device authorization returned when starting the device flow
+///
+/// Display `user_code` and `verification_uri` to the user, then call
+/// [`TokenManager::poll_device_flow`] to wait for approval.
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeviceAuthorization {
+    /// Code used by the client to poll for the token
+    pub device_code: String,
+
+    /// Code the user enters on the verification page
+    pub user_code: String,
+
+    /// URL the user visits to approve access
+    pub verification_uri: String,
+
+    /// Minimum number of seconds between polling requests
+    #[serde(default = "default_device_interval")]
+    pub interval: u64,
+
+    /// Lifetime of the device code in seconds
+    #[serde(default)]
+    pub expires_in: Option<u64>,
+}
+
+fn default_device_interval() -> u64 {
+    5
+}
+
+/// Result of an OAuth token introspection request
+///
+/// An inactive (revoked, expired or unknown) token is reported with
+/// `active == false` and all other fields empty.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct IntrospectionResult {
+    /// Whether the token is currently valid
+    pub active: bool,
+
+    /// Space-separated scopes granted to the token
+    #[serde(default)]
+    pub scope: Option<String>,
+
+    /// Consumer key of the connected app the token was issued to
+    #[serde(default)]
+    pub client_id: Option<String>,
+
+    /// Username of the token owner
+    #[serde(default)]
+    pub username: Option<String>,
+
+    /// Identity URL of the token owner
+    #[serde(default)]
+    pub sub: Option<String>,
+
+    /// Token type, e.g. `access_token`
+    #[serde(default)]
+    pub token_type: Option<String>,
+
+    /// Expiry as seconds since the Unix epoch
+    #[serde(default)]
+    pub exp: Option<i64>,
+
+    /// Issue time as seconds since the Unix epoch
+    #[serde(default)]
+    pub iat: Option<i64>,
+
+    /// Not-before time as seconds since the Unix epoch
+    #[serde(default)]
+    pub nbf: Option<i64>,
 }
 
 /// Managed access token with automatic refresh
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AccessToken {
     token: String,
     expires_at: Option<DateTime<Utc>>,
     instance_url: String,
+
+    /// Refresh token issued alongside this access token, if any
+    #[serde(default)]
+    refresh_token: Option<String>,
+
+    /// When Salesforce issued the token, if reported
+    #[serde(default)]
+    issued_at: Option<DateTime<Utc>>,
+
+    /// Scopes granted to the token, if reported
+    #[serde(default)]
+    scopes: Option<HashSet<String>>,
 }
 
 impl AccessToken {
@@ -61,6 +353,9 @@ impl AccessToken {
             token,
             expires_at,
             instance_url,
+            refresh_token: None,
+            issued_at: None,
+            scopes: None,
         }
     }
 
@@ -83,27 +378,422 @@ impl AccessToken {
     pub fn instance_url(&self) -> &str {
         &self.instance_url
     }
+
+    /// Get the refresh token issued with this access token
+    pub fn refresh_token(&self) -> Option<&str> {
+        self.refresh_token.as_deref()
+    }
+
+    /// Get the time Salesforce issued the token (`issued_at` in the response)
+    pub fn issued_at(&self) -> Option<DateTime<Utc>> {
+        self.issued_at
+    }
+
+    /// Get the granted scopes, or `None` if the token response omitted them
+    pub fn scopes(&self) -> Option<&HashSet<String>> {
+        self.scopes.as_ref()
+    }
+
+    /// Check whether `scope` is known to be granted
+    ///
+    /// Returns `false` when the granted scopes are unknown.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes
+            .as_ref()
+            .is_some_and(|scopes| scopes.contains(scope))
+    }
+
+    /// Build a one-click login link into the org via `frontdoor.jsp`
+    ///
+    /// `ret_url` is the page to open after login, relative to the instance
+    /// (e.g. `/lightning/page/home`). The link embeds the session ID, so
+    /// treat it like the token itself.
+    ///
+    /// # Example
+    /// ```
+    /// use salesforce_client::AccessToken;
+    ///
+    /// let token = AccessToken::new(
+    ///     "00Dxx!session".to_string(),
+    ///     "https://acme.my.salesforce.com".to_string(),
+    ///     Some(3600),
+    /// );
+    ///
+    /// let url = token.frontdoor_url(Some("/lightning/page/home")).unwrap();
+    /// assert_eq!(
+    ///     url,
+    ///     "https://acme.my.salesforce.com/secur/frontdoor.jsp?sid=00Dxx%21session&retURL=%2Flightning%2Fpage%2Fhome"
+    /// );
+    /// ```
+    pub fn frontdoor_url(&self, ret_url: Option<&str>) -> SfResult<String> {
+        if self.is_expired() {
+            return Err(SfError::Auth(
+                "Cannot build a frontdoor URL from an expired session".to_string(),
+            ));
+        }
+
+        let mut url = url::Url::parse(&self.instance_url)
+            .and_then(|base| base.join("/secur/frontdoor.jsp"))
+            .map_err(|e| {
+                SfError::Config(format!("Invalid instance URL {}: {}", self.instance_url, e))
+            })?;
+
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("sid", &self.token);
+            if let Some(ret_url) = ret_url {
+                query.append_pair("retURL", ret_url);
+            }
+        }
+
+        Ok(url.into())
+    }
+}
+
+/// Source of bearer tokens for API requests
+///
+/// Lets the client and CRUD layer obtain a current token per request instead
+/// of capturing a string at construction time.
+#[async_trait]
+pub(crate) trait TokenProvider: Send + Sync {
+    /// Get the access token to send in the `Authorization` header
+    async fn bearer(&self) -> SfResult<String>;
+
+    /// Whether `invalidate` followed by `bearer` can yield a different token
+    fn can_refresh(&self) -> bool {
+        false
+    }
+
+    /// Discard the current token so the next `bearer` call fetches a new one
+    async fn invalidate(&self) {}
 }
 
+/// Token provider for a fixed access token
+pub(crate) struct StaticToken(pub(crate) String);
+
+#[async_trait]
+impl TokenProvider for StaticToken {
+    async fn bearer(&self) -> SfResult<String> {
+        Ok(self.0.clone())
+    }
+}
+
+#[async_trait]
+impl TokenProvider for TokenManager {
+    async fn bearer(&self) -> SfResult<String> {
+        Ok(self.get_token().await?.token().to_string())
+    }
+
+    fn can_refresh(&self) -> bool {
+        true
+    }
+
+    async fn invalidate(&self) {
+        TokenManager::invalidate(self).await;
+    }
+}
+
+/// Loose check for `local@domain.tld` (Salesforce usernames are email-shaped)
+fn is_email_like(username: &str) -> bool {
+    match username.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && domain.contains('.')
+                && !domain.contains('@')
+                && !username.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+/// Check that an auth URL is an https origin without a path
+fn validate_auth_url(auth_url: &str) -> Result<String, SfError> {
+    let parsed = url::Url::parse(auth_url)
+        .map_err(|e| SfError::Config(format!("Invalid auth URL '{}': {}", auth_url, e)))?;
+
+    if parsed.scheme() != "https" {
+        return Err(SfError::Config(format!(
+            "Auth URL '{}' must use https",
+            auth_url
+        )));
+    }
+
+    if parsed.path() != "/" || parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err(SfError::Config(format!(
+            "Auth URL '{}' must not contain a path, e.g. https://mydomain.my.salesforce.com",
+            auth_url
+        )));
+    }
+
+    Ok(auth_url.trim_end_matches('/').to_string())
+}
+
+/// Send an authorized request, refreshing the session once on 401
+///
+/// Salesforce answers requests with a revoked or expired session with
+/// `401 INVALID_SESSION_ID`. When the provider can refresh, the token is
+/// invalidated and the request is rebuilt and sent exactly once more; a
+/// second 401 surfaces as `SfError::Auth`. Failures of the refresh itself
+/// are returned as-is, so this never loops. Other 401s, e.g. from IP
+/// restrictions, fail with `SfError::Api` without a refresh.
+pub(crate) async fn send_authorized<F>(
+    provider: &dyn TokenProvider,
+    build: F,
+) -> SfResult<reqwest::Response>
+where
+    F: Fn() -> reqwest::RequestBuilder,
+{
+    let access_token = provider.bearer().await?;
+    let response = build()
+        .header("Authorization", format!("Bearer {}", access_token))
+        .send()
+        .await?;
+
+    if response.status() != reqwest::StatusCode::UNAUTHORIZED || !provider.can_refresh() {
+        return Ok(response);
+    }
+
+    // A new token only helps if the session itself was rejected
+    let rejected = SfError::Api {
+        status: response.status().as_u16(),
+        body: response.text().await.unwrap_or_default(),
+    };
+    if !rejected
+        .error_codes()
+        .contains(&ErrorCode::InvalidSessionId)
+    {
+        return Err(rejected);
+    }
+
+    warn!("Session rejected by Salesforce, refreshing access token and retrying once");
+    provider.invalidate().await;
+
+    let access_token = provider.bearer().await?;
+    let response = build()
+        .header("Authorization", format!("Bearer {}", access_token))
+        .send()
+        .await?;
+
+    if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+        let body = response.text().await.unwrap_or_default();
+        return Err(SfError::Auth(format!(
+            "Session still invalid after token refresh: {}",
+            body
+        )));
+    }
+
+    Ok(response)
+}
+
+/// Callback invoked with a newly issued token
+type RefreshedHook = Box<dyn Fn(&AccessToken) + Send + Sync>;
+
+/// Callback invoked when a token refresh fails
+type RefreshFailedHook = Box<dyn Fn(&SfError) + Send + Sync>;
+
 /// Token manager that handles automatic refresh
 pub struct TokenManager {
-    credentials: OAuthCredentials,
+    credentials: RwLock<OAuthCredentials>,
     current_token: Arc<RwLock<Option<AccessToken>>>,
     http_client: reqwest::Client,
     auth_url: String,
+    store: Option<Arc<dyn TokenStore>>,
+    store_loaded: AtomicBool,
+    on_token_refreshed: Option<RefreshedHook>,
+    on_refresh_failed: Option<RefreshFailedHook>,
+    verify_signatures: bool,
+    saml_assertion: RwLock<Option<String>>,
+    required_scopes: Vec<String>,
 }
 
 impl TokenManager {
     /// Create a new token manager
     pub fn new(credentials: OAuthCredentials) -> Self {
         Self {
-            credentials,
+            credentials: RwLock::new(credentials),
             current_token: Arc::new(RwLock::new(None)),
             http_client: reqwest::Client::new(),
             auth_url: "https://login.salesforce.com".to_string(),
+            store: None,
+            store_loaded: AtomicBool::new(false),
+            on_token_refreshed: None,
+            on_refresh_failed: None,
+            verify_signatures: false,
+            saml_assertion: RwLock::new(None),
+            required_scopes: Vec::new(),
         }
     }
 
+    /// Persist tokens with the given store
+    ///
+    /// A persisted token is loaded on first use and only refreshed once it
+    /// has expired. Every newly issued token is saved back to the store.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use salesforce_client::auth::store::FileTokenStore;
+    /// use salesforce_client::{OAuthCredentials, TokenManager};
+    ///
+    /// let manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+    ///     .with_store(FileTokenStore::new("/var/lib/my-app/salesforce-token.json"));
+    /// ```
+    pub fn with_store(mut self, store: impl TokenStore + 'static) -> Self {
+        self.store = Some(Arc::new(store));
+        self
+    }
+
+    /// Use a preconfigured HTTP client for token requests
+    ///
+    /// Needed behind egress proxies or with custom CA bundles, since the
+    /// default client is built without them.
+    pub fn with_http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = http_client;
+        self
+    }
+
+    /// Verify the `signature` of every token response
+    ///
+    /// Salesforce signs `id + issued_at` with the consumer secret; responses
+    /// with a missing or wrong signature are rejected with `SfError::Auth`.
+    /// Requires `client_secret` to be set.
+    pub fn verify_signatures(mut self, enabled: bool) -> Self {
+        self.verify_signatures = enabled;
+        self
+    }
+
+    /// Reject tokens that were not granted all of `scopes`
+    ///
+    /// Checked whenever a token is issued, so a misconfigured connected app
+    /// fails with a clear `SfError::Auth` instead of a 403 on a later API
+    /// call. Token responses without a `scope` field are accepted.
+    ///
+    /// # Example
+    /// ```
+    /// use salesforce_client::{OAuthCredentials, TokenManager};
+    ///
+    /// let manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+    ///     .require_scopes(&["api", "refresh_token"]);
+    /// ```
+    pub fn require_scopes(mut self, scopes: &[&str]) -> Self {
+        self.required_scopes = scopes.iter().map(|scope| scope.to_string()).collect();
+        self
+    }
+
+    /// Enable the SAML 2.0 bearer assertion flow
+    ///
+    /// `assertion` is a base64url-encoded, signed SAML assertion from your
+    /// identity provider. It is exchanged for an access token whenever no
+    /// refresh token is available, before the password and client
+    /// credentials flows. Assertions are short-lived; supply a new one with
+    /// [`TokenManager::set_saml_assertion`] before it expires.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{OAuthCredentials, TokenManager, SfError};
+    /// # async fn example(assertion: String) -> Result<(), SfError> {
+    /// let manager = TokenManager::new(OAuthCredentials {
+    ///     client_id: "your_client_id".to_string(),
+    ///     ..Default::default()
+    /// })
+    /// .with_saml_assertion(assertion);
+    ///
+    /// let token = manager.get_token().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_saml_assertion(mut self, assertion: impl Into<String>) -> Self {
+        self.saml_assertion = RwLock::new(Some(assertion.into()));
+        self
+    }
+
+    /// Replace the SAML assertion used by the bearer flow
+    pub async fn set_saml_assertion(&self, assertion: impl Into<String>) {
+        *self.saml_assertion.write().await = Some(assertion.into());
+    }
+
+    /// Call `hook` with every token fetched by [`TokenManager::get_token`]
+    ///
+    /// Runs after the manager's internal locks are released, so the hook
+    /// may safely call back into the manager. The token carries the refresh
+    /// token from the response, including rotated refresh tokens.
+    ///
+    /// # Example
+    /// ```
+    /// use salesforce_client::{OAuthCredentials, TokenManager};
+    ///
+    /// let manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+    ///     .on_token_refreshed(|token| {
+    ///         tracing::info!(instance = token.instance_url(), "Salesforce token refreshed");
+    ///     })
+    ///     .on_refresh_failed(|error| {
+    ///         tracing::warn!("Salesforce token refresh failed: {}", error);
+    ///     });
+    /// ```
+    pub fn on_token_refreshed(
+        mut self,
+        hook: impl Fn(&AccessToken) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_token_refreshed = Some(Box::new(hook));
+        self
+    }
+
+    /// Call `hook` whenever [`TokenManager::get_token`] fails to fetch a token
+    ///
+    /// Like `on_token_refreshed`, this runs without holding internal locks.
+    pub fn on_refresh_failed(mut self, hook: impl Fn(&SfError) + Send + Sync + 'static) -> Self {
+        self.on_refresh_failed = Some(Box::new(hook));
+        self
+    }
+
+    /// Create a token manager for the given environment
+    ///
+    /// Custom URLs are validated like [`TokenManager::with_auth_url`].
+    pub fn for_environment(
+        credentials: OAuthCredentials,
+        environment: &Environment,
+    ) -> Result<Self, SfError> {
+        match environment {
+            Environment::Production => Ok(Self::new(credentials)),
+            Environment::Sandbox => Ok(Self::sandbox(credentials)),
+            Environment::Custom(url) => Self::new(credentials).with_auth_url(url),
+        }
+    }
+
+    /// Authenticate against a custom login endpoint (e.g. My Domain)
+    ///
+    /// Required for orgs with "Prevent login from login.salesforce.com"
+    /// enabled. The URL must use https and must not contain a path; it
+    /// applies to every grant flow.
+    ///
+    /// # Example
+    /// ```
+    /// use salesforce_client::{OAuthCredentials, TokenManager};
+    ///
+    /// let manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+    ///     .with_auth_url("https://acme.my.salesforce.com")
+    ///     .unwrap();
+    ///
+    /// assert_eq!(manager.auth_url(), "https://acme.my.salesforce.com");
+    /// ```
+    pub fn with_auth_url(mut self, auth_url: &str) -> Result<Self, SfError> {
+        self.auth_url = validate_auth_url(auth_url)?;
+        Ok(self)
+    }
+
+    /// Override the auth URL without validation
+    #[cfg(test)]
+    pub(crate) fn with_auth_url_unchecked(mut self, auth_url: impl Into<String>) -> Self {
+        self.auth_url = auth_url.into();
+        self
+    }
+
+    /// Get the login endpoint used for all grant flows
+    pub fn auth_url(&self) -> &str {
+        &self.auth_url
+    }
+
     /// Create a token manager for sandbox environment
     pub fn sandbox(credentials: OAuthCredentials) -> Self {
         let mut manager = Self::new(credentials);
@@ -140,19 +830,94 @@ impl TokenManager {
             }
         }
 
+        // On first use, try the persisted token before hitting the network
+        if token_guard.is_none() {
+            if let Some(token) = self.load_persisted_token().await {
+                *token_guard = Some(token.clone());
+                if !token.is_expired() {
+                    info!("Using persisted access token");
+                    return Ok(token);
+                }
+            }
+        }
+
         // Actually refresh the token
-        let new_token = self.fetch_new_token().await?;
+        let mut new_token = match self.fetch_new_token().await {
+            Ok(token) => token,
+            Err(e) => {
+                drop(token_guard);
+                if let Some(hook) = &self.on_refresh_failed {
+                    hook(&e);
+                }
+                return Err(e);
+            }
+        };
+        // Connected apps may rotate refresh tokens; the old one stops working.
+        // Without rotation the current refresh token stays valid, so keep it
+        // on the token for persistence.
+        {
+            let mut credentials = self.credentials.write().await;
+            match new_token.refresh_token() {
+                Some(refresh_token)
+                    if credentials.refresh_token.as_deref() != Some(refresh_token) =>
+                {
+                    debug!("Storing rotated refresh token");
+                    credentials.refresh_token = Some(refresh_token.to_string());
+                }
+                Some(_) => {}
+                None => new_token.refresh_token = credentials.refresh_token.clone(),
+            }
+        }
         *token_guard = Some(new_token.clone());
+        drop(token_guard);
+
+        self.persist_token(&new_token).await;
+        if let Some(hook) = &self.on_token_refreshed {
+            hook(&new_token);
+        }
 
         info!("Successfully refreshed access token");
         Ok(new_token)
     }
 
+    /// Load the token from the store once; unreadable stores are ignored
+    async fn load_persisted_token(&self) -> Option<AccessToken> {
+        let store = self.store.as_ref()?;
+        if self.store_loaded.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+
+        match store.load().await {
+            Ok(Some(token)) => {
+                if let Some(refresh_token) = token.refresh_token() {
+                    self.credentials.write().await.refresh_token = Some(refresh_token.to_string());
+                }
+                Some(token)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                warn!("Ignoring unreadable token store: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Save a newly issued token; failures are logged, not surfaced
+    async fn persist_token(&self, token: &AccessToken) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save(token).await {
+                warn!("Failed to persist access token: {}", e);
+            }
+        }
+    }
+
     /// Fetch a new token from Salesforce
     async fn fetch_new_token(&self) -> Result<AccessToken, SfError> {
+        let credentials = self.credentials.read().await.clone();
+
         // Try refresh token flow first
-        if let Some(refresh_token) = &self.credentials.refresh_token {
-            match self.refresh_token_flow(refresh_token).await {
+        if let Some(refresh_token) = &credentials.refresh_token {
+            match self.refresh_token_flow(&credentials, refresh_token).await {
                 Ok(token) => return Ok(token),
                 Err(e) => {
                     warn!(
@@ -163,9 +928,20 @@ impl TokenManager {
             }
         }
 
+        // SAML bearer flow, only when an assertion was explicitly provided
+        let saml_assertion = self.saml_assertion.read().await.clone();
+        if let Some(assertion) = saml_assertion {
+            return self.saml_bearer_flow(&credentials, &assertion).await;
+        }
+
         // Fall back to password flow
-        if self.credentials.username.is_some() && self.credentials.password.is_some() {
-            return self.password_flow().await;
+        if credentials.username.is_some() && credentials.password.is_some() {
+            return self.password_flow(&credentials).await;
+        }
+
+        // Client credentials flow when only the consumer key and secret are known
+        if credentials.is_client_credentials_only() {
+            return self.client_credentials_flow(&credentials).await;
         }
 
         Err(SfError::Auth(
@@ -174,69 +950,379 @@ impl TokenManager {
     }
 
     /// OAuth 2.0 Refresh Token Flow
-    async fn refresh_token_flow(&self, refresh_token: &str) -> Result<AccessToken, SfError> {
-        let url = format!("{}/services/oauth2/token", self.auth_url);
-
+    async fn refresh_token_flow(
+        &self,
+        credentials: &OAuthCredentials,
+        refresh_token: &str,
+    ) -> Result<AccessToken, SfError> {
         let params = [
             ("grant_type", "refresh_token"),
-            ("client_id", &self.credentials.client_id),
-            ("client_secret", &self.credentials.client_secret),
+            ("client_id", &credentials.client_id),
+            ("client_secret", &credentials.client_secret),
             ("refresh_token", refresh_token),
         ];
 
-        let response = self.http_client.post(&url).form(&params).send().await?;
-
-        if !response.status().is_success() {
-            let body = response.text().await?;
-            return Err(SfError::Auth(format!("Token refresh failed: {}", body)));
-        }
-
-        let token_response: TokenResponse = response.json().await?;
-
-        Ok(AccessToken::new(
-            token_response.access_token,
-            token_response.instance_url,
-            token_response.expires_in,
-        ))
+        self.request_token(&params, "Token refresh failed").await
     }
 
     /// OAuth 2.0 Password Flow (less secure, use for development only)
-    async fn password_flow(&self) -> Result<AccessToken, SfError> {
-        let username = self
-            .credentials
+    ///
+    /// The username is trimmed and must look like an email address. The
+    /// security token, if set, is trimmed and appended to the password.
+    async fn password_flow(&self, credentials: &OAuthCredentials) -> Result<AccessToken, SfError> {
+        let username = credentials
             .username
-            .as_ref()
+            .as_deref()
+            .map(str::trim)
             .ok_or_else(|| SfError::Auth("Username not provided".to_string()))?;
-        let password = self
-            .credentials
+        let password = credentials
             .password
             .as_ref()
             .ok_or_else(|| SfError::Auth("Password not provided".to_string()))?;
 
-        let url = format!("{}/services/oauth2/token", self.auth_url);
+        if !is_email_like(username) {
+            return Err(SfError::Config(format!(
+                "Username must be an email-style Salesforce username, got '{}'",
+                username
+            )));
+        }
+
+        let password = match credentials.security_token.as_deref().map(str::trim) {
+            Some(token) => format!("{}{}", password, token),
+            None => password.clone(),
+        };
 
         let params = [
             ("grant_type", "password"),
-            ("client_id", &self.credentials.client_id),
-            ("client_secret", &self.credentials.client_secret),
+            ("client_id", credentials.client_id.as_str()),
+            ("client_secret", credentials.client_secret.as_str()),
             ("username", username),
-            ("password", password),
+            ("password", password.as_str()),
+        ];
+
+        self.request_token(&params, "Authentication failed").await
+    }
+
+    /// SAML 2.0 Bearer Assertion Flow
+    async fn saml_bearer_flow(
+        &self,
+        credentials: &OAuthCredentials,
+        assertion: &str,
+    ) -> Result<AccessToken, SfError> {
+        let params = [
+            ("grant_type", SAML_BEARER_GRANT_TYPE),
+            ("client_id", credentials.client_id.as_str()),
+            ("assertion", assertion),
+        ];
+
+        self.request_token(&params, "SAML bearer flow failed").await
+    }
+
+    /// OAuth 2.0 Client Credentials Flow (server-to-server, uses the run-as user)
+    async fn client_credentials_flow(
+        &self,
+        credentials: &OAuthCredentials,
+    ) -> Result<AccessToken, SfError> {
+        let params = [
+            ("grant_type", "client_credentials"),
+            ("client_id", credentials.client_id.as_str()),
+            ("client_secret", credentials.client_secret.as_str()),
+        ];
+
+        self.request_token(&params, "Client credentials flow failed")
+            .await
+    }
+
+    /// Start the OAuth 2.0 Device Flow (for CLIs and devices without a browser)
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{OAuthCredentials, TokenManager, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// let manager = TokenManager::new(OAuthCredentials {
+    ///     client_id: "your_client_id".to_string(),
+    ///     ..Default::default()
+    /// });
+    ///
+    /// let authorization = manager.device_flow().await?;
+    /// println!(
+    ///     "Visit {} and enter code {}",
+    ///     authorization.verification_uri, authorization.user_code
+    /// );
+    ///
+    /// let token = manager.poll_device_flow(&authorization).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn device_flow(&self) -> Result<DeviceAuthorization, SfError> {
+        let url = format!("{}/services/oauth2/token", self.auth_url);
+        let client_id = self.credentials.read().await.client_id.clone();
+
+        let params = [
+            ("response_type", "device_code"),
+            ("client_id", client_id.as_str()),
+        ];
+
+        let response = self.http_client.post(&url).form(&params).send().await?;
+
+        if !response.status().is_success() {
+            let body = response.text().await?;
+            return Err(SfError::Auth(format!(
+                "Device authorization failed: {}",
+                body
+            )));
+        }
+
+        let authorization: DeviceAuthorization = response.json().await?;
+        info!("Device flow started, waiting for user approval");
+        Ok(authorization)
+    }
+
+    /// Poll the token endpoint until the user approves the device flow
+    ///
+    /// Waits `interval` seconds between attempts (increased on `slow_down`)
+    /// and gives up once the device code expires. On success the access token
+    /// and refresh token are stored in the manager so later `get_token()`
+    /// calls refresh automatically.
+    pub async fn poll_device_flow(
+        &self,
+        authorization: &DeviceAuthorization,
+    ) -> Result<AccessToken, SfError> {
+        let url = format!("{}/services/oauth2/token", self.auth_url);
+        let client_id = self.credentials.read().await.client_id.clone();
+
+        let params = [
+            ("grant_type", "device"),
+            ("client_id", client_id.as_str()),
+            ("code", authorization.device_code.as_str()),
         ];
 
+        let lifetime = authorization
+            .expires_in
+            .map(StdDuration::from_secs)
+            .unwrap_or(DEFAULT_DEVICE_CODE_LIFETIME);
+        let deadline = tokio::time::Instant::now() + lifetime;
+        let mut interval = StdDuration::from_secs(authorization.interval);
+
+        loop {
+            if tokio::time::Instant::now() + interval > deadline {
+                return Err(SfError::Auth(
+                    "Device code expired before the user approved access".to_string(),
+                ));
+            }
+            tokio::time::sleep(interval).await;
+
+            let response = self.http_client.post(&url).form(&params).send().await?;
+
+            if response.status().is_success() {
+                let token_response = self.parse_token_response(response).await?;
+                return Ok(self.store_token_response(token_response).await);
+            }
+
+            let body = response.text().await?;
+            let error: OAuthErrorResponse = serde_json::from_str(&body)
+                .map_err(|_| SfError::Auth(format!("Device flow failed: {}", body)))?;
+
+            match error.error.as_str() {
+                "authorization_pending" => {
+                    debug!("Device flow authorization pending");
+                }
+                "slow_down" => {
+                    interval += DEVICE_SLOW_DOWN_STEP;
+                    debug!(
+                        "Device flow asked to slow down, polling every {:?}",
+                        interval
+                    );
+                }
+                "expired_token" => {
+                    return Err(SfError::Auth(
+                        "Device code expired before the user approved access".to_string(),
+                    ));
+                }
+                _ => {
+                    return Err(SfError::Auth(format!(
+                        "Device flow failed: {}",
+                        error.error_description.unwrap_or(error.error)
+                    )));
+                }
+            }
+        }
+    }
+
+    /// Build the authorization URL for the authorization code + PKCE flow
+    ///
+    /// Redirect the user to this URL; Salesforce calls back `redirect_uri`
+    /// with `code` and `state` query parameters once they approve access.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{OAuthCredentials, TokenManager, SfError};
+    /// use salesforce_client::auth::pkce::{generate_state, PkceChallenge};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let manager = TokenManager::new(OAuthCredentials::default());
+    /// let pkce = PkceChallenge::new();
+    /// let state = generate_state();
+    ///
+    /// let url = manager
+    ///     .authorization_url("http://localhost:8080/callback", &pkce, &state)
+    ///     .await?;
+    ///
+    /// // ... redirect the user, verify `state`, then:
+    /// let token = manager
+    ///     .exchange_code("code_from_callback", "http://localhost:8080/callback", &pkce)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn authorization_url(
+        &self,
+        redirect_uri: &str,
+        pkce: &PkceChallenge,
+        state: &str,
+    ) -> Result<String, SfError> {
+        let client_id = self.credentials.read().await.client_id.clone();
+
+        let mut url = url::Url::parse(&format!("{}/services/oauth2/authorize", self.auth_url))
+            .map_err(|e| SfError::Config(format!("Invalid auth URL: {}", e)))?;
+
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &client_id)
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("code_challenge", pkce.challenge())
+            .append_pair("code_challenge_method", pkce::CHALLENGE_METHOD)
+            .append_pair("state", state);
+
+        Ok(url.into())
+    }
+
+    /// Exchange an authorization code for tokens (authorization code + PKCE flow)
+    ///
+    /// The returned access token and refresh token are stored in the manager
+    /// so later `get_token()` calls refresh automatically.
+    pub async fn exchange_code(
+        &self,
+        code: &str,
+        redirect_uri: &str,
+        pkce: &PkceChallenge,
+    ) -> Result<AccessToken, SfError> {
+        let url = format!("{}/services/oauth2/token", self.auth_url);
+        let credentials = self.credentials.read().await.clone();
+
+        let mut params = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("client_id", credentials.client_id.as_str()),
+            ("redirect_uri", redirect_uri),
+            ("code_verifier", pkce.verifier()),
+        ];
+        // Public clients (desktop/mobile apps) have no secret
+        if !credentials.client_secret.is_empty() {
+            params.push(("client_secret", credentials.client_secret.as_str()));
+        }
+
         let response = self.http_client.post(&url).form(&params).send().await?;
 
         if !response.status().is_success() {
             let body = response.text().await?;
-            return Err(SfError::Auth(format!("Authentication failed: {}", body)));
+            return Err(SfError::Auth(format!(
+                "Authorization code exchange failed: {}",
+                body
+            )));
         }
 
+        let token_response = self.parse_token_response(response).await?;
+        Ok(self.store_token_response(token_response).await)
+    }
+
+    /// Store a token obtained outside `get_token()` along with its refresh token
+    async fn store_token_response(&self, token_response: TokenResponse) -> AccessToken {
+        if let Some(refresh_token) = token_response.refresh_token.clone() {
+            self.credentials.write().await.refresh_token = Some(refresh_token);
+        }
+
+        let token = token_response.into_access_token();
+        *self.current_token.write().await = Some(token.clone());
+        self.persist_token(&token).await;
+
+        info!("Stored new access token");
+        token
+    }
+
+    /// Deserialize a successful token response, verifying it if enabled
+    async fn parse_token_response(&self, response: reqwest::Response) -> SfResult<TokenResponse> {
         let token_response: TokenResponse = response.json().await?;
 
-        Ok(AccessToken::new(
-            token_response.access_token,
-            token_response.instance_url,
-            token_response.expires_in,
-        ))
+        if self.verify_signatures {
+            let client_secret = self.credentials.read().await.client_secret.clone();
+            token_response.verify_signature(&client_secret)?;
+        }
+        token_response.check_scopes(&self.required_scopes)?;
+
+        Ok(token_response)
+    }
+
+    /// POST a grant to the token endpoint and parse the response
+    async fn request_token(
+        &self,
+        params: &[(&str, &str)],
+        failure: &str,
+    ) -> Result<AccessToken, SfError> {
+        let url = format!("{}/services/oauth2/token", self.auth_url);
+
+        let response = self.http_client.post(&url).form(params).send().await?;
+
+        if !response.status().is_success() {
+            let body = response.text().await?;
+            return Err(SfError::Auth(format!("{}: {}", failure, body)));
+        }
+
+        let token_response = self.parse_token_response(response).await?;
+
+        Ok(token_response.into_access_token())
+    }
+
+    /// Check whether a token issued by the org is still active
+    ///
+    /// Authenticates with the manager's client ID and secret. An inactive
+    /// token is not an error; inspect [`IntrospectionResult::active`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{OAuthCredentials, TokenManager, SfError};
+    /// # async fn example(incoming: &str) -> Result<(), SfError> {
+    /// let manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"));
+    ///
+    /// let result = manager.introspect(incoming).await?;
+    /// if !result.active {
+    ///     println!("Rejecting revoked or expired token");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn introspect(&self, token: &str) -> Result<IntrospectionResult, SfError> {
+        let url = format!("{}/services/oauth2/introspect", self.auth_url);
+        let credentials = self.credentials.read().await.clone();
+
+        let params = [
+            ("token", token),
+            ("token_type_hint", "access_token"),
+            ("client_id", credentials.client_id.as_str()),
+            ("client_secret", credentials.client_secret.as_str()),
+        ];
+
+        let response = self.http_client.post(&url).form(&params).send().await?;
+
+        if !response.status().is_success() {
+            let body = response.text().await?;
+            return Err(SfError::Auth(format!(
+                "Token introspection failed: {}",
+                body
+            )));
+        }
+
+        let body = response.text().await?;
+        Ok(serde_json::from_str(&body)?)
     }
 
     /// Invalidate the current token (force refresh on next request)
@@ -272,4 +1358,827 @@ mod tests {
 
         assert!(!token.is_expired());
     }
+
+    #[test]
+    fn test_frontdoor_url_encoding() {
+        let token = AccessToken::new(
+            "00Dxx!AQ0.ab/c+d=".to_string(),
+            "https://acme.my.salesforce.com".to_string(),
+            Some(3600),
+        );
+
+        assert_eq!(
+            token.frontdoor_url(None).unwrap(),
+            "https://acme.my.salesforce.com/secur/frontdoor.jsp?sid=00Dxx%21AQ0.ab%2Fc%2Bd%3D"
+        );
+        assert_eq!(
+            token
+                .frontdoor_url(Some("/001/o?filter=a&b=c d"))
+                .unwrap(),
+            "https://acme.my.salesforce.com/secur/frontdoor.jsp?sid=00Dxx%21AQ0.ab%2Fc%2Bd%3D&retURL=%2F001%2Fo%3Ffilter%3Da%26b%3Dc+d"
+        );
+    }
+
+    #[test]
+    fn test_frontdoor_url_rejects_expired_token() {
+        let token = AccessToken::new(
+            "00Dxx!session".to_string(),
+            "https://acme.my.salesforce.com".to_string(),
+            Some(0),
+        );
+
+        let err = token.frontdoor_url(Some("/home")).unwrap_err();
+        assert!(matches!(err, SfError::Auth(_)));
+    }
+
+    fn signed_response(signature: &str) -> String {
+        format!(
+            r#"{{"access_token":"token","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer","id":"https://login.salesforce.com/id/00Dxx0000001gPLEAY/005xx000001SwiUAAS","issued_at":"1700000000000","signature":"{}"}}"#,
+            signature
+        )
+    }
+
+    // HMAC-SHA256("my_secret", id + issued_at), base64-encoded
+    const GOOD_SIGNATURE: &str = "VuVzHe5FocO6y7UiCDyXgY7Ci4n9bTYDaRTko/wv4yI=";
+
+    #[test]
+    fn test_issued_at_parsed() {
+        let response: TokenResponse = serde_json::from_str(&signed_response("")).unwrap();
+        let token = response.into_access_token();
+
+        assert_eq!(
+            token.issued_at(),
+            DateTime::from_timestamp_millis(1_700_000_000_000)
+        );
+        assert_eq!(
+            token.issued_at().unwrap().to_rfc3339(),
+            "2023-11-14T22:13:20+00:00"
+        );
+    }
+
+    #[test]
+    fn test_verify_signature() {
+        let good: TokenResponse = serde_json::from_str(&signed_response(GOOD_SIGNATURE)).unwrap();
+        assert!(good.verify_signature("my_secret").is_ok());
+        assert!(good.verify_signature("other_secret").is_err());
+
+        let tampered: TokenResponse = serde_json::from_str(
+            &signed_response(GOOD_SIGNATURE).replace("1700000000000", "1700000000001"),
+        )
+        .unwrap();
+        let err = tampered.verify_signature("my_secret").unwrap_err();
+        assert!(matches!(err, SfError::Auth(msg) if msg == "signature mismatch"));
+
+        let garbage: TokenResponse = serde_json::from_str(&signed_response("not base64!")).unwrap();
+        assert!(garbage.verify_signature("my_secret").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_manager_verifies_signatures_when_enabled() {
+        let mut server = mockito::Server::new_async().await;
+        let tampered = server
+            .mock("POST", "/services/oauth2/token")
+            .with_body(signed_response("AAAA"))
+            .expect(2)
+            .create_async()
+            .await;
+
+        let credentials = OAuthCredentials::client_credentials("my_id", "my_secret");
+        let unverified =
+            TokenManager::new(credentials.clone()).with_auth_url_unchecked(server.url());
+        assert!(unverified.get_token().await.is_ok());
+
+        let verified = TokenManager::new(credentials.clone())
+            .verify_signatures(true)
+            .with_auth_url_unchecked(server.url());
+        assert!(matches!(
+            verified.get_token().await.unwrap_err(),
+            SfError::Auth(msg) if msg == "signature mismatch"
+        ));
+        tampered.assert_async().await;
+
+        let signed = server
+            .mock("POST", "/services/oauth2/token")
+            .with_body(signed_response(GOOD_SIGNATURE))
+            .expect(1)
+            .create_async()
+            .await;
+        let token = verified.get_token().await.unwrap();
+        assert!(token.issued_at().is_some());
+        signed.assert_async().await;
+    }
+
+    fn scoped_response(scope: Option<&str>) -> TokenResponse {
+        let mut body = serde_json::json!({
+            "access_token": "token",
+            "instance_url": "https://acme.my.salesforce.com",
+            "token_type": "Bearer",
+        });
+        if let Some(scope) = scope {
+            body["scope"] = scope.into();
+        }
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[test]
+    fn test_token_scopes() {
+        let required = vec!["api".to_string(), "refresh_token".to_string()];
+
+        let missing = scoped_response(Some("web id"));
+        let err = missing.check_scopes(&required).unwrap_err();
+        assert!(
+            matches!(err, SfError::Auth(msg) if msg == "Token is missing required scopes: api, refresh_token")
+        );
+
+        let extra = scoped_response(Some("api id refresh_token web"));
+        assert!(extra.check_scopes(&required).is_ok());
+        let token = extra.into_access_token();
+        assert!(token.has_scope("api"));
+        assert!(token.has_scope("web"));
+        assert!(!token.has_scope("full"));
+        assert_eq!(token.scopes().unwrap().len(), 4);
+
+        let absent = scoped_response(None);
+        assert!(absent.check_scopes(&required).is_ok());
+        let token = absent.into_access_token();
+        assert!(token.scopes().is_none());
+        assert!(!token.has_scope("api"));
+    }
+
+    #[tokio::test]
+    async fn test_manager_requires_scopes() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/oauth2/token")
+            .with_body(
+                r#"{"access_token":"token","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer","scope":"id web"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+            .require_scopes(&["api"])
+            .with_auth_url_unchecked(server.url());
+
+        let err = manager.get_token().await.unwrap_err();
+        assert!(matches!(err, SfError::Auth(msg) if msg.ends_with("scopes: api")));
+        assert!(manager.current_token.read().await.is_none());
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_user_identity_deserialization() {
+        let payload = r#"{
+            "sub": "https://login.salesforce.com/id/00Dxx0000001gPLEAY/005xx000001SwiUAAS",
+            "user_id": "005xx000001SwiUAAS",
+            "organization_id": "00Dxx0000001gPLEAY",
+            "preferred_username": "jane@acme.com",
+            "nickname": "jane",
+            "name": "Jane Doe",
+            "email": "jane@acme.com",
+            "email_verified": true,
+            "given_name": "Jane",
+            "family_name": "Doe",
+            "zoneinfo": "America/Los_Angeles",
+            "photos": {
+                "picture": "https://acme--c.documentforce.com/profilephoto/005/F",
+                "thumbnail": "https://acme--c.documentforce.com/profilephoto/005/T"
+            },
+            "profile": "https://acme.my.salesforce.com/005xx000001SwiUAAS",
+            "urls": {
+                "enterprise": "https://acme.my.salesforce.com/services/Soap/c/{version}/00Dxx0000001gPL",
+                "rest": "https://acme.my.salesforce.com/services/data/v{version}/",
+                "sobjects": "https://acme.my.salesforce.com/services/data/v{version}/sobjects/",
+                "query": "https://acme.my.salesforce.com/services/data/v{version}/query/",
+                "custom_domain": "https://acme.my.salesforce.com"
+            },
+            "active": true,
+            "user_type": "STANDARD",
+            "language": "en_US",
+            "locale": "en_US",
+            "utcOffset": -28800000,
+            "updated_at": "2024-01-15T18:30:00Z",
+            "is_app_installed": true
+        }"#;
+
+        let identity: UserIdentity = serde_json::from_str(payload).unwrap();
+
+        assert_eq!(identity.user_id, "005xx000001SwiUAAS");
+        assert_eq!(identity.organization_id, "00Dxx0000001gPLEAY");
+        assert_eq!(identity.username, "jane@acme.com");
+        assert_eq!(identity.display_name, "Jane Doe");
+        assert_eq!(identity.email.as_deref(), Some("jane@acme.com"));
+        assert_eq!(identity.locale.as_deref(), Some("en_US"));
+        assert_eq!(identity.timezone.as_deref(), Some("America/Los_Angeles"));
+        assert_eq!(identity.urls.len(), 5);
+        assert_eq!(
+            identity.urls["rest"],
+            "https://acme.my.salesforce.com/services/data/v{version}/"
+        );
+    }
+
+    async fn introspect_with(body: &str) -> Result<IntrospectionResult, SfError> {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/oauth2/introspect")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("token".into(), "00Dxx!incoming".into()),
+                mockito::Matcher::UrlEncoded("token_type_hint".into(), "access_token".into()),
+                mockito::Matcher::UrlEncoded("client_id".into(), "my_id".into()),
+                mockito::Matcher::UrlEncoded("client_secret".into(), "my_secret".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut manager =
+            TokenManager::new(OAuthCredentials::client_credentials("my_id", "my_secret"));
+        manager.auth_url = server.url();
+
+        let result = manager.introspect("00Dxx!incoming").await;
+        mock.assert_async().await;
+        result
+    }
+
+    #[tokio::test]
+    async fn test_introspect_active_token() {
+        let result = introspect_with(
+            r#"{"active":true,"scope":"api refresh_token","client_id":"my_id","username":"jane@acme.com","sub":"https://login.salesforce.com/id/00Dxx/005xx","token_type":"access_token","exp":1700003600,"iat":1700000000,"nbf":1700000000}"#,
+        )
+        .await
+        .unwrap();
+
+        assert!(result.active);
+        assert_eq!(result.scope.as_deref(), Some("api refresh_token"));
+        assert_eq!(result.username.as_deref(), Some("jane@acme.com"));
+        assert_eq!(result.exp, Some(1700003600));
+    }
+
+    #[tokio::test]
+    async fn test_introspect_inactive_token() {
+        let result = introspect_with(r#"{"active":false}"#).await.unwrap();
+
+        assert!(!result.active);
+        assert!(result.scope.is_none());
+        assert!(result.exp.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_introspect_malformed_response() {
+        let err = introspect_with("<html>oops</html>").await.unwrap_err();
+        assert!(matches!(err, SfError::Serialization(_)));
+
+        let err = introspect_with(r#"{"scope":"api"}"#).await.unwrap_err();
+        assert!(matches!(err, SfError::Serialization(_)));
+    }
+
+    #[tokio::test]
+    async fn test_custom_http_client_used_for_token_requests() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/oauth2/token")
+            .match_header("x-egress", "corp")
+            .with_body(
+                r#"{"access_token":"token","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            "x-egress",
+            reqwest::header::HeaderValue::from_static("corp"),
+        );
+        let http_client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+
+        let mut manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+            .with_http_client(http_client);
+        manager.auth_url = server.url();
+
+        assert_eq!(manager.get_token().await.unwrap().token(), "token");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_saml_bearer_flow() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/oauth2/token")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded(
+                    "grant_type".into(),
+                    "urn:ietf:params:oauth:grant-type:saml2-bearer".into(),
+                ),
+                mockito::Matcher::UrlEncoded("client_id".into(), "my_id".into()),
+                // Reserved base64 characters must be percent-encoded in the form body
+                mockito::Matcher::Regex("assertion=PHNhbWw%2BYWJj%2FZGVm%3D".to_string()),
+            ]))
+            .with_body(
+                r#"{"access_token":"saml_token","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        // Username/password are present but the explicit assertion wins
+        let manager = TokenManager::new(OAuthCredentials {
+            client_id: "my_id".to_string(),
+            username: Some("jane@acme.com".to_string()),
+            password: Some("hunter2".to_string()),
+            ..Default::default()
+        })
+        .with_saml_assertion("PHNhbWw+YWJj/ZGVm=")
+        .with_auth_url_unchecked(server.url());
+
+        assert_eq!(manager.get_token().await.unwrap().token(), "saml_token");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_refresh_token_rotation() {
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("POST", "/services/oauth2/token")
+            .match_body(mockito::Matcher::UrlEncoded(
+                "refresh_token".into(),
+                "rt1".into(),
+            ))
+            .with_body(
+                r#"{"access_token":"at1","refresh_token":"rt2","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer","expires_in":0}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("POST", "/services/oauth2/token")
+            .match_body(mockito::Matcher::UrlEncoded(
+                "refresh_token".into(),
+                "rt2".into(),
+            ))
+            .with_body(
+                r#"{"access_token":"at2","refresh_token":"rt3","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer","expires_in":7200}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let dir = tempfile::tempdir().unwrap();
+        let store = store::FileTokenStore::new(dir.path().join("token.json"));
+        let manager = TokenManager::new(OAuthCredentials {
+            client_id: "id".to_string(),
+            client_secret: "secret".to_string(),
+            refresh_token: Some("rt1".to_string()),
+            ..Default::default()
+        })
+        .with_store(store.clone())
+        .with_auth_url_unchecked(server.url());
+
+        assert_eq!(manager.get_token().await.unwrap().token(), "at1");
+        assert_eq!(manager.get_token().await.unwrap().token(), "at2");
+        first.assert_async().await;
+        second.assert_async().await;
+
+        assert_eq!(
+            manager.credentials.read().await.refresh_token.as_deref(),
+            Some("rt3")
+        );
+        let persisted = store.load().await.unwrap().unwrap();
+        assert_eq!(persisted.refresh_token(), Some("rt3"));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_hooks() {
+        let mut server = mockito::Server::new_async().await;
+        let rotated = server
+            .mock("POST", "/services/oauth2/token")
+            .match_body(mockito::Matcher::UrlEncoded(
+                "refresh_token".into(),
+                "original_rt".into(),
+            ))
+            .with_body(
+                r#"{"access_token":"new_token","refresh_token":"rotated_rt","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer","expires_in":0}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let refreshed = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let failures = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+        let mut manager = TokenManager::new(OAuthCredentials {
+            client_id: "id".to_string(),
+            client_secret: "secret".to_string(),
+            refresh_token: Some("original_rt".to_string()),
+            ..Default::default()
+        })
+        .on_token_refreshed({
+            let refreshed = refreshed.clone();
+            move |token| {
+                refreshed.lock().unwrap().push((
+                    token.token().to_string(),
+                    token.refresh_token().map(str::to_string),
+                ))
+            }
+        })
+        .on_refresh_failed({
+            let failures = failures.clone();
+            move |error| failures.lock().unwrap().push(error.to_string())
+        });
+        manager.auth_url = server.url();
+
+        manager.get_token().await.unwrap();
+        assert_eq!(
+            *refreshed.lock().unwrap(),
+            vec![("new_token".to_string(), Some("rotated_rt".to_string()))]
+        );
+        assert!(failures.lock().unwrap().is_empty());
+        rotated.assert_async().await;
+
+        // The token expired immediately; the next refresh is rejected
+        let rejected = server
+            .mock("POST", "/services/oauth2/token")
+            .with_status(400)
+            .with_body(r#"{"error":"invalid_grant"}"#)
+            .create_async()
+            .await;
+
+        let err = manager.get_token().await.unwrap_err();
+        assert_eq!(refreshed.lock().unwrap().len(), 1);
+        assert_eq!(*failures.lock().unwrap(), vec![err.to_string()]);
+        rejected.assert_async().await;
+    }
+
+    #[test]
+    fn test_client_credentials_constructor() {
+        let credentials = OAuthCredentials::client_credentials("id", "secret");
+
+        assert!(credentials.is_client_credentials_only());
+        assert!(credentials.refresh_token.is_none());
+        assert!(!OAuthCredentials::default().is_client_credentials_only());
+    }
+
+    #[tokio::test]
+    async fn test_client_credentials_flow() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/oauth2/token")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("grant_type".into(), "client_credentials".into()),
+                mockito::Matcher::UrlEncoded("client_id".into(), "my_id".into()),
+                mockito::Matcher::UrlEncoded("client_secret".into(), "my_secret".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(
+                r#"{"access_token":"00Dxx!token","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut manager =
+            TokenManager::new(OAuthCredentials::client_credentials("my_id", "my_secret"));
+        manager.auth_url = server.url();
+
+        let token = manager.get_token().await.unwrap();
+        assert_eq!(token.token(), "00Dxx!token");
+        assert_eq!(token.instance_url(), "https://acme.my.salesforce.com");
+
+        // Second call is served from current_token without hitting the endpoint
+        let cached = manager.get_token().await.unwrap();
+        assert_eq!(cached.token(), "00Dxx!token");
+        assert!(manager.current_token.read().await.is_some());
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_device_flow_end_to_end() {
+        let mut server = mockito::Server::new_async().await;
+        let start = server
+            .mock("POST", "/services/oauth2/token")
+            .match_body(mockito::Matcher::UrlEncoded(
+                "response_type".into(),
+                "device_code".into(),
+            ))
+            .with_status(200)
+            .with_body(
+                r#"{"device_code":"dev123","user_code":"ABCD-1234","verification_uri":"https://login.salesforce.com/setup/connect","interval":1}"#,
+            )
+            .create_async()
+            .await;
+        let device_grant = mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("grant_type".into(), "device".into()),
+            mockito::Matcher::UrlEncoded("code".into(), "dev123".into()),
+        ]);
+        let pending = server
+            .mock("POST", "/services/oauth2/token")
+            .match_body(device_grant.clone())
+            .with_status(400)
+            .with_body(r#"{"error":"authorization_pending","error_description":"pending"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/services/oauth2/token")
+            .match_body(device_grant)
+            .with_status(200)
+            .with_body(
+                r#"{"access_token":"device_token","refresh_token":"device_refresh","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer"}"#,
+            )
+            .create_async()
+            .await;
+
+        let mut manager = TokenManager::new(OAuthCredentials {
+            client_id: "cli_app".to_string(),
+            ..Default::default()
+        });
+        manager.auth_url = server.url();
+
+        let authorization = manager.device_flow().await.unwrap();
+        assert_eq!(authorization.user_code, "ABCD-1234");
+        assert_eq!(authorization.interval, 1);
+        start.assert_async().await;
+
+        // First poll is pending, the second one succeeds
+        let token = manager.poll_device_flow(&authorization).await;
+        pending.assert_async().await;
+
+        let token = token.unwrap();
+        assert_eq!(token.token(), "device_token");
+        assert_eq!(
+            manager.credentials.read().await.refresh_token.as_deref(),
+            Some("device_refresh")
+        );
+        assert_eq!(manager.get_token().await.unwrap().token(), "device_token");
+    }
+
+    #[tokio::test]
+    async fn test_authorization_url() {
+        let manager = TokenManager::new(OAuthCredentials {
+            client_id: "web_app".to_string(),
+            ..Default::default()
+        });
+        let pkce =
+            PkceChallenge::from_verifier("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk").unwrap();
+
+        let url = manager
+            .authorization_url("http://localhost:8080/callback", &pkce, "xyz")
+            .await
+            .unwrap();
+
+        assert_eq!(
+            url,
+            "https://login.salesforce.com/services/oauth2/authorize?response_type=code\
+             &client_id=web_app&redirect_uri=http%3A%2F%2Flocalhost%3A8080%2Fcallback\
+             &code_challenge=E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM\
+             &code_challenge_method=S256&state=xyz"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_exchange_code() {
+        let mut server = mockito::Server::new_async().await;
+        let pkce = PkceChallenge::new();
+        let mock = server
+            .mock("POST", "/services/oauth2/token")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("grant_type".into(), "authorization_code".into()),
+                mockito::Matcher::UrlEncoded("code".into(), "aPrx.code".into()),
+                mockito::Matcher::UrlEncoded("client_id".into(), "web_app".into()),
+                mockito::Matcher::UrlEncoded(
+                    "redirect_uri".into(),
+                    "http://localhost:8080/callback".into(),
+                ),
+                mockito::Matcher::UrlEncoded("code_verifier".into(), pkce.verifier().into()),
+            ]))
+            .with_status(200)
+            .with_body(
+                r#"{"access_token":"code_token","refresh_token":"code_refresh","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer"}"#,
+            )
+            .create_async()
+            .await;
+
+        let mut manager = TokenManager::new(OAuthCredentials {
+            client_id: "web_app".to_string(),
+            ..Default::default()
+        });
+        manager.auth_url = server.url();
+
+        let token = manager
+            .exchange_code("aPrx.code", "http://localhost:8080/callback", &pkce)
+            .await
+            .unwrap();
+
+        assert_eq!(token.token(), "code_token");
+        assert_eq!(
+            manager.credentials.read().await.refresh_token.as_deref(),
+            Some("code_refresh")
+        );
+        assert_eq!(manager.get_token().await.unwrap().token(), "code_token");
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_default_auth_url() {
+        let manager = TokenManager::new(OAuthCredentials::default());
+        assert_eq!(manager.auth_url(), "https://login.salesforce.com");
+
+        let sandbox = TokenManager::sandbox(OAuthCredentials::default());
+        assert_eq!(sandbox.auth_url(), "https://test.salesforce.com");
+    }
+
+    #[test]
+    fn test_environment_auth_urls() {
+        let custom = Environment::Custom("https://acme.my.salesforce.com".to_string());
+
+        for (environment, expected) in [
+            (Environment::Production, "https://login.salesforce.com"),
+            (Environment::Sandbox, "https://test.salesforce.com"),
+            (custom, "https://acme.my.salesforce.com"),
+        ] {
+            assert_eq!(environment.auth_url(), expected);
+            let manager =
+                TokenManager::for_environment(OAuthCredentials::default(), &environment).unwrap();
+            assert_eq!(manager.auth_url(), expected);
+        }
+
+        let invalid = Environment::Custom("http://acme.my.salesforce.com".to_string());
+        assert!(TokenManager::for_environment(OAuthCredentials::default(), &invalid).is_err());
+    }
+
+    #[test]
+    fn test_with_auth_url_validation() {
+        let manager = TokenManager::new(OAuthCredentials::default())
+            .with_auth_url("https://acme.my.salesforce.com/")
+            .unwrap();
+        assert_eq!(manager.auth_url(), "https://acme.my.salesforce.com");
+
+        for invalid in [
+            "http://acme.my.salesforce.com",
+            "https://acme.my.salesforce.com/services/oauth2",
+            "https://acme.my.salesforce.com?x=1",
+            "acme.my.salesforce.com",
+        ] {
+            let result = TokenManager::new(OAuthCredentials::default()).with_auth_url(invalid);
+            assert!(matches!(result, Err(SfError::Config(_))), "{}", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_auth_url_override_used_by_all_flows() {
+        let mut server = mockito::Server::new_async().await;
+        let body = r#"{"access_token":"token","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer"}"#;
+        let refresh = server
+            .mock("POST", "/services/oauth2/token")
+            .match_body(mockito::Matcher::UrlEncoded(
+                "grant_type".into(),
+                "refresh_token".into(),
+            ))
+            .with_body(body)
+            .expect(1)
+            .create_async()
+            .await;
+        let password = server
+            .mock("POST", "/services/oauth2/token")
+            .match_body(mockito::Matcher::UrlEncoded(
+                "grant_type".into(),
+                "password".into(),
+            ))
+            .with_body(body)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let refresh_manager = TokenManager::new(OAuthCredentials {
+            refresh_token: Some("refresh".to_string()),
+            ..Default::default()
+        })
+        .with_auth_url_unchecked(server.url());
+        refresh_manager.get_token().await.unwrap();
+
+        let password_manager = TokenManager::new(OAuthCredentials {
+            username: Some("user@example.com".to_string()),
+            password: Some("secret".to_string()),
+            ..Default::default()
+        })
+        .with_auth_url_unchecked(server.url());
+        password_manager.get_token().await.unwrap();
+
+        refresh.assert_async().await;
+        password.assert_async().await;
+    }
+
+    fn password_credentials(username: &str, security_token: Option<&str>) -> OAuthCredentials {
+        OAuthCredentials {
+            client_id: "id".to_string(),
+            client_secret: "secret".to_string(),
+            username: Some(username.to_string()),
+            password: Some("hunter2".to_string()),
+            security_token: security_token.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_password_flow_appends_security_token() {
+        let mut server = mockito::Server::new_async().await;
+        let with_token = server
+            .mock("POST", "/services/oauth2/token")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("grant_type".into(), "password".into()),
+                mockito::Matcher::UrlEncoded("username".into(), "jane@acme.com".into()),
+                mockito::Matcher::UrlEncoded("password".into(), "hunter2XyZ123".into()),
+            ]))
+            .with_body(
+                r#"{"access_token":"token","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let without_token = server
+            .mock("POST", "/services/oauth2/token")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("username".into(), "jane@acme.com".into()),
+                mockito::Matcher::UrlEncoded("password".into(), "hunter2".into()),
+            ]))
+            .with_body(
+                r#"{"access_token":"token","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        TokenManager::new(password_credentials(" jane@acme.com\n", Some(" XyZ123 ")))
+            .with_auth_url_unchecked(server.url())
+            .get_token()
+            .await
+            .unwrap();
+        TokenManager::new(password_credentials("jane@acme.com", None))
+            .with_auth_url_unchecked(server.url())
+            .get_token()
+            .await
+            .unwrap();
+
+        with_token.assert_async().await;
+        without_token.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_password_flow_rejects_invalid_username() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/oauth2/token")
+            .expect(0)
+            .create_async()
+            .await;
+
+        for username in ["jane", "jane@acme", "@acme.com", "jane doe@acme.com", ""] {
+            let err = TokenManager::new(password_credentials(username, Some("XyZ123")))
+                .with_auth_url_unchecked(server.url())
+                .get_token()
+                .await
+                .unwrap_err();
+            assert!(matches!(err, SfError::Config(_)), "{}: {:?}", username, err);
+        }
+
+        assert!(is_email_like("jane@acme.com.uat"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_device_flow_slow_down_and_expiry() {
+        let mut server = mockito::Server::new_async().await;
+        let slow_down = server
+            .mock("POST", "/services/oauth2/token")
+            .with_status(400)
+            .with_body(r#"{"error":"slow_down"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let mut manager = TokenManager::new(OAuthCredentials::default());
+        manager.auth_url = server.url();
+
+        // 1s + 6s polls fit in the 15s lifetime, the next 11s interval does not
+        let authorization = DeviceAuthorization {
+            device_code: "dev123".to_string(),
+            user_code: "ABCD-1234".to_string(),
+            verification_uri: "https://login.salesforce.com/setup/connect".to_string(),
+            interval: 1,
+            expires_in: Some(15),
+        };
+
+        let err = manager.poll_device_flow(&authorization).await.unwrap_err();
+        assert!(matches!(err, SfError::Auth(msg) if msg.contains("expired")));
+        slow_down.assert_async().await;
+    }
 }
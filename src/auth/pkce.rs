@@ -0,0 +1,123 @@
+//! Proof Key for Code Exchange (PKCE) helpers
+//!
+//! Generates the code verifier/challenge pair and `state` values used by the
+//! OAuth 2.0 authorization code flow (RFC 7636).
+
+use crate::error::{SfError, SfResult};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+/// Code challenge method sent to the authorization endpoint
+pub const CHALLENGE_METHOD: &str = "S256";
+
+/// PKCE code verifier and its S256 code challenge
+#[derive(Debug, Clone)]
+pub struct PkceChallenge {
+    verifier: String,
+    challenge: String,
+}
+
+impl PkceChallenge {
+    /// Generate a new random code verifier (43 characters)
+    pub fn new() -> Self {
+        let verifier = random_token(32);
+        let challenge = challenge_for(&verifier);
+
+        Self {
+            verifier,
+            challenge,
+        }
+    }
+
+    /// Build a challenge from an existing code verifier
+    ///
+    /// The verifier must be 43-128 characters from the unreserved set
+    /// (`A-Z a-z 0-9 - . _ ~`).
+    pub fn from_verifier(verifier: impl Into<String>) -> SfResult<Self> {
+        let verifier = verifier.into();
+
+        let valid_chars = verifier
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '.' | '_' | '~'));
+        if !(43..=128).contains(&verifier.len()) || !valid_chars {
+            return Err(SfError::Config(
+                "PKCE code verifier must be 43-128 unreserved characters".to_string(),
+            ));
+        }
+
+        let challenge = challenge_for(&verifier);
+        Ok(Self {
+            verifier,
+            challenge,
+        })
+    }
+
+    /// Get the code verifier (sent with the token exchange)
+    pub fn verifier(&self) -> &str {
+        &self.verifier
+    }
+
+    /// Get the code challenge (sent with the authorization request)
+    pub fn challenge(&self) -> &str {
+        &self.challenge
+    }
+}
+
+impl Default for PkceChallenge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Generate a random `state` value for CSRF protection
+pub fn generate_state() -> String {
+    random_token(16)
+}
+
+/// Base64url-encoded random bytes without padding
+fn random_token(bytes: usize) -> String {
+    let mut buf = vec![0u8; bytes];
+    rand::thread_rng().fill_bytes(&mut buf);
+    URL_SAFE_NO_PAD.encode(buf)
+}
+
+/// BASE64URL(SHA256(verifier))
+fn challenge_for(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_s256_challenge_rfc7636_vector() {
+        // Test vector from RFC 7636, Appendix B
+        let pkce =
+            PkceChallenge::from_verifier("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk").unwrap();
+
+        assert_eq!(
+            pkce.challenge(),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_generated_verifier() {
+        let pkce = PkceChallenge::new();
+
+        assert_eq!(pkce.verifier().len(), 43);
+        assert_eq!(pkce.challenge(), challenge_for(pkce.verifier()));
+        assert!(PkceChallenge::from_verifier(pkce.verifier()).is_ok());
+        assert_ne!(pkce.verifier(), PkceChallenge::new().verifier());
+    }
+
+    #[test]
+    fn test_invalid_verifier() {
+        assert!(PkceChallenge::from_verifier("too-short").is_err());
+        assert!(PkceChallenge::from_verifier("a".repeat(129)).is_err());
+        assert!(PkceChallenge::from_verifier(format!("{}+", "a".repeat(43))).is_err());
+    }
+}
@@ -0,0 +1,381 @@
+//! Named connection profiles
+//!
+//! Reads a TOML file with one section per org so credentials don't have to
+//! be passed around by hand:
+//!
+//! ```toml
+//! [prod]
+//! flow = "refresh_token"
+//! auth_url = "https://acme.my.salesforce.com"
+//! client_id = "3MVG9..."
+//! client_secret = "..."
+//! refresh_token = "5Aep861..."
+//!
+//! [dev]
+//! flow = "access_token"
+//! instance_url = "https://acme--dev.sandbox.my.salesforce.com"
+//! access_token = "00D..."
+//! ```
+
+use super::{Environment, OAuthCredentials};
+use crate::error::{SfError, SfResult};
+use crate::ClientConfig;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Keys recognized in a profile section
+const KNOWN_KEYS: &[&str] = &[
+    "flow",
+    "instance_url",
+    "auth_url",
+    "access_token",
+    "client_id",
+    "client_secret",
+    "refresh_token",
+    "username",
+    "password",
+    "security_token",
+];
+
+/// How a profile authenticates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuthFlow {
+    /// Use a fixed access token (`instance_url`, `access_token`)
+    AccessToken,
+
+    /// OAuth refresh token flow (`client_id`, `refresh_token`)
+    RefreshToken,
+
+    /// OAuth username/password flow (`client_id`, `client_secret`, `username`, `password`)
+    Password,
+
+    /// OAuth client credentials flow (`client_id`, `client_secret`)
+    ClientCredentials,
+}
+
+impl AuthFlow {
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "access_token" => Some(Self::AccessToken),
+            "refresh_token" => Some(Self::RefreshToken),
+            "password" => Some(Self::Password),
+            "client_credentials" => Some(Self::ClientCredentials),
+            _ => None,
+        }
+    }
+
+    fn required_keys(self) -> &'static [&'static str] {
+        match self {
+            Self::AccessToken => &["instance_url", "access_token"],
+            Self::RefreshToken => &["client_id", "refresh_token"],
+            Self::Password => &["client_id", "client_secret", "username", "password"],
+            Self::ClientCredentials => &["client_id", "client_secret"],
+        }
+    }
+}
+
+/// A single named connection profile
+#[derive(Debug, Clone)]
+pub struct Profile {
+    /// Section name in the profiles file
+    pub name: String,
+
+    /// Authentication flow
+    pub flow: AuthFlow,
+
+    /// Instance URL (required for `access_token` profiles)
+    pub instance_url: Option<String>,
+
+    /// Login endpoint for OAuth flows (defaults to production)
+    pub auth_url: Option<String>,
+
+    /// Fixed access token
+    pub access_token: Option<String>,
+
+    /// OAuth client ID (Consumer Key)
+    pub client_id: Option<String>,
+
+    /// OAuth client secret (Consumer Secret)
+    pub client_secret: Option<String>,
+
+    /// Refresh token
+    pub refresh_token: Option<String>,
+
+    /// Username for the password flow
+    pub username: Option<String>,
+
+    /// Password for the password flow
+    pub password: Option<String>,
+
+    /// Security token for the password flow
+    pub security_token: Option<String>,
+}
+
+impl Profile {
+    /// Environment for OAuth token requests, derived from `auth_url`
+    pub fn environment(&self) -> Environment {
+        match self
+            .auth_url
+            .as_deref()
+            .map(|url| url.trim_end_matches('/'))
+        {
+            None | Some("https://login.salesforce.com") => Environment::Production,
+            Some("https://test.salesforce.com") => Environment::Sandbox,
+            Some(url) => Environment::Custom(url.to_string()),
+        }
+    }
+
+    /// OAuth credentials for this profile
+    ///
+    /// Fails for `access_token` profiles, which have no OAuth credentials.
+    pub fn credentials(&self) -> SfResult<OAuthCredentials> {
+        if self.flow == AuthFlow::AccessToken {
+            return Err(SfError::Config(format!(
+                "Profile '{}' uses a fixed access token and has no OAuth credentials",
+                self.name
+            )));
+        }
+
+        let mut credentials = OAuthCredentials {
+            client_id: self.client_id.clone().unwrap_or_default(),
+            client_secret: self.client_secret.clone().unwrap_or_default(),
+            ..Default::default()
+        };
+
+        // Only set the fields of the selected flow so another flow never
+        // takes precedence in the token manager
+        match self.flow {
+            AuthFlow::RefreshToken => credentials.refresh_token = self.refresh_token.clone(),
+            AuthFlow::Password => {
+                credentials.username = self.username.clone();
+                credentials.password = self.password.clone();
+                credentials.security_token = self.security_token.clone();
+            }
+            AuthFlow::ClientCredentials | AuthFlow::AccessToken => {}
+        }
+
+        Ok(credentials)
+    }
+
+    /// Client configuration for this profile
+    ///
+    /// For OAuth profiles the base URL is replaced by the instance URL of
+    /// the issued token once the client logs in.
+    pub fn client_config(&self) -> ClientConfig {
+        ClientConfig::new(
+            self.instance_url.clone().unwrap_or_default(),
+            self.access_token.clone().unwrap_or_default(),
+        )
+        .with_environment(self.environment())
+    }
+
+    fn from_table(name: &str, table: &toml::Table) -> SfResult<Self> {
+        for key in table.keys() {
+            if !KNOWN_KEYS.contains(&key.as_str()) {
+                warn!("Ignoring unknown key '{}' in profile '{}'", key, name);
+            }
+        }
+
+        let get = |key: &str| -> SfResult<Option<String>> {
+            match table.get(key) {
+                None => Ok(None),
+                Some(toml::Value::String(value)) => Ok(Some(value.clone())),
+                Some(_) => Err(SfError::Config(format!(
+                    "Profile '{}' key '{}' must be a string",
+                    name, key
+                ))),
+            }
+        };
+        let missing = |key: &str| {
+            SfError::Config(format!(
+                "Profile '{}' is missing required key '{}'",
+                name, key
+            ))
+        };
+
+        let flow_name = get("flow")?.ok_or_else(|| missing("flow"))?;
+        let flow = AuthFlow::parse(&flow_name).ok_or_else(|| {
+            SfError::Config(format!(
+                "Profile '{}' has unknown flow '{}' (expected access_token, refresh_token, password or client_credentials)",
+                name, flow_name
+            ))
+        })?;
+
+        for key in flow.required_keys() {
+            if get(key)?.is_none() {
+                return Err(missing(key));
+            }
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            flow,
+            instance_url: get("instance_url")?,
+            auth_url: get("auth_url")?,
+            access_token: get("access_token")?,
+            client_id: get("client_id")?,
+            client_secret: get("client_secret")?,
+            refresh_token: get("refresh_token")?,
+            username: get("username")?,
+            password: get("password")?,
+            security_token: get("security_token")?,
+        })
+    }
+}
+
+/// Collection of named profiles loaded from a TOML file
+#[derive(Debug, Clone, Default)]
+pub struct Profiles {
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+    /// Default profiles file location
+    ///
+    /// `$XDG_CONFIG_HOME/salesforce-client/profiles.toml`, falling back to
+    /// `~/.config/salesforce-client/profiles.toml`.
+    pub fn default_path() -> Option<PathBuf> {
+        let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+
+        Some(config_dir.join("salesforce-client").join("profiles.toml"))
+    }
+
+    /// Load profiles from the default location
+    pub fn load_default() -> SfResult<Self> {
+        let path = Self::default_path().ok_or_else(|| {
+            SfError::Config("Cannot locate profiles file: HOME is not set".to_string())
+        })?;
+        Self::load(path)
+    }
+
+    /// Load profiles from a TOML file
+    ///
+    /// # Example
+    /// ```no_run
+    /// use salesforce_client::auth::Profiles;
+    ///
+    /// # fn example() -> Result<(), salesforce_client::SfError> {
+    /// let profiles = Profiles::load("profiles.toml")?;
+    /// let credentials = profiles.get("prod")?.credentials()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn load(path: impl AsRef<Path>) -> SfResult<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| {
+            SfError::Config(format!(
+                "Failed to read profiles file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+
+        Self::parse(&contents)
+    }
+
+    /// Parse profiles from TOML text
+    pub fn parse(contents: &str) -> SfResult<Self> {
+        let table: toml::Table = contents
+            .parse()
+            .map_err(|e| SfError::Config(format!("Invalid profiles file: {}", e)))?;
+
+        let mut profiles = BTreeMap::new();
+        for (name, value) in &table {
+            let section = value
+                .as_table()
+                .ok_or_else(|| SfError::Config(format!("Profile '{}' must be a table", name)))?;
+            profiles.insert(name.clone(), Profile::from_table(name, section)?);
+        }
+
+        Ok(Self { profiles })
+    }
+
+    /// Get a profile by name
+    pub fn get(&self, name: &str) -> SfResult<&Profile> {
+        self.profiles
+            .get(name)
+            .ok_or_else(|| SfError::Config(format!("Profile '{}' not found", name)))
+    }
+
+    /// Names of all profiles, sorted
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn test_load_profiles() {
+        let profiles = Profiles::load(fixture("profiles.toml")).unwrap();
+        assert_eq!(profiles.names().collect::<Vec<_>>(), vec!["dev", "prod"]);
+
+        let prod = profiles.get("prod").unwrap();
+        assert_eq!(prod.flow, AuthFlow::RefreshToken);
+        assert_eq!(
+            prod.environment(),
+            Environment::Custom("https://acme.my.salesforce.com".to_string())
+        );
+        let credentials = prod.credentials().unwrap();
+        assert_eq!(credentials.client_id, "3MVG9prod");
+        assert_eq!(credentials.refresh_token.as_deref(), Some("5Aep861prod"));
+        assert!(credentials.username.is_none());
+
+        let dev = profiles.get("dev").unwrap();
+        assert_eq!(dev.flow, AuthFlow::AccessToken);
+        let config = dev.client_config();
+        assert_eq!(
+            config.base_url,
+            "https://acme--dev.sandbox.my.salesforce.com"
+        );
+        assert_eq!(config.access_token, "00Ddev!token");
+        assert_eq!(config.environment, Environment::Sandbox);
+        assert!(dev.credentials().is_err());
+
+        assert!(matches!(profiles.get("qa"), Err(SfError::Config(_))));
+    }
+
+    #[test]
+    fn test_malformed_profile() {
+        let err = Profiles::load(fixture("profiles_malformed.toml")).unwrap_err();
+        match err {
+            SfError::Config(message) => {
+                assert_eq!(
+                    message,
+                    "Profile 'broken' is missing required key 'client_secret'"
+                );
+            }
+            other => panic!("expected Config error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_values() {
+        let err = Profiles::parse("[prod]\nflow = \"magic\"\n").unwrap_err();
+        assert!(matches!(err, SfError::Config(msg) if msg.contains("unknown flow 'magic'")));
+
+        let err =
+            Profiles::parse("[prod]\nflow = \"access_token\"\ninstance_url = 1\n").unwrap_err();
+        assert!(
+            matches!(err, SfError::Config(msg) if msg == "Profile 'prod' key 'instance_url' must be a string")
+        );
+
+        let err = Profiles::parse("[prod]\ninstance_url = \"https://x\"\n").unwrap_err();
+        assert!(
+            matches!(err, SfError::Config(msg) if msg == "Profile 'prod' is missing required key 'flow'")
+        );
+    }
+}
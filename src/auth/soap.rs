@@ -0,0 +1,258 @@
+//! SOAP username/password login
+//!
+//! Fallback for orgs that only allow the partner API `login()` call and
+//! have no connected app for OAuth. Enabled with the `soap-login` feature.
+
+use super::{AccessToken, Environment};
+use crate::error::{SfError, SfResult};
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use tracing::info;
+
+/// Partner API version used for the login call
+const SOAP_API_VERSION: &str = "57.0";
+
+/// Log in with the SOAP partner API
+///
+/// `password_with_token` is the user's password with the security token
+/// appended. The returned token's instance URL is derived from the
+/// `serverUrl` in the login response.
+///
+/// # Example
+/// ```no_run
+/// # use salesforce_client::{auth, SfError};
+/// # async fn example() -> Result<(), SfError> {
+/// let token = auth::soap_login("user@example.com", "passwordTOKEN", false).await?;
+/// println!("Session for {}", token.instance_url());
+/// # Ok(())
+/// # }
+/// ```
+pub async fn soap_login(
+    username: &str,
+    password_with_token: &str,
+    is_sandbox: bool,
+) -> SfResult<AccessToken> {
+    let environment = if is_sandbox {
+        Environment::Sandbox
+    } else {
+        Environment::Production
+    };
+
+    login_at(
+        &reqwest::Client::new(),
+        environment.auth_url(),
+        username,
+        password_with_token,
+    )
+    .await
+}
+
+async fn login_at(
+    http_client: &reqwest::Client,
+    login_url: &str,
+    username: &str,
+    password_with_token: &str,
+) -> SfResult<AccessToken> {
+    let url = format!("{}/services/Soap/u/{}", login_url, SOAP_API_VERSION);
+
+    let response = http_client
+        .post(&url)
+        .header("Content-Type", "text/xml; charset=UTF-8")
+        .header("SOAPAction", "login")
+        .body(login_envelope(username, password_with_token))
+        .send()
+        .await?;
+
+    // Faults are returned with a 500 status, so parse the body either way
+    let status = response.status();
+    let body = response.text().await?;
+    let token = parse_login_response(&body).map_err(|e| match e {
+        SfError::Serialization(_) if !status.is_success() => SfError::Api {
+            status: status.as_u16(),
+            body: body.clone(),
+        },
+        e => e,
+    })?;
+
+    info!("SOAP login succeeded for {}", username);
+    Ok(token)
+}
+
+fn login_envelope(username: &str, password_with_token: &str) -> String {
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="utf-8"?>"#,
+            r#"<env:Envelope xmlns:xsd="http://www.w3.org/2001/XMLSchema""#,
+            r#" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance""#,
+            r#" xmlns:env="http://schemas.xmlsoap.org/soap/envelope/">"#,
+            r#"<env:Body><n1:login xmlns:n1="urn:partner.soap.sforce.com">"#,
+            "<n1:username>{}</n1:username>",
+            "<n1:password>{}</n1:password>",
+            "</n1:login></env:Body></env:Envelope>"
+        ),
+        escape(username),
+        escape(password_with_token)
+    )
+}
+
+/// Extract the session from a `loginResponse`, or the fault details
+fn parse_login_response(xml: &str) -> SfResult<AccessToken> {
+    let mut reader = Reader::from_str(xml);
+    let mut current = Vec::new();
+
+    let mut session_id = None;
+    let mut server_url = None;
+    let mut session_seconds = None;
+    let mut fault_code = None;
+    let mut fault_string = None;
+
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => current = e.local_name().as_ref().to_vec(),
+            Ok(Event::End(_)) => current.clear(),
+            Ok(Event::Text(t)) => {
+                let text = t.unescape().map_err(xml_error)?.into_owned();
+                match current.as_slice() {
+                    b"sessionId" => session_id = Some(text),
+                    b"serverUrl" => server_url = Some(text),
+                    b"sessionSecondsValid" => session_seconds = text.parse().ok(),
+                    b"faultcode" => fault_code = Some(text),
+                    b"faultstring" => fault_string = Some(text),
+                    _ => {}
+                }
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => {}
+            Err(e) => return Err(xml_error(e)),
+        }
+    }
+
+    if let Some(message) = fault_string {
+        // Fault codes are namespaced, e.g. `sf:INVALID_LOGIN`
+        let code = fault_code.unwrap_or_default();
+        let code = code.rsplit(':').next().unwrap_or_default();
+        return Err(SfError::Auth(format!(
+            "SOAP login failed: {}: {}",
+            code, message
+        )));
+    }
+
+    match (session_id, server_url) {
+        (Some(session_id), Some(server_url)) => {
+            let instance_url = url::Url::parse(&server_url)
+                .map_err(|e| SfError::Auth(format!("Invalid serverUrl {}: {}", server_url, e)))?
+                .origin()
+                .ascii_serialization();
+
+            Ok(AccessToken::new(session_id, instance_url, session_seconds))
+        }
+        _ => Err(xml_error(
+            "missing sessionId or serverUrl in login response",
+        )),
+    }
+}
+
+fn xml_error(e: impl std::fmt::Display) -> SfError {
+    SfError::Serialization(serde::de::Error::custom(format!(
+        "Invalid SOAP response: {}",
+        e
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOGIN_RESPONSE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<soapenv:Envelope xmlns:soapenv="http://schemas.xmlsoap.org/soap/envelope/" xmlns="urn:partner.soap.sforce.com" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <soapenv:Body>
+    <loginResponse>
+      <result>
+        <metadataServerUrl>https://acme.my.salesforce.com/services/Soap/m/57.0/00Dxx0000001gPL</metadataServerUrl>
+        <passwordExpired>false</passwordExpired>
+        <sandbox>false</sandbox>
+        <serverUrl>https://acme.my.salesforce.com/services/Soap/u/57.0/00Dxx0000001gPL</serverUrl>
+        <sessionId>00Dxx0000001gPL!AQ0AQH0dMHZfz972Szmpkb58urFRkgeBGsxL</sessionId>
+        <userId>005xx000001SwiUAAS</userId>
+        <userInfo>
+          <organizationId>00Dxx0000001gPLEAY</organizationId>
+          <sessionSecondsValid>7200</sessionSecondsValid>
+          <userName>jane@acme.com</userName>
+        </userInfo>
+      </result>
+    </loginResponse>
+  </soapenv:Body>
+</soapenv:Envelope>"#;
+
+    const FAULT_RESPONSE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<soapenv:Envelope xmlns:soapenv="http://schemas.xmlsoap.org/soap/envelope/" xmlns:sf="urn:fault.partner.soap.sforce.com" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">
+  <soapenv:Body>
+    <soapenv:Fault>
+      <faultcode>sf:INVALID_LOGIN</faultcode>
+      <faultstring>INVALID_LOGIN: Invalid username, password, security token; or user locked out.</faultstring>
+      <detail>
+        <sf:LoginFault xsi:type="sf:LoginFault">
+          <sf:exceptionCode>INVALID_LOGIN</sf:exceptionCode>
+          <sf:exceptionMessage>Invalid username, password, security token; or user locked out.</sf:exceptionMessage>
+        </sf:LoginFault>
+      </detail>
+    </soapenv:Fault>
+  </soapenv:Body>
+</soapenv:Envelope>"#;
+
+    #[test]
+    fn test_parse_login_response() {
+        let token = parse_login_response(LOGIN_RESPONSE).unwrap();
+
+        assert_eq!(
+            token.token(),
+            "00Dxx0000001gPL!AQ0AQH0dMHZfz972Szmpkb58urFRkgeBGsxL"
+        );
+        assert_eq!(token.instance_url(), "https://acme.my.salesforce.com");
+        assert!(!token.is_expired());
+    }
+
+    #[test]
+    fn test_parse_fault_response() {
+        let err = parse_login_response(FAULT_RESPONSE).unwrap_err();
+
+        match err {
+            SfError::Auth(message) => {
+                assert!(message.starts_with("SOAP login failed: INVALID_LOGIN: "));
+                assert!(message.contains("user locked out"));
+            }
+            other => panic!("expected Auth error, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_login_request() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/Soap/u/57.0")
+            .match_header("soapaction", "login")
+            .match_body(mockito::Matcher::Regex(
+                "<n1:username>jane@acme.com</n1:username><n1:password>p&lt;wd&amp;TOKEN</n1:password>"
+                    .to_string(),
+            ))
+            .with_status(200)
+            .with_header("content-type", "text/xml")
+            .with_body(LOGIN_RESPONSE)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let token = login_at(
+            &reqwest::Client::new(),
+            &server.url(),
+            "jane@acme.com",
+            "p<wd&TOKEN",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(token.instance_url(), "https://acme.my.salesforce.com");
+        mock.assert_async().await;
+    }
+}
@@ -0,0 +1,228 @@
+//! Token persistence
+//!
+//! Lets a [`TokenManager`](super::TokenManager) survive process restarts
+//! without fetching a new token every time.
+
+use super::AccessToken;
+use crate::error::{SfError, SfResult};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use tracing::debug;
+
+/// Storage backend for access tokens
+///
+/// Implementations are used by `TokenManager::with_store` to load a persisted
+/// token on first use and to save every newly issued token.
+#[async_trait]
+pub trait TokenStore: Send + Sync {
+    /// Load the persisted token, if any
+    async fn load(&self) -> SfResult<Option<AccessToken>>;
+
+    /// Persist a newly issued token
+    async fn save(&self, token: &AccessToken) -> SfResult<()>;
+}
+
+/// File-based token store writing a JSON blob
+///
+/// On Unix the file is created with `0600` permissions since it contains
+/// the access token and refresh token in plain text.
+#[derive(Debug, Clone)]
+pub struct FileTokenStore {
+    path: PathBuf,
+}
+
+impl FileTokenStore {
+    /// Create a store backed by the given file
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// Get the file path
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+}
+
+#[async_trait]
+impl TokenStore for FileTokenStore {
+    async fn load(&self) -> SfResult<Option<AccessToken>> {
+        let bytes = match tokio::fs::read(&self.path).await {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => {
+                return Err(SfError::Config(format!(
+                    "Failed to read token store {}: {}",
+                    self.path.display(),
+                    e
+                )))
+            }
+        };
+
+        let token = serde_json::from_slice(&bytes)?;
+        debug!("Loaded access token from {}", self.path.display());
+        Ok(Some(token))
+    }
+
+    async fn save(&self, token: &AccessToken) -> SfResult<()> {
+        let bytes = serde_json::to_vec(token)?;
+        let io_error = |e: std::io::Error| {
+            SfError::Config(format!(
+                "Failed to write token store {}: {}",
+                self.path.display(),
+                e
+            ))
+        };
+
+        // Write to a temporary file first so a crash never leaves a truncated store
+        let tmp_path = self.path.with_extension("tmp");
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+
+        let mut file = options.open(&tmp_path).await.map_err(io_error)?;
+        tokio::io::AsyncWriteExt::write_all(&mut file, &bytes)
+            .await
+            .map_err(io_error)?;
+        file.sync_all().await.map_err(io_error)?;
+        drop(file);
+
+        tokio::fs::rename(&tmp_path, &self.path)
+            .await
+            .map_err(io_error)?;
+
+        debug!("Saved access token to {}", self.path.display());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{OAuthCredentials, TokenManager};
+
+    fn token_response(access_token: &str) -> String {
+        format!(
+            r#"{{"access_token":"{}","refresh_token":"refresh","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer","expires_in":7200}}"#,
+            access_token
+        )
+    }
+
+    #[tokio::test]
+    async fn test_file_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileTokenStore::new(dir.path().join("token.json"));
+
+        assert!(store.load().await.unwrap().is_none());
+
+        let token = AccessToken::new(
+            "stored_token".to_string(),
+            "https://acme.my.salesforce.com".to_string(),
+            Some(3600),
+        );
+        store.save(&token).await.unwrap();
+
+        let loaded = store.load().await.unwrap().unwrap();
+        assert_eq!(loaded.token(), "stored_token");
+        assert_eq!(loaded.instance_url(), "https://acme.my.salesforce.com");
+        assert!(!loaded.is_expired());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(store.path())
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o777, 0o600);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_manager_uses_persisted_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileTokenStore::new(dir.path().join("token.json"));
+        store
+            .save(&AccessToken::new(
+                "persisted".to_string(),
+                "https://acme.my.salesforce.com".to_string(),
+                Some(3600),
+            ))
+            .await
+            .unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/oauth2/token")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let mut manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+            .with_store(store);
+        manager.auth_url = server.url();
+
+        assert_eq!(manager.get_token().await.unwrap().token(), "persisted");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_manager_refreshes_expired_persisted_token() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FileTokenStore::new(dir.path().join("token.json"));
+        store
+            .save(&AccessToken::new(
+                "stale".to_string(),
+                "https://acme.my.salesforce.com".to_string(),
+                Some(0),
+            ))
+            .await
+            .unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/oauth2/token")
+            .with_status(200)
+            .with_body(token_response("fresh"))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+            .with_store(store.clone());
+        manager.auth_url = server.url();
+
+        assert_eq!(manager.get_token().await.unwrap().token(), "fresh");
+        assert_eq!(store.load().await.unwrap().unwrap().token(), "fresh");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_manager_ignores_corrupt_store() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("token.json");
+        tokio::fs::write(&path, b"{not json").await.unwrap();
+        let store = FileTokenStore::new(&path);
+        assert!(store.load().await.is_err());
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/oauth2/token")
+            .with_status(200)
+            .with_body(token_response("recovered"))
+            .create_async()
+            .await;
+
+        let mut manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+            .with_store(store.clone());
+        manager.auth_url = server.url();
+
+        assert_eq!(manager.get_token().await.unwrap().token(), "recovered");
+
+        // The corrupt file is replaced and the refresh token is persisted too
+        let saved = store.load().await.unwrap().unwrap();
+        assert_eq!(saved.token(), "recovered");
+        assert_eq!(saved.refresh_token(), Some("refresh"));
+        mock.assert_async().await;
+    }
+}
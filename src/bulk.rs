@@ -0,0 +1,348 @@
+//! Bulk API 2.0 query jobs
+//!
+//! For extracts too large for the REST query endpoint. The query runs as an
+//! asynchronous job and its results are downloaded as CSV, one page at a
+//! time. Enabled with the `bulk-api` feature.
+
+use crate::auth::{self, TokenProvider};
+use crate::error::{SfError, SfResult};
+use crate::pagination::{self, PageSource, Prefetch};
+use async_trait::async_trait;
+use futures::Stream;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tracing::{debug, info};
+
+/// State of a Bulk API 2.0 job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum JobState {
+    /// Job created, not yet processing
+    UploadComplete,
+
+    /// Job is being processed
+    InProgress,
+
+    /// Job was aborted
+    Aborted,
+
+    /// Job finished and results are available
+    JobComplete,
+
+    /// Job failed
+    Failed,
+
+    /// A state this client doesn't know about
+    #[serde(other)]
+    Unknown,
+}
+
+/// Status of a Bulk API 2.0 query job
+#[derive(Debug, Clone, Deserialize)]
+pub struct BulkJobInfo {
+    /// Job ID
+    pub id: String,
+
+    /// Current state
+    pub state: JobState,
+
+    /// Queried sObject
+    #[serde(default)]
+    pub object: Option<String>,
+
+    /// Records processed so far
+    #[serde(rename = "numberRecordsProcessed", default)]
+    pub records_processed: u64,
+
+    /// Reason the job failed
+    #[serde(rename = "errorMessage", default)]
+    pub error_message: Option<String>,
+}
+
+/// Options for Bulk API 2.0 queries
+#[derive(Debug, Clone)]
+pub struct BulkQueryOptions {
+    /// Delay between job status checks
+    pub poll_interval: Duration,
+
+    /// Maximum time to wait for the job to complete
+    pub timeout: Duration,
+
+    /// Maximum records per results page (None = server default)
+    pub page_size: Option<usize>,
+}
+
+impl Default for BulkQueryOptions {
+    fn default() -> Self {
+        Self {
+            poll_interval: Duration::from_secs(2),
+            timeout: Duration::from_secs(600),
+            page_size: None,
+        }
+    }
+}
+
+impl BulkQueryOptions {
+    /// Set the delay between job status checks
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Set the maximum time to wait for the job to complete
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the maximum records per results page
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+}
+
+/// Iterator over the result pages of a completed bulk query job
+///
+/// ```ignore
+/// let mut pages = client.bulk_query::<Account>("SELECT Id FROM Account").await?;
+///
+/// while let Some(batch) = pages.next().await? {
+///     for account in batch {
+///         println!("{:?}", account);
+///     }
+/// }
+/// ```
+pub struct BulkQuery<T> {
+    client: reqwest::Client,
+    results_url: String,
+    token_provider: Arc<dyn TokenProvider>,
+    job: BulkJobInfo,
+    page_size: Option<usize>,
+    locator: Option<String>,
+    finished: bool,
+    prefetch: Option<Prefetch<T>>,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> BulkQuery<T> {
+    /// Create a query job and wait for it to complete
+    pub(crate) async fn start(
+        client: reqwest::Client,
+        api_url: &str,
+        token_provider: Arc<dyn TokenProvider>,
+        soql: &str,
+        options: &BulkQueryOptions,
+    ) -> SfResult<Self> {
+        let jobs_url = format!("{}/jobs/query", api_url);
+        let body = serde_json::json!({ "operation": "query", "query": soql });
+
+        let response = auth::send_authorized(token_provider.as_ref(), || {
+            client.post(&jobs_url).json(&body)
+        })
+        .await?;
+        let mut job: BulkJobInfo = parse_json(response).await?;
+        info!("Created bulk query job {}", job.id);
+
+        let job_url = format!("{}/{}", jobs_url, job.id);
+        let started = Instant::now();
+
+        loop {
+            match job.state {
+                JobState::JobComplete => break,
+                JobState::Failed | JobState::Aborted => {
+                    return Err(SfError::InvalidQuery(format!(
+                        "Bulk query job {} ended in state {:?}: {}",
+                        job.id,
+                        job.state,
+                        job.error_message.as_deref().unwrap_or("no error message")
+                    )));
+                }
+                _ => {}
+            }
+
+            if started.elapsed() >= options.timeout {
+                return Err(SfError::Timeout {
+                    seconds: options.timeout.as_secs(),
+                });
+            }
+
+            debug!("Bulk query job {} is {:?}, polling", job.id, job.state);
+            tokio::time::sleep(options.poll_interval).await;
+
+            let response =
+                auth::send_authorized(token_provider.as_ref(), || client.get(&job_url)).await?;
+            job = parse_json(response).await?;
+        }
+
+        info!(
+            "Bulk query job {} completed with {} records",
+            job.id, job.records_processed
+        );
+
+        Ok(Self {
+            client,
+            results_url: format!("{}/results", job_url),
+            token_provider,
+            job,
+            page_size: options.page_size,
+            locator: None,
+            finished: false,
+            prefetch: None,
+            _phantom: std::marker::PhantomData,
+        })
+    }
+
+    /// Status of the completed job
+    pub fn job(&self) -> &BulkJobInfo {
+        &self.job
+    }
+
+    /// Fetch the next page of results
+    pub async fn next(&mut self) -> SfResult<Option<Vec<T>>> {
+        let Some(prefetch) = self.prefetch.as_mut() else {
+            return self.fetch_page().await;
+        };
+
+        let page = prefetch.recv().await;
+        if !matches!(page, Ok(Some(_))) {
+            self.finished = true;
+        }
+        page
+    }
+
+    async fn fetch_page(&mut self) -> SfResult<Option<Vec<T>>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let mut query = Vec::new();
+        if let Some(locator) = &self.locator {
+            query.push(("locator", locator.clone()));
+        }
+        if let Some(page_size) = self.page_size {
+            query.push(("maxRecords", page_size.to_string()));
+        }
+
+        debug!("Fetching bulk query results for job {}", self.job.id);
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.client
+                .get(&self.results_url)
+                .header("Accept", "text/csv")
+                .query(&query)
+        })
+        .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await?;
+            return Err(SfError::Api {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        // The last page has the literal locator "null"
+        self.locator = response
+            .headers()
+            .get("Sforce-Locator")
+            .and_then(|value| value.to_str().ok())
+            .filter(|locator| !locator.is_empty() && *locator != "null")
+            .map(str::to_string);
+        if self.locator.is_none() {
+            self.finished = true;
+            info!("Bulk query results complete for job {}", self.job.id);
+        }
+
+        let body = response.bytes().await?;
+        let records = csv::Reader::from_reader(body.as_ref())
+            .deserialize()
+            .collect::<Result<Vec<T>, _>>()
+            .map_err(|e| {
+                SfError::Serialization(serde::de::Error::custom(format!(
+                    "Invalid bulk query CSV: {}",
+                    e
+                )))
+            })?;
+
+        Ok(Some(records))
+    }
+
+    /// Collect all remaining pages into a single vector
+    ///
+    /// **Warning:** This loads all results into memory, which defeats the
+    /// purpose of the Bulk API for very large extracts.
+    pub async fn collect_all(mut self) -> SfResult<Vec<T>> {
+        let mut all_records = Vec::new();
+
+        while let Some(batch) = self.next().await? {
+            all_records.extend(batch);
+        }
+
+        info!(
+            "Collected {} total records from bulk query job {}",
+            all_records.len(),
+            self.job.id
+        );
+        Ok(all_records)
+    }
+}
+
+#[async_trait]
+impl<T: DeserializeOwned + Send> PageSource<T> for BulkQuery<T> {
+    async fn next_page(&mut self) -> SfResult<Option<Vec<T>>> {
+        self.next().await
+    }
+}
+
+impl<T: DeserializeOwned + Send> BulkQuery<T> {
+    /// Stream the remaining records one at a time
+    ///
+    /// Like [`PaginatedQuery::records`](crate::PaginatedQuery::records),
+    /// fetching the next results page only once the current one is used up.
+    pub fn records(self) -> impl Stream<Item = SfResult<T>> {
+        pagination::records(self)
+    }
+}
+
+impl<T: DeserializeOwned + Send + 'static> BulkQuery<T> {
+    /// Download up to `pages` results pages ahead in the background
+    ///
+    /// Like [`PaginatedQuery::with_prefetch`](crate::PaginatedQuery::with_prefetch):
+    /// pages keep their order, and dropping the query cancels the download.
+    pub fn with_prefetch(mut self, pages: usize) -> Self {
+        if pages == 0 || self.finished || self.prefetch.is_some() {
+            return self;
+        }
+
+        let fetcher = BulkQuery::<T> {
+            client: self.client.clone(),
+            results_url: self.results_url.clone(),
+            token_provider: self.token_provider.clone(),
+            job: self.job.clone(),
+            page_size: self.page_size,
+            locator: self.locator.take(),
+            finished: false,
+            prefetch: None,
+            _phantom: std::marker::PhantomData,
+        };
+
+        self.prefetch = Some(Prefetch::spawn(fetcher, pages));
+        self
+    }
+}
+
+async fn parse_json<T: DeserializeOwned>(response: reqwest::Response) -> SfResult<T> {
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(SfError::Api {
+            status: status.as_u16(),
+            body,
+        });
+    }
+
+    Ok(serde_json::from_str(&body)?)
+}
@@ -3,11 +3,16 @@
 //! Reduces API calls and improves performance for read-heavy workloads.
 
 use crate::error::{SfError, SfResult};
-use moka::future::Cache;
+use crate::pagination::{QueryEndpoint, QueryResult};
+use moka::future::{Cache, CacheBuilder};
+use moka::Expiry;
+use reqwest::header::{HeaderMap, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tracing::{debug, info};
 
 /// Configuration for the cache
@@ -21,14 +26,55 @@ pub struct CacheConfig {
 
     /// Time-to-idle for cache entries
     pub tti: Option<Duration>,
+
+    /// Time-to-live for metadata such as describe results
+    ///
+    /// Metadata changes rarely and is not invalidated by writes, so it is
+    /// kept much longer than query results.
+    pub metadata_ttl: Duration,
+
+    /// Time after which metadata that can be revalidated is dropped anyway
+    ///
+    /// Counted from the last download or `304`, so an entry nobody reads
+    /// past its `metadata_ttl` is evicted instead of kept for good.
+    pub metadata_max_age: Duration,
+
+    /// Normalize whitespace and keyword case in query cache keys, so
+    /// equivalent queries written differently share an entry
+    pub normalize_queries: bool,
+
+    /// Age after which cached query results are stale: still served, but
+    /// refreshed in the background (None = serve until `ttl` expires)
+    pub stale_while_revalidate: Option<Duration>,
+
+    /// Whether queries returning no records are cached
+    ///
+    /// Turn off for polling queries that are usually empty, so new records
+    /// show up without waiting for the TTL.
+    pub cache_empty_results: bool,
+
+    /// Total size in bytes of serialized entries to keep, in place of the
+    /// `max_capacity` entry count (None = count entries)
+    pub max_bytes: Option<u64>,
+
+    /// Largest serialized entry to cache, in bytes; larger results are
+    /// returned but not stored (None = no limit)
+    pub max_entry_bytes: Option<usize>,
 }
 
 impl Default for CacheConfig {
     fn default() -> Self {
         Self {
             max_capacity: 10_000,
-            ttl: Duration::from_secs(300),      // 5 minutes
-            tti: Some(Duration::from_secs(60)), // 1 minute idle
+            ttl: Duration::from_secs(300),                 // 5 minutes
+            tti: Some(Duration::from_secs(60)),            // 1 minute idle
+            metadata_ttl: Duration::from_secs(3600),       // 1 hour
+            metadata_max_age: Duration::from_secs(86_400), // 1 day
+            normalize_queries: true,
+            stale_while_revalidate: None,
+            cache_empty_results: true,
+            max_bytes: None,
+            max_entry_bytes: None,
         }
     }
 }
@@ -57,30 +103,253 @@ impl CacheConfig {
         self
     }
 
+    /// Set time-to-live for metadata
+    pub fn metadata_ttl(mut self, duration: Duration) -> Self {
+        self.metadata_ttl = duration;
+        self
+    }
+
+    /// Set how long metadata that can be revalidated is kept at most
+    pub fn metadata_max_age(mut self, duration: Duration) -> Self {
+        self.metadata_max_age = duration;
+        self
+    }
+
+    /// Set whether query cache keys are normalized
+    pub fn normalize_queries(mut self, normalize: bool) -> Self {
+        self.normalize_queries = normalize;
+        self
+    }
+
+    /// Serve query results older than `after` while refreshing them in the
+    /// background
+    ///
+    /// A query hitting a stale entry returns it immediately and starts one
+    /// background re-query per entry; entries still expire after `ttl`.
+    pub fn stale_while_revalidate(mut self, after: Duration) -> Self {
+        self.stale_while_revalidate = Some(after);
+        self
+    }
+
+    /// Bound each cache by the total size of its serialized entries
+    ///
+    /// Replaces the `max_capacity` entry count, so one huge query result
+    /// takes as much room as many small ones.
+    pub fn max_bytes(mut self, bytes: u64) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Never cache entries larger than `bytes` once serialized
+    pub fn max_entry_bytes(mut self, bytes: usize) -> Self {
+        self.max_entry_bytes = Some(bytes);
+        self
+    }
+
+    /// Set whether queries returning no records are cached
+    pub fn cache_empty_results(mut self, cache_empty: bool) -> Self {
+        self.cache_empty_results = cache_empty;
+        self
+    }
+
     /// Disable caching (for testing)
     pub fn disabled() -> Self {
         Self {
             max_capacity: 0,
             ttl: Duration::from_secs(0),
             tti: None,
+            metadata_ttl: Duration::from_secs(0),
+            metadata_max_age: Duration::from_secs(0),
+            normalize_queries: true,
+            stale_while_revalidate: None,
+            cache_empty_results: true,
+            max_bytes: None,
+            max_entry_bytes: None,
+        }
+    }
+
+    /// Cache builder bounded by entry count, or by `len` bytes per entry
+    /// when `max_bytes` is set
+    fn builder<K, V>(&self, len: fn(&V) -> usize) -> CacheBuilder<K, V, Cache<K, V>>
+    where
+        K: Hash + Eq + Send + Sync + 'static,
+        V: Clone + Send + Sync + 'static,
+    {
+        match self.max_bytes {
+            Some(max_bytes) => Cache::builder()
+                .max_capacity(max_bytes)
+                .weigher(move |_, value| u32::try_from(len(value)).unwrap_or(u32::MAX)),
+            None => Cache::builder().max_capacity(self.max_capacity),
+        }
+    }
+}
+
+/// Whether a serialized entry is over `max_entry_bytes`
+fn oversized(max_entry_bytes: Option<usize>, len: usize) -> bool {
+    max_entry_bytes.is_some_and(|max| len > max)
+}
+
+/// How a single query uses the query cache
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CachePolicy {
+    /// Serve cached results when present, and cache fresh ones
+    #[default]
+    Default,
+
+    /// Neither read nor write the cache, e.g. to check current state
+    /// before an update
+    Bypass,
+
+    /// Skip the cached result but cache the fresh one
+    Refresh,
+
+    /// Serve only from the cache, failing with
+    /// [`SfError::NotCached`] instead of querying Salesforce
+    CacheOnly,
+
+    /// Like `Default`, but an empty result is not cached
+    SkipEmpty,
+}
+
+impl CachePolicy {
+    /// Whether a cached result may be served
+    pub(crate) fn reads(self) -> bool {
+        matches!(self, Self::Default | Self::CacheOnly | Self::SkipEmpty)
+    }
+
+    /// Whether a fresh result is stored
+    pub(crate) fn writes(self, empty: bool) -> bool {
+        match self {
+            Self::Default | Self::Refresh => true,
+            Self::SkipEmpty => !empty,
+            Self::Bypass | Self::CacheOnly => false,
         }
     }
 }
 
 /// Cache key for query results
+///
+/// Includes the endpoint so `/query` results are never served for
+/// `/queryAll` calls, which also return deleted records, and whether the
+/// result was followed to its last page, so a first page and a full result
+/// are never served for one another.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct QueryKey {
+    endpoint: QueryEndpoint,
     query: String,
+    all_pages: bool,
 }
 
 impl QueryKey {
-    fn new(query: impl Into<String>) -> Self {
+    fn new(endpoint: QueryEndpoint, query: impl Into<String>, all_pages: bool) -> Self {
         Self {
+            endpoint,
             query: query.into(),
+            all_pages,
         }
     }
 }
 
+/// SOQL keywords uppercased by [`normalize_query`]
+const SOQL_KEYWORDS: &[&str] = &[
+    "SELECT",
+    "FROM",
+    "WHERE",
+    "AND",
+    "OR",
+    "NOT",
+    "IN",
+    "LIKE",
+    "INCLUDES",
+    "EXCLUDES",
+    "ORDER",
+    "BY",
+    "ASC",
+    "DESC",
+    "NULLS",
+    "FIRST",
+    "LAST",
+    "LIMIT",
+    "OFFSET",
+    "GROUP",
+    "HAVING",
+    "ROLLUP",
+    "CUBE",
+    "WITH",
+    "USING",
+    "SCOPE",
+    "TYPEOF",
+    "WHEN",
+    "THEN",
+    "ELSE",
+    "END",
+    "FOR",
+    "VIEW",
+    "REFERENCE",
+    "UPDATE",
+    "TRACKING",
+    "VIEWSTAT",
+    "NULL",
+    "TRUE",
+    "FALSE",
+];
+
+/// Collapse whitespace and uppercase keywords outside string literals
+///
+/// `select  id from Account` and `SELECT id FROM Account` normalize to the
+/// same key. Literals are copied unchanged, since `'Acme'` and `'acme'` can
+/// match different records.
+fn normalize_query(soql: &str) -> String {
+    fn push_word(normalized: &mut String, word: &mut String) {
+        if SOQL_KEYWORDS
+            .iter()
+            .any(|keyword| keyword.eq_ignore_ascii_case(word))
+        {
+            normalized.push_str(&word.to_ascii_uppercase());
+        } else {
+            normalized.push_str(word);
+        }
+        word.clear();
+    }
+
+    let mut normalized = String::with_capacity(soql.len());
+    let mut word = String::new();
+    let mut space = false;
+    let mut chars = soql.trim().chars();
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            push_word(&mut normalized, &mut word);
+            space = true;
+            continue;
+        }
+        if space {
+            normalized.push(' ');
+            space = false;
+        }
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+            continue;
+        }
+
+        push_word(&mut normalized, &mut word);
+        normalized.push(c);
+        if c == '\'' {
+            while let Some(c) = chars.next() {
+                normalized.push(c);
+                match c {
+                    '\\' => normalized.extend(chars.next()),
+                    '\'' => break,
+                    _ => {}
+                }
+            }
+        }
+    }
+    push_word(&mut normalized, &mut word);
+
+    normalized
+}
+
 /// Cache key for individual records
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct RecordKey {
@@ -104,6 +373,73 @@ impl RecordKey {
     }
 }
 
+/// Cached query results, tagged with the sObject they were queried from
+#[derive(Clone)]
+struct QueryEntry {
+    /// Lowercased sObject name, `None` if the query couldn't be attributed
+    /// to a single sObject
+    sobject: Option<String>,
+    bytes: Vec<u8>,
+    stored: Instant,
+}
+
+/// The single sObject a query reads from, if it can be determined
+///
+/// Only plain queries are attributed. Subqueries, semi-joins, `TYPEOF` and
+/// relationship fields such as `Account.Name` read other objects too, so
+/// they return `None`.
+fn query_sobject(soql: &str) -> Option<String> {
+    // Blank out string literals so their contents aren't read as keywords
+    let mut stripped = String::with_capacity(soql.len());
+    let mut chars = soql.chars();
+    while let Some(c) = chars.next() {
+        if c != '\'' {
+            stripped.push(c);
+            continue;
+        }
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    chars.next();
+                }
+                '\'' => break,
+                _ => {}
+            }
+        }
+        stripped.push(' ');
+    }
+
+    let tokens: Vec<&str> = stripped
+        .split(|c: char| !(c.is_alphanumeric() || c == '_' || c == '.'))
+        .filter(|token| !token.is_empty())
+        .collect();
+
+    let count = |keyword: &str| {
+        tokens
+            .iter()
+            .filter(|token| token.eq_ignore_ascii_case(keyword))
+            .count()
+    };
+    if count("SELECT") != 1 || count("FROM") != 1 || count("TYPEOF") != 0 {
+        return None;
+    }
+
+    // Relationship fields; numbers and date literals start with a digit
+    if tokens
+        .iter()
+        .any(|token| token.contains('.') && !token.starts_with(|c: char| c.is_ascii_digit()))
+    {
+        return None;
+    }
+
+    let from = tokens
+        .iter()
+        .position(|token| token.eq_ignore_ascii_case("FROM"))?;
+    tokens
+        .get(from + 1)
+        .map(|sobject| sobject.to_ascii_lowercase())
+}
+
 /// Cached value wrapper with metadata
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct CachedValue<T> {
@@ -120,10 +456,61 @@ impl<T> CachedValue<T> {
     }
 }
 
+/// Hit, miss, insert and invalidation counts for a cache
+#[derive(Debug, Default)]
+struct CacheCounters {
+    hits: AtomicU64,
+    misses: AtomicU64,
+    inserts: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+impl CacheCounters {
+    fn lookup(&self, hit: bool) {
+        let counter = if hit { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn insert(&self) {
+        self.inserts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn invalidation(&self) {
+        self.invalidations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn stats(&self, entry_count: u64, weighted_size: u64) -> CacheStats {
+        CacheStats {
+            entry_count,
+            weighted_size,
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            inserts: self.inserts.load(Ordering::Relaxed),
+            invalidations: self.invalidations.load(Ordering::Relaxed),
+        }
+    }
+
+    fn reset(&self) {
+        for counter in [&self.hits, &self.misses, &self.inserts, &self.invalidations] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
 /// Query result cache
 pub struct QueryCache {
-    cache: Arc<Cache<QueryKey, Vec<u8>>>,
+    cache: Arc<Cache<QueryKey, QueryEntry>>,
     enabled: bool,
+    normalize: bool,
+    cache_empty: bool,
+    max_entry_bytes: Option<usize>,
+    stale_after: Option<Duration>,
+    /// Keys with a stale-while-revalidate refresh in flight
+    refreshing: Mutex<HashSet<QueryKey>>,
+    /// Bumped before every invalidation, so a refresh that overlapped one
+    /// can tell its result may predate a write
+    epoch: AtomicU64,
+    counters: CacheCounters,
 }
 
 impl QueryCache {
@@ -131,10 +518,11 @@ impl QueryCache {
     pub fn new(config: CacheConfig) -> Self {
         let enabled = config.max_capacity > 0 && config.ttl.as_secs() > 0;
 
-        let cache = Cache::builder()
-            .max_capacity(config.max_capacity)
+        let cache = config
+            .builder(|entry: &QueryEntry| entry.bytes.len())
             .time_to_live(config.ttl)
             .time_to_idle(config.tti.unwrap_or(config.ttl))
+            .support_invalidation_closures()
             .build();
 
         if enabled {
@@ -149,11 +537,73 @@ impl QueryCache {
         Self {
             cache: Arc::new(cache),
             enabled,
+            normalize: config.normalize_queries,
+            cache_empty: config.cache_empty_results,
+            max_entry_bytes: config.max_entry_bytes,
+            stale_after: config.stale_while_revalidate,
+            refreshing: Mutex::new(HashSet::new()),
+            epoch: AtomicU64::new(0),
+            counters: CacheCounters::default(),
+        }
+    }
+
+    fn key(&self, endpoint: QueryEndpoint, query: &str, all_pages: bool) -> QueryKey {
+        if self.normalize {
+            QueryKey::new(endpoint, normalize_query(query), all_pages)
+        } else {
+            QueryKey::new(endpoint, query, all_pages)
         }
     }
 
     /// Get cached query results
     pub async fn get<T>(&self, query: &str) -> Option<Vec<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.get_at(QueryEndpoint::Query, query).await
+    }
+
+    /// Get cached results of a query against `endpoint`
+    pub async fn get_at<T>(&self, endpoint: QueryEndpoint, query: &str) -> Option<Vec<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.get_result_at(endpoint, query, true)
+            .await
+            .map(|result| result.records)
+    }
+
+    /// Get cached results of a query against `endpoint`, with `totalSize`
+    /// and paging state
+    ///
+    /// `all_pages` picks between results followed to the last page and
+    /// first pages, which are cached separately. `totalSize` is `None` for
+    /// results stored through [`set_at`](Self::set_at), which count as all
+    /// pages. A cached `nextRecordsUrl` is a server-side cursor that
+    /// Salesforce expires after about 15 minutes, so it may be dead by the
+    /// time it's read.
+    pub async fn get_result_at<T>(
+        &self,
+        endpoint: QueryEndpoint,
+        query: &str,
+        all_pages: bool,
+    ) -> Option<QueryResult<T>>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        self.get_result_with_staleness(endpoint, query, all_pages)
+            .await
+            .map(|(result, _)| result)
+    }
+
+    /// Get cached results and whether they are past the
+    /// stale-while-revalidate age
+    pub(crate) async fn get_result_with_staleness<T>(
+        &self,
+        endpoint: QueryEndpoint,
+        query: &str,
+        all_pages: bool,
+    ) -> Option<(QueryResult<T>, bool)>
     where
         T: for<'de> Deserialize<'de>,
     {
@@ -161,13 +611,16 @@ impl QueryCache {
             return None;
         }
 
-        let key = QueryKey::new(query);
+        let key = self.key(endpoint, query, all_pages);
 
-        if let Some(cached_bytes) = self.cache.get(&key).await {
-            match serde_json::from_slice::<CachedValue<Vec<T>>>(&cached_bytes) {
+        let result = if let Some(entry) = self.cache.get(&key).await {
+            match serde_json::from_slice::<CachedValue<QueryResult<T>>>(&entry.bytes) {
                 Ok(cached_value) => {
                     debug!("Cache hit for query: {}", query);
-                    Some(cached_value.data)
+                    let stale = self
+                        .stale_after
+                        .is_some_and(|after| entry.stored.elapsed() >= after);
+                    Some((cached_value.data, stale))
                 }
                 Err(e) => {
                     debug!("Cache deserialization error: {}", e);
@@ -177,24 +630,85 @@ impl QueryCache {
         } else {
             debug!("Cache miss for query: {}", query);
             None
-        }
+        };
+        self.counters.lookup(result.is_some());
+        result
     }
 
     /// Store query results in cache
     pub async fn set<T>(&self, query: &str, data: Vec<T>) -> SfResult<()>
+    where
+        T: Serialize,
+    {
+        self.set_at(QueryEndpoint::Query, query, data).await
+    }
+
+    /// Store results of a query against `endpoint`
+    pub async fn set_at<T>(
+        &self,
+        endpoint: QueryEndpoint,
+        query: &str,
+        data: Vec<T>,
+    ) -> SfResult<()>
+    where
+        T: Serialize,
+    {
+        // The records alone say nothing about how many matched the query
+        let result = QueryResult {
+            total_size: None,
+            done: true,
+            next_records_url: None,
+            records: data,
+        };
+        self.set_result_at(endpoint, query, true, &result).await
+    }
+
+    /// Store results of a query against `endpoint`, with `totalSize` and
+    /// paging state
+    ///
+    /// `all_pages` tells a result followed to the last page from a first
+    /// page; see [`get_result_at`](Self::get_result_at).
+    pub async fn set_result_at<T>(
+        &self,
+        endpoint: QueryEndpoint,
+        query: &str,
+        all_pages: bool,
+        result: &QueryResult<T>,
+    ) -> SfResult<()>
+    where
+        T: Serialize,
+    {
+        self.insert(self.key(endpoint, query, all_pages), query, result)
+            .await
+    }
+
+    async fn insert<T>(&self, key: QueryKey, query: &str, result: &QueryResult<T>) -> SfResult<()>
     where
         T: Serialize,
     {
         if !self.enabled {
             return Ok(());
         }
+        if result.records.is_empty() && !self.cache_empty {
+            debug!("Not caching empty query results: {}", query);
+            return Ok(());
+        }
 
-        let key = QueryKey::new(query);
-        let cached_value = CachedValue::new(data);
+        let cached_value = CachedValue::new(result);
 
         match serde_json::to_vec(&cached_value) {
+            Ok(bytes) if oversized(self.max_entry_bytes, bytes.len()) => {
+                debug!("Not caching {} byte query results: {}", bytes.len(), query);
+                Ok(())
+            }
             Ok(bytes) => {
-                self.cache.insert(key, bytes).await;
+                let entry = QueryEntry {
+                    sobject: query_sobject(query),
+                    bytes,
+                    stored: Instant::now(),
+                };
+                self.cache.insert(key, entry).await;
+                self.counters.insert();
                 debug!("Cached query results: {}", query);
                 Ok(())
             }
@@ -205,33 +719,161 @@ impl QueryCache {
         }
     }
 
-    /// Invalidate cached query results
+    /// Claim the background refresh of a stale query, `None` if another
+    /// refresh of it is already in flight
+    ///
+    /// The claim is released when dropped.
+    pub(crate) fn claim_refresh(
+        self: &Arc<Self>,
+        endpoint: QueryEndpoint,
+        query: &str,
+        all_pages: bool,
+    ) -> Option<RefreshClaim> {
+        let key = self.key(endpoint, query, all_pages);
+        let claimed = self
+            .refreshing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.clone());
+
+        claimed.then(|| RefreshClaim {
+            cache: self.clone(),
+            key,
+            epoch: self.epoch.load(Ordering::SeqCst),
+        })
+    }
+
+    /// Store the result of a claimed refresh, unless the cache was
+    /// invalidated while it ran
+    ///
+    /// A refresh that started before a write may have read the records as
+    /// they were before it; caching that would undo the invalidation.
+    pub(crate) async fn set_refreshed<T>(
+        &self,
+        claim: &RefreshClaim,
+        query: &str,
+        result: &QueryResult<T>,
+    ) -> SfResult<()>
+    where
+        T: Serialize,
+    {
+        if self.epoch.load(Ordering::SeqCst) != claim.epoch {
+            debug!(
+                "Discarding refresh that overlapped an invalidation: {}",
+                query
+            );
+            return Ok(());
+        }
+
+        self.insert(claim.key.clone(), query, result).await?;
+
+        // An invalidation that began after the check above may have run
+        // before the insert, and so missed it
+        if self.epoch.load(Ordering::SeqCst) != claim.epoch {
+            debug!(
+                "Discarding refresh that overlapped an invalidation: {}",
+                query
+            );
+            self.cache.invalidate(&claim.key).await;
+        }
+        Ok(())
+    }
+
+    /// Drop the stale entry a refresh claimed without storing the result
+    ///
+    /// Unlike [`QueryCache::invalidate`] this leaves the epoch alone, so
+    /// other refreshes in flight still keep their results.
+    pub(crate) async fn discard_refresh(&self, claim: &RefreshClaim) {
+        self.cache.invalidate(&claim.key).await;
+        self.counters.invalidation();
+        debug!("Dropped stale query results: {}", claim.key.query);
+    }
+
+    /// Invalidate cached query results, from either endpoint
     pub async fn invalidate(&self, query: &str) {
         if !self.enabled {
             return;
         }
 
-        let key = QueryKey::new(query);
-        self.cache.invalidate(&key).await;
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+        for endpoint in [QueryEndpoint::Query, QueryEndpoint::QueryAll] {
+            for all_pages in [true, false] {
+                self.cache
+                    .invalidate(&self.key(endpoint, query, all_pages))
+                    .await;
+            }
+        }
+        self.counters.invalidation();
         debug!("Invalidated cache for query: {}", query);
     }
 
+    /// Invalidate cached queries that may read records of `sobject`
+    ///
+    /// Queries that couldn't be attributed to a single sObject, such as
+    /// those with subqueries or relationship fields, are invalidated too.
+    /// Changes made by triggers or roll-up summaries to other objects are
+    /// not tracked.
+    pub async fn invalidate_sobject(&self, sobject: &str) {
+        if !self.enabled {
+            return;
+        }
+
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+        let sobject_lower = sobject.to_ascii_lowercase();
+        let invalidated = self.cache.invalidate_entries_if(move |_, entry| {
+            entry
+                .sobject
+                .as_ref()
+                .map_or(true, |cached| *cached == sobject_lower)
+        });
+        if let Err(e) = invalidated {
+            debug!("Falling back to clearing the query cache: {}", e);
+            self.cache.invalidate_all();
+        }
+        self.counters.invalidation();
+        debug!("Invalidated cached queries for {}", sobject);
+    }
+
     /// Clear all cached queries
     pub async fn clear(&self) {
         if !self.enabled {
             return;
         }
 
+        self.epoch.fetch_add(1, Ordering::SeqCst);
         self.cache.invalidate_all();
+        self.counters.invalidation();
         info!("Cleared all query cache entries");
     }
 
     /// Get cache statistics
     pub fn stats(&self) -> CacheStats {
-        CacheStats {
-            entry_count: self.cache.entry_count(),
-            weighted_size: self.cache.weighted_size(),
-        }
+        self.counters
+            .stats(self.cache.entry_count(), self.cache.weighted_size())
+    }
+
+    /// Reset the hit, miss, insert and invalidation counters
+    pub fn stats_reset(&self) {
+        self.counters.reset();
+    }
+}
+
+/// An in-flight stale-while-revalidate refresh, from
+/// [`QueryCache::claim_refresh`]
+pub(crate) struct RefreshClaim {
+    cache: Arc<QueryCache>,
+    key: QueryKey,
+    /// Invalidation epoch when the refresh was claimed
+    epoch: u64,
+}
+
+impl Drop for RefreshClaim {
+    fn drop(&mut self) {
+        self.cache
+            .refreshing
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&self.key);
     }
 }
 
@@ -243,12 +885,40 @@ pub struct CacheStats {
 
     /// Total weighted size of entries
     pub weighted_size: u64,
+
+    /// Lookups answered from the cache
+    pub hits: u64,
+
+    /// Lookups that found nothing usable
+    pub misses: u64,
+
+    /// Entries stored
+    pub inserts: u64,
+
+    /// Invalidation and clear calls
+    pub invalidations: u64,
+}
+
+impl CacheStats {
+    /// Fraction of lookups that were hits, 0.0 before any lookup
+    pub fn hit_ratio(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
 }
 
 /// Record-level cache for individual SObject records
 pub struct RecordCache {
     cache: Arc<Cache<RecordKey, Vec<u8>>>,
+    /// Whether a record exists, for IDs checked without fetching the body
+    existence: Cache<RecordKey, bool>,
     enabled: bool,
+    max_entry_bytes: Option<usize>,
+    counters: CacheCounters,
 }
 
 impl RecordCache {
@@ -256,15 +926,24 @@ impl RecordCache {
     pub fn new(config: CacheConfig) -> Self {
         let enabled = config.max_capacity > 0 && config.ttl.as_secs() > 0;
 
-        let cache = Cache::builder()
-            .max_capacity(config.max_capacity)
+        let cache = config
+            .builder(Vec::len)
             .time_to_live(config.ttl)
             .time_to_idle(config.tti.unwrap_or(config.ttl))
+            .support_invalidation_closures()
+            .build();
+        let existence = Cache::builder()
+            .max_capacity(config.max_capacity)
+            .time_to_live(config.ttl)
+            .support_invalidation_closures()
             .build();
 
         Self {
             cache: Arc::new(cache),
+            existence,
             enabled,
+            max_entry_bytes: config.max_entry_bytes,
+            counters: CacheCounters::default(),
         }
     }
 
@@ -279,7 +958,7 @@ impl RecordCache {
 
         let key = RecordKey::new(sobject, id);
 
-        if let Some(cached_bytes) = self.cache.get(&key).await {
+        let record = if let Some(cached_bytes) = self.cache.get(&key).await {
             match serde_json::from_slice::<CachedValue<T>>(&cached_bytes) {
                 Ok(cached_value) => {
                     debug!("Cache hit for {} {}", sobject, id);
@@ -292,7 +971,9 @@ impl RecordCache {
             }
         } else {
             None
-        }
+        };
+        self.counters.lookup(record.is_some());
+        record
     }
 
     /// Store record in cache
@@ -308,8 +989,13 @@ impl RecordCache {
         let cached_value = CachedValue::new(data);
 
         match serde_json::to_vec(&cached_value) {
+            Ok(bytes) if oversized(self.max_entry_bytes, bytes.len()) => {
+                debug!("Not caching {} byte {} {}", bytes.len(), sobject, id);
+                Ok(())
+            }
             Ok(bytes) => {
                 self.cache.insert(key, bytes).await;
+                self.counters.insert();
                 debug!("Cached {} {}", sobject, id);
                 Ok(())
             }
@@ -317,6 +1003,36 @@ impl RecordCache {
         }
     }
 
+    /// Whether a record is known to exist (`None` if unknown)
+    ///
+    /// A cached record body counts as existing.
+    pub async fn get_exists(&self, sobject: &str, id: &str) -> Option<bool> {
+        if !self.enabled {
+            return None;
+        }
+
+        let key = RecordKey::new(sobject, id);
+        let exists = if self.cache.contains_key(&key) {
+            Some(true)
+        } else {
+            self.existence.get(&key).await
+        };
+        self.counters.lookup(exists.is_some());
+        exists
+    }
+
+    /// Record whether a record exists
+    pub async fn set_exists(&self, sobject: &str, id: &str, exists: bool) {
+        if !self.enabled {
+            return;
+        }
+
+        self.existence
+            .insert(RecordKey::new(sobject, id), exists)
+            .await;
+        self.counters.insert();
+    }
+
     /// Invalidate cached record
     pub async fn invalidate(&self, sobject: &str, id: &str) {
         if !self.enabled {
@@ -324,7 +1040,9 @@ impl RecordCache {
         }
 
         let key = RecordKey::new(sobject, id);
+        self.existence.invalidate(&key).await;
         self.cache.invalidate(&key).await;
+        self.counters.invalidation();
         debug!("Invalidated cache for {} {}", sobject, id);
     }
 
@@ -337,11 +1055,272 @@ impl RecordCache {
         // Note: This is expensive - iterates all keys
         // Consider adding an index if this becomes a common operation
         let sobject_owned = sobject.to_string();
-        let _ = self
+        if let Err(e) = self
             .cache
-            .invalidate_entries_if(move |key, _| key.sobject == sobject_owned);
+            .invalidate_entries_if(move |key, _| key.sobject == sobject_owned)
+        {
+            debug!("Falling back to clearing the record cache: {}", e);
+            self.cache.invalidate_all();
+        }
+        let sobject_owned = sobject.to_string();
+        if let Err(e) = self
+            .existence
+            .invalidate_entries_if(move |key, _| key.sobject == sobject_owned)
+        {
+            debug!("Falling back to clearing the existence cache: {}", e);
+            self.existence.invalidate_all();
+        }
+        self.counters.invalidation();
         info!("Invalidated all cached {} records", sobject);
     }
+
+    /// Clear all cached records
+    pub async fn clear(&self) {
+        self.cache.invalidate_all();
+        self.existence.invalidate_all();
+        self.counters.invalidation();
+        info!("Cleared all record cache entries");
+    }
+
+    /// Get cache statistics, counting record bodies and existence checks
+    pub fn stats(&self) -> CacheStats {
+        self.counters.stats(
+            self.cache.entry_count() + self.existence.entry_count(),
+            self.cache.weighted_size() + self.existence.weighted_size(),
+        )
+    }
+
+    /// Reset the hit, miss, insert and invalidation counters
+    pub fn stats_reset(&self) {
+        self.counters.reset();
+    }
+}
+
+/// `ETag` and `Last-Modified` of a cached response, for conditional requests
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Validators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Validators sent with a response
+    pub(crate) fn from_headers(headers: &HeaderMap) -> Self {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    /// Make `request` conditional, so an unchanged resource returns 304
+    pub(crate) fn apply(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some(etag) = &self.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &self.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+}
+
+/// Cached metadata and the validators its response came with
+#[derive(Clone)]
+pub(crate) struct MetadataEntry {
+    bytes: Vec<u8>,
+    stored: Instant,
+    pub(crate) validators: Validators,
+}
+
+/// Expires metadata after `metadata_ttl`, unless it can be revalidated
+///
+/// Entries with validators stay past their TTL, up to `max_age` after they
+/// were stored or last revalidated, so a stale one can be refreshed with a
+/// 304 instead of a new download.
+struct MetadataExpiry {
+    ttl: Duration,
+    max_age: Duration,
+}
+
+impl MetadataExpiry {
+    fn expiry(&self, entry: &MetadataEntry) -> Option<Duration> {
+        if entry.validators.is_empty() {
+            Some(self.ttl)
+        } else {
+            Some(self.ttl.max(self.max_age))
+        }
+    }
+}
+
+impl Expiry<String, MetadataEntry> for MetadataExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        entry: &MetadataEntry,
+        _created_at: Instant,
+    ) -> Option<Duration> {
+        self.expiry(entry)
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        entry: &MetadataEntry,
+        _updated_at: Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        self.expiry(entry)
+    }
+}
+
+/// Cache for org metadata such as describe results, keyed by name
+pub struct MetadataCache {
+    cache: Arc<Cache<String, MetadataEntry>>,
+    enabled: bool,
+    ttl: Duration,
+    max_entry_bytes: Option<usize>,
+}
+
+impl MetadataCache {
+    /// Create a new metadata cache using `config.metadata_ttl`
+    pub fn new(config: CacheConfig) -> Self {
+        let enabled = config.max_capacity > 0 && config.metadata_ttl.as_secs() > 0;
+
+        let cache = config
+            .builder(|entry: &MetadataEntry| entry.bytes.len())
+            .expire_after(MetadataExpiry {
+                ttl: config.metadata_ttl,
+                max_age: config.metadata_max_age,
+            })
+            .build();
+
+        Self {
+            cache: Arc::new(cache),
+            enabled,
+            ttl: config.metadata_ttl,
+            max_entry_bytes: config.max_entry_bytes,
+        }
+    }
+
+    /// Get cached metadata, if still within `metadata_ttl`
+    pub async fn get<T>(&self, key: &str) -> Option<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        if !self.enabled {
+            return None;
+        }
+
+        let entry = self.cache.get(key).await?;
+        if entry.stored.elapsed() >= self.ttl {
+            debug!("Cached metadata {} needs revalidating", key);
+            return None;
+        }
+        match serde_json::from_slice::<CachedValue<T>>(&entry.bytes) {
+            Ok(cached_value) => {
+                debug!("Metadata cache hit for {}", key);
+                Some(cached_value.data)
+            }
+            Err(e) => {
+                debug!("Cache deserialization error: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Store metadata in cache
+    pub async fn set<T>(&self, key: &str, data: &T) -> SfResult<()>
+    where
+        T: Serialize,
+    {
+        self.set_validated(key, data, Validators::default()).await
+    }
+
+    /// Store metadata with the validators of the response it came from
+    pub(crate) async fn set_validated<T>(
+        &self,
+        key: &str,
+        data: &T,
+        validators: Validators,
+    ) -> SfResult<()>
+    where
+        T: Serialize,
+    {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let bytes = serde_json::to_vec(&CachedValue::new(data))
+            .map_err(|e| SfError::Cache(format!("Serialization failed: {}", e)))?;
+        if oversized(self.max_entry_bytes, bytes.len()) {
+            debug!("Not caching {} byte metadata {}", bytes.len(), key);
+            return Ok(());
+        }
+        let entry = MetadataEntry {
+            bytes,
+            stored: Instant::now(),
+            validators,
+        };
+        self.cache.insert(key.to_string(), entry).await;
+        debug!("Cached metadata {}", key);
+        Ok(())
+    }
+
+    /// A cached entry that can be revalidated, fresh or not
+    pub(crate) async fn get_revalidatable(&self, key: &str) -> Option<MetadataEntry> {
+        if !self.enabled {
+            return None;
+        }
+
+        self.cache
+            .get(key)
+            .await
+            .filter(|entry| !entry.validators.is_empty())
+    }
+
+    /// Mark `entry` fresh again after a 304, returning its data
+    pub(crate) async fn revalidated<T>(&self, key: &str, mut entry: MetadataEntry) -> SfResult<T>
+    where
+        T: for<'de> Deserialize<'de>,
+    {
+        let cached_value = serde_json::from_slice::<CachedValue<T>>(&entry.bytes)
+            .map_err(|e| SfError::Cache(format!("Deserialization failed: {}", e)))?;
+
+        entry.stored = Instant::now();
+        self.cache.insert(key.to_string(), entry).await;
+        debug!("Revalidated cached metadata {}", key);
+        Ok(cached_value.data)
+    }
+
+    /// Invalidate cached metadata
+    pub async fn invalidate(&self, key: &str) {
+        if !self.enabled {
+            return;
+        }
+
+        self.cache.invalidate(key).await;
+        debug!("Invalidated metadata cache for {}", key);
+    }
+
+    /// Clear all cached metadata
+    pub async fn clear(&self) {
+        if !self.enabled {
+            return;
+        }
+
+        self.cache.invalidate_all();
+        info!("Cleared all metadata cache entries");
+    }
 }
 
 #[cfg(test)]
@@ -376,6 +1355,356 @@ mod tests {
         let cached = cache.get::<TestRecord>(query).await;
         assert!(cached.is_some());
         assert_eq!(cached.unwrap(), data);
+
+        // The record count isn't passed off as totalSize
+        let result = cache
+            .get_result_at::<TestRecord>(QueryEndpoint::Query, query, true)
+            .await
+            .unwrap();
+        assert_eq!(result.total_size, None);
+        assert!(result.next_records_url.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_query_cache_endpoints() {
+        let cache = QueryCache::new(CacheConfig::new().ttl(Duration::from_secs(60)));
+
+        let query = "SELECT Id FROM Account";
+        let live = vec![TestRecord {
+            id: "1".to_string(),
+            name: "Live".to_string(),
+        }];
+        cache.set(query, live.clone()).await.unwrap();
+
+        assert!(cache
+            .get_at::<TestRecord>(QueryEndpoint::QueryAll, query)
+            .await
+            .is_none());
+
+        let all = vec![TestRecord {
+            id: "2".to_string(),
+            name: "Deleted".to_string(),
+        }];
+        cache
+            .set_at(QueryEndpoint::QueryAll, query, all.clone())
+            .await
+            .unwrap();
+        assert_eq!(cache.get::<TestRecord>(query).await.unwrap(), live);
+        assert_eq!(
+            cache
+                .get_at::<TestRecord>(QueryEndpoint::QueryAll, query)
+                .await
+                .unwrap(),
+            all
+        );
+
+        cache.invalidate(query).await;
+        assert!(cache.get::<TestRecord>(query).await.is_none());
+        assert!(cache
+            .get_at::<TestRecord>(QueryEndpoint::QueryAll, query)
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_skip_empty_results() {
+        let cache = QueryCache::new(
+            CacheConfig::new()
+                .ttl(Duration::from_secs(60))
+                .cache_empty_results(false),
+        );
+        let empty = "SELECT Id FROM Task WHERE Status = 'New'";
+        let one = "SELECT Id FROM Account LIMIT 1";
+
+        cache.set::<TestRecord>(empty, Vec::new()).await.unwrap();
+        let record = TestRecord {
+            id: "1".to_string(),
+            name: "Test".to_string(),
+        };
+        cache.set(one, vec![record.clone()]).await.unwrap();
+
+        assert!(cache.get::<TestRecord>(empty).await.is_none());
+        assert_eq!(cache.get::<TestRecord>(one).await, Some(vec![record]));
+    }
+
+    #[tokio::test]
+    async fn test_max_entry_bytes() {
+        let cache = QueryCache::new(
+            CacheConfig::new()
+                .ttl(Duration::from_secs(60))
+                .max_entry_bytes(200),
+        );
+        let record = |name: &str| TestRecord {
+            id: "1".to_string(),
+            name: name.to_string(),
+        };
+
+        cache.set("small", vec![record("Test")]).await.unwrap();
+        cache
+            .set("large", vec![record(&"x".repeat(500))])
+            .await
+            .unwrap();
+
+        assert!(cache.get::<TestRecord>("small").await.is_some());
+        assert!(cache.get::<TestRecord>("large").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_eviction_by_weight() {
+        let cache = RecordCache::new(
+            CacheConfig::new()
+                .ttl(Duration::from_secs(60))
+                .max_bytes(2_000),
+        );
+        for id in 0..10 {
+            let record = TestRecord {
+                id: id.to_string(),
+                name: "x".repeat(500),
+            };
+            cache.set("Account", &id.to_string(), record).await.unwrap();
+        }
+        cache.cache.run_pending_tasks().await;
+
+        // Each entry is over 500 bytes, so far fewer than 10 fit
+        let stats = cache.stats();
+        assert!(stats.weighted_size <= 2_000, "{:?}", stats);
+        assert!(stats.entry_count <= 3, "{:?}", stats);
+        assert!(stats.entry_count >= 1, "{:?}", stats);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_discarded_after_invalidation() {
+        let cache = Arc::new(QueryCache::new(
+            CacheConfig::new().ttl(Duration::from_secs(60)),
+        ));
+        let query = "SELECT Id FROM Account";
+        let result = QueryResult::<TestRecord> {
+            total_size: Some(0),
+            done: true,
+            next_records_url: None,
+            records: Vec::new(),
+        };
+
+        // A write lands while the refresh is in flight
+        let claim = cache
+            .claim_refresh(QueryEndpoint::Query, query, true)
+            .unwrap();
+        cache.invalidate_sobject("Account").await;
+        cache.set_refreshed(&claim, query, &result).await.unwrap();
+        drop(claim);
+        assert!(cache.get::<TestRecord>(query).await.is_none());
+
+        let claim = cache
+            .claim_refresh(QueryEndpoint::Query, query, true)
+            .unwrap();
+        cache.set_refreshed(&claim, query, &result).await.unwrap();
+        assert!(cache.get::<TestRecord>(query).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_discarded_refresh_keeps_other_refreshes() {
+        let cache = Arc::new(QueryCache::new(
+            CacheConfig::new().ttl(Duration::from_secs(60)),
+        ));
+        let accounts = "SELECT Id FROM Account";
+        let contacts = "SELECT Id FROM Contact";
+        let result = QueryResult::<TestRecord> {
+            total_size: Some(0),
+            done: true,
+            next_records_url: None,
+            records: Vec::new(),
+        };
+        cache.set::<TestRecord>(accounts, Vec::new()).await.unwrap();
+
+        let skipped = cache
+            .claim_refresh(QueryEndpoint::Query, accounts, true)
+            .unwrap();
+        let kept = cache
+            .claim_refresh(QueryEndpoint::Query, contacts, true)
+            .unwrap();
+        cache.discard_refresh(&skipped).await;
+        cache.set_refreshed(&kept, contacts, &result).await.unwrap();
+
+        assert!(cache.get::<TestRecord>(accounts).await.is_none());
+        assert!(cache.get::<TestRecord>(contacts).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_query_cache_stats() {
+        let cache = QueryCache::new(CacheConfig::new().ttl(Duration::from_secs(60)));
+        let query = "SELECT Id FROM Account";
+
+        assert!(cache.get::<TestRecord>(query).await.is_none());
+        cache.set::<TestRecord>(query, Vec::new()).await.unwrap();
+        assert!(cache.get::<TestRecord>(query).await.is_some());
+        assert!(cache.get::<TestRecord>(query).await.is_some());
+        cache.invalidate(query).await;
+        assert!(cache.get::<TestRecord>(query).await.is_none());
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.inserts, 1);
+        assert_eq!(stats.invalidations, 1);
+        assert_eq!(stats.hit_ratio(), 0.5);
+
+        cache.stats_reset();
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.inserts), (0, 0, 0));
+        assert_eq!(stats.hit_ratio(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_query_keys_normalized() {
+        let cache = QueryCache::new(CacheConfig::new().ttl(Duration::from_secs(60)));
+        let data = vec![TestRecord {
+            id: "1".to_string(),
+            name: "Test".to_string(),
+        }];
+        cache
+            .set(
+                "SELECT Id FROM Account WHERE Name = 'Acme  Corp'",
+                data.clone(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(
+            cache
+                .get::<TestRecord>("  select Id\n  from Account where Name = 'Acme  Corp' ")
+                .await,
+            Some(data)
+        );
+        // Literals are compared exactly
+        assert!(cache
+            .get::<TestRecord>("SELECT Id FROM Account WHERE Name = 'Acme Corp'")
+            .await
+            .is_none());
+        assert!(cache
+            .get::<TestRecord>("SELECT Id FROM Account WHERE Name = 'acme  corp'")
+            .await
+            .is_none());
+
+        let exact = QueryCache::new(
+            CacheConfig::new()
+                .ttl(Duration::from_secs(60))
+                .normalize_queries(false),
+        );
+        exact
+            .set::<TestRecord>("SELECT Id FROM Account", Vec::new())
+            .await
+            .unwrap();
+        assert!(exact
+            .get::<TestRecord>("select Id from Account")
+            .await
+            .is_none());
+    }
+
+    #[test]
+    fn test_normalize_query() {
+        assert_eq!(
+            normalize_query("select  Id,Name\tfrom Account where Name like 'it\\'s  a  test%'"),
+            "SELECT Id,Name FROM Account WHERE Name LIKE 'it\\'s  a  test%'"
+        );
+        assert_eq!(
+            normalize_query("SELECT Id FROM Account ORDER BY Name desc nulls last LIMIT 5"),
+            "SELECT Id FROM Account ORDER BY Name DESC NULLS LAST LIMIT 5"
+        );
+    }
+
+    #[test]
+    fn test_query_sobject() {
+        assert_eq!(
+            query_sobject("SELECT Id, Name FROM Account WHERE Name = 'x.y FROM Contact'"),
+            Some("account".to_string())
+        );
+        assert_eq!(
+            query_sobject(
+                "select count() from Contact where CreatedDate > 2024-01-01T00:00:00.000Z"
+            ),
+            Some("contact".to_string())
+        );
+        assert_eq!(query_sobject("SELECT Id, Account.Name FROM Contact"), None);
+        assert_eq!(
+            query_sobject("SELECT Id, (SELECT Id FROM Contacts) FROM Account"),
+            None
+        );
+        assert_eq!(
+            query_sobject("SELECT Id FROM Account WHERE Id IN (SELECT AccountId FROM Contact)"),
+            None
+        );
+        assert_eq!(query_sobject("not soql"), None);
+    }
+
+    #[tokio::test]
+    async fn test_query_cache_invalidate_sobject() {
+        let cache = QueryCache::new(CacheConfig::new().ttl(Duration::from_secs(60)));
+        let accounts = "SELECT Id FROM Account";
+        let contacts = "SELECT Id FROM Contact";
+        let joined = "SELECT Id, Account.Name FROM Contact";
+        for query in [accounts, contacts, joined] {
+            cache.set::<TestRecord>(query, Vec::new()).await.unwrap();
+        }
+
+        cache.invalidate_sobject("Contact").await;
+        assert!(cache.get::<TestRecord>(accounts).await.is_some());
+        assert!(cache.get::<TestRecord>(contacts).await.is_none());
+        assert!(cache.get::<TestRecord>(joined).await.is_none());
+
+        // Unattributed queries are dropped by writes to any object
+        cache.set::<TestRecord>(joined, Vec::new()).await.unwrap();
+        cache.invalidate_sobject("Lead").await;
+        assert!(cache.get::<TestRecord>(accounts).await.is_some());
+        assert!(cache.get::<TestRecord>(joined).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_record_existence() {
+        let cache = RecordCache::new(CacheConfig::new().ttl(Duration::from_secs(60)));
+
+        assert_eq!(cache.get_exists("Account", "001A").await, None);
+
+        cache.set_exists("Account", "001A", false).await;
+        assert_eq!(cache.get_exists("Account", "001A").await, Some(false));
+
+        // A cached body means the record exists
+        let record = TestRecord {
+            id: "001B".to_string(),
+            name: "Test".to_string(),
+        };
+        cache.set("Account", "001B", record).await.unwrap();
+        assert_eq!(cache.get_exists("Account", "001B").await, Some(true));
+
+        cache.invalidate("Account", "001A").await;
+        assert_eq!(cache.get_exists("Account", "001A").await, None);
+
+        cache.clear().await;
+        assert_eq!(cache.get_exists("Account", "001B").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_revalidatable_metadata_expires_after_max_age() {
+        let cache = MetadataCache::new(
+            CacheConfig::new()
+                .metadata_ttl(Duration::from_secs(1))
+                .metadata_max_age(Duration::from_secs(2)),
+        );
+        let validators = Validators {
+            etag: Some("\"v1\"".to_string()),
+            last_modified: None,
+        };
+        cache
+            .set_validated("describe/Account", &"Account", validators)
+            .await
+            .unwrap();
+
+        // Stale but still revalidatable
+        tokio::time::sleep(Duration::from_millis(1200)).await;
+        assert!(cache.get::<String>("describe/Account").await.is_none());
+        assert!(cache.get_revalidatable("describe/Account").await.is_some());
+
+        tokio::time::sleep(Duration::from_millis(1000)).await;
+        assert!(cache.get_revalidatable("describe/Account").await.is_none());
     }
 
     #[tokio::test]
@@ -2,9 +2,17 @@
 //!
 //! Provides type-safe methods for manipulating Salesforce records.
 
-use crate::error::{SfError, SfResult};
-use serde::{Deserialize, Serialize};
-use tracing::{debug, info};
+use crate::auth::{self, TokenProvider};
+use crate::error::{ErrorCode, SfError, SfResult};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, warn};
 
 /// Response from a successful insert operation
 #[derive(Debug, Deserialize, Clone)]
@@ -18,6 +26,10 @@ pub struct InsertResponse {
     /// Any errors that occurred
     #[serde(default)]
     pub errors: Vec<SalesforceError>,
+
+    /// Whether an upsert created the record rather than updating it
+    #[serde(default)]
+    pub created: bool,
 }
 
 /// Response from an update or delete operation
@@ -31,12 +43,31 @@ pub struct UpdateResponse {
     pub errors: Vec<SalesforceError>,
 }
 
+/// A record created by `insert_and_fetch`, with the result of reading it back
+///
+/// The record exists once this is returned, even if `record` is an error.
+#[derive(Debug)]
+pub struct InsertedRecord<T> {
+    /// ID of the created record
+    pub id: String,
+
+    /// The stored record, or the error from fetching it
+    pub record: SfResult<T>,
+}
+
+impl<T> InsertedRecord<T> {
+    /// The stored record, discarding the ID on fetch failure
+    pub fn into_record(self) -> SfResult<T> {
+        self.record
+    }
+}
+
 /// Salesforce error detail
 #[derive(Debug, Deserialize, Clone)]
 pub struct SalesforceError {
     /// Error status code
-    #[serde(rename = "statusCode")]
-    pub status_code: String,
+    #[serde(rename = "statusCode", alias = "errorCode")]
+    pub status_code: ErrorCode,
 
     /// Error message
     pub message: String,
@@ -44,6 +75,351 @@ pub struct SalesforceError {
     /// Fields that caused the error
     #[serde(default)]
     pub fields: Vec<String>,
+
+    /// Matching records for `DUPLICATES_DETECTED` errors
+    #[serde(rename = "duplicateResult", default)]
+    pub duplicate_result: Option<DuplicateResult>,
+}
+
+/// Details of a `DUPLICATES_DETECTED` error
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DuplicateResult {
+    /// Whether the duplicate rule allows saving anyway
+    pub allow_save: bool,
+
+    /// API name of the duplicate rule that fired
+    pub duplicate_rule: String,
+
+    /// Object the duplicate rule belongs to
+    pub duplicate_rule_entity_type: String,
+
+    /// Message configured on the duplicate rule
+    pub error_message: Option<String>,
+
+    /// Matches per matching rule
+    pub match_results: Vec<MatchResult>,
+}
+
+impl DuplicateResult {
+    /// All matching records across matching rules
+    pub fn candidates(&self) -> impl Iterator<Item = &MatchRecord> {
+        self.match_results
+            .iter()
+            .flat_map(|result| &result.match_records)
+    }
+}
+
+/// Records found by one matching rule
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MatchResult {
+    /// Object of the matched records
+    pub entity_type: String,
+
+    /// API name of the matching rule
+    pub rule: String,
+
+    /// Matching engine, e.g. `FuzzyMatchEngine`
+    pub match_engine: String,
+
+    /// Matched records
+    pub match_records: Vec<MatchRecord>,
+}
+
+/// A record matched by a duplicate rule
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct MatchRecord {
+    /// Match confidence from 0 to 100
+    pub match_confidence: f64,
+
+    /// The matching record (only `Id` unless record details were requested)
+    pub record: serde_json::Value,
+}
+
+impl MatchRecord {
+    /// ID of the matching record
+    pub fn id(&self) -> Option<&str> {
+        self.record.get("Id").and_then(|id| id.as_str())
+    }
+}
+
+/// Maximum number of records per sObject Collections request
+pub const COLLECTION_BATCH_SIZE: usize = 200;
+
+/// Per-record result of an sObject Collections operation
+#[derive(Debug, Deserialize, Clone)]
+pub struct SaveResult {
+    /// The record ID (absent when the row failed)
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Whether the row was saved
+    pub success: bool,
+
+    /// Errors for this row
+    #[serde(default)]
+    pub errors: Vec<SalesforceError>,
+
+    /// For upserts, whether the record was created rather than updated
+    #[serde(default)]
+    pub created: Option<bool>,
+}
+
+impl SaveResult {
+    /// Check whether any error on this row has the given status code
+    pub fn has_error(&self, status_code: &str) -> bool {
+        self.errors.iter().any(|e| e.status_code == status_code)
+    }
+
+    /// Whether a delete failed only because the record was already deleted
+    pub fn is_already_deleted(&self) -> bool {
+        !self.success && self.has_error("ENTITY_IS_DELETED")
+    }
+
+    /// Whether this row was valid but rolled back because another row in
+    /// an `allOrNone` request failed
+    pub fn is_rolled_back(&self) -> bool {
+        !self.success && self.has_error("ALL_OR_NONE_OPERATION_ROLLED_BACK")
+    }
+}
+
+/// Summary of a collections batch
+///
+/// Tells rows that actually failed apart from rows that were only rolled
+/// back by `allOrNone`, so callers know which input rows need fixing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchOutcome {
+    /// Rows that were saved
+    pub succeeded: usize,
+
+    /// Rows rolled back because another row failed
+    pub rolled_back: usize,
+
+    /// Indices of rows that failed on their own
+    pub failed_indices: Vec<usize>,
+}
+
+impl BatchOutcome {
+    /// Summarize results returned in input order
+    pub fn from_results(results: &[SaveResult]) -> Self {
+        let mut outcome = Self::default();
+        for (index, result) in results.iter().enumerate() {
+            if result.success {
+                outcome.succeeded += 1;
+            } else if result.is_rolled_back() {
+                outcome.rolled_back += 1;
+            } else {
+                outcome.failed_indices.push(index);
+            }
+        }
+        outcome
+    }
+
+    /// Index of the first row that failed on its own
+    pub fn failed_index(&self) -> Option<usize> {
+        self.failed_indices.first().copied()
+    }
+
+    /// Whether every row was saved
+    pub fn is_success(&self) -> bool {
+        self.failed_indices.is_empty() && self.rolled_back == 0
+    }
+}
+
+/// Options for `delete_many_resilient`
+#[derive(Debug, Clone)]
+pub struct ResilientDeleteOptions {
+    /// Total delete passes, including the first
+    pub max_passes: u32,
+
+    /// Wait before the second pass; doubles for each later pass
+    pub initial_backoff: Duration,
+
+    /// Longest wait between passes
+    pub max_backoff: Duration,
+}
+
+impl Default for ResilientDeleteOptions {
+    fn default() -> Self {
+        Self {
+            max_passes: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl ResilientDeleteOptions {
+    /// Create options with 3 passes and a 1 second initial backoff, capped
+    /// at 30 seconds
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the total number of passes (at least 1)
+    pub fn max_passes(mut self, max_passes: u32) -> Self {
+        self.max_passes = max_passes.max(1);
+        self
+    }
+
+    /// Set the wait before the second pass
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    /// Set the longest wait between passes
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+}
+
+/// A record that could not be deleted
+#[derive(Debug, Clone)]
+pub struct FailedDelete {
+    /// The record ID
+    pub id: String,
+
+    /// Errors from the last attempt
+    pub errors: Vec<SalesforceError>,
+}
+
+impl FailedDelete {
+    /// Error codes from the last attempt
+    pub fn error_codes(&self) -> Vec<ErrorCode> {
+        self.errors.iter().map(|e| e.status_code.clone()).collect()
+    }
+}
+
+/// Outcome of `delete_many_resilient`
+#[derive(Debug, Clone, Default)]
+pub struct DeleteReport {
+    /// Records deleted, including ones that were already deleted
+    pub deleted: usize,
+
+    /// Passes made, including the first
+    pub passes: u32,
+
+    /// Records that still could not be deleted after the last pass
+    ///
+    /// If a retry pass fails as a whole, its records are listed here with
+    /// the errors of that failure.
+    pub failed: Vec<FailedDelete>,
+}
+
+impl DeleteReport {
+    /// Whether every record was deleted
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Progress of a chunked ingest, passed to [`IngestOptions::on_progress`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IngestProgress {
+    /// Records sent so far, saved or not
+    pub done: usize,
+
+    /// Records in the whole call
+    pub total: usize,
+
+    /// Records that failed so far
+    pub failures: usize,
+}
+
+/// Callback invoked after each chunk of an ingest
+pub type ProgressCallback = Arc<dyn Fn(IngestProgress) + Send + Sync>;
+
+/// Options for chunked collection writes such as `insert_many_with`
+#[derive(Clone)]
+pub struct IngestOptions {
+    /// Records per request, at most [`COLLECTION_BATCH_SIZE`]
+    pub chunk_size: usize,
+
+    /// Maximum chunks in flight at once
+    pub concurrency: usize,
+
+    /// Roll back a whole chunk if any of its records fails
+    pub all_or_none: bool,
+
+    /// Called after each chunk completes
+    pub on_progress: Option<ProgressCallback>,
+
+    /// Stops scheduling new chunks once cancelled
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl Default for IngestOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: COLLECTION_BATCH_SIZE,
+            concurrency: 1,
+            all_or_none: false,
+            on_progress: None,
+            cancellation: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for IngestOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("IngestOptions")
+            .field("chunk_size", &self.chunk_size)
+            .field("concurrency", &self.concurrency)
+            .field("all_or_none", &self.all_or_none)
+            .field("on_progress", &self.on_progress.is_some())
+            .field("cancellation", &self.cancellation)
+            .finish()
+    }
+}
+
+impl IngestOptions {
+    /// Create options that send full chunks one at a time
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the records per request (clamped to 1..=200)
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.clamp(1, COLLECTION_BATCH_SIZE);
+        self
+    }
+
+    /// Set the maximum chunks in flight at once (at least 1)
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Roll back a whole chunk if any of its records fails
+    pub fn all_or_none(mut self, all_or_none: bool) -> Self {
+        self.all_or_none = all_or_none;
+        self
+    }
+
+    /// Call `callback` after each chunk completes
+    pub fn on_progress(
+        mut self,
+        callback: impl Fn(IngestProgress) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_progress = Some(Arc::new(callback));
+        self
+    }
+
+    /// Stop scheduling new chunks when `token` is cancelled
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
 }
 
 /// Batch response for multiple operations
@@ -68,109 +444,1669 @@ pub struct BatchResult {
     pub result: Option<serde_json::Value>,
 }
 
-/// Builder for upsert operations
-#[derive(Debug)]
-pub struct UpsertBuilder {
-    /// External ID field name
-    pub external_id_field: String,
+/// Maximum number of subrequests in one Composite API request
+pub const COMPOSITE_MAX_SUBREQUESTS: usize = 25;
+
+/// A single subrequest of a Composite API request
+#[derive(Debug, Clone, Serialize)]
+pub struct CompositeSubrequest {
+    /// HTTP method (`GET`, `POST`, `PATCH`, `DELETE`)
+    pub method: String,
+
+    /// URL relative to the instance, e.g. `/services/data/v57.0/sobjects/Account`
+    pub url: String,
+
+    /// Name later subrequests use to refer to this one's result
+    #[serde(rename = "referenceId")]
+    pub reference_id: String,
+
+    /// Request body
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<serde_json::Value>,
+}
+
+/// Builder for Composite API requests
+///
+/// Subrequests run in order and can refer to earlier results with reference
+/// expressions such as `@{newAccount.id}`, passed as plain strings in URLs
+/// or bodies.
+///
+/// # Example
+/// ```
+/// use salesforce_client::{CompositeRequestBuilder, DEFAULT_API_VERSION};
+/// use serde_json::json;
+///
+/// let request = CompositeRequestBuilder::for_api_version(DEFAULT_API_VERSION)
+///     .all_or_none(true)
+///     .create("newAccount", "Account", json!({"Name": "Acme"}))
+///     .create(
+///         "newContact",
+///         "Contact",
+///         json!({"LastName": "Smith", "AccountId": "@{newAccount.id}"}),
+///     );
+/// assert_eq!(request.len(), 2);
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct CompositeRequestBuilder {
+    #[serde(rename = "allOrNone")]
+    all_or_none: bool,
+
+    #[serde(rename = "compositeRequest")]
+    subrequests: Vec<CompositeSubrequest>,
+
+    /// API version in the URLs of `create`, `update` and `delete` subrequests
+    #[serde(skip)]
+    api_version: String,
+}
+
+impl CompositeRequestBuilder {
+    /// Create an empty composite request whose subrequest URLs use `version`
+    ///
+    /// [`SalesforceClient::composite_request`](crate::SalesforceClient::composite_request)
+    /// starts one for the client's configured version.
+    pub fn for_api_version(version: impl Into<String>) -> Self {
+        Self {
+            all_or_none: false,
+            subrequests: Vec::new(),
+            api_version: version.into(),
+        }
+    }
+
+    /// Roll back every subrequest if any of them fails
+    pub fn all_or_none(mut self, all_or_none: bool) -> Self {
+        self.all_or_none = all_or_none;
+        self
+    }
+
+    /// Add an arbitrary subrequest
+    pub fn request(
+        mut self,
+        method: &str,
+        url: impl Into<String>,
+        reference_id: impl Into<String>,
+        body: Option<serde_json::Value>,
+    ) -> Self {
+        self.subrequests.push(CompositeSubrequest {
+            method: method.to_uppercase(),
+            url: url.into(),
+            reference_id: reference_id.into(),
+            body,
+        });
+        self
+    }
+
+    /// Add a record creation
+    pub fn create(
+        self,
+        reference_id: impl Into<String>,
+        sobject: &str,
+        body: serde_json::Value,
+    ) -> Self {
+        let url = format!("/services/data/{}/sobjects/{}", self.api_version, sobject);
+        self.request("POST", url, reference_id, Some(body))
+    }
+
+    /// Add a record update
+    pub fn update(
+        self,
+        reference_id: impl Into<String>,
+        sobject: &str,
+        id: &str,
+        body: serde_json::Value,
+    ) -> Self {
+        let url = format!(
+            "/services/data/{}/sobjects/{}/{}",
+            self.api_version, sobject, id
+        );
+        self.request("PATCH", url, reference_id, Some(body))
+    }
+
+    /// Add a record deletion
+    pub fn delete(self, reference_id: impl Into<String>, sobject: &str, id: &str) -> Self {
+        let url = format!(
+            "/services/data/{}/sobjects/{}/{}",
+            self.api_version, sobject, id
+        );
+        self.request("DELETE", url, reference_id, None)
+    }
+
+    /// Add a GET of any relative URL
+    pub fn get(self, reference_id: impl Into<String>, url: impl Into<String>) -> Self {
+        self.request("GET", url, reference_id, None)
+    }
+
+    /// Subrequests added so far
+    pub fn subrequests(&self) -> &[CompositeSubrequest] {
+        &self.subrequests
+    }
+
+    /// Number of subrequests
+    pub fn len(&self) -> usize {
+        self.subrequests.len()
+    }
+
+    /// Whether no subrequests have been added
+    pub fn is_empty(&self) -> bool {
+        self.subrequests.is_empty()
+    }
+
+    /// Whether any subrequest modifies data
+    pub(crate) fn has_writes(&self) -> bool {
+        self.subrequests.iter().any(|s| s.method != "GET")
+    }
+
+    fn validate(&self) -> SfResult<()> {
+        if self.subrequests.is_empty() {
+            return Err(SfError::InvalidQuery(
+                "composite request has no subrequests".to_string(),
+            ));
+        }
+        if self.subrequests.len() > COMPOSITE_MAX_SUBREQUESTS {
+            return Err(SfError::InvalidQuery(format!(
+                "composite request has {} subrequests, the limit is {}",
+                self.subrequests.len(),
+                COMPOSITE_MAX_SUBREQUESTS
+            )));
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for subrequest in &self.subrequests {
+            if !seen.insert(subrequest.reference_id.as_str()) {
+                return Err(SfError::InvalidQuery(format!(
+                    "duplicate composite referenceId '{}'",
+                    subrequest.reference_id
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Result of one composite subrequest
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompositeSubresponse {
+    /// Reference ID of the subrequest
+    #[serde(rename = "referenceId")]
+    pub reference_id: String,
+
+    /// HTTP status of the subrequest
+    #[serde(rename = "httpStatusCode")]
+    pub status: u16,
+
+    /// Response body (a record, a create result, or a list of errors)
+    #[serde(default)]
+    pub body: serde_json::Value,
+
+    /// Response headers
+    #[serde(rename = "httpHeaders", default)]
+    pub headers: std::collections::HashMap<String, String>,
+}
+
+impl CompositeSubresponse {
+    /// Whether the subrequest returned a 2xx status
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Deserialize the body into a typed value
+    pub fn body_as<T: DeserializeOwned>(&self) -> SfResult<T> {
+        Ok(serde_json::from_value(self.body.clone())?)
+    }
+
+    /// Errors reported by a failed subrequest
+    pub fn errors(&self) -> Vec<SalesforceError> {
+        if self.is_success() {
+            return Vec::new();
+        }
+        serde_json::from_value(self.body.clone()).unwrap_or_default()
+    }
+}
+
+/// Response from a Composite API request
+#[derive(Debug, Clone, Deserialize)]
+pub struct CompositeResponse {
+    /// Subrequest results, in request order
+    #[serde(rename = "compositeResponse")]
+    pub responses: Vec<CompositeSubresponse>,
+}
+
+impl CompositeResponse {
+    /// Result of the subrequest with `reference_id`
+    pub fn get(&self, reference_id: &str) -> Option<&CompositeSubresponse> {
+        self.responses
+            .iter()
+            .find(|r| r.reference_id == reference_id)
+    }
+
+    /// Whether any subrequest failed
+    pub fn has_errors(&self) -> bool {
+        self.responses.iter().any(|r| !r.is_success())
+    }
+}
+
+/// Maximum number of records, across all levels, in one Composite Tree request
+pub const TREE_MAX_RECORDS: usize = 200;
+
+/// A record in a Composite Tree request, with its nested children
+///
+/// # Example
+/// ```
+/// use salesforce_client::TreeRecord;
+///
+/// let account = TreeRecord::new("Account", "acme")
+///     .field("Name", "Acme")
+///     .child(
+///         "Contacts",
+///         TreeRecord::new("Contact", "smith").field("LastName", "Smith"),
+///     );
+/// assert_eq!(account.record_count(), 2);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TreeRecord {
+    sobject: String,
+    reference_id: String,
+    fields: serde_json::Map<String, serde_json::Value>,
+    children: Vec<(String, Vec<TreeRecord>)>,
+}
+
+impl TreeRecord {
+    /// Create a record of type `sobject` named `reference_id` in the response
+    pub fn new(sobject: impl Into<String>, reference_id: impl Into<String>) -> Self {
+        Self {
+            sobject: sobject.into(),
+            reference_id: reference_id.into(),
+            fields: serde_json::Map::new(),
+            children: Vec::new(),
+        }
+    }
+
+    /// Set a field value
+    pub fn field(mut self, name: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.fields.insert(name.into(), value.into());
+        self
+    }
+
+    /// Add a child record under a child relationship, e.g. `Contacts`
+    pub fn child(mut self, relationship: impl Into<String>, record: TreeRecord) -> Self {
+        let relationship = relationship.into();
+        match self
+            .children
+            .iter_mut()
+            .find(|(name, _)| *name == relationship)
+        {
+            Some((_, records)) => records.push(record),
+            None => self.children.push((relationship, vec![record])),
+        }
+        self
+    }
+
+    /// The record's sObject type
+    pub fn sobject(&self) -> &str {
+        &self.sobject
+    }
+
+    /// The record's reference ID
+    pub fn reference_id(&self) -> &str {
+        &self.reference_id
+    }
+
+    /// Number of records in this tree, including this one
+    pub fn record_count(&self) -> usize {
+        1 + self
+            .children
+            .iter()
+            .flat_map(|(_, records)| records)
+            .map(TreeRecord::record_count)
+            .sum::<usize>()
+    }
 
-    /// External ID value
-    pub external_id_value: String,
+    fn to_value(&self) -> serde_json::Value {
+        let mut object = self.fields.clone();
+        object.insert(
+            "attributes".to_string(),
+            serde_json::json!({ "type": self.sobject, "referenceId": self.reference_id }),
+        );
+        for (relationship, records) in &self.children {
+            let records: Vec<_> = records.iter().map(TreeRecord::to_value).collect();
+            object.insert(
+                relationship.clone(),
+                serde_json::json!({ "records": records }),
+            );
+        }
+        serde_json::Value::Object(object)
+    }
+}
+
+/// Result for one record of a Composite Tree request
+#[derive(Debug, Clone, Deserialize)]
+pub struct TreeRecordResult {
+    /// Reference ID of the record
+    #[serde(rename = "referenceId")]
+    pub reference_id: String,
+
+    /// ID of the created record
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Errors for this record
+    #[serde(default)]
+    pub errors: Vec<SalesforceError>,
+}
+
+/// Response from a Composite Tree request
+///
+/// The tree is saved atomically: when `has_errors` is set no records were
+/// created and `results` only lists the records that failed.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TreeResponse {
+    /// Whether any record failed
+    #[serde(rename = "hasErrors")]
+    pub has_errors: bool,
+
+    /// Per-record results
+    pub results: Vec<TreeRecordResult>,
+}
+
+impl TreeResponse {
+    /// Created ID of the record with `reference_id`
+    pub fn id(&self, reference_id: &str) -> Option<&str> {
+        self.get(reference_id).and_then(|r| r.id.as_deref())
+    }
+
+    /// Result of the record with `reference_id`
+    pub fn get(&self, reference_id: &str) -> Option<&TreeRecordResult> {
+        self.results.iter().find(|r| r.reference_id == reference_id)
+    }
+}
+
+/// Longest window accepted by the getDeleted/getUpdated endpoints
+pub const MAX_REPLICATION_WINDOW_DAYS: i64 = 30;
+
+/// A record deleted within a getDeleted window
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeletedRecord {
+    /// The record ID
+    pub id: String,
+
+    /// When the record was deleted
+    #[serde(rename = "deletedDate", deserialize_with = "deserialize_sf_datetime")]
+    pub deleted_date: DateTime<Utc>,
+}
+
+/// Response from the getDeleted endpoint
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeletedRecords {
+    /// Records deleted in the window
+    #[serde(rename = "deletedRecords", default)]
+    pub deleted_records: Vec<DeletedRecord>,
+
+    /// Earliest time deleted records are still available for
+    #[serde(
+        rename = "earliestDateAvailable",
+        deserialize_with = "deserialize_sf_datetime"
+    )]
+    pub earliest_date_available: DateTime<Utc>,
+
+    /// Time up to which the response is complete; use as the next start
+    #[serde(
+        rename = "latestDateCovered",
+        deserialize_with = "deserialize_sf_datetime"
+    )]
+    pub latest_date_covered: DateTime<Utc>,
 }
 
-impl Clone for UpsertBuilder {
-    fn clone(&self) -> Self {
-        Self {
-            external_id_field: self.external_id_field.clone(),
-            external_id_value: self.external_id_value.clone(),
+/// Response from the getUpdated endpoint
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdatedRecords {
+    /// IDs of records created or updated in the window
+    #[serde(default)]
+    pub ids: Vec<String>,
+
+    /// Time up to which the response is complete; use as the next start
+    #[serde(
+        rename = "latestDateCovered",
+        deserialize_with = "deserialize_sf_datetime"
+    )]
+    pub latest_date_covered: DateTime<Utc>,
+}
+
+/// Parse Salesforce timestamps such as `2024-05-03T15:57:00.000+0000`
+fn deserialize_sf_datetime<'de, D>(deserializer: D) -> Result<DateTime<Utc>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = String::deserialize(deserializer)?;
+    DateTime::parse_from_str(&value, "%Y-%m-%dT%H:%M:%S%.f%z")
+        .or_else(|_| DateTime::parse_from_rfc3339(&value))
+        .map(|date| date.with_timezone(&Utc))
+        .map_err(serde::de::Error::custom)
+}
+
+/// Content of a file to upload
+///
+/// In-memory content can be resent, so uploads of it are retried and
+/// survive a session refresh. Content streamed from a reader is sent
+/// exactly once.
+pub enum FileContent {
+    /// In-memory bytes
+    Bytes(bytes::Bytes),
+
+    /// Bytes streamed from a reader, e.g. a `tokio::fs::File`
+    Reader(Box<dyn AsyncRead + Send + Unpin>),
+}
+
+impl FileContent {
+    /// Stream content from a reader without loading it into memory
+    pub fn from_reader(reader: impl AsyncRead + Send + Unpin + 'static) -> Self {
+        Self::Reader(Box::new(reader))
+    }
+}
+
+impl From<bytes::Bytes> for FileContent {
+    fn from(bytes: bytes::Bytes) -> Self {
+        Self::Bytes(bytes)
+    }
+}
+
+impl From<Vec<u8>> for FileContent {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::Bytes(bytes.into())
+    }
+}
+
+impl From<&'static [u8]> for FileContent {
+    fn from(bytes: &'static [u8]) -> Self {
+        Self::Bytes(bytes::Bytes::from_static(bytes))
+    }
+}
+
+impl std::fmt::Debug for FileContent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bytes(bytes) => write!(f, "FileContent::Bytes({} bytes)", bytes.len()),
+            Self::Reader(_) => write!(f, "FileContent::Reader"),
+        }
+    }
+}
+
+/// Headers describing downloaded blob content
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlobMetadata {
+    /// `Content-Type` of the blob
+    pub content_type: Option<String>,
+
+    /// `Content-Length` of the blob, if the server sent one
+    pub content_length: Option<u64>,
+}
+
+/// Downloaded blob content
+#[derive(Debug, Clone)]
+pub struct Blob {
+    /// Content type and length
+    pub metadata: BlobMetadata,
+
+    /// The content
+    pub data: bytes::Bytes,
+}
+
+/// Name of the blob field holding file content for `sobject`
+///
+/// `VersionData` for ContentVersion, `Body` for Attachment and Document.
+fn blob_field(sobject: &str) -> &'static str {
+    match sobject {
+        "ContentVersion" => "VersionData",
+        _ => "Body",
+    }
+}
+
+/// Whether assignment rules run for Leads and Cases (`Sforce-Auto-Assign`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AutoAssign {
+    /// Run the active assignment rule
+    True,
+
+    /// Don't run assignment rules
+    False,
+
+    /// Run the assignment rule with this ID
+    RuleId(String),
+}
+
+impl AutoAssign {
+    fn header_value(&self) -> &str {
+        match self {
+            Self::True => "TRUE",
+            Self::False => "FALSE",
+            Self::RuleId(id) => id,
+        }
+    }
+}
+
+/// Duplicate rule behavior (`Sforce-Duplicate-Rule-Header`)
+///
+/// Unset flags are left out of the header and take the server default.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DuplicateRuleHeader {
+    /// Save even if duplicate rules flag the record (when the rule allows it)
+    pub allow_save: Option<bool>,
+
+    /// Return full fields of matching records in duplicate errors
+    pub include_record_details: Option<bool>,
+
+    /// Only match records the current user can see
+    pub run_as_current_user: Option<bool>,
+}
+
+impl DuplicateRuleHeader {
+    /// Create a header with all flags unset
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set `allowSave`
+    pub fn allow_save(mut self, allow_save: bool) -> Self {
+        self.allow_save = Some(allow_save);
+        self
+    }
+
+    /// Set `includeRecordDetails`
+    pub fn include_record_details(mut self, include: bool) -> Self {
+        self.include_record_details = Some(include);
+        self
+    }
+
+    /// Set `runAsCurrentUser`
+    pub fn run_as_current_user(mut self, run_as_current_user: bool) -> Self {
+        self.run_as_current_user = Some(run_as_current_user);
+        self
+    }
+
+    /// Header value, e.g. `allowSave=true, includeRecordDetails=false`
+    pub fn header_value(&self) -> String {
+        [
+            ("allowSave", self.allow_save),
+            ("includeRecordDetails", self.include_record_details),
+            ("runAsCurrentUser", self.run_as_current_user),
+        ]
+        .iter()
+        .filter_map(|(name, value)| value.map(|value| format!("{}={}", name, value)))
+        .collect::<Vec<_>>()
+        .join(", ")
+    }
+}
+
+/// Options for `insert_with` and `upsert_with`
+#[derive(Debug, Clone, Default)]
+pub struct InsertOptions {
+    /// Assignment rule behavior; the org default applies when unset
+    pub auto_assign: Option<AutoAssign>,
+
+    /// Duplicate rule behavior; the org default applies when unset
+    pub duplicate_rule: Option<DuplicateRuleHeader>,
+
+    /// Maximum length in characters of string fields, by field name
+    ///
+    /// Longer values are cut client-side instead of failing the request
+    /// with `STRING_TOO_LONG`.
+    pub truncate_fields: HashMap<String, usize>,
+}
+
+impl InsertOptions {
+    /// Create options with the default behavior
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set assignment rule behavior
+    pub fn auto_assign(mut self, auto_assign: AutoAssign) -> Self {
+        self.auto_assign = Some(auto_assign);
+        self
+    }
+
+    /// Set duplicate rule behavior
+    pub fn duplicate_rule(mut self, duplicate_rule: DuplicateRuleHeader) -> Self {
+        self.duplicate_rule = Some(duplicate_rule);
+        self
+    }
+
+    /// Truncate string fields to a maximum number of characters
+    ///
+    /// A warning listing the truncated fields is logged for each request.
+    pub fn truncate_fields<K: Into<String>>(
+        mut self,
+        limits: impl IntoIterator<Item = (K, usize)>,
+    ) -> Self {
+        self.truncate_fields
+            .extend(limits.into_iter().map(|(field, max)| (field.into(), max)));
+        self
+    }
+
+    fn body<T: Serialize>(&self, data: &T) -> SfResult<serde_json::Value> {
+        serialize_truncated(data, &self.truncate_fields)
+    }
+
+    fn apply(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let request = match &self.duplicate_rule {
+            Some(header) => request.header("Sforce-Duplicate-Rule-Header", header.header_value()),
+            None => request,
+        };
+        apply_auto_assign(request, self.auto_assign.as_ref())
+    }
+}
+
+/// Options for `update_with`
+#[derive(Debug, Clone, Default)]
+pub struct UpdateOptions {
+    /// Only update if the record was not modified after this time
+    ///
+    /// Sent as `If-Unmodified-Since`; a newer `LastModifiedDate` fails the
+    /// update with `SfError::PreconditionFailed`.
+    pub if_unmodified_since: Option<DateTime<Utc>>,
+
+    /// Assignment rule behavior; the org default applies when unset
+    pub auto_assign: Option<AutoAssign>,
+
+    /// Maximum length in characters of string fields, by field name
+    ///
+    /// Longer values are cut client-side instead of failing the request
+    /// with `STRING_TOO_LONG`.
+    pub truncate_fields: HashMap<String, usize>,
+
+    /// Fields to clear, sent as explicit JSON `null`
+    pub fields_to_null: Vec<String>,
+}
+
+impl UpdateOptions {
+    /// Create options with no conditions
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only update if the record was not modified after `since`
+    pub fn if_unmodified_since(mut self, since: DateTime<Utc>) -> Self {
+        self.if_unmodified_since = Some(since);
+        self
+    }
+
+    /// Set assignment rule behavior
+    pub fn auto_assign(mut self, auto_assign: AutoAssign) -> Self {
+        self.auto_assign = Some(auto_assign);
+        self
+    }
+
+    /// Truncate string fields to a maximum number of characters
+    ///
+    /// A warning listing the truncated fields is logged for each request.
+    pub fn truncate_fields<K: Into<String>>(
+        mut self,
+        limits: impl IntoIterator<Item = (K, usize)>,
+    ) -> Self {
+        self.truncate_fields
+            .extend(limits.into_iter().map(|(field, max)| (field.into(), max)));
+        self
+    }
+
+    /// Clear fields by sending them as explicit `null`
+    ///
+    /// Mirrors the SOAP API's `fieldsToNull`. Listing a field that `data`
+    /// also sets to a non-null value fails the update before it is sent.
+    pub fn fields_to_null<I, S>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.fields_to_null
+            .extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    fn body<T: Serialize>(&self, data: &T) -> SfResult<serde_json::Value> {
+        let mut value = serialize_truncated(data, &self.truncate_fields)?;
+        if self.fields_to_null.is_empty() {
+            return Ok(value);
+        }
+
+        let Some(fields) = value.as_object_mut() else {
+            return Err(SfError::Serialization(serde::ser::Error::custom(
+                "fields_to_null requires the record to serialize to an object",
+            )));
+        };
+        for field in &self.fields_to_null {
+            match fields.get(field) {
+                Some(existing) if !existing.is_null() => {
+                    return Err(SfError::Serialization(serde::ser::Error::custom(format!(
+                        "field {} is listed in fields_to_null but has a value",
+                        field
+                    ))));
+                }
+                _ => {
+                    fields.insert(field.clone(), serde_json::Value::Null);
+                }
+            }
+        }
+        Ok(value)
+    }
+
+    fn apply(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let request = match self.if_unmodified_since {
+            Some(since) => request.header("If-Unmodified-Since", http_date(since)),
+            None => request,
+        };
+        apply_auto_assign(request, self.auto_assign.as_ref())
+    }
+}
+
+fn apply_auto_assign(
+    request: reqwest::RequestBuilder,
+    auto_assign: Option<&AutoAssign>,
+) -> reqwest::RequestBuilder {
+    match auto_assign {
+        Some(auto_assign) => request.header("Sforce-Auto-Assign", auto_assign.header_value()),
+        None => request,
+    }
+}
+
+/// Serialize a record body, cutting string fields to their limits
+///
+/// Truncation counts characters, not bytes, so multi-byte characters are
+/// never split.
+fn serialize_truncated<T: Serialize>(
+    data: &T,
+    limits: &HashMap<String, usize>,
+) -> SfResult<serde_json::Value> {
+    let mut value = serde_json::to_value(data)?;
+    if limits.is_empty() {
+        return Ok(value);
+    }
+
+    let mut truncated = Vec::new();
+    if let Some(fields) = value.as_object_mut() {
+        for (field, max) in limits {
+            let Some(serde_json::Value::String(text)) = fields.get_mut(field) else {
+                continue;
+            };
+            if let Some((end, _)) = text.char_indices().nth(*max) {
+                text.truncate(end);
+                truncated.push(field.as_str());
+            }
+        }
+    }
+
+    if !truncated.is_empty() {
+        truncated.sort_unstable();
+        warn!(
+            "Truncated fields to their maximum length: {}",
+            truncated.join(", ")
+        );
+    }
+    Ok(value)
+}
+
+/// Format a time as an RFC 7231 HTTP date, e.g. `Tue, 15 Nov 1994 08:12:31 GMT`
+fn http_date(time: DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// A quick action available on an object, from `/sobjects/{name}/quickActions`
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct QuickAction {
+    /// API name, e.g. `LogACall` or `Account.New_Opportunity`
+    pub name: String,
+
+    /// Display label
+    pub label: String,
+
+    /// Action type, e.g. `Create`, `Update`, `LogACall`
+    #[serde(rename = "type")]
+    pub action_type: String,
+
+    /// Standard action enum or custom action ID
+    pub action_enum_or_id: Option<String>,
+
+    /// Related resource URLs
+    pub urls: std::collections::HashMap<String, String>,
+}
+
+/// Result of invoking a quick action
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickActionResult {
+    /// ID of the record created or updated, when the action saves one
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Record the action ran against
+    #[serde(default)]
+    pub context_id: Option<String>,
+
+    /// Feed item posted by the action, if any
+    #[serde(default)]
+    pub feed_item_id: Option<String>,
+
+    /// Whether a record was created
+    #[serde(default)]
+    pub created: bool,
+
+    /// Whether the action succeeded
+    pub success: bool,
+
+    /// Errors reported by the action
+    #[serde(default)]
+    pub errors: Vec<SalesforceError>,
+}
+
+/// A request to submit a record for approval
+///
+/// # Example
+/// ```
+/// use salesforce_client::ApprovalRequest;
+///
+/// let request = ApprovalRequest::new("006xx000001a2bC")
+///     .comments("Discount above 20%")
+///     .next_approver_ids(["005xx000001X8Uz"])
+///     .process_definition("Opportunity_Discount");
+/// ```
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalRequest {
+    action_type: String,
+
+    /// Record to submit
+    pub context_id: String,
+
+    /// Submitter comments
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comments: Option<String>,
+
+    /// Approvers for the first step, when the process lets the submitter choose
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub next_approver_ids: Vec<String>,
+
+    /// Approval process to use instead of the first one whose entry criteria match
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub process_definition_name_or_id: Option<String>,
+
+    /// Skip the process's entry criteria
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_entry_criteria: Option<bool>,
+}
+
+impl ApprovalRequest {
+    /// Submit a record for approval
+    pub fn new(context_id: impl Into<String>) -> Self {
+        Self {
+            action_type: "Submit".to_string(),
+            context_id: context_id.into(),
+            comments: None,
+            next_approver_ids: Vec::new(),
+            process_definition_name_or_id: None,
+            skip_entry_criteria: None,
+        }
+    }
+
+    /// Set the submitter comments
+    pub fn comments(mut self, comments: impl Into<String>) -> Self {
+        self.comments = Some(comments.into());
+        self
+    }
+
+    /// Set the approvers for the first step
+    pub fn next_approver_ids<I, S>(mut self, ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.next_approver_ids = ids.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Use a specific approval process, by developer name or ID
+    pub fn process_definition(mut self, name_or_id: impl Into<String>) -> Self {
+        self.process_definition_name_or_id = Some(name_or_id.into());
+        self
+    }
+
+    /// Skip the process's entry criteria
+    pub fn skip_entry_criteria(mut self, skip: bool) -> Self {
+        self.skip_entry_criteria = Some(skip);
+        self
+    }
+}
+
+/// Result of one approval submission
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalResult {
+    /// Whether the record was submitted
+    pub success: bool,
+
+    /// Submitted record
+    #[serde(default)]
+    pub entity_id: Option<String>,
+
+    /// Approval process instance
+    #[serde(default)]
+    pub instance_id: Option<String>,
+
+    /// Instance status, e.g. `Pending` or `Approved`
+    #[serde(default)]
+    pub instance_status: Option<String>,
+
+    /// Users or queues assigned to the current step
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub actor_ids: Vec<String>,
+
+    /// Work items created for the current step
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub new_workitem_ids: Vec<String>,
+
+    /// Errors for a failed submission
+    #[serde(default, deserialize_with = "deserialize_null_default")]
+    pub errors: Vec<SalesforceError>,
+}
+
+/// An approval process, from `GET /process/approvals`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApprovalProcess {
+    /// Process ID
+    pub id: String,
+
+    /// Developer name
+    pub name: String,
+
+    /// Object the process applies to
+    pub object: String,
+
+    /// Description
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Evaluation order among the object's processes
+    #[serde(default)]
+    pub sort_order: Option<u32>,
+}
+
+/// Treat an explicit `null` like a missing field
+fn deserialize_null_default<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Default + Deserialize<'de>,
+{
+    Ok(Option::<T>::deserialize(deserializer)?.unwrap_or_default())
+}
+
+/// Builder for upsert operations
+#[derive(Debug)]
+pub struct UpsertBuilder {
+    /// External ID field name
+    pub external_id_field: String,
+
+    /// External ID value (`None` creates through the `Id` endpoint)
+    pub external_id_value: Option<String>,
+}
+
+impl Clone for UpsertBuilder {
+    fn clone(&self) -> Self {
+        Self {
+            external_id_field: self.external_id_field.clone(),
+            external_id_value: self.external_id_value.clone(),
+        }
+    }
+}
+
+impl UpsertBuilder {
+    /// Create a new upsert builder
+    pub fn new(external_id_field: impl Into<String>, external_id_value: impl Into<String>) -> Self {
+        Self {
+            external_id_field: external_id_field.into(),
+            external_id_value: Some(external_id_value.into()),
+        }
+    }
+
+    /// Upsert keyed on the Salesforce record ID
+    ///
+    /// Updates the record, failing with `SfError::NotFound` if it doesn't exist.
+    pub fn by_id(id: impl Into<String>) -> Self {
+        Self::new("Id", id)
+    }
+
+    /// Create a record through the `Id` upsert endpoint
+    ///
+    /// Lets create-or-update flows keyed on stored record IDs share one code
+    /// path: use this when no ID is stored yet, [`by_id`](Self::by_id) otherwise.
+    pub fn create_by_id() -> Self {
+        Self {
+            external_id_field: "Id".to_string(),
+            external_id_value: None,
+        }
+    }
+}
+
+/// CRUD operations implementation
+pub(crate) struct CrudOperations {
+    http_client: reqwest::Client,
+    /// REST API root that resource paths are appended to
+    api_url: String,
+    token_provider: Arc<dyn TokenProvider>,
+}
+
+impl CrudOperations {
+    /// Create a new CRUD operations handler rooted at `api_url`
+    pub fn new(
+        http_client: reqwest::Client,
+        api_url: String,
+        token_provider: Arc<dyn TokenProvider>,
+    ) -> Self {
+        Self {
+            http_client,
+            api_url,
+            token_provider,
+        }
+    }
+
+    /// A handler for the same org that targets the Tooling API
+    pub fn tooling(&self) -> Self {
+        Self {
+            http_client: self.http_client.clone(),
+            api_url: format!("{}/tooling", self.api_url),
+            token_provider: self.token_provider.clone(),
+        }
+    }
+
+    /// Retrieve a single record by ID
+    ///
+    /// Only `fields` are returned when non-empty, otherwise all fields.
+    pub async fn get<T: DeserializeOwned>(
+        &self,
+        sobject: &str,
+        id: &str,
+        fields: &[&str],
+    ) -> SfResult<T> {
+        let url = format!("{}/sobjects/{}/{}", self.api_url, sobject, id);
+        let fields = fields.join(",");
+
+        debug!("Retrieving {} record {}", sobject, id);
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            let request = self.http_client.get(&url);
+            if fields.is_empty() {
+                request
+            } else {
+                request.query(&[("fields", &fields)])
+            }
+        })
+        .await?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(SfError::NotFound {
+                sobject: sobject.to_string(),
+                id: id.to_string(),
+            });
+        }
+
+        if !status.is_success() {
+            let body = response.text().await?;
+            return Err(SfError::Api {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        // Parse from text so malformed bodies surface as serialization errors
+        let body = response.text().await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Check whether a record exists without fetching its body
+    ///
+    /// Salesforce doesn't support HEAD on records, so this requests only
+    /// the `Id` field.
+    pub async fn exists(&self, sobject: &str, id: &str) -> SfResult<bool> {
+        let url = format!("{}/sobjects/{}/{}", self.api_url, sobject, id);
+
+        debug!("Checking whether {} record {} exists", sobject, id);
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.get(&url).query(&[("fields", "Id")])
+        })
+        .await?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(false);
+        }
+
+        if !status.is_success() {
+            let body = response.text().await?;
+            return Err(SfError::Api {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        Ok(true)
+    }
+
+    /// Insert a new record
+    ///
+    /// # Example
+    /// ```ignore
+    /// #[derive(Serialize)]
+    /// struct NewAccount {
+    ///     #[serde(rename = "Name")]
+    ///     name: String,
+    /// }
+    ///
+    /// let account = NewAccount { name: "Acme Corp".to_string() };
+    /// let response = client.insert("Account", &account).await?;
+    /// println!("Created account with ID: {}", response.id);
+    /// ```
+    pub async fn insert<T: Serialize>(&self, sobject: &str, data: &T) -> SfResult<InsertResponse> {
+        self.insert_with(sobject, data, &InsertOptions::default())
+            .await
+    }
+
+    /// Insert a new record with per-call options
+    pub async fn insert_with<T: Serialize>(
+        &self,
+        sobject: &str,
+        data: &T,
+        options: &InsertOptions,
+    ) -> SfResult<InsertResponse> {
+        let url = format!("{}/sobjects/{}", self.api_url, sobject);
+
+        debug!("Inserting {} record", sobject);
+        let body = options.body(data)?;
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            options.apply(
+                self.http_client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body),
+            )
+        })
+        .await?;
+
+        let insert_response = parse_insert_response(response).await?;
+
+        info!(
+            "Successfully inserted {} with ID: {}",
+            sobject, insert_response.id
+        );
+        Ok(insert_response)
+    }
+
+    /// Create a record with file content using a multipart request
+    ///
+    /// The `entity_content` part carries `metadata` as JSON and the binary
+    /// part is named after the object's blob field.
+    pub async fn upload<T: Serialize>(
+        &self,
+        sobject: &str,
+        metadata: &T,
+        content: FileContent,
+        filename: &str,
+        content_type: &str,
+    ) -> SfResult<InsertResponse> {
+        use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
+        use reqwest::multipart::{Form, Part};
+
+        let url = format!("{}/sobjects/{}", self.api_url, sobject);
+
+        let entity = serde_json::to_vec(metadata)?;
+        let file_type = HeaderValue::from_str(content_type)
+            .map_err(|_| SfError::Config(format!("Invalid content type '{}'", content_type)))?;
+        let field = blob_field(sobject);
+
+        let form = |body: reqwest::Body| {
+            let json_headers = HeaderMap::from_iter([(
+                CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            )]);
+            let file_headers = HeaderMap::from_iter([(CONTENT_TYPE, file_type.clone())]);
+
+            Form::new()
+                .part(
+                    "entity_content",
+                    Part::bytes(entity.clone()).headers(json_headers),
+                )
+                .part(
+                    field,
+                    Part::stream(body)
+                        .file_name(filename.to_string())
+                        .headers(file_headers),
+                )
+        };
+
+        debug!("Uploading {} {} as {}", filename, content_type, sobject);
+
+        let response = match content {
+            FileContent::Bytes(bytes) => {
+                auth::send_authorized(self.token_provider.as_ref(), || {
+                    self.http_client
+                        .post(&url)
+                        .multipart(form(bytes.clone().into()))
+                })
+                .await?
+            }
+            FileContent::Reader(reader) => {
+                // A stream can't be replayed, so send once with the current token
+                let body = reqwest::Body::wrap_stream(tokio_util::io::ReaderStream::new(reader));
+                let access_token = self.token_provider.bearer().await?;
+                self.http_client
+                    .post(&url)
+                    .header("Authorization", format!("Bearer {}", access_token))
+                    .multipart(form(body))
+                    .send()
+                    .await?
+            }
+        };
+
+        let insert_response = parse_insert_response(response).await?;
+        info!(
+            "Uploaded {} as {} {}",
+            filename, sobject, insert_response.id
+        );
+        Ok(insert_response)
+    }
+
+    /// Download the content of a blob field such as `VersionData`
+    pub async fn download_blob(&self, sobject: &str, id: &str, blob_field: &str) -> SfResult<Blob> {
+        let (response, metadata) = self.blob_response(sobject, id, blob_field).await?;
+        let data = response.bytes().await?;
+
+        info!("Downloaded {} bytes from {} {}", data.len(), sobject, id);
+        Ok(Blob { metadata, data })
+    }
+
+    /// Start a blob download, returning the response once headers arrive
+    pub(crate) async fn blob_response(
+        &self,
+        sobject: &str,
+        id: &str,
+        blob_field: &str,
+    ) -> SfResult<(reqwest::Response, BlobMetadata)> {
+        let url = format!(
+            "{}/sobjects/{}/{}/{}",
+            self.api_url, sobject, id, blob_field
+        );
+
+        debug!("Downloading {} of {} {}", blob_field, sobject, id);
+
+        let response =
+            auth::send_authorized(self.token_provider.as_ref(), || self.http_client.get(&url))
+                .await?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(SfError::NotFound {
+                sobject: sobject.to_string(),
+                id: id.to_string(),
+            });
+        }
+
+        if !status.is_success() {
+            let body = response.text().await?;
+            return Err(SfError::Api {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        let metadata = BlobMetadata {
+            content_type: response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string),
+            content_length: response.content_length(),
+        };
+        Ok((response, metadata))
+    }
+
+    /// Insert up to [`COLLECTION_BATCH_SIZE`] records in one request
+    ///
+    /// Results are returned in input order.
+    pub async fn insert_collection<T: Serialize>(
+        &self,
+        sobject: &str,
+        records: &[T],
+        all_or_none: bool,
+    ) -> SfResult<Vec<SaveResult>> {
+        let url = format!("{}/composite/sobjects", self.api_url);
+
+        let records = records
+            .iter()
+            .map(|record| with_type_attribute(sobject, record))
+            .collect::<SfResult<Vec<_>>>()?;
+        let body = serde_json::json!({
+            "allOrNone": all_or_none,
+            "records": records,
+        });
+
+        debug!("Inserting {} {} records", records.len(), sobject);
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.post(&url).json(&body)
+        })
+        .await?;
+
+        let results = parse_collection_response(response, records.len()).await?;
+        info!(
+            "Inserted {}/{} {} records",
+            results.iter().filter(|r| r.success).count(),
+            results.len(),
+            sobject
+        );
+        Ok(results)
+    }
+
+    /// Upsert up to [`COLLECTION_BATCH_SIZE`] records by external ID
+    ///
+    /// Every record must have a non-null `external_id_field`; results are
+    /// returned in input order.
+    pub async fn upsert_collection<T: Serialize>(
+        &self,
+        sobject: &str,
+        external_id_field: &str,
+        records: &[T],
+        all_or_none: bool,
+    ) -> SfResult<Vec<SaveResult>> {
+        let url = format!(
+            "{}/composite/sobjects/{}/{}",
+            self.api_url, sobject, external_id_field
+        );
+
+        let records = records
+            .iter()
+            .enumerate()
+            .map(|(index, record)| {
+                let value = with_type_attribute(sobject, record)?;
+                if value.get(external_id_field).map_or(true, |v| v.is_null()) {
+                    return Err(SfError::Serialization(serde::ser::Error::custom(format!(
+                        "record {} is missing external ID field {}",
+                        index, external_id_field
+                    ))));
+                }
+                Ok(value)
+            })
+            .collect::<SfResult<Vec<_>>>()?;
+        let body = serde_json::json!({
+            "allOrNone": all_or_none,
+            "records": records,
+        });
+
+        debug!(
+            "Upserting {} {} records by {}",
+            records.len(),
+            sobject,
+            external_id_field
+        );
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.patch(&url).json(&body)
+        })
+        .await?;
+
+        let results = parse_collection_response(response, records.len()).await?;
+        info!(
+            "Upserted {}/{} {} records",
+            results.iter().filter(|r| r.success).count(),
+            results.len(),
+            sobject
+        );
+        Ok(results)
+    }
+
+    /// Update up to [`COLLECTION_BATCH_SIZE`] records in one request
+    ///
+    /// Every record must have a non-null `Id`; results are returned in
+    /// input order.
+    pub async fn update_collection<T: Serialize>(
+        &self,
+        sobject: &str,
+        records: &[T],
+        all_or_none: bool,
+    ) -> SfResult<Vec<SaveResult>> {
+        let url = format!("{}/composite/sobjects", self.api_url);
+
+        let records = records
+            .iter()
+            .enumerate()
+            .map(|(index, record)| {
+                let value = with_type_attribute(sobject, record)?;
+                if value.get("Id").map_or(true, |v| v.is_null()) {
+                    return Err(SfError::Serialization(serde::ser::Error::custom(format!(
+                        "record {} is missing Id",
+                        index
+                    ))));
+                }
+                Ok(value)
+            })
+            .collect::<SfResult<Vec<_>>>()?;
+        let body = serde_json::json!({
+            "allOrNone": all_or_none,
+            "records": records,
+        });
+
+        debug!("Updating {} {} records", records.len(), sobject);
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.patch(&url).json(&body)
+        })
+        .await?;
+
+        let results = parse_collection_response(response, records.len()).await?;
+        info!(
+            "Updated {}/{} {} records",
+            results.iter().filter(|r| r.success).count(),
+            results.len(),
+            sobject
+        );
+        Ok(results)
+    }
+
+    /// Delete up to [`COLLECTION_BATCH_SIZE`] records in one request
+    ///
+    /// Results are returned in input order with `id` always set.
+    pub async fn delete_collection(
+        &self,
+        sobject: &str,
+        ids: &[&str],
+        all_or_none: bool,
+    ) -> SfResult<Vec<SaveResult>> {
+        let url = format!("{}/composite/sobjects", self.api_url);
+        let query = [
+            ("ids", ids.join(",")),
+            ("allOrNone", all_or_none.to_string()),
+        ];
+
+        debug!("Deleting {} {} records", ids.len(), sobject);
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.delete(&url).query(&query)
+        })
+        .await?;
+
+        let mut results = parse_collection_response(response, ids.len()).await?;
+        for (result, id) in results.iter_mut().zip(ids) {
+            result.id.get_or_insert_with(|| id.to_string());
         }
+
+        info!(
+            "Deleted {}/{} {} records",
+            results.iter().filter(|r| r.success).count(),
+            results.len(),
+            sobject
+        );
+        Ok(results)
     }
-}
 
-impl UpsertBuilder {
-    /// Create a new upsert builder
-    pub fn new(external_id_field: impl Into<String>, external_id_value: impl Into<String>) -> Self {
-        Self {
-            external_id_field: external_id_field.into(),
-            external_id_value: external_id_value.into(),
+    /// Insert record trees with the Composite Tree API
+    ///
+    /// Every top-level record must be of type `sobject`.
+    pub async fn insert_tree(
+        &self,
+        sobject: &str,
+        records: &[TreeRecord],
+    ) -> SfResult<TreeResponse> {
+        let total: usize = records.iter().map(TreeRecord::record_count).sum();
+        if total == 0 || total > TREE_MAX_RECORDS {
+            return Err(SfError::InvalidQuery(format!(
+                "composite tree request has {} records, expected 1 to {}",
+                total, TREE_MAX_RECORDS
+            )));
+        }
+        if let Some(record) = records.iter().find(|r| r.sobject != sobject) {
+            return Err(SfError::InvalidQuery(format!(
+                "tree record '{}' is a {}, expected {}",
+                record.reference_id, record.sobject, sobject
+            )));
         }
-    }
-}
 
-/// CRUD operations implementation
-pub(crate) struct CrudOperations {
-    http_client: reqwest::Client,
-    base_url: String,
-    access_token: String,
-}
+        let url = format!("{}/composite/tree/{}", self.api_url, sobject);
+        let records: Vec<_> = records.iter().map(TreeRecord::to_value).collect();
+        let body = serde_json::json!({ "records": records });
 
-impl CrudOperations {
-    /// Create a new CRUD operations handler
-    pub fn new(http_client: reqwest::Client, base_url: String, access_token: String) -> Self {
-        Self {
-            http_client,
-            base_url,
-            access_token,
+        debug!("Inserting {} {} tree records", total, sobject);
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.post(&url).json(&body)
+        })
+        .await?;
+
+        // Record-level failures come back as a 400 with the regular body
+        let status = response.status();
+        let body = response.text().await?;
+        let tree: TreeResponse = match serde_json::from_str(&body) {
+            Ok(tree) => tree,
+            Err(_) if !status.is_success() => {
+                return Err(SfError::Api {
+                    status: status.as_u16(),
+                    body,
+                })
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        if tree.has_errors {
+            info!(
+                "Composite tree insert of {} {} records failed",
+                total, sobject
+            );
+        } else {
+            info!("Inserted {} {} tree records", tree.results.len(), sobject);
         }
+        Ok(tree)
     }
 
-    /// Insert a new record
-    ///
-    /// # Example
-    /// ```ignore
-    /// #[derive(Serialize)]
-    /// struct NewAccount {
-    ///     #[serde(rename = "Name")]
-    ///     name: String,
-    /// }
-    ///
-    /// let account = NewAccount { name: "Acme Corp".to_string() };
-    /// let response = client.insert("Account", &account).await?;
-    /// println!("Created account with ID: {}", response.id);
-    /// ```
-    pub async fn insert<T: Serialize>(&self, sobject: &str, data: &T) -> SfResult<InsertResponse> {
-        let url = format!("{}/services/data/v57.0/sobjects/{}", self.base_url, sobject);
+    /// Execute a Composite API request
+    pub async fn composite(
+        &self,
+        request: &CompositeRequestBuilder,
+    ) -> SfResult<CompositeResponse> {
+        request.validate()?;
 
-        debug!("Inserting {} record", sobject);
+        let url = format!("{}/composite", self.api_url);
 
-        let response = self
-            .http_client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.access_token))
-            .header("Content-Type", "application/json")
-            .json(data)
-            .send()
-            .await?;
+        debug!(
+            "Executing composite request with {} subrequests",
+            request.len()
+        );
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.post(&url).json(request)
+        })
+        .await?;
 
         let status = response.status();
+        let body = response.text().await?;
         if !status.is_success() {
-            let body = response.text().await?;
             return Err(SfError::Api {
                 status: status.as_u16(),
                 body,
             });
         }
 
-        let insert_response: InsertResponse = response.json().await?;
-
-        if !insert_response.success {
-            let error_msg = insert_response
-                .errors
+        let composite: CompositeResponse = serde_json::from_str(&body)?;
+        info!(
+            "Composite request completed: {}/{} subrequests succeeded",
+            composite
+                .responses
                 .iter()
-                .map(|e| format!("{}: {}", e.status_code, e.message))
-                .collect::<Vec<_>>()
-                .join(", ");
+                .filter(|r| r.is_success())
+                .count(),
+            composite.responses.len()
+        );
+        Ok(composite)
+    }
+
+    /// List records deleted between `start` and `end`
+    pub async fn get_deleted(
+        &self,
+        sobject: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> SfResult<DeletedRecords> {
+        self.get_replication(sobject, "deleted", start, end).await
+    }
+
+    /// List records created or updated between `start` and `end`
+    pub async fn get_updated(
+        &self,
+        sobject: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> SfResult<UpdatedRecords> {
+        self.get_replication(sobject, "updated", start, end).await
+    }
+
+    async fn get_replication<T: DeserializeOwned>(
+        &self,
+        sobject: &str,
+        resource: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> SfResult<T> {
+        validate_replication_window(start, end)?;
+
+        let url = format!("{}/sobjects/{}/{}/", self.api_url, sobject, resource);
+        let query = [
+            (
+                "start",
+                start.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            ),
+            (
+                "end",
+                end.to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            ),
+        ];
+
+        debug!(
+            "Fetching {} {} records from {} to {}",
+            resource, sobject, start, end
+        );
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.get(&url).query(&query)
+        })
+        .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
             return Err(SfError::Api {
-                status: 400,
-                body: error_msg,
+                status: status.as_u16(),
+                body,
             });
         }
 
-        info!(
-            "Successfully inserted {} with ID: {}",
-            sobject, insert_response.id
-        );
-        Ok(insert_response)
+        Ok(serde_json::from_str(&body)?)
     }
 
     /// Update an existing record
@@ -187,21 +2123,32 @@ impl CrudOperations {
     /// client.update("Account", "001xx000003DGbX", &update).await?;
     /// ```
     pub async fn update<T: Serialize>(&self, sobject: &str, id: &str, data: &T) -> SfResult<()> {
-        let url = format!(
-            "{}/services/data/v57.0/sobjects/{}/{}",
-            self.base_url, sobject, id
-        );
+        self.update_with(sobject, id, data, &UpdateOptions::default())
+            .await
+    }
+
+    /// Update an existing record with conditional headers
+    pub async fn update_with<T: Serialize>(
+        &self,
+        sobject: &str,
+        id: &str,
+        data: &T,
+        options: &UpdateOptions,
+    ) -> SfResult<()> {
+        let url = format!("{}/sobjects/{}/{}", self.api_url, sobject, id);
 
         debug!("Updating {} record {}", sobject, id);
+        let body = options.body(data)?;
 
-        let response = self
-            .http_client
-            .patch(&url)
-            .header("Authorization", format!("Bearer {}", self.access_token))
-            .header("Content-Type", "application/json")
-            .json(data)
-            .send()
-            .await?;
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            options.apply(
+                self.http_client
+                    .patch(&url)
+                    .header("Content-Type", "application/json")
+                    .json(&body),
+            )
+        })
+        .await?;
 
         let status = response.status();
         if status == reqwest::StatusCode::NOT_FOUND {
@@ -211,6 +2158,13 @@ impl CrudOperations {
             });
         }
 
+        if status == reqwest::StatusCode::PRECONDITION_FAILED {
+            return Err(SfError::PreconditionFailed {
+                sobject: sobject.to_string(),
+                id: id.to_string(),
+            });
+        }
+
         if !status.is_success() {
             let body = response.text().await?;
             return Err(SfError::Api {
@@ -225,19 +2179,14 @@ impl CrudOperations {
 
     /// Delete a record
     pub async fn delete(&self, sobject: &str, id: &str) -> SfResult<()> {
-        let url = format!(
-            "{}/services/data/v57.0/sobjects/{}/{}",
-            self.base_url, sobject, id
-        );
+        let url = format!("{}/sobjects/{}/{}", self.api_url, sobject, id);
 
         debug!("Deleting {} record {}", sobject, id);
 
-        let response = self
-            .http_client
-            .delete(&url)
-            .header("Authorization", format!("Bearer {}", self.access_token))
-            .send()
-            .await?;
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.delete(&url)
+        })
+        .await?;
 
         let status = response.status();
         if status == reqwest::StatusCode::NOT_FOUND {
@@ -259,6 +2208,203 @@ impl CrudOperations {
         Ok(())
     }
 
+    /// Retrieve a record by external ID
+    pub async fn get_by_external_id<T: DeserializeOwned>(
+        &self,
+        sobject: &str,
+        external_id_field: &str,
+        value: &str,
+    ) -> SfResult<T> {
+        let url = format!(
+            "{}/sobjects/{}/{}/{}",
+            self.api_url,
+            sobject,
+            external_id_field,
+            encode_path_segment(value)
+        );
+        let external_id = format!("{}={}", external_id_field, value);
+
+        debug!("Retrieving {} record with {}", sobject, external_id);
+
+        let response =
+            auth::send_authorized(self.token_provider.as_ref(), || self.http_client.get(&url))
+                .await?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(SfError::NotFound {
+                sobject: sobject.to_string(),
+                id: external_id,
+            });
+        }
+
+        if status == reqwest::StatusCode::MULTIPLE_CHOICES {
+            // The body lists the URL of every matching record
+            let body = response.text().await?;
+            let records: Vec<String> = serde_json::from_str(&body)?;
+            return Err(SfError::MultipleMatches {
+                sobject: sobject.to_string(),
+                external_id,
+                records,
+            });
+        }
+
+        if !status.is_success() {
+            let body = response.text().await?;
+            return Err(SfError::Api {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        let body = response.text().await?;
+        Ok(serde_json::from_str(&body)?)
+    }
+
+    /// Submit records for approval
+    pub async fn submit_for_approval(
+        &self,
+        requests: &[ApprovalRequest],
+    ) -> SfResult<Vec<ApprovalResult>> {
+        let url = format!("{}/process/approvals/", self.api_url);
+        let body = serde_json::json!({ "requests": requests });
+
+        debug!("Submitting {} records for approval", requests.len());
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.post(&url).json(&body)
+        })
+        .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(SfError::Api {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        let results: Vec<ApprovalResult> = serde_json::from_str(&body)?;
+        info!(
+            "Submitted {} of {} records for approval",
+            results.iter().filter(|r| r.success).count(),
+            results.len()
+        );
+        Ok(results)
+    }
+
+    /// List approval processes, keyed by object
+    pub async fn approval_processes(&self) -> SfResult<HashMap<String, Vec<ApprovalProcess>>> {
+        #[derive(Deserialize)]
+        struct ApprovalsResponse {
+            #[serde(default)]
+            approvals: HashMap<String, Vec<ApprovalProcess>>,
+        }
+
+        let url = format!("{}/process/approvals/", self.api_url);
+
+        let response =
+            auth::send_authorized(self.token_provider.as_ref(), || self.http_client.get(&url))
+                .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(SfError::Api {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        let response: ApprovalsResponse = serde_json::from_str(&body)?;
+        Ok(response.approvals)
+    }
+
+    /// Invoke a quick action on an object
+    pub async fn invoke_quick_action<T: Serialize>(
+        &self,
+        sobject: &str,
+        action: &str,
+        body: &T,
+    ) -> SfResult<QuickActionResult> {
+        let url = format!(
+            "{}/sobjects/{}/quickActions/{}",
+            self.api_url, sobject, action
+        );
+
+        debug!("Invoking {} quick action {}", sobject, action);
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.post(&url).json(body)
+        })
+        .await?;
+
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(SfError::Api {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        let result: QuickActionResult = serde_json::from_str(&body)?;
+        info!(
+            "Invoked {} quick action {}: success={}",
+            sobject, action, result.success
+        );
+        Ok(result)
+    }
+
+    /// Delete a record by external ID
+    pub async fn delete_by_external_id(
+        &self,
+        sobject: &str,
+        external_id_field: &str,
+        value: &str,
+    ) -> SfResult<()> {
+        let url = format!(
+            "{}/sobjects/{}/{}/{}",
+            self.api_url,
+            sobject,
+            external_id_field,
+            encode_path_segment(value)
+        );
+
+        debug!(
+            "Deleting {} record with {} {}",
+            sobject, external_id_field, value
+        );
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.delete(&url)
+        })
+        .await?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(SfError::NotFound {
+                sobject: sobject.to_string(),
+                id: format!("{}={}", external_id_field, value),
+            });
+        }
+
+        if !status.is_success() {
+            let body = response.text().await?;
+            return Err(SfError::Api {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        info!(
+            "Successfully deleted {} with {} {}",
+            sobject, external_id_field, value
+        );
+        Ok(())
+    }
+
     /// Upsert a record (insert or update based on external ID)
     ///
     /// # Example
@@ -272,26 +2418,82 @@ impl CrudOperations {
         builder: UpsertBuilder,
         data: &T,
     ) -> SfResult<InsertResponse> {
-        let url = format!(
-            "{}/services/data/v57.0/sobjects/{}/{}/{}",
-            self.base_url, sobject, builder.external_id_field, builder.external_id_value
-        );
+        self.upsert_with(sobject, builder, data, &InsertOptions::default())
+            .await
+    }
 
-        debug!(
-            "Upserting {} record with external ID {}",
-            sobject, builder.external_id_value
-        );
+    /// Upsert a record with per-call options
+    pub async fn upsert_with<T: Serialize>(
+        &self,
+        sobject: &str,
+        builder: UpsertBuilder,
+        data: &T,
+        options: &InsertOptions,
+    ) -> SfResult<InsertResponse> {
+        let field = builder.external_id_field;
+        // Creating through the Id endpoint is a POST without an ID segment
+        let (method, url, value) = match builder.external_id_value {
+            None if field == "Id" => (
+                reqwest::Method::POST,
+                format!("{}/sobjects/{}/Id", self.api_url, sobject),
+                String::new(),
+            ),
+            Some(value) if !value.is_empty() => (
+                reqwest::Method::PATCH,
+                format!("{}/sobjects/{}/{}/{}", self.api_url, sobject, field, value),
+                value,
+            ),
+            _ => {
+                return Err(SfError::InvalidQuery(format!(
+                    "Upsert on {} needs a {} value",
+                    sobject, field
+                )))
+            }
+        };
+
+        debug!("Upserting {} record with {} {}", sobject, field, value);
+        let body = options.body(data)?;
 
-        let response = self
-            .http_client
-            .patch(&url)
-            .header("Authorization", format!("Bearer {}", self.access_token))
-            .header("Content-Type", "application/json")
-            .json(data)
-            .send()
-            .await?;
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            options.apply(
+                self.http_client
+                    .request(method.clone(), &url)
+                    .header("Content-Type", "application/json")
+                    .json(&body),
+            )
+        })
+        .await?;
 
         let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND && field == "Id" {
+            return Err(SfError::NotFound {
+                sobject: sobject.to_string(),
+                id: value,
+            });
+        }
+
+        if status == reqwest::StatusCode::MULTIPLE_CHOICES {
+            // The external ID matched more than one record
+            let body = response.text().await?;
+            let records: Vec<String> = serde_json::from_str(&body)?;
+            return Err(SfError::MultipleMatches {
+                sobject: sobject.to_string(),
+                external_id: format!("{}={}", field, value),
+                records,
+            });
+        }
+
+        // Updates by Id may answer with no body
+        if status == reqwest::StatusCode::NO_CONTENT {
+            info!("Successfully upserted {} with ID: {}", sobject, value);
+            return Ok(InsertResponse {
+                id: value,
+                success: true,
+                errors: Vec::new(),
+                created: false,
+            });
+        }
+
         if !status.is_success() {
             let body = response.text().await?;
             return Err(SfError::Api {
@@ -300,7 +2502,8 @@ impl CrudOperations {
             });
         }
 
-        let upsert_response: InsertResponse = response.json().await?;
+        let mut upsert_response: InsertResponse = response.json().await?;
+        upsert_response.created |= status == reqwest::StatusCode::CREATED;
         info!(
             "Successfully upserted {} with ID: {}",
             sobject, upsert_response.id
@@ -309,3 +2512,126 @@ impl CrudOperations {
         Ok(upsert_response)
     }
 }
+
+/// Copy a response body into `writer` chunk by chunk, returning the byte count
+pub(crate) async fn copy_body<W>(mut response: reqwest::Response, writer: &mut W) -> SfResult<u64>
+where
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut written = 0u64;
+    while let Some(chunk) = response.chunk().await? {
+        writer.write_all(&chunk).await?;
+        written += chunk.len() as u64;
+    }
+    writer.flush().await?;
+
+    Ok(written)
+}
+
+/// Parse a create response, turning `success: false` into an error
+async fn parse_insert_response(response: reqwest::Response) -> SfResult<InsertResponse> {
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await?;
+        return Err(SfError::Api {
+            status: status.as_u16(),
+            body,
+        });
+    }
+
+    let insert_response: InsertResponse = response.json().await?;
+
+    if !insert_response.success {
+        let error_msg = insert_response
+            .errors
+            .iter()
+            .map(|e| format!("{}: {}", e.status_code, e.message))
+            .collect::<Vec<_>>()
+            .join(", ");
+        return Err(SfError::Api {
+            status: 400,
+            body: error_msg,
+        });
+    }
+
+    Ok(insert_response)
+}
+
+/// Check a getDeleted/getUpdated window is ordered and at most 30 days long
+fn validate_replication_window(start: DateTime<Utc>, end: DateTime<Utc>) -> SfResult<()> {
+    if end <= start {
+        return Err(SfError::InvalidQuery(format!(
+            "replication window end {} must be after start {}",
+            end, start
+        )));
+    }
+    if end - start > chrono::Duration::days(MAX_REPLICATION_WINDOW_DAYS) {
+        return Err(SfError::InvalidQuery(format!(
+            "replication window from {} to {} exceeds {} days",
+            start, end, MAX_REPLICATION_WINDOW_DAYS
+        )));
+    }
+    Ok(())
+}
+
+/// Serialize a record and set `attributes.type`, as required by collections
+fn with_type_attribute<T: Serialize>(sobject: &str, record: &T) -> SfResult<serde_json::Value> {
+    let mut value = serde_json::to_value(record)?;
+
+    let object = value.as_object_mut().ok_or_else(|| {
+        SfError::Serialization(serde::ser::Error::custom(
+            "collection records must serialize to JSON objects",
+        ))
+    })?;
+    object.insert(
+        "attributes".to_string(),
+        serde_json::json!({ "type": sobject }),
+    );
+
+    Ok(value)
+}
+
+/// Percent-encode a value for use as a single URL path segment
+///
+/// Everything but RFC 3986 unreserved characters is encoded, including `+`,
+/// which Salesforce would otherwise read as a space.
+fn encode_path_segment(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Parse a collections response, checking it has one result per record
+async fn parse_collection_response(
+    response: reqwest::Response,
+    expected: usize,
+) -> SfResult<Vec<SaveResult>> {
+    let status = response.status();
+    let body = response.text().await?;
+    if !status.is_success() {
+        return Err(SfError::Api {
+            status: status.as_u16(),
+            body,
+        });
+    }
+
+    let results: Vec<SaveResult> = serde_json::from_str(&body)?;
+    if results.len() != expected {
+        return Err(SfError::Api {
+            status: status.as_u16(),
+            body: format!(
+                "Expected {} results from collections request, got {}",
+                expected,
+                results.len()
+            ),
+        });
+    }
+
+    Ok(results)
+}
@@ -0,0 +1,820 @@
+//! SObject metadata from the describe endpoints
+//!
+//! Missing attributes fall back to defaults and attributes this crate does
+//! not model are kept in `extra`, so payloads from newer API versions still
+//! deserialize.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Full metadata for one SObject, from `/sobjects/{name}/describe`
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SObjectDescribe {
+    /// API name, e.g. `Account`
+    pub name: String,
+
+    /// Display label
+    pub label: String,
+
+    /// Plural display label
+    pub label_plural: String,
+
+    /// Three-character ID prefix
+    pub key_prefix: Option<String>,
+
+    /// Whether this is a custom object
+    pub custom: bool,
+
+    /// Whether records can be queried
+    pub queryable: bool,
+
+    /// Whether records can be created
+    pub createable: bool,
+
+    /// Whether records can be updated
+    pub updateable: bool,
+
+    /// Whether records can be deleted
+    pub deletable: bool,
+
+    /// Field metadata
+    pub fields: Vec<FieldDescribe>,
+
+    /// Relationships from child objects to this one
+    pub child_relationships: Vec<ChildRelationship>,
+
+    /// Record types available on this object
+    pub record_type_infos: Vec<RecordTypeInfo>,
+
+    /// Related resource URLs
+    pub urls: HashMap<String, String>,
+
+    /// Attributes not modeled above
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl SObjectDescribe {
+    /// Look up a field by API name (case-insensitive, like SOQL)
+    pub fn field(&self, name: &str) -> Option<&FieldDescribe> {
+        self.fields
+            .iter()
+            .find(|field| field.name.eq_ignore_ascii_case(name))
+    }
+
+    /// API names of all fields
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.fields.iter().map(|field| field.name.as_str())
+    }
+
+    /// Look up a child relationship by name, e.g. `Contacts`
+    pub fn child_relationship(&self, name: &str) -> Option<&ChildRelationship> {
+        self.child_relationships.iter().find(|relationship| {
+            relationship
+                .relationship_name
+                .as_deref()
+                .is_some_and(|n| n.eq_ignore_ascii_case(name))
+        })
+    }
+}
+
+/// Metadata for one field
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FieldDescribe {
+    /// API name
+    pub name: String,
+
+    /// Display label
+    pub label: String,
+
+    /// Field type, e.g. `string`, `picklist`, `reference`
+    #[serde(rename = "type")]
+    pub field_type: String,
+
+    /// Maximum length for text fields
+    pub length: u32,
+
+    /// Total digits for numeric fields
+    pub precision: u32,
+
+    /// Digits after the decimal point for numeric fields
+    pub scale: u32,
+
+    /// Whether the field accepts null
+    pub nillable: bool,
+
+    /// Whether the field can be set on create
+    pub createable: bool,
+
+    /// Whether the field can be updated
+    pub updateable: bool,
+
+    /// Whether this is a custom field
+    pub custom: bool,
+
+    /// Whether the field is an external ID
+    pub external_id: bool,
+
+    /// Whether values must be unique
+    pub unique: bool,
+
+    /// Default value, if any
+    pub default_value: Option<serde_json::Value>,
+
+    /// Values for picklist fields
+    pub picklist_values: Vec<PicklistValue>,
+
+    /// Object types a reference field can point to
+    pub reference_to: Vec<String>,
+
+    /// Relationship name for reference fields, e.g. `Parent`
+    pub relationship_name: Option<String>,
+
+    /// Attributes not modeled above
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Org-wide list of SObjects, from `/sobjects/`
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DescribeGlobal {
+    /// Character encoding of the org
+    pub encoding: String,
+
+    /// Maximum records per Collections/composite request
+    pub max_batch_size: u32,
+
+    /// Every SObject visible to the current user
+    pub sobjects: Vec<SObjectBasicInfo>,
+}
+
+impl DescribeGlobal {
+    /// Objects that can be queried with SOQL
+    pub fn queryable_objects(&self) -> impl Iterator<Item = &SObjectBasicInfo> {
+        self.sobjects.iter().filter(|sobject| sobject.queryable)
+    }
+
+    /// Custom objects, excluding custom settings
+    pub fn custom_objects(&self) -> impl Iterator<Item = &SObjectBasicInfo> {
+        self.sobjects
+            .iter()
+            .filter(|sobject| sobject.custom && !sobject.custom_setting)
+    }
+
+    /// Look up an object by API name (case-insensitive)
+    pub fn sobject(&self, name: &str) -> Option<&SObjectBasicInfo> {
+        self.sobjects
+            .iter()
+            .find(|sobject| sobject.name.eq_ignore_ascii_case(name))
+    }
+}
+
+/// Summary of one SObject in a global describe
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct SObjectBasicInfo {
+    /// API name
+    pub name: String,
+
+    /// Display label
+    pub label: String,
+
+    /// Plural display label
+    pub label_plural: String,
+
+    /// Three-character ID prefix
+    pub key_prefix: Option<String>,
+
+    /// Whether this is a custom object
+    pub custom: bool,
+
+    /// Whether this is a custom setting
+    pub custom_setting: bool,
+
+    /// Whether records can be queried
+    pub queryable: bool,
+
+    /// Whether records can be retrieved by ID
+    pub retrieveable: bool,
+
+    /// Whether records can be searched with SOSL
+    pub searchable: bool,
+
+    /// Whether records can be created
+    pub createable: bool,
+
+    /// Whether records can be updated
+    pub updateable: bool,
+
+    /// Whether records can be deleted
+    pub deletable: bool,
+
+    /// Whether getUpdated/getDeleted are supported
+    pub replicateable: bool,
+
+    /// Related resource URLs
+    pub urls: HashMap<String, String>,
+
+    /// Attributes not modeled above
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A picklist entry
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PicklistValue {
+    /// API value
+    pub value: String,
+
+    /// Display label
+    pub label: Option<String>,
+
+    /// Whether the value can be selected
+    pub active: bool,
+
+    /// Whether this is the default value
+    pub default_value: bool,
+
+    /// Base64 bitmap of the controlling values this entry is valid for
+    pub valid_for: Option<String>,
+}
+
+/// A relationship from a child object
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ChildRelationship {
+    /// Child object API name
+    #[serde(rename = "childSObject")]
+    pub child_sobject: String,
+
+    /// Lookup field on the child
+    pub field: String,
+
+    /// Relationship name used in subqueries, e.g. `Contacts`
+    pub relationship_name: Option<String>,
+
+    /// Whether deleting the parent deletes the children
+    pub cascade_delete: bool,
+}
+
+/// A record type available on an object
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RecordTypeInfo {
+    /// Display name
+    pub name: String,
+
+    /// API name
+    pub developer_name: String,
+
+    /// Record type ID
+    pub record_type_id: Option<String>,
+
+    /// Whether the record type is active
+    pub active: bool,
+
+    /// Whether the current user can use it
+    pub available: bool,
+
+    /// Whether it is the current user's default
+    pub default_record_type_mapping: bool,
+
+    /// Whether it is the master record type
+    pub master: bool,
+}
+
+/// Picklist values of one field for a record type, from the UI API
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PicklistFieldValues {
+    /// Controlling field values mapped to the index used in `valid_for`
+    ///
+    /// Empty unless this is a dependent picklist.
+    pub controller_values: HashMap<String, u32>,
+
+    /// Default value for the record type, if any
+    pub default_value: Option<PicklistEntry>,
+
+    /// Values available for the record type
+    pub values: Vec<PicklistEntry>,
+
+    /// Version tag of this response
+    pub e_tag: Option<String>,
+
+    /// Resource URL
+    pub url: Option<String>,
+}
+
+impl PicklistFieldValues {
+    /// Whether this picklist depends on a controlling field
+    pub fn is_dependent(&self) -> bool {
+        !self.controller_values.is_empty()
+    }
+
+    /// Whether `value` is the record type's default
+    pub fn is_default(&self, value: &str) -> bool {
+        self.default_value
+            .as_ref()
+            .is_some_and(|default| default.value == value)
+    }
+
+    /// Values valid when the controlling field is set to `controlling_value`
+    ///
+    /// Returns every value if this picklist is not dependent.
+    pub fn values_for(&self, controlling_value: &str) -> Vec<&PicklistEntry> {
+        if !self.is_dependent() {
+            return self.values.iter().collect();
+        }
+
+        match self.controller_values.get(controlling_value) {
+            Some(index) => self
+                .values
+                .iter()
+                .filter(|entry| entry.valid_for.contains(index))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// A picklist value from the UI API
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PicklistEntry {
+    /// API value
+    pub value: String,
+
+    /// Display label
+    pub label: String,
+
+    /// Indices into `controller_values` this value is valid for
+    pub valid_for: Vec<u32>,
+
+    /// Extra attributes, e.g. for opportunity stages
+    pub attributes: Option<serde_json::Value>,
+}
+
+/// Picklist values of every picklist field for a record type
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RecordTypePicklists {
+    /// Values by field API name
+    pub picklist_field_values: HashMap<String, PicklistFieldValues>,
+
+    /// Version tag of this response
+    pub e_tag: Option<String>,
+}
+
+impl RecordTypePicklists {
+    /// Values for one field
+    pub fn field(&self, name: &str) -> Option<&PicklistFieldValues> {
+        self.picklist_field_values.get(name)
+    }
+}
+
+/// Page layouts of an SObject, from `/sobjects/{name}/describe/layouts`
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DescribeLayouts {
+    /// Every layout assigned to one of the object's record types
+    pub layouts: Vec<Layout>,
+
+    /// Which layout each record type uses
+    pub record_type_mappings: Vec<RecordTypeMapping>,
+
+    /// Attributes not modeled above
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl DescribeLayouts {
+    /// Look up a layout by ID
+    pub fn layout(&self, id: &str) -> Option<&Layout> {
+        self.layouts.iter().find(|layout| layout.id == id)
+    }
+
+    /// The layout assigned to a record type
+    pub fn layout_for_record_type(&self, record_type_id: &str) -> Option<&Layout> {
+        self.record_type_mappings
+            .iter()
+            .find(|mapping| mapping.record_type_id == record_type_id)
+            .and_then(|mapping| self.layout(&mapping.layout_id))
+    }
+}
+
+/// A page layout
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Layout {
+    /// Layout ID
+    pub id: String,
+
+    /// Sections shown when viewing a record
+    pub detail_layout_sections: Vec<LayoutSection>,
+
+    /// Sections shown when editing a record
+    pub edit_layout_sections: Vec<LayoutSection>,
+
+    /// Attributes not modeled above, e.g. `relatedLists`
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Layout {
+    /// API names of the fields on the detail view, in layout order
+    pub fn detail_fields(&self) -> Vec<&str> {
+        self.detail_layout_sections
+            .iter()
+            .flat_map(|section| &section.layout_rows)
+            .flat_map(|row| &row.layout_items)
+            .flat_map(LayoutItem::field_names)
+            .collect()
+    }
+}
+
+/// A section of a page layout
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LayoutSection {
+    /// Section heading
+    pub heading: Option<String>,
+
+    /// Whether the heading is shown
+    pub use_heading: bool,
+
+    /// Number of columns
+    pub columns: u32,
+
+    /// Number of rows
+    pub rows: u32,
+
+    /// Rows of the section
+    pub layout_rows: Vec<LayoutRow>,
+
+    /// Attributes not modeled above
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// A row of a layout section
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LayoutRow {
+    /// Items in the row, one per column
+    pub layout_items: Vec<LayoutItem>,
+
+    /// Number of items
+    pub num_items: u32,
+}
+
+/// A cell of a layout row
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LayoutItem {
+    /// Display label
+    pub label: String,
+
+    /// Whether the item is required
+    pub required: bool,
+
+    /// Whether the item is an empty spacer
+    pub placeholder: bool,
+
+    /// Whether the item can be set on create
+    pub editable_for_new: bool,
+
+    /// Whether the item can be updated
+    pub editable_for_update: bool,
+
+    /// Fields and other components shown in the item
+    pub layout_components: Vec<LayoutComponent>,
+
+    /// Attributes not modeled above
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl LayoutItem {
+    /// API names of the fields in this item
+    pub fn field_names(&self) -> impl Iterator<Item = &str> {
+        self.layout_components
+            .iter()
+            .filter(|component| component.component_type == "Field")
+            .filter_map(|component| component.value.as_deref())
+    }
+}
+
+/// A component of a layout item
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct LayoutComponent {
+    /// Component type, e.g. `Field`, `CustomLink`, `VisualforcePage`
+    #[serde(rename = "type")]
+    pub component_type: String,
+
+    /// Field API name for `Field` components
+    pub value: Option<String>,
+
+    /// Lines of text shown
+    pub display_lines: u32,
+
+    /// Position in the tab order
+    pub tab_order: u32,
+
+    /// Attributes not modeled above, e.g. the field's `details`
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The layout assigned to a record type
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RecordTypeMapping {
+    /// Record type ID
+    pub record_type_id: String,
+
+    /// Record type display name
+    pub name: String,
+
+    /// Layout ID
+    pub layout_id: String,
+
+    /// Whether the current user can use the record type
+    pub available: bool,
+
+    /// Whether it is the current user's default record type
+    pub default_record_type_mapping: bool,
+
+    /// Whether it is the master record type
+    pub master: bool,
+
+    /// Attributes not modeled above
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Compact layouts of an SObject, from `/sobjects/{name}/describe/compactLayouts`
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DescribeCompactLayouts {
+    /// Compact layouts in use
+    pub compact_layouts: Vec<CompactLayout>,
+
+    /// ID of the object's default compact layout (`None` for the system default)
+    pub default_compact_layout_id: Option<String>,
+
+    /// Which compact layout each record type uses
+    pub record_type_compact_layout_mappings: Vec<RecordTypeCompactLayoutMapping>,
+}
+
+impl DescribeCompactLayouts {
+    /// The compact layout assigned to a record type
+    pub fn layout_for_record_type(&self, record_type_id: &str) -> Option<&CompactLayout> {
+        let mapping = self
+            .record_type_compact_layout_mappings
+            .iter()
+            .find(|mapping| mapping.record_type_id == record_type_id)?;
+
+        // The system default layout has no ID, so match on name
+        self.compact_layouts
+            .iter()
+            .find(|layout| layout.name == mapping.compact_layout_name)
+    }
+}
+
+/// A compact layout, shown in highlights panels and record previews
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct CompactLayout {
+    /// Layout ID (`None` for the system default)
+    pub id: Option<String>,
+
+    /// API name
+    pub name: String,
+
+    /// Display label
+    pub label: String,
+
+    /// Object the layout belongs to
+    pub object_type: String,
+
+    /// Fields shown, in order
+    pub field_items: Vec<LayoutItem>,
+
+    /// Attributes not modeled above
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// The compact layout assigned to a record type
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct RecordTypeCompactLayoutMapping {
+    /// Record type ID
+    pub record_type_id: String,
+
+    /// Record type display name
+    pub record_type_name: String,
+
+    /// Compact layout ID (`None` for the system default)
+    pub compact_layout_id: Option<String>,
+
+    /// Compact layout API name
+    pub compact_layout_name: String,
+
+    /// Whether the current user can use the record type
+    pub available: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn account_describe() -> SObjectDescribe {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/account_describe.json"
+        );
+        serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_deserialize_describe() {
+        let describe = account_describe();
+
+        assert_eq!(describe.name, "Account");
+        assert_eq!(describe.label_plural, "Accounts");
+        assert_eq!(describe.key_prefix.as_deref(), Some("001"));
+        assert!(describe.queryable && !describe.custom);
+        assert_eq!(describe.fields.len(), 6);
+
+        let name = describe.field("name").unwrap();
+        assert_eq!(name.field_type, "string");
+        assert_eq!(name.length, 255);
+        assert!(!name.nillable && name.updateable);
+
+        let account_type = describe.field("Type").unwrap();
+        assert_eq!(account_type.picklist_values.len(), 3);
+        assert!(!account_type.picklist_values[2].active);
+
+        let parent = describe.field("ParentId").unwrap();
+        assert_eq!(parent.reference_to, vec!["Account".to_string()]);
+        assert_eq!(parent.relationship_name.as_deref(), Some("Parent"));
+
+        assert!(describe.field("External_Id__c").unwrap().external_id);
+        assert!(describe.field("Missing__c").is_none());
+
+        let contacts = describe.child_relationship("Contacts").unwrap();
+        assert_eq!(contacts.child_sobject, "Contact");
+        assert!(contacts.cascade_delete);
+
+        assert_eq!(describe.record_type_infos[0].developer_name, "Master");
+        assert!(describe.record_type_infos[0].master);
+
+        // Unmodeled attributes are kept
+        assert!(describe.extra.contains_key("supportedScopes"));
+        assert!(name.extra.contains_key("soapType"));
+    }
+
+    #[test]
+    fn test_deserialize_describe_global() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/describe_global.json"
+        );
+        let global: DescribeGlobal =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        assert_eq!(global.encoding, "UTF-8");
+        assert_eq!(global.max_batch_size, 200);
+        assert_eq!(global.sobjects.len(), 5);
+
+        let queryable: Vec<_> = global
+            .queryable_objects()
+            .map(|s| s.name.as_str())
+            .collect();
+        assert_eq!(
+            queryable,
+            vec!["Account", "Contact", "Invoice__c", "Sync_Settings__c"]
+        );
+
+        let custom: Vec<_> = global.custom_objects().map(|s| s.name.as_str()).collect();
+        assert_eq!(custom, vec!["Invoice__c"]);
+
+        let event = global.sobject("accountchangeevent").unwrap();
+        assert!(event.key_prefix.is_none());
+        assert!(event.extra.contains_key("triggerable"));
+    }
+
+    #[test]
+    fn test_deserialize_picklist_values() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/picklist_values.json"
+        );
+        let picklists: RecordTypePicklists =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        assert_eq!(picklists.picklist_field_values.len(), 3);
+
+        let industry = picklists.field("Industry").unwrap();
+        assert!(!industry.is_dependent());
+        assert_eq!(industry.values.len(), 3);
+        assert_eq!(industry.values_for("anything").len(), 3);
+
+        let sub_industry = picklists.field("Sub_Industry__c").unwrap();
+        assert!(sub_industry.is_dependent());
+        let labels = |entries: Vec<&PicklistEntry>| -> Vec<String> {
+            entries.iter().map(|e| e.label.clone()).collect()
+        };
+        assert_eq!(
+            labels(sub_industry.values_for("Technology")),
+            vec!["Hardware", "Software"]
+        );
+        assert_eq!(
+            labels(sub_industry.values_for("Telecommunications")),
+            vec!["Hardware", "Wireless"]
+        );
+        assert!(sub_industry.values_for("Retail").is_empty());
+
+        let rating = picklists.field("Rating").unwrap();
+        assert!(rating.is_default("Warm"));
+        assert!(!rating.is_default("Hot"));
+        assert!(!industry.is_default("Technology"));
+    }
+
+    #[test]
+    fn test_deserialize_layouts() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/account_layouts.json"
+        );
+        let layouts: DescribeLayouts =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        assert_eq!(layouts.layouts.len(), 2);
+        assert_eq!(layouts.record_type_mappings.len(), 2);
+        assert!(layouts.extra.contains_key("recordTypeSelectorRequired"));
+
+        let customer = layouts.layout_for_record_type("012A0000000aAaA").unwrap();
+        assert_eq!(customer.detail_fields(), vec!["Name", "Type", "ParentId"]);
+        assert!(customer.extra.contains_key("relatedLists"));
+
+        let section = &customer.detail_layout_sections[0];
+        assert_eq!(section.heading.as_deref(), Some("Account Information"));
+        assert_eq!((section.columns, section.rows), (2, 2));
+        let name = &section.layout_rows[0].layout_items[0];
+        assert!(name.required && name.editable_for_new);
+        assert!(name.layout_components[0].extra.contains_key("details"));
+        assert!(section.layout_rows[0].layout_items[1].placeholder);
+
+        // Non-field components are skipped
+        let partner = layouts.layout_for_record_type("012A0000000bBbB").unwrap();
+        assert_eq!(partner.detail_layout_sections.len(), 2);
+        assert_eq!(partner.detail_fields(), vec!["External_Id__c"]);
+        assert!(layouts
+            .layout_for_record_type("012000000000000AAA")
+            .is_none());
+    }
+
+    #[test]
+    fn test_deserialize_compact_layouts() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/account_compact_layouts.json"
+        );
+        let compact: DescribeCompactLayouts =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        assert_eq!(compact.compact_layouts.len(), 2);
+        assert!(compact.default_compact_layout_id.is_none());
+
+        let partner = compact.layout_for_record_type("012A0000000bBbB").unwrap();
+        assert_eq!(partner.name, "Partner_Compact");
+        let fields: Vec<_> = partner
+            .field_items
+            .iter()
+            .flat_map(LayoutItem::field_names)
+            .collect();
+        assert_eq!(fields, vec!["Name", "Phone"]);
+
+        let customer = compact.layout_for_record_type("012A0000000aAaA").unwrap();
+        assert_eq!(customer.name, "SYSTEM");
+        assert!(customer.id.is_none());
+    }
+
+    #[test]
+    fn test_describe_round_trip() {
+        let describe = account_describe();
+        let json = serde_json::to_string(&describe).unwrap();
+        let again: SObjectDescribe = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(again.fields.len(), describe.fields.len());
+        assert_eq!(again.field("Type").unwrap().picklist_values.len(), 3);
+        assert_eq!(again.child_relationships[0].child_sobject, "Contact");
+    }
+}
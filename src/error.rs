@@ -2,6 +2,9 @@
 //!
 //! Provides comprehensive error handling with detailed context.
 
+use crate::crud::{DuplicateResult, SalesforceError, SaveResult};
+use serde::{Deserialize, Serialize};
+use std::fmt;
 use thiserror::Error;
 
 /// Custom error type for Salesforce API operations.
@@ -36,6 +39,22 @@ pub enum SfError {
     #[error("Record not found: {sobject} with id {id}")]
     NotFound { sobject: String, id: String },
 
+    /// An external ID value matched more than one record (HTTP 300)
+    ///
+    /// `records` holds the URL of each matching record.
+    #[error("Multiple {sobject} records match {external_id}: {records:?}")]
+    MultipleMatches {
+        sobject: String,
+        external_id: String,
+        records: Vec<String>,
+    },
+
+    /// A conditional request's precondition did not hold (HTTP 412)
+    ///
+    /// The record was changed since the time given in the request.
+    #[error("Precondition failed: {sobject} {id} was modified")]
+    PreconditionFailed { sobject: String, id: String },
+
     /// Invalid query or SOQL syntax
     #[error("Invalid query: {0}")]
     InvalidQuery(String),
@@ -51,7 +70,317 @@ pub enum SfError {
     /// Timeout error
     #[error("Operation timed out after {seconds} seconds")]
     Timeout { seconds: u64 },
+
+    /// A query gathered more records than the configured `max_records`
+    #[error(
+        "Query result too large: {fetched} records fetched, limit is {limit}; \
+         use query_stream or query_paginated to process records incrementally"
+    )]
+    ResultTooLarge { fetched: usize, limit: usize },
+
+    /// A chunk of a batch failed after other chunks were saved
+    ///
+    /// `results` lines up with the input records: `Some` for rows of chunks
+    /// that were sent, `None` for rows of the failed chunk and of chunks
+    /// never sent.
+    #[error("Batch failed after some chunks were saved: {source}")]
+    PartialBatch {
+        results: Vec<Option<SaveResult>>,
+        source: Box<SfError>,
+    },
+
+    /// A `CachePolicy::CacheOnly` query had no cached result
+    #[error("Query result is not cached")]
+    NotCached,
+
+    /// Local I/O errors, e.g. writing a download to disk
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl SfError {
+    /// Salesforce error codes in the body of an API error
+    ///
+    /// Empty for other errors or bodies that are not a Salesforce error list.
+    pub fn error_codes(&self) -> Vec<ErrorCode> {
+        self.salesforce_errors()
+            .into_iter()
+            .map(|e| e.status_code)
+            .collect()
+    }
+
+    /// Salesforce error details in the body of an API error
+    ///
+    /// Empty for other errors or bodies that are not a Salesforce error list.
+    pub fn salesforce_errors(&self) -> Vec<SalesforceError> {
+        let SfError::Api { body, .. } = self else {
+            return Vec::new();
+        };
+
+        serde_json::from_str(body).unwrap_or_default()
+    }
+
+    /// Duplicate rule details if this is a `DUPLICATES_DETECTED` API error
+    ///
+    /// Lets callers show the matching records and decide whether to retry
+    /// with `allowSave`.
+    pub fn duplicate_result(&self) -> Option<DuplicateResult> {
+        self.salesforce_errors()
+            .into_iter()
+            .find(|e| e.status_code == ErrorCode::DuplicatesDetected)
+            .and_then(|e| e.duplicate_result)
+    }
+}
+
+/// Salesforce API error code, e.g. `REQUIRED_FIELD_MISSING`
+///
+/// Codes this crate doesn't list are kept verbatim in `Other`. Compares
+/// equal to its string form, so `code == "DUPLICATE_VALUE"` also works.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+#[non_exhaustive]
+pub enum ErrorCode {
+    /// `ALL_OR_NONE_OPERATION_ROLLED_BACK`
+    AllOrNoneOperationRolledBack,
+
+    /// `DUPLICATE_VALUE`: a unique field already has this value
+    DuplicateValue,
+
+    /// `DUPLICATES_DETECTED`: a duplicate rule blocked the save
+    DuplicatesDetected,
+
+    /// `ENTITY_IS_DELETED`
+    EntityIsDeleted,
+
+    /// `FIELD_CUSTOM_VALIDATION_EXCEPTION`: a validation rule failed
+    FieldCustomValidationException,
+
+    /// `INSUFFICIENT_ACCESS_OR_READONLY`
+    InsufficientAccessOrReadonly,
+
+    /// `INVALID_FIELD`
+    InvalidField,
+
+    /// `INVALID_FIELD_FOR_INSERT_UPDATE`
+    InvalidFieldForInsertUpdate,
+
+    /// `INVALID_SESSION_ID`
+    InvalidSessionId,
+
+    /// `INVALID_TYPE`
+    InvalidType,
+
+    /// `MALFORMED_ID`
+    MalformedId,
+
+    /// `MALFORMED_QUERY`
+    MalformedQuery,
+
+    /// `NO_APPLICABLE_PROCESS`, e.g. a record failing approval entry criteria
+    NoApplicableProcess,
+
+    /// `NOT_FOUND`
+    NotFound,
+
+    /// `REQUEST_LIMIT_EXCEEDED`
+    RequestLimitExceeded,
+
+    /// `REQUIRED_FIELD_MISSING`
+    RequiredFieldMissing,
+
+    /// `STRING_TOO_LONG`
+    StringTooLong,
+
+    /// `UNABLE_TO_LOCK_ROW`: another transaction holds a lock on the record
+    UnableToLockRow,
+
+    /// Any other code
+    Other(String),
+}
+
+impl ErrorCode {
+    /// The code as sent by Salesforce
+    pub fn as_str(&self) -> &str {
+        match self {
+            ErrorCode::AllOrNoneOperationRolledBack => "ALL_OR_NONE_OPERATION_ROLLED_BACK",
+            ErrorCode::DuplicateValue => "DUPLICATE_VALUE",
+            ErrorCode::DuplicatesDetected => "DUPLICATES_DETECTED",
+            ErrorCode::EntityIsDeleted => "ENTITY_IS_DELETED",
+            ErrorCode::FieldCustomValidationException => "FIELD_CUSTOM_VALIDATION_EXCEPTION",
+            ErrorCode::InsufficientAccessOrReadonly => "INSUFFICIENT_ACCESS_OR_READONLY",
+            ErrorCode::InvalidField => "INVALID_FIELD",
+            ErrorCode::InvalidFieldForInsertUpdate => "INVALID_FIELD_FOR_INSERT_UPDATE",
+            ErrorCode::InvalidSessionId => "INVALID_SESSION_ID",
+            ErrorCode::InvalidType => "INVALID_TYPE",
+            ErrorCode::MalformedId => "MALFORMED_ID",
+            ErrorCode::MalformedQuery => "MALFORMED_QUERY",
+            ErrorCode::NoApplicableProcess => "NO_APPLICABLE_PROCESS",
+            ErrorCode::NotFound => "NOT_FOUND",
+            ErrorCode::RequestLimitExceeded => "REQUEST_LIMIT_EXCEEDED",
+            ErrorCode::RequiredFieldMissing => "REQUIRED_FIELD_MISSING",
+            ErrorCode::StringTooLong => "STRING_TOO_LONG",
+            ErrorCode::UnableToLockRow => "UNABLE_TO_LOCK_ROW",
+            ErrorCode::Other(code) => code,
+        }
+    }
+
+    /// Whether the record was locked by another transaction
+    ///
+    /// These clear on their own, so the operation can be retried.
+    pub fn is_row_lock(&self) -> bool {
+        matches!(self, ErrorCode::UnableToLockRow)
+    }
+
+    /// Whether the save was rejected as a duplicate
+    pub fn is_duplicate(&self) -> bool {
+        matches!(
+            self,
+            ErrorCode::DuplicateValue | ErrorCode::DuplicatesDetected
+        )
+    }
+
+    /// Whether the record data failed validation and needs fixing
+    pub fn is_validation(&self) -> bool {
+        matches!(
+            self,
+            ErrorCode::FieldCustomValidationException
+                | ErrorCode::RequiredFieldMissing
+                | ErrorCode::StringTooLong
+                | ErrorCode::InvalidField
+                | ErrorCode::InvalidFieldForInsertUpdate
+        )
+    }
+}
+
+impl From<&str> for ErrorCode {
+    fn from(code: &str) -> Self {
+        match code {
+            "ALL_OR_NONE_OPERATION_ROLLED_BACK" => ErrorCode::AllOrNoneOperationRolledBack,
+            "DUPLICATE_VALUE" => ErrorCode::DuplicateValue,
+            "DUPLICATES_DETECTED" => ErrorCode::DuplicatesDetected,
+            "ENTITY_IS_DELETED" => ErrorCode::EntityIsDeleted,
+            "FIELD_CUSTOM_VALIDATION_EXCEPTION" => ErrorCode::FieldCustomValidationException,
+            "INSUFFICIENT_ACCESS_OR_READONLY" => ErrorCode::InsufficientAccessOrReadonly,
+            "INVALID_FIELD" => ErrorCode::InvalidField,
+            "INVALID_FIELD_FOR_INSERT_UPDATE" => ErrorCode::InvalidFieldForInsertUpdate,
+            "INVALID_SESSION_ID" => ErrorCode::InvalidSessionId,
+            "INVALID_TYPE" => ErrorCode::InvalidType,
+            "MALFORMED_ID" => ErrorCode::MalformedId,
+            "MALFORMED_QUERY" => ErrorCode::MalformedQuery,
+            "NO_APPLICABLE_PROCESS" => ErrorCode::NoApplicableProcess,
+            "NOT_FOUND" => ErrorCode::NotFound,
+            "REQUEST_LIMIT_EXCEEDED" => ErrorCode::RequestLimitExceeded,
+            "REQUIRED_FIELD_MISSING" => ErrorCode::RequiredFieldMissing,
+            "STRING_TOO_LONG" => ErrorCode::StringTooLong,
+            "UNABLE_TO_LOCK_ROW" => ErrorCode::UnableToLockRow,
+            other => ErrorCode::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<String> for ErrorCode {
+    fn from(code: String) -> Self {
+        match ErrorCode::from(code.as_str()) {
+            ErrorCode::Other(_) => ErrorCode::Other(code),
+            known => known,
+        }
+    }
+}
+
+impl From<ErrorCode> for String {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::Other(code) => code,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<str> for ErrorCode {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for ErrorCode {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
 }
 
 /// Result type alias for Salesforce operations
 pub type SfResult<T> = Result<T, SfError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_code_round_trip() {
+        for code in [
+            "REQUIRED_FIELD_MISSING",
+            "DUPLICATE_VALUE",
+            "FIELD_CUSTOM_VALIDATION_EXCEPTION",
+            "UNABLE_TO_LOCK_ROW",
+            "INVALID_FIELD",
+            "MALFORMED_QUERY",
+            "INVALID_SESSION_ID",
+            "CANNOT_INSERT_UPDATE_ACTIVATE_ENTITY",
+        ] {
+            let json = format!("\"{}\"", code);
+            let parsed: ErrorCode = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed, code);
+            assert_eq!(serde_json::to_string(&parsed).unwrap(), json);
+        }
+
+        assert_eq!(
+            ErrorCode::from("UNABLE_TO_LOCK_ROW"),
+            ErrorCode::UnableToLockRow
+        );
+        assert_eq!(
+            ErrorCode::from("CANNOT_INSERT_UPDATE_ACTIVATE_ENTITY"),
+            ErrorCode::Other("CANNOT_INSERT_UPDATE_ACTIVATE_ENTITY".to_string())
+        );
+        assert_eq!(ErrorCode::MalformedQuery.to_string(), "MALFORMED_QUERY");
+    }
+
+    #[test]
+    fn test_error_code_helpers() {
+        assert!(ErrorCode::UnableToLockRow.is_row_lock());
+        assert!(!ErrorCode::DuplicateValue.is_row_lock());
+        assert!(ErrorCode::DuplicateValue.is_duplicate());
+        assert!(ErrorCode::DuplicatesDetected.is_duplicate());
+        assert!(ErrorCode::RequiredFieldMissing.is_validation());
+        assert!(!ErrorCode::Other("UNKNOWN".to_string()).is_validation());
+    }
+
+    #[test]
+    fn test_error_codes_from_api_error() {
+        let error = SfError::Api {
+            status: 400,
+            body: r#"[{"statusCode":"FIELD_CUSTOM_VALIDATION_EXCEPTION","message":"Amount must be positive","fields":[]},{"errorCode":"SOMETHING_NEW","message":"?"}]"#.to_string(),
+        };
+        assert_eq!(
+            error.error_codes(),
+            vec![
+                ErrorCode::FieldCustomValidationException,
+                ErrorCode::Other("SOMETHING_NEW".to_string())
+            ]
+        );
+
+        let error = SfError::Api {
+            status: 502,
+            body: "<html>Bad Gateway</html>".to_string(),
+        };
+        assert!(error.error_codes().is_empty());
+        assert!(SfError::Auth("expired".to_string())
+            .error_codes()
+            .is_empty());
+    }
+}
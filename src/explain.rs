@@ -0,0 +1,99 @@
+//! Query plans from the `explain` parameter of the query resource
+//!
+//! `GET /query/?explain=` takes a SOQL query, a report ID or a list view ID
+//! and returns the plans the optimizer considered, cheapest first, without
+//! running the query. A `relative_cost` above 1 means the query is not
+//! selective.
+
+use serde::{Deserialize, Serialize};
+
+/// Response from the explain resource
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ExplainResponse {
+    /// Candidate plans, cheapest first
+    pub plans: Vec<QueryPlan>,
+}
+
+impl ExplainResponse {
+    /// The plan the optimizer would use
+    pub fn best(&self) -> Option<&QueryPlan> {
+        self.plans.first()
+    }
+}
+
+/// One candidate execution plan
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct QueryPlan {
+    /// Estimated number of records the leading operation returns
+    pub cardinality: u64,
+
+    /// Indexed fields used by the leading operation
+    pub fields: Vec<String>,
+
+    /// Primary operation, e.g. `Index`, `Other`, `Sharing` or `TableScan`
+    pub leading_operation_type: String,
+
+    /// Cost relative to the selectivity threshold; above 1 is not selective
+    pub relative_cost: f64,
+
+    /// Approximate record count of the queried object
+    pub sobject_cardinality: u64,
+
+    /// Queried object
+    pub sobject_type: String,
+
+    /// Why filters were or weren't used for optimization
+    pub notes: Vec<PlanNote>,
+}
+
+impl QueryPlan {
+    /// Whether the plan is selective enough to avoid a full scan
+    pub fn is_selective(&self) -> bool {
+        self.relative_cost <= 1.0
+    }
+}
+
+/// Optimizer explanation for a plan
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct PlanNote {
+    /// Explanation, e.g. `Not considering filter for optimization because unindexed`
+    pub description: String,
+
+    /// Fields the note applies to
+    pub fields: Vec<String>,
+
+    /// Object the note applies to
+    pub table_enum_or_id: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_fixture() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/explain_plan.json"
+        );
+        let response: ExplainResponse =
+            serde_json::from_str(&std::fs::read_to_string(path).unwrap()).unwrap();
+
+        assert_eq!(response.plans.len(), 2);
+
+        let best = response.best().unwrap();
+        assert_eq!(best.leading_operation_type, "Index");
+        assert_eq!(best.fields, vec!["External_Id__c"]);
+        assert!(best.is_selective());
+
+        let scan = &response.plans[1];
+        assert_eq!(scan.cardinality, 2843);
+        assert_eq!(scan.sobject_cardinality, 184532);
+        assert!(!scan.is_selective());
+        assert_eq!(scan.notes[1].fields, vec!["IsDeleted"]);
+        assert_eq!(scan.notes[1].table_enum_or_id, "Account");
+    }
+}
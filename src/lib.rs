@@ -46,29 +46,78 @@
 //! ```
 
 // Module declarations
+pub mod aggregate;
 pub mod auth;
+#[cfg(feature = "bulk-api")]
+pub mod bulk;
 pub mod cache;
 pub mod crud;
+pub mod describe;
 pub mod error;
+pub mod explain;
 pub mod pagination;
 pub mod query_builder;
 pub mod rate_limit;
+pub mod registry;
 pub mod retry;
+pub mod soql;
+pub mod tooling;
 
 // Re-exports for convenience
-pub use auth::{AccessToken, OAuthCredentials, TokenManager};
-pub use cache::{CacheConfig, QueryCache};
-pub use crud::{InsertResponse, UpdateResponse, UpsertBuilder};
-pub use error::{SfError, SfResult};
-pub use pagination::{PaginatedQuery, QueryOptions};
-pub use query_builder::{CountQueryBuilder, QueryBuilder, SubqueryBuilder};
+pub use aggregate::AggregateRow;
+pub use auth::store::{FileTokenStore, TokenStore};
+pub use auth::{
+    AccessToken, DeviceAuthorization, Environment, IntrospectionResult, OAuthCredentials,
+    TokenManager, UserIdentity,
+};
+#[cfg(feature = "bulk-api")]
+pub use bulk::{BulkJobInfo, BulkQuery, BulkQueryOptions, JobState};
+pub use cache::{CacheConfig, CachePolicy, CacheStats, MetadataCache, QueryCache, RecordCache};
+pub use crud::{
+    ApprovalProcess, ApprovalRequest, ApprovalResult, AutoAssign, BatchOutcome, Blob, BlobMetadata,
+    CompositeRequestBuilder, CompositeResponse, CompositeSubresponse, DeleteReport, DeletedRecord,
+    DeletedRecords, DuplicateResult, DuplicateRuleHeader, FailedDelete, FileContent, IngestOptions,
+    IngestProgress, InsertOptions, InsertResponse, InsertedRecord, MatchRecord, MatchResult,
+    ProgressCallback, QuickAction, QuickActionResult, ResilientDeleteOptions, SalesforceError,
+    SaveResult, TreeRecord, TreeRecordResult, TreeResponse, UpdateOptions, UpdateResponse,
+    UpdatedRecords, UpsertBuilder,
+};
+pub use describe::{
+    CompactLayout, DescribeCompactLayouts, DescribeGlobal, DescribeLayouts, FieldDescribe, Layout,
+    LayoutComponent, LayoutItem, LayoutRow, LayoutSection, PicklistEntry, PicklistFieldValues,
+    PicklistValue, RecordTypeCompactLayoutMapping, RecordTypeMapping, RecordTypePicklists,
+    SObjectBasicInfo, SObjectDescribe,
+};
+pub use error::{ErrorCode, SfError, SfResult};
+pub use explain::{ExplainResponse, PlanNote, QueryPlan};
+pub use pagination::{
+    DuplicateKeys, HasId, KeysetQuery, PageSource, PaginatedQuery, QueryEndpoint, QueryOptions,
+    QueryResult,
+};
+pub use query_builder::{
+    CountQueryBuilder, FieldsSpec, Query, QueryBuilder, Scope, Selector, SubqueryBuilder,
+    TypeofBuilder,
+};
 pub use rate_limit::{RateLimitConfig, RateLimiter};
+pub use registry::ClientRegistry;
 pub use retry::RetryConfig;
+pub use soql::{Condition, ConditionGroup, FieldPath, FieldRef, SoqlValue};
+pub use tooling::ToolingClient;
 
+use futures::{StreamExt, TryStreamExt};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
+
+/// REST API version used unless [`ClientConfig::with_api_version`] says otherwise
+pub const DEFAULT_API_VERSION: &str = "v57.0";
+
+/// Longest URL-encoded SOQL query sent unless
+/// [`ClientConfig::with_max_query_length`] says otherwise
+pub const DEFAULT_MAX_QUERY_LENGTH: usize = 16_000;
 
 /// Client configuration builder
 ///
@@ -88,11 +137,35 @@ pub struct ClientConfig {
     /// Cache configuration
     pub cache_config: CacheConfig,
 
+    /// Cache configuration for records fetched with `get` (None = `cache_config`)
+    pub record_cache_config: Option<CacheConfig>,
+
     /// Rate limit configuration
     pub rate_limit_config: RateLimitConfig,
 
-    /// Enable automatic pagination
+    /// Have `query` and `query_all_records` follow `nextRecordsUrl` to the
+    /// last page instead of returning only the first
     pub auto_paginate: bool,
+
+    /// REST API version, e.g. `v57.0`
+    pub api_version: String,
+
+    /// Longest URL-encoded query to send; longer ones fail locally
+    pub max_query_length: usize,
+
+    /// Most records `query_all` and `collect_all` gather (None = unlimited)
+    pub max_records: Option<usize>,
+
+    /// Environment used for OAuth token requests
+    pub environment: Environment,
+
+    /// HTTP client to use instead of building one (proxies, custom CAs, ...)
+    pub http_client: Option<reqwest::Client>,
+
+    /// Honor the `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY` environment variables
+    ///
+    /// Only applies when no custom `http_client` is set.
+    pub env_proxy: bool,
 }
 
 impl ClientConfig {
@@ -103,8 +176,15 @@ impl ClientConfig {
             access_token: access_token.into(),
             retry_config: RetryConfig::default(),
             cache_config: CacheConfig::default(),
+            record_cache_config: None,
             rate_limit_config: RateLimitConfig::default(),
             auto_paginate: true,
+            api_version: DEFAULT_API_VERSION.to_string(),
+            max_query_length: DEFAULT_MAX_QUERY_LENGTH,
+            max_records: None,
+            environment: Environment::Production,
+            http_client: None,
+            env_proxy: true,
         }
     }
 
@@ -120,18 +200,171 @@ impl ClientConfig {
         self
     }
 
+    /// Configure the record cache separately from query caching
+    ///
+    /// Records fetched with [`SalesforceClient::get`] are cached per ID and
+    /// dropped when the record is updated, upserted or deleted through the
+    /// client.
+    pub fn with_record_cache(mut self, config: CacheConfig) -> Self {
+        self.record_cache_config = Some(config);
+        self
+    }
+
     /// Configure rate limiting
     pub fn with_rate_limit(mut self, config: RateLimitConfig) -> Self {
         self.rate_limit_config = config;
         self
     }
 
+    /// Use a specific REST API version instead of [`DEFAULT_API_VERSION`]
+    ///
+    /// Accepts `v61.0` or `61.0`; anything else fails with
+    /// [`SfError::Config`].
+    ///
+    /// # Example
+    /// ```
+    /// use salesforce_client::ClientConfig;
+    ///
+    /// let config = ClientConfig::new("https://example.my.salesforce.com", "token")
+    ///     .with_api_version("v61.0")?;
+    /// assert_eq!(config.api_version, "v61.0");
+    /// # Ok::<(), salesforce_client::SfError>(())
+    /// ```
+    pub fn with_api_version(mut self, version: impl AsRef<str>) -> SfResult<Self> {
+        let version = version.as_ref();
+        let number = version.strip_prefix('v').unwrap_or(version);
+
+        let valid = number.split_once('.').is_some_and(|(major, minor)| {
+            !major.is_empty()
+                && major.len() <= 3
+                && minor.len() == 1
+                && major
+                    .chars()
+                    .chain(minor.chars())
+                    .all(|c| c.is_ascii_digit())
+        });
+        if !valid {
+            return Err(SfError::Config(format!(
+                "Invalid API version {:?}, expected the form vNN.N",
+                version
+            )));
+        }
+
+        self.api_version = format!("v{}", number);
+        Ok(self)
+    }
+
+    /// Set the longest URL-encoded query to send
+    ///
+    /// Queries travel in the URL, and Salesforce rejects overly long ones
+    /// with an HTML `414 URI Too Long` page. Longer queries fail with
+    /// [`SfError::InvalidQuery`] before anything is sent.
+    pub fn with_max_query_length(mut self, max_query_length: usize) -> Self {
+        self.max_query_length = max_query_length;
+        self
+    }
+
+    /// Cap the records gathered into memory by `query_all` and `collect_all`
+    ///
+    /// Past the cap they fail with [`SfError::ResultTooLarge`] instead of
+    /// continuing to accumulate. [`QueryOptions::max_records`] overrides it
+    /// per query.
+    pub fn with_max_records(mut self, max_records: usize) -> Self {
+        self.max_records = Some(max_records);
+        self
+    }
+
+    /// REST API root, e.g. `https://example.my.salesforce.com/services/data/v57.0`
+    pub fn api_url(&self) -> String {
+        format!("{}/services/data/{}", self.base_url, self.api_version)
+    }
+
+    /// Authenticate against the sandbox login endpoint (test.salesforce.com)
+    pub fn sandbox(mut self) -> Self {
+        self.environment = Environment::Sandbox;
+        self
+    }
+
+    /// Configure the environment used for OAuth token requests
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    /// Use a preconfigured HTTP client for all requests
+    ///
+    /// OAuth clients pass it to their `TokenManager` too, so proxy and TLS
+    /// settings also apply to token requests.
+    ///
+    /// # Example
+    /// ```
+    /// use salesforce_client::ClientConfig;
+    ///
+    /// let http_client = reqwest::Client::builder()
+    ///     .proxy(reqwest::Proxy::https("http://proxy.corp.example:3128").unwrap())
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// let config = ClientConfig::new("", "").with_http_client(http_client);
+    /// ```
+    pub fn with_http_client(mut self, http_client: reqwest::Client) -> Self {
+        self.http_client = Some(http_client);
+        self
+    }
+
+    /// Configure whether proxy environment variables are honored
+    ///
+    /// Enabled by default: `HTTPS_PROXY`, `HTTP_PROXY`, `ALL_PROXY` and
+    /// `NO_PROXY` (or their lowercase forms) are read when the client is
+    /// built. Pass `false` to always connect directly. Ignored when a custom
+    /// client is set with [`ClientConfig::with_http_client`].
+    pub fn with_env_proxy(mut self, enabled: bool) -> Self {
+        self.env_proxy = enabled;
+        self
+    }
+
+    /// Get the configured HTTP client, or build one from the proxy settings
+    fn http_client(&self) -> reqwest::Client {
+        if let Some(http_client) = &self.http_client {
+            return http_client.clone();
+        }
+
+        let mut builder = reqwest::Client::builder();
+        if !self.env_proxy {
+            builder = builder.no_proxy();
+        }
+        // Building only fails if the TLS backend cannot be initialized
+        builder.build().unwrap_or_else(|_| reqwest::Client::new())
+    }
+
     /// Disable automatic pagination
+    ///
+    /// [`SalesforceClient::query`] then returns only the first page (up to
+    /// 2000 records), as it did before `auto_paginate` took effect.
     pub fn no_pagination(mut self) -> Self {
         self.auto_paginate = false;
         self
     }
 
+    /// Load a named profile from the default profiles file
+    ///
+    /// Reads `~/.config/salesforce-client/profiles.toml` (see
+    /// [`auth::Profiles`]). For OAuth profiles, pair the config with
+    /// `Profile::credentials()` and [`SalesforceClient::with_oauth_config`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// use salesforce_client::{ClientConfig, SalesforceClient};
+    ///
+    /// # fn example() -> Result<(), salesforce_client::SfError> {
+    /// let client = SalesforceClient::new(ClientConfig::from_profile("dev")?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_profile(name: &str) -> SfResult<Self> {
+        Ok(auth::Profiles::load_default()?.get(name)?.client_config())
+    }
+
     /// Disable all optional features (for testing or simple use cases)
     pub fn minimal() -> Self {
         Self {
@@ -139,8 +372,15 @@ impl ClientConfig {
             access_token: String::new(),
             retry_config: RetryConfig::no_retry(),
             cache_config: CacheConfig::disabled(),
+            record_cache_config: None,
             rate_limit_config: RateLimitConfig::unlimited(),
             auto_paginate: false,
+            api_version: DEFAULT_API_VERSION.to_string(),
+            max_query_length: DEFAULT_MAX_QUERY_LENGTH,
+            max_records: None,
+            environment: Environment::Production,
+            http_client: None,
+            env_proxy: true,
         }
     }
 }
@@ -173,11 +413,20 @@ pub struct SalesforceClient {
     /// Query result cache
     query_cache: Arc<QueryCache>,
 
+    /// Describe and other metadata cache
+    metadata_cache: Arc<MetadataCache>,
+
+    /// Individual record cache
+    record_cache: Arc<RecordCache>,
+
     /// Rate limiter
     rate_limiter: Arc<RateLimiter>,
 
     /// CRUD operations handler
     crud: Arc<crud::CrudOperations>,
+
+    /// Source of the bearer token for each request
+    token_provider: Arc<dyn auth::TokenProvider>,
 }
 
 impl SalesforceClient {
@@ -195,14 +444,30 @@ impl SalesforceClient {
     /// let client = SalesforceClient::new(config);
     /// ```
     pub fn new(config: ClientConfig) -> Self {
-        let http_client = reqwest::Client::new();
+        let token_provider = Arc::new(auth::StaticToken(config.access_token.clone()));
+        Self::with_token_provider(config, token_provider)
+    }
+
+    /// Build a client around an arbitrary token provider
+    fn with_token_provider(
+        config: ClientConfig,
+        token_provider: Arc<dyn auth::TokenProvider>,
+    ) -> Self {
+        let http_client = config.http_client();
         let query_cache = Arc::new(QueryCache::new(config.cache_config.clone()));
+        let metadata_cache = Arc::new(MetadataCache::new(config.cache_config.clone()));
+        let record_cache = Arc::new(RecordCache::new(
+            config
+                .record_cache_config
+                .clone()
+                .unwrap_or_else(|| config.cache_config.clone()),
+        ));
         let rate_limiter = Arc::new(RateLimiter::new(config.rate_limit_config.clone()));
 
         let crud = Arc::new(crud::CrudOperations::new(
             http_client.clone(),
-            config.base_url.clone(),
-            config.access_token.clone(),
+            config.api_url(),
+            token_provider.clone(),
         ));
 
         info!(
@@ -214,8 +479,11 @@ impl SalesforceClient {
             config: Arc::new(config),
             http_client,
             query_cache,
+            metadata_cache,
+            record_cache,
             rate_limiter,
             crud,
+            token_provider,
         }
     }
 
@@ -225,24 +493,88 @@ impl SalesforceClient {
     /// ```no_run
     /// use salesforce_client::{SalesforceClient, OAuthCredentials};
     ///
+    /// # async fn example() -> Result<(), salesforce_client::SfError> {
     /// let credentials = OAuthCredentials {
     ///     client_id: "your_client_id".to_string(),
     ///     client_secret: "your_client_secret".to_string(),
     ///     refresh_token: Some("your_refresh_token".to_string()),
     ///     username: None,
     ///     password: None,
+    ///     security_token: None,
     /// };
     ///
-    /// // This will be implemented with TokenManager integration
-    /// // let client = SalesforceClient::with_oauth(credentials).await?;
+    /// let client = SalesforceClient::with_oauth(credentials).await?;
+    /// # Ok(())
+    /// # }
     /// ```
     pub async fn with_oauth(credentials: OAuthCredentials) -> SfResult<Self> {
-        let token_manager = TokenManager::new(credentials);
+        Self::with_oauth_config(ClientConfig::new(String::new(), String::new()), credentials).await
+    }
+
+    /// Create an OAuth client using the environment and settings from `config`
+    ///
+    /// # Example
+    /// ```no_run
+    /// use salesforce_client::{ClientConfig, OAuthCredentials, SalesforceClient};
+    ///
+    /// # async fn example() -> Result<(), salesforce_client::SfError> {
+    /// let config = ClientConfig::new("", "").sandbox();
+    /// let credentials = OAuthCredentials::client_credentials("id", "secret");
+    ///
+    /// let client = SalesforceClient::with_oauth_config(config, credentials).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn with_oauth_config(
+        mut config: ClientConfig,
+        credentials: OAuthCredentials,
+    ) -> SfResult<Self> {
+        // Share one HTTP client so token requests use the same proxy/TLS setup
+        let http_client = config.http_client();
+        config.http_client = Some(http_client.clone());
+
+        let token_manager = TokenManager::for_environment(credentials, &config.environment)?
+            .with_http_client(http_client);
+        Self::with_token_manager(config, token_manager).await
+    }
+
+    /// Create a client that obtains tokens from a `TokenManager`
+    ///
+    /// A token is fetched up front to discover the instance URL, which
+    /// replaces `config.base_url`. Every request then asks the manager for
+    /// the current token, so expired sessions are refreshed transparently
+    /// for the lifetime of the client.
+    ///
+    /// Pass an `Arc<TokenManager>` to keep a handle to the manager (e.g. to
+    /// call `invalidate()`).
+    ///
+    /// # Example
+    /// ```no_run
+    /// use salesforce_client::{ClientConfig, OAuthCredentials, SalesforceClient, TokenManager};
+    /// use std::sync::Arc;
+    ///
+    /// # async fn example() -> Result<(), salesforce_client::SfError> {
+    /// let manager = Arc::new(TokenManager::new(OAuthCredentials::client_credentials(
+    ///     "your_client_id",
+    ///     "your_client_secret",
+    /// )));
+    ///
+    /// let config = ClientConfig::new("", "");
+    /// let client = SalesforceClient::with_token_manager(config, manager.clone()).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn with_token_manager(
+        mut config: ClientConfig,
+        token_manager: impl Into<Arc<TokenManager>>,
+    ) -> SfResult<Self> {
+        let token_manager = token_manager.into();
         let token = token_manager.get_token().await?;
 
-        let config = ClientConfig::new(token.instance_url(), token.token());
+        config.base_url = token.instance_url().to_string();
+        config.access_token = token.token().to_string();
 
-        Ok(Self::new(config))
+        Ok(Self::with_token_provider(config, token_manager))
     }
 
     /// Execute a SOQL query with caching, retry, and rate limiting
@@ -251,7 +583,13 @@ impl SalesforceClient {
     /// - Rate limiting (waits if limit exceeded)
     /// - Caching (returns cached results if available)
     /// - Retry logic (retries transient failures)
-    /// - Pagination (fetches only first page by default)
+    /// - Pagination (follows `nextRecordsUrl` to the last page while
+    ///   [`ClientConfig::auto_paginate`] is set, the default; with
+    ///   [`ClientConfig::no_pagination`] only the first page is returned)
+    ///
+    /// With auto-pagination, gathering more than
+    /// [`ClientConfig::max_records`] fails with [`SfError::ResultTooLarge`].
+    /// The complete result is cached as one entry.
     ///
     /// # Example
     /// ```no_run
@@ -273,51 +611,312 @@ impl SalesforceClient {
     where
         T: DeserializeOwned + Serialize + Clone,
     {
-        let query_str = soql.as_ref();
+        let result = self
+            .query_endpoint(
+                QueryEndpoint::Query,
+                soql.as_ref(),
+                self.config.auto_paginate,
+                CachePolicy::Default,
+            )
+            .await?;
+        Ok(result.records)
+    }
 
-        // Check cache first
-        if let Some(cached) = self.query_cache.get::<T>(query_str).await {
-            debug!("Returning cached query results");
-            return Ok(cached);
+    /// Execute a SOQL query, choosing how it uses the query cache
+    ///
+    /// `query` is this with [`CachePolicy::Default`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, CachePolicy, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// // Current state, whatever is cached
+    /// let fresh: Vec<serde_json::Value> = client
+    ///     .query_with_policy("SELECT Id, Status__c FROM Order__c", CachePolicy::Bypass)
+    ///     .await?;
+    ///
+    /// // Reference data, only if already cached
+    /// match client
+    ///     .query_with_policy::<serde_json::Value>("SELECT Id FROM Country__c", CachePolicy::CacheOnly)
+    ///     .await
+    /// {
+    ///     Ok(countries) => println!("{} cached countries", countries.len()),
+    ///     Err(SfError::NotCached) => println!("not cached yet"),
+    ///     Err(e) => return Err(e),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, soql))]
+    pub async fn query_with_policy<T>(
+        &self,
+        soql: impl AsRef<str>,
+        policy: CachePolicy,
+    ) -> SfResult<Vec<T>>
+    where
+        T: DeserializeOwned + Serialize + Clone,
+    {
+        let result = self
+            .query_endpoint(
+                QueryEndpoint::Query,
+                soql.as_ref(),
+                self.config.auto_paginate,
+                policy,
+            )
+            .await?;
+        Ok(result.records)
+    }
+
+    /// Execute a SOQL query that also returns deleted and archived records
+    ///
+    /// Like [`query`](Self::query), but against `/queryAll`, so rows with
+    /// `IsDeleted = true` and archived Tasks and Events are included. Results
+    /// are cached separately from `query`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::{Deserialize, Serialize};
+    /// # #[derive(Debug, Clone, Deserialize, Serialize)]
+    /// # struct Account { #[serde(rename = "Id")] id: String }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let deleted: Vec<Account> = client
+    ///     .query_all_records("SELECT Id FROM Account WHERE IsDeleted = true")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, soql))]
+    pub async fn query_all_records<T>(&self, soql: impl AsRef<str>) -> SfResult<Vec<T>>
+    where
+        T: DeserializeOwned + Serialize + Clone,
+    {
+        let result = self
+            .query_endpoint(
+                QueryEndpoint::QueryAll,
+                soql.as_ref(),
+                self.config.auto_paginate,
+                CachePolicy::Default,
+            )
+            .await?;
+        Ok(result.records)
+    }
+
+    /// Execute a SOQL query, keeping `totalSize` and paging state
+    ///
+    /// Returns the first page like [`query`](Self::query), sharing its
+    /// cache, along with the total number of matching records and the
+    /// `nextRecordsUrl` of the next page, e.g. for "showing 50 of 12,340".
+    ///
+    /// A cached result keeps the `nextRecordsUrl` it was fetched with, and
+    /// Salesforce expires that cursor after about 15 minutes; fetching the
+    /// next page from a result older than that fails.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::{Deserialize, Serialize};
+    /// # #[derive(Debug, Clone, Deserialize, Serialize)]
+    /// # struct Account { #[serde(rename = "Id")] id: String }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let result = client
+    ///     .query_with_meta::<Account>("SELECT Id FROM Account")
+    ///     .await?;
+    /// println!(
+    ///     "Showing {} of {}",
+    ///     result.records.len(),
+    ///     result.total_size.unwrap_or_default()
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, soql))]
+    pub async fn query_with_meta<T>(&self, soql: impl AsRef<str>) -> SfResult<QueryResult<T>>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        self.query_endpoint(
+            QueryEndpoint::Query,
+            soql.as_ref(),
+            false,
+            CachePolicy::Default,
+        )
+        .await
+    }
+
+    /// Cached, retried query against `endpoint`, following every page when
+    /// `paginate` is set
+    async fn query_endpoint<T>(
+        &self,
+        endpoint: QueryEndpoint,
+        soql: &str,
+        paginate: bool,
+        policy: CachePolicy,
+    ) -> SfResult<QueryResult<T>>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        // Check cache first; first pages and full results are cached apart
+        if policy.reads() {
+            if let Some((cached, stale)) = self
+                .query_cache
+                .get_result_with_staleness::<T>(endpoint, soql, paginate)
+                .await
+            {
+                if stale && policy != CachePolicy::CacheOnly {
+                    self.revalidate(endpoint, soql, paginate, policy);
+                }
+                debug!("Returning cached query results");
+                return Ok(cached);
+            }
+        }
+        if policy == CachePolicy::CacheOnly {
+            return Err(SfError::NotCached);
+        }
+
+        let result = self.fetch_endpoint::<T>(endpoint, soql, paginate).await?;
+        if policy.writes(result.records.is_empty()) {
+            if let Err(e) = self
+                .query_cache
+                .set_result_at(endpoint, soql, paginate, &result)
+                .await
+            {
+                debug!("Failed to cache query results: {}", e);
+            }
         }
 
+        Ok(result)
+    }
+
+    /// Retried query against `endpoint`, bypassing the cache
+    async fn fetch_endpoint<T>(
+        &self,
+        endpoint: QueryEndpoint,
+        soql: &str,
+        paginate: bool,
+    ) -> SfResult<QueryResult<T>>
+    where
+        T: DeserializeOwned,
+    {
         // Apply rate limiting
         self.rate_limiter.acquire().await?;
 
         // Execute query with retry logic
-        let result = retry::with_retry(&self.config.retry_config, || async {
-            self.execute_query(query_str).await
+        let url = self.query_url(endpoint);
+        let mut result = retry::with_retry(&self.config.retry_config, || async {
+            self.fetch_query_response::<T>(&url, soql).await
         })
         .await?;
 
-        // Cache the results (clone only if T is Clone, otherwise skip caching)
-        // Note: We require T: Clone for caching
-        if let Ok(()) = self.query_cache.set(query_str, result.clone()).await {
-            // Cached successfully
+        if paginate && result.has_more() {
+            pagination::check_max_records(result.records.len(), self.config.max_records)?;
+            let mut pages = PaginatedQuery::<T>::new(
+                self.http_client.clone(),
+                self.config.base_url.clone(),
+                self.token_provider.clone(),
+                Vec::new(),
+                result.next_records_url.take(),
+            );
+            while let Some(batch) = pages.next().await? {
+                result.records.extend(batch);
+                pagination::check_max_records(result.records.len(), self.config.max_records)?;
+            }
+            result.done = true;
         }
+        info!("Query returned {} records", result.records.len());
 
         Ok(result)
     }
 
-    /// Execute query without caching (internal method)
-    async fn execute_query<T>(&self, soql: &str) -> SfResult<Vec<T>>
+    /// Re-run a stale cached query in the background and cache the result
+    ///
+    /// At most one refresh per query runs at a time, and the result is
+    /// cached as `policy` would cache it. Records are refreshed as raw
+    /// JSON, which the caller's type is deserialized from on the next read.
+    /// Failures are logged and leave the stale entry in place.
+    fn revalidate(&self, endpoint: QueryEndpoint, soql: &str, paginate: bool, policy: CachePolicy) {
+        let Some(claim) = self.query_cache.claim_refresh(endpoint, soql, paginate) else {
+            debug!("Cached query refresh already in flight");
+            return;
+        };
+
+        let client = self.clone();
+        let soql = soql.to_string();
+        tokio::spawn(async move {
+            let result = match client
+                .fetch_endpoint::<serde_json::Value>(endpoint, &soql, paginate)
+                .await
+            {
+                Ok(result) => result,
+                Err(e) => {
+                    warn!("Background refresh of cached query failed: {}", e);
+                    return;
+                }
+            };
+
+            // The stale entry is out of date either way
+            if !policy.writes(result.records.is_empty()) {
+                debug!("Dropping stale query results instead of caching the refresh");
+                client.query_cache.discard_refresh(&claim).await;
+                return;
+            }
+            if let Err(e) = client
+                .query_cache
+                .set_refreshed(&claim, &soql, &result)
+                .await
+            {
+                debug!("Failed to cache refreshed query results: {}", e);
+            }
+        });
+    }
+
+    /// URL of a query resource
+    fn query_url(&self, endpoint: QueryEndpoint) -> String {
+        format!("{}/{}", self.config.api_url(), endpoint.path())
+    }
+
+    /// Fetch the first page of a query from the given query resource
+    async fn execute_query_at<T>(&self, url: &str, soql: &str) -> SfResult<Vec<T>>
     where
         T: DeserializeOwned,
     {
-        let url = format!("{}/services/data/v57.0/query", self.config.base_url);
+        let query_response = self.fetch_query_response::<T>(url, soql).await?;
+
+        info!("Query returned {} records", query_response.records.len());
+        Ok(query_response.records)
+    }
 
+    /// Fail on queries too long to send in a URL
+    fn check_query_length(&self, soql: &str) -> SfResult<()> {
+        let length = encoded_query_length(soql);
+        if length > self.config.max_query_length {
+            return Err(query_too_long(length));
+        }
+        Ok(())
+    }
+
+    /// Fetch the raw first-page response of a query
+    async fn fetch_query_response<T>(
+        &self,
+        url: &str,
+        soql: &str,
+    ) -> SfResult<pagination::QueryResult<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.check_query_length(soql)?;
         debug!("Executing SOQL query");
 
-        let response = self
-            .http_client
-            .get(&url)
-            .query(&[("q", soql)])
-            .header(
-                "Authorization",
-                format!("Bearer {}", self.config.access_token),
-            )
-            .send()
-            .await?;
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.get(url).query(&[("q", soql)])
+        })
+        .await?;
 
         let status = response.status();
         if !status.is_success() {
@@ -337,6 +936,9 @@ impl SalesforceClient {
             if status.as_u16() == 429 {
                 return Err(SfError::RateLimit { retry_after });
             }
+            if status.as_u16() == 414 {
+                return Err(query_too_long(encoded_query_length(soql)));
+            }
 
             return Err(SfError::Api {
                 status: status.as_u16(),
@@ -344,306 +946,7607 @@ impl SalesforceClient {
             });
         }
 
-        let query_response: pagination::QueryResponse<T> = response.json().await?;
-
-        info!("Query returned {} records", query_response.records.len());
-        Ok(query_response.records)
+        Ok(response.json().await?)
     }
 
-    /// Query with automatic pagination - fetches ALL results
+    /// Execute a [`QueryBuilder`] query
     ///
-    /// **Warning**: This can consume significant memory for large result sets.
-    /// For queries returning >100k records, consider using `query_paginated` instead.
+    /// Equivalent to `query(builder.try_build()?)`, sharing its cache
+    /// entries; a builder that fails validation is rejected with
+    /// [`SfError::InvalidQuery`] before anything is sent.
+    /// Use [`count`](Self::count) for a [`CountQueryBuilder`].
     ///
     /// # Example
     /// ```no_run
-    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use salesforce_client::{QueryBuilder, SalesforceClient, ClientConfig, SfError};
     /// # use serde::{Deserialize, Serialize};
     /// # #[derive(Debug, Clone, Deserialize, Serialize)]
     /// # struct Account { #[serde(rename = "Id")] id: String }
     /// # async fn example() -> Result<(), SfError> {
     /// # let config = ClientConfig::new("https://example.com", "token");
     /// # let client = SalesforceClient::new(config);
-    /// // Fetches all accounts, automatically handling pagination
     /// let accounts: Vec<Account> = client
-    ///     .query_all("SELECT Id, Name FROM Account")
+    ///     .execute(
+    ///         QueryBuilder::select(&["Id", "Name"])
+    ///             .from("Account")
+    ///             .where_clause("Industry = 'Energy'")
+    ///             .limit(50),
+    ///     )
     ///     .await?;
     /// # Ok(())
     /// # }
     /// ```
-    #[instrument(skip(self, soql))]
-    pub async fn query_all<T>(&self, soql: impl AsRef<str>) -> SfResult<Vec<T>>
+    pub async fn execute<T>(
+        &self,
+        builder: QueryBuilder<query_builder::Complete>,
+    ) -> SfResult<Vec<T>>
     where
-        T: DeserializeOwned + Serialize,
+        T: DeserializeOwned + Serialize + Clone,
     {
-        info!("Executing query with full pagination");
-
-        let mut all_records = Vec::new();
-        let mut pages = self.query_paginated::<T>(soql.as_ref()).await?;
-
-        while let Some(batch) = pages.next().await? {
-            all_records.extend(batch);
-        }
-
-        info!("Collected {} total records", all_records.len());
-        Ok(all_records)
+        self.query(builder.try_build()?).await
     }
 
-    /// Get a paginated query iterator for manual pagination control
+    /// Run a typed [`Query`], returning the first page of records
     ///
-    /// This is the most memory-efficient way to handle large result sets.
+    /// Equivalent to [`query`](Self::query) with the query's SOQL, sharing
+    /// its cache, so reusing one `Query` value hits the cache.
     ///
     /// # Example
     /// ```no_run
-    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use salesforce_client::{Query, QueryBuilder, SalesforceClient, ClientConfig, SfError};
     /// # use serde::{Deserialize, Serialize};
     /// # #[derive(Debug, Clone, Deserialize, Serialize)]
     /// # struct Account { #[serde(rename = "Id")] id: String }
     /// # async fn example() -> Result<(), SfError> {
     /// # let config = ClientConfig::new("https://example.com", "token");
     /// # let client = SalesforceClient::new(config);
-    /// let mut pages = client
-    ///     .query_paginated::<Account>("SELECT Id FROM Account")
-    ///     .await?;
+    /// let top_accounts: Query<Account> = QueryBuilder::select(&["Id"])
+    ///     .from("Account")
+    ///     .order_by_desc("AnnualRevenue")
+    ///     .limit(10)
+    ///     .try_build_typed()?;
     ///
-    /// while let Some(batch) = pages.next().await? {
-    ///     for account in batch {
-    ///         println!("{:?}", account);
-    ///     }
-    /// }
+    /// let accounts = client.run(&top_accounts).await?;
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn query_paginated<T>(&self, soql: &str) -> SfResult<PaginatedQuery<T>>
+    pub async fn run<T>(&self, query: &Query<T>) -> SfResult<Vec<T>>
     where
-        T: DeserializeOwned,
+        T: DeserializeOwned + Serialize + Clone,
     {
-        // Execute first query to get initial results and nextRecordsUrl
-        let url = format!("{}/services/data/v57.0/query", self.config.base_url);
+        self.query(query.as_str()).await
+    }
 
-        self.rate_limiter.acquire().await?;
+    /// Run a query to the end and key the records with `key_fn`
+    ///
+    /// All pages are fetched. When two records share a key the later one
+    /// wins; see [`query_map_with`](Self::query_map_with) to fail instead.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct Account {
+    ///     #[serde(rename = "AccountNumber")]
+    ///     number: String,
+    /// }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// let by_number = client
+    ///     .query_map("SELECT AccountNumber FROM Account", |a: &Account| a.number.clone())
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query_map<T, K, F>(
+        &self,
+        soql: impl AsRef<str>,
+        key_fn: F,
+    ) -> SfResult<HashMap<K, T>>
+    where
+        T: DeserializeOwned + 'static,
+        K: Eq + std::hash::Hash + std::fmt::Debug,
+        F: Fn(&T) -> K,
+    {
+        self.query_map_with(soql, key_fn, DuplicateKeys::LastWins)
+            .await
+    }
 
-        let response = self
-            .http_client
-            .get(&url)
-            .query(&[("q", soql)])
-            .header(
-                "Authorization",
-                format!("Bearer {}", self.config.access_token),
-            )
-            .send()
-            .await?;
+    /// Run a query to the end and key the records, choosing how duplicate
+    /// keys are handled
+    #[instrument(skip(self, soql, key_fn))]
+    pub async fn query_map_with<T, K, F>(
+        &self,
+        soql: impl AsRef<str>,
+        key_fn: F,
+        duplicates: DuplicateKeys,
+    ) -> SfResult<HashMap<K, T>>
+    where
+        T: DeserializeOwned + 'static,
+        K: Eq + std::hash::Hash + std::fmt::Debug,
+        F: Fn(&T) -> K,
+    {
+        let records: Vec<T> = self.query_stream(soql).try_collect().await?;
 
-        let status = response.status();
-        if !status.is_success() {
-            let body = response.text().await.unwrap_or_default();
-            return Err(SfError::Api {
-                status: status.as_u16(),
-                body,
-            });
+        let mut map = HashMap::with_capacity(records.len());
+        for record in records {
+            let key = key_fn(&record);
+            if duplicates == DuplicateKeys::Error && map.contains_key(&key) {
+                return Err(SfError::InvalidQuery(format!(
+                    "Query returned more than one record with key {:?}",
+                    key
+                )));
+            }
+            map.insert(key, record);
         }
 
-        let query_response: pagination::QueryResponse<T> = response.json().await?;
-        let next_url = query_response.next_records_url.clone();
+        Ok(map)
+    }
 
-        Ok(PaginatedQuery::new(
-            self.http_client.clone(),
-            self.config.base_url.clone(),
-            self.config.access_token.clone(),
-            next_url,
-        ))
+    /// Run a query to the end and key the records by their `Id`
+    pub async fn query_by_id<T>(&self, soql: impl AsRef<str>) -> SfResult<HashMap<String, T>>
+    where
+        T: DeserializeOwned + HasId + 'static,
+    {
+        self.query_map(soql, |record: &T| record.id().to_string())
+            .await
     }
 
-    /// Insert a new record
+    /// Run a query once per chunk of IDs, combining the results
+    ///
+    /// Each chunk adds `field IN (...)` to the builder's WHERE clause, which
+    /// keeps queries with thousands of IDs under the SOQL length limit.
+    /// Chunks run one after another; see
+    /// [`query_in_chunks_with`](Self::query_in_chunks_with) to run several
+    /// at once. Records are returned in chunk order, and a record with an
+    /// `Id` already seen in an earlier chunk is dropped. An empty ID list
+    /// returns no records without calling Salesforce.
     ///
     /// # Example
     /// ```no_run
-    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
-    /// # use serde::Serialize;
-    /// #[derive(Serialize)]
-    /// struct NewAccount {
-    ///     #[serde(rename = "Name")]
-    ///     name: String,
-    ///     #[serde(rename = "Industry")]
-    ///     industry: String,
-    /// }
-    /// # async fn example() -> Result<(), SfError> {
+    /// # use salesforce_client::{QueryBuilder, SalesforceClient, ClientConfig, SfError};
+    /// # use serde::Deserialize;
+    /// # #[derive(Debug, Deserialize)]
+    /// # struct Contact { #[serde(rename = "Id")] id: String }
+    /// # async fn example(account_ids: Vec<String>) -> Result<(), SfError> {
     /// # let config = ClientConfig::new("https://example.com", "token");
     /// # let client = SalesforceClient::new(config);
-    ///
-    /// let account = NewAccount {
-    ///     name: "Acme Corporation".to_string(),
-    ///     industry: "Technology".to_string(),
-    /// };
-    ///
-    /// let response = client.insert("Account", &account).await?;
-    /// println!("Created account with ID: {}", response.id);
+    /// let contacts: Vec<Contact> = client
+    ///     .query_in_chunks(
+    ///         QueryBuilder::select(&["Id", "Email"]).from("Contact"),
+    ///         "AccountId",
+    ///         account_ids,
+    ///         500,
+    ///     )
+    ///     .await?;
     /// # Ok(())
     /// # }
     /// ```
-    #[instrument(skip(self, data))]
-    pub async fn insert<T: Serialize>(&self, sobject: &str, data: &T) -> SfResult<InsertResponse> {
-        self.rate_limiter.acquire().await?;
+    pub async fn query_in_chunks<T, I>(
+        &self,
+        builder: QueryBuilder<query_builder::Complete>,
+        field: &str,
+        ids: I,
+        chunk_size: usize,
+    ) -> SfResult<Vec<T>>
+    where
+        T: DeserializeOwned,
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.query_in_chunks_with(builder, field, ids, chunk_size, 1)
+            .await
+    }
 
-        retry::with_retry(&self.config.retry_config, || async {
-            self.crud.insert(sobject, data).await
-        })
-        .await
+    /// Run a chunked ID query with up to `concurrency` chunks in flight
+    ///
+    /// Like [`query_in_chunks`](Self::query_in_chunks); every chunk still
+    /// goes through the rate limiter and results keep chunk order.
+    #[instrument(skip(self, builder, ids))]
+    pub async fn query_in_chunks_with<T, I>(
+        &self,
+        builder: QueryBuilder<query_builder::Complete>,
+        field: &str,
+        ids: I,
+        chunk_size: usize,
+        concurrency: usize,
+    ) -> SfResult<Vec<T>>
+    where
+        T: DeserializeOwned,
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        let ids: Vec<String> = ids.into_iter().map(Into::into).collect();
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let queries = ids
+            .chunks(chunk_size.max(1))
+            .map(|chunk| {
+                builder
+                    .clone()
+                    .and_cond(Condition::in_ids(field, chunk.iter().cloned()))
+                    .try_build()
+            })
+            .collect::<SfResult<Vec<_>>>()?;
+        debug!("Querying {} ids in {} chunks", ids.len(), queries.len());
+
+        let chunks: Vec<Vec<serde_json::Value>> = futures::stream::iter(queries)
+            .map(|soql| self.query_stream::<serde_json::Value>(soql).try_collect())
+            .buffered(concurrency.max(1))
+            .try_collect()
+            .await?;
+
+        let mut seen = std::collections::HashSet::new();
+        let records = chunks
+            .into_iter()
+            .flatten()
+            .filter(|record| match record.get("Id").and_then(|id| id.as_str()) {
+                Some(id) => seen.insert(id.to_string()),
+                None => true,
+            })
+            .map(|record| serde_json::from_value(record).map_err(SfError::from))
+            .collect::<SfResult<Vec<T>>>()?;
+
+        info!("Chunked query returned {} records", records.len());
+        Ok(records)
     }
 
-    /// Update an existing record
+    /// Execute an aggregate query, e.g. with `GROUP BY`
+    ///
+    /// Returns untyped rows, so aliased aggregates and unaliased `expr0`
+    /// style fields are read by name. Results are cached like
+    /// [`query`](Self::query).
     ///
     /// # Example
     /// ```no_run
     /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
-    /// # use serde::Serialize;
-    /// #[derive(Serialize)]
-    /// struct AccountUpdate {
-    ///     #[serde(rename = "Name")]
-    ///     name: String,
-    /// }
     /// # async fn example() -> Result<(), SfError> {
     /// # let config = ClientConfig::new("https://example.com", "token");
     /// # let client = SalesforceClient::new(config);
+    /// let rows = client
+    ///     .query_aggregate("SELECT Industry, COUNT(Id) cnt FROM Account GROUP BY Industry")
+    ///     .await?;
+    /// for row in rows {
+    ///     println!("{:?}: {:?}", row.get_str("Industry"), row.get_i64("cnt"));
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, soql))]
+    pub async fn query_aggregate(&self, soql: impl AsRef<str>) -> SfResult<Vec<AggregateRow>> {
+        self.query(soql).await
+    }
+
+    /// Execute a `SELECT COUNT()` query and return the count
     ///
-    /// let update = AccountUpdate {
-    ///     name: "Acme Corp (Updated)".to_string(),
-    /// };
+    /// COUNT() returns no records, only `totalSize`, so this can't go through
+    /// [`query`](Self::query). Counts are not cached. Passing a query that
+    /// returns records, including `COUNT(Id)` aggregates, fails with
+    /// [`SfError::InvalidQuery`].
     ///
-    /// client.update("Account", "001xx000003DGbX", &update).await?;
-    /// println!("Account updated successfully");
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let count = client
+    ///     .query_count("SELECT COUNT() FROM Account WHERE Industry = 'Energy'")
+    ///     .await?;
+    /// println!("{} energy accounts", count);
     /// # Ok(())
     /// # }
     /// ```
-    #[instrument(skip(self, data))]
-    pub async fn update<T: Serialize>(&self, sobject: &str, id: &str, data: &T) -> SfResult<()> {
+    #[instrument(skip(self, soql))]
+    pub async fn query_count(&self, soql: impl AsRef<str>) -> SfResult<u64> {
+        let soql = soql.as_ref();
+        let url = self.query_url(QueryEndpoint::Query);
+
         self.rate_limiter.acquire().await?;
 
-        retry::with_retry(&self.config.retry_config, || async {
-            self.crud.update(sobject, id, data).await
+        let response = retry::with_retry(&self.config.retry_config, || async {
+            self.fetch_query_response::<serde_json::Value>(&url, soql)
+                .await
         })
         .await?;
 
-        // Invalidate cache for this record
-        self.query_cache.clear().await;
+        if !response.records.is_empty() {
+            return Err(SfError::InvalidQuery(format!(
+                "query_count expects a SELECT COUNT() query, but the query returned records: {}",
+                soql
+            )));
+        }
 
-        Ok(())
+        let count = u64::try_from(response.total_size.unwrap_or(0)).unwrap_or(0);
+        info!("Count query returned {}", count);
+        Ok(count)
     }
 
-    /// Delete a record
+    /// Execute a [`CountQueryBuilder`] query and return the count
+    ///
+    /// `COUNT()` is read from `totalSize`, like
+    /// [`query_count`](Self::query_count). With
+    /// [`count_field`](CountQueryBuilder::count_field) the count is read
+    /// from the aggregate row instead, and no row counts as 0; these go
+    /// through [`query_aggregate`](Self::query_aggregate) and share its cache.
     ///
     /// # Example
     /// ```no_run
-    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use salesforce_client::{CountQueryBuilder, SalesforceClient, ClientConfig, SfError};
     /// # async fn example() -> Result<(), SfError> {
     /// # let config = ClientConfig::new("https://example.com", "token");
     /// # let client = SalesforceClient::new(config);
+    /// let count = client
+    ///     .count(CountQueryBuilder::count_from("Contact").where_clause("Email = null"))
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn count(&self, query: CountQueryBuilder) -> SfResult<u64> {
+        if !query.is_field_count() {
+            return self.query_count(query.build()).await;
+        }
+
+        let soql = query.build();
+        let rows = self.query_aggregate(&soql).await?;
+        let Some(row) = rows.first() else {
+            return Ok(0);
+        };
+
+        row.expr_i64(0)
+            .and_then(|count| u64::try_from(count).ok())
+            .ok_or_else(|| {
+                SfError::InvalidQuery(format!("Count query returned no count value: {}", soql))
+            })
+    }
+
+    /// Get the optimizer's query plans without running the query
     ///
-    /// client.delete("Account", "001xx000003DGbX").await?;
-    /// println!("Account deleted successfully");
+    /// `query` may be SOQL, a report ID or a list view ID. Plans are never
+    /// cached, so they reflect current statistics.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let explain = client
+    ///     .explain("SELECT Id FROM Account WHERE Industry = 'Energy'")
+    ///     .await?;
+    /// if let Some(plan) = explain.best() {
+    ///     println!("{} (cost {})", plan.leading_operation_type, plan.relative_cost);
+    /// }
     /// # Ok(())
     /// # }
     /// ```
-    #[instrument(skip(self))]
-    pub async fn delete(&self, sobject: &str, id: &str) -> SfResult<()> {
+    #[instrument(skip(self, query))]
+    pub async fn explain(&self, query: impl AsRef<str>) -> SfResult<ExplainResponse> {
+        let query = query.as_ref();
+        let url = format!("{}/", self.query_url(QueryEndpoint::Query));
+
         self.rate_limiter.acquire().await?;
 
         retry::with_retry(&self.config.retry_config, || async {
-            self.crud.delete(sobject, id).await
-        })
-        .await?;
+            let response = auth::send_authorized(self.token_provider.as_ref(), || {
+                self.http_client.get(&url).query(&[("explain", query)])
+            })
+            .await?;
 
-        // Invalidate cache
-        self.query_cache.clear().await;
+            let status = response.status();
+            let body = response.text().await?;
+            if !status.is_success() {
+                return Err(SfError::Api {
+                    status: status.as_u16(),
+                    body,
+                });
+            }
 
-        Ok(())
+            Ok(serde_json::from_str(&body)?)
+        })
+        .await
     }
 
-    /// Upsert a record (insert or update based on external ID)
+    /// Query with automatic pagination - fetches ALL results
+    ///
+    /// **Warning**: This can consume significant memory for large result sets.
+    /// For queries returning >100k records, consider using `query_paginated` instead.
+    /// [`ClientConfig::with_max_records`] turns runaway results into
+    /// [`SfError::ResultTooLarge`].
     ///
     /// # Example
     /// ```no_run
-    /// # use salesforce_client::{SalesforceClient, ClientConfig, UpsertBuilder, SfError};
-    /// # use serde::Serialize;
-    /// #[derive(Serialize)]
-    /// struct Account {
-    ///     #[serde(rename = "Name")]
-    ///     name: String,
-    /// }
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::{Deserialize, Serialize};
+    /// # #[derive(Debug, Clone, Deserialize, Serialize)]
+    /// # struct Account { #[serde(rename = "Id")] id: String }
     /// # async fn example() -> Result<(), SfError> {
     /// # let config = ClientConfig::new("https://example.com", "token");
     /// # let client = SalesforceClient::new(config);
+    /// // Fetches all accounts, automatically handling pagination
+    /// let accounts: Vec<Account> = client
+    ///     .query_all("SELECT Id, Name FROM Account")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, soql))]
+    pub async fn query_all<T>(&self, soql: impl AsRef<str>) -> SfResult<Vec<T>>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        info!("Executing query with full pagination");
+
+        self.query_paginated::<T>(soql.as_ref())
+            .await?
+            .collect_all()
+            .await
+    }
+
+    /// Query with automatic pagination, honoring [`QueryOptions`]
     ///
-    /// let account = Account {
-    ///     name: "Acme Corporation".to_string(),
-    /// };
+    /// Stops fetching pages once [`QueryOptions::limit`] records have been
+    /// collected, truncating the final page; a limit of 0 sends no request.
+    /// Gathering more than [`QueryOptions::max_records`] (or the client's
+    /// [`ClientConfig::max_records`]) fails with [`SfError::ResultTooLarge`].
+    /// [`QueryOptions::include_deleted`] queries `/queryAll`.
     ///
-    /// let upsert = UpsertBuilder::new("External_Id__c", "EXT-12345");
-    /// let response = client.upsert("Account", upsert, &account).await?;
-    /// println!("Upserted account with ID: {}", response.id);
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, QueryOptions, SfError};
+    /// # use serde::Deserialize;
+    /// # #[derive(Debug, Deserialize)]
+    /// # struct Account { #[serde(rename = "Id")] id: String }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let first_5000: Vec<Account> = client
+    ///     .query_all_with("SELECT Id FROM Account", QueryOptions::new().limit(5000))
+    ///     .await?;
     /// # Ok(())
     /// # }
     /// ```
-    #[instrument(skip(self, data))]
-    pub async fn upsert<T: Serialize>(
+    #[instrument(skip(self, soql, options))]
+    pub async fn query_all_with<T>(
         &self,
-        sobject: &str,
-        builder: UpsertBuilder,
-        data: &T,
-    ) -> SfResult<InsertResponse> {
-        self.rate_limiter.acquire().await?;
+        soql: impl AsRef<str>,
+        options: QueryOptions,
+    ) -> SfResult<Vec<T>>
+    where
+        T: DeserializeOwned + 'static,
+    {
+        let max_records = options.max_records.or(self.config.max_records);
+        let stream = self
+            .stream_at(self.query_url(options.endpoint()), soql.as_ref())
+            .take(options.limit.unwrap_or(usize::MAX));
+        futures::pin_mut!(stream);
 
-        let result = retry::with_retry(&self.config.retry_config, || async {
-            self.crud.upsert(sobject, builder.clone(), data).await
-        })
-        .await?;
+        let mut records = Vec::new();
+        while let Some(record) = stream.try_next().await? {
+            records.push(record);
+            pagination::check_max_records(records.len(), max_records)?;
+        }
 
-        // Invalidate cache
-        self.query_cache.clear().await;
+        info!("Collected {} total records", records.len());
+        Ok(records)
+    }
 
-        Ok(result)
+    /// Stream query results one record at a time
+    ///
+    /// Pages are fetched lazily: the next page is requested only once the
+    /// current one has been consumed, and dropping the stream stops fetching.
+    /// An error ends the stream after it is yielded.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::Deserialize;
+    /// # #[derive(Debug, Deserialize)]
+    /// # struct Task { #[serde(rename = "Id")] id: String }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// use futures::TryStreamExt;
+    ///
+    /// let stream = client.query_stream::<Task>("SELECT Id FROM Task");
+    /// futures::pin_mut!(stream);
+    /// while let Some(task) = stream.try_next().await? {
+    ///     println!("{}", task.id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_stream<T>(
+        &self,
+        soql: impl AsRef<str>,
+    ) -> impl futures::Stream<Item = SfResult<T>> + '_
+    where
+        T: DeserializeOwned + 'static,
+    {
+        self.stream_at(self.query_url(QueryEndpoint::Query), soql.as_ref())
     }
 
-    // ========================================================================
-    // Utility Methods
-    // ========================================================================
+    /// Stream the records of a query against the given query resource
+    fn stream_at<T>(&self, url: String, soql: &str) -> impl futures::Stream<Item = SfResult<T>> + '_
+    where
+        T: DeserializeOwned + 'static,
+    {
+        enum State<T> {
+            Start(String, String),
+            Pages(PaginatedQuery<T>),
+        }
 
-    /// Clear the query cache
-    pub async fn clear_cache(&self) {
-        self.query_cache.clear().await;
-        info!("Cache cleared");
-    }
+        futures::stream::try_unfold(
+            State::Start(url, soql.to_string()),
+            move |state| async move {
+                match state {
+                    State::Start(url, soql) => {
+                        self.rate_limiter.acquire().await?;
 
-    /// Get the current configuration
-    pub fn config(&self) -> &ClientConfig {
-        &self.config
-    }
+                        let first = retry::with_retry(&self.config.retry_config, || async {
+                            self.fetch_query_response::<T>(&url, &soql).await
+                        })
+                        .await?;
 
-    /// Get rate limiter status
-    pub fn rate_limit_status(&self) -> rate_limit::RateLimitStatus {
-        self.rate_limiter.status()
+                        let mut pages = self.pages_from(first);
+                        SfResult::Ok(
+                            pages
+                                .next()
+                                .await?
+                                .map(|batch| (batch, State::Pages(pages))),
+                        )
+                    }
+                    State::Pages(mut pages) => Ok(pages
+                        .next()
+                        .await?
+                        .map(|batch| (batch, State::Pages(pages)))),
+                }
+            },
+        )
+        .map_ok(|batch| futures::stream::iter(batch.into_iter().map(Ok)))
+        .try_flatten()
     }
-}
 
-#[cfg(test)]
+    /// Get a paginated query iterator for manual pagination control
+    ///
+    /// This is the most memory-efficient way to handle large result sets.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::{Deserialize, Serialize};
+    /// # #[derive(Debug, Clone, Deserialize, Serialize)]
+    /// # struct Account { #[serde(rename = "Id")] id: String }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let mut pages = client
+    ///     .query_paginated::<Account>("SELECT Id FROM Account")
+    ///     .await?;
+    ///
+    /// while let Some(batch) = pages.next().await? {
+    ///     for account in batch {
+    ///         println!("{:?}", account);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query_paginated<T>(&self, soql: &str) -> SfResult<PaginatedQuery<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.query_paginated_with(soql, &QueryOptions::default())
+            .await
+    }
+
+    /// Get a paginated query iterator with options
+    ///
+    /// With [`QueryOptions::include_deleted`] the query runs against
+    /// `/queryAll`; later pages follow `nextRecordsUrl` either way. With
+    /// [`QueryOptions::limit`] the iterator stops after that many records,
+    /// and a limit of 0 sends no request.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, QueryOptions, SfError};
+    /// # use serde::Deserialize;
+    /// # #[derive(Debug, Deserialize)]
+    /// # struct Task { #[serde(rename = "Id")] id: String }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let options = QueryOptions::new().include_deleted();
+    /// let mut pages = client
+    ///     .query_paginated_with::<Task>("SELECT Id FROM Task WHERE IsArchived = true", &options)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn query_paginated_with<T>(
+        &self,
+        soql: &str,
+        options: &QueryOptions,
+    ) -> SfResult<PaginatedQuery<T>>
+    where
+        T: DeserializeOwned,
+    {
+        if options.limit == Some(0) {
+            return Ok(PaginatedQuery::new(
+                self.http_client.clone(),
+                self.config.base_url.clone(),
+                self.token_provider.clone(),
+                Vec::new(),
+                None,
+            ));
+        }
+
+        Ok(self
+            .paginate_at(&self.query_url(options.endpoint()), soql)
+            .await?
+            .with_limit(options.limit)
+            .with_max_records(options.max_records.or(self.config.max_records)))
+    }
+
+    /// Start a paginated query against the given query resource
+    async fn paginate_at<T>(&self, url: &str, soql: &str) -> SfResult<PaginatedQuery<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.check_query_length(soql)?;
+
+        // Execute first query to get initial results and nextRecordsUrl
+        self.rate_limiter.acquire().await?;
+
+        let response = auth::send_authorized(self.token_provider.as_ref(), || {
+            self.http_client.get(url).query(&[("q", soql)])
+        })
+        .await?;
+
+        let status = response.status();
+        if status.as_u16() == 414 {
+            return Err(query_too_long(encoded_query_length(soql)));
+        }
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(SfError::Api {
+                status: status.as_u16(),
+                body,
+            });
+        }
+
+        let query_response: pagination::QueryResult<T> = response.json().await?;
+        Ok(self.pages_from(query_response))
+    }
+
+    /// Iterator over a query's pages, starting with the first response
+    fn pages_from<T>(&self, first: pagination::QueryResult<T>) -> PaginatedQuery<T>
+    where
+        T: DeserializeOwned,
+    {
+        let next_url = if first.done {
+            None
+        } else {
+            first.next_records_url
+        };
+
+        PaginatedQuery::new(
+            self.http_client.clone(),
+            self.config.base_url.clone(),
+            self.token_provider.clone(),
+            first.records,
+            next_url,
+        )
+        .with_max_records(self.config.max_records)
+    }
+
+    /// Page through a query by record ID instead of OFFSET
+    ///
+    /// SOQL OFFSET stops at 2000, so deep pages of a builder query fail.
+    /// This orders by `Id` and asks for `Id > last_id` on each page, which
+    /// reaches any depth. The builder must select `Id` and must not set
+    /// ORDER BY (other than `Id`), LIMIT, OFFSET or GROUP BY; `page_size` is
+    /// between 1 and 2000, or at most 200 with `FIELDS(ALL)`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, QueryBuilder, SfError};
+    /// # use serde::Deserialize;
+    /// # #[derive(Debug, Deserialize)]
+    /// # struct Account { #[serde(rename = "Id")] id: String }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let builder = QueryBuilder::select(&["Id", "Name"]).from("Account");
+    /// let mut pages = client.query_pages_by_id::<Account>(builder, 500)?;
+    ///
+    /// while let Some(batch) = pages.next().await? {
+    ///     println!("Got {} accounts", batch.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn query_pages_by_id<T>(
+        &self,
+        builder: QueryBuilder<query_builder::Complete>,
+        page_size: u32,
+    ) -> SfResult<KeysetQuery<'_, T>>
+    where
+        T: DeserializeOwned,
+    {
+        KeysetQuery::new(self, builder, page_size)
+    }
+
+    /// Run a query as a Bulk API 2.0 job
+    ///
+    /// Creates the job, waits for it to complete, then returns an iterator
+    /// over CSV result pages deserialized into `T`. Use this instead of
+    /// [`query`](Self::query) for extracts of millions of rows. CSV values
+    /// are strings, so numeric fields deserialize into numbers only if `T`
+    /// declares them as such, and empty values map to `None`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::Deserialize;
+    /// # #[derive(Debug, Deserialize)]
+    /// # struct Account { #[serde(rename = "Id")] id: String }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let mut pages = client
+    ///     .bulk_query::<Account>("SELECT Id FROM Account")
+    ///     .await?;
+    ///
+    /// while let Some(batch) = pages.next().await? {
+    ///     println!("Got {} accounts", batch.len());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "bulk-api")]
+    pub async fn bulk_query<T>(&self, soql: &str) -> SfResult<BulkQuery<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.bulk_query_with_options(soql, BulkQueryOptions::default())
+            .await
+    }
+
+    /// Run a query as a Bulk API 2.0 job with custom polling and page size
+    #[cfg(feature = "bulk-api")]
+    pub async fn bulk_query_with_options<T>(
+        &self,
+        soql: &str,
+        options: BulkQueryOptions,
+    ) -> SfResult<BulkQuery<T>>
+    where
+        T: DeserializeOwned,
+    {
+        self.rate_limiter.acquire().await?;
+
+        BulkQuery::start(
+            self.http_client.clone(),
+            &self.config.api_url(),
+            self.token_provider.clone(),
+            soql,
+            &options,
+        )
+        .await
+    }
+
+    /// Retrieve a single record by ID
+    ///
+    /// With caching enabled the record is cached, and served from the cache
+    /// until it expires or is changed through this client.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct Account {
+    ///     #[serde(rename = "Id")]
+    ///     id: String,
+    ///     #[serde(rename = "Name")]
+    ///     name: String,
+    /// }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// let account: Account = client.get("Account", "001xx000003DGbX").await?;
+    /// println!("{} is {}", account.id, account.name);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn get<T: DeserializeOwned>(&self, sobject: &str, id: &str) -> SfResult<T> {
+        if let Some(cached) = self
+            .record_cache
+            .get::<serde_json::Value>(sobject, id)
+            .await
+        {
+            debug!("Returning cached {} {}", sobject, id);
+            return Ok(serde_json::from_value(cached)?);
+        }
+
+        self.rate_limiter.acquire().await?;
+
+        let record: serde_json::Value = retry::with_retry(&self.config.retry_config, || async {
+            self.crud.get(sobject, id, &[]).await
+        })
+        .await?;
+
+        if let Err(e) = self.record_cache.set(sobject, id, &record).await {
+            debug!("Failed to cache record: {}", e);
+        }
+        Ok(serde_json::from_value(record)?)
+    }
+
+    /// Retrieve only the given fields of a record
+    ///
+    /// Use `Option` for struct fields that are not requested.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct Account {
+    ///     #[serde(rename = "Name")]
+    ///     name: String,
+    ///     #[serde(rename = "AnnualRevenue")]
+    ///     annual_revenue: Option<f64>,
+    /// }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// let account: Account = client
+    ///     .get_fields("Account", "001xx000003DGbX", &["Name", "AnnualRevenue"])
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn get_fields<T: DeserializeOwned>(
+        &self,
+        sobject: &str,
+        id: &str,
+        fields: &[&str],
+    ) -> SfResult<T> {
+        if fields.is_empty() {
+            return Err(SfError::InvalidQuery(
+                "get_fields requires at least one field".to_string(),
+            ));
+        }
+
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            self.crud.get(sobject, id, fields).await
+        })
+        .await
+    }
+
+    /// Check whether a record exists without fetching its body
+    ///
+    /// Returns `false` for 404; any other error status is returned as an
+    /// error. With caching enabled the answer is cached per record and
+    /// cleared when the record is deleted through this client.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// if !client.exists("Account", "001xx000003DGbX").await? {
+    ///     println!("Account is gone");
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn exists(&self, sobject: &str, id: &str) -> SfResult<bool> {
+        if let Some(exists) = self.record_cache.get_exists(sobject, id).await {
+            debug!("Returning cached existence for {} {}", sobject, id);
+            return Ok(exists);
+        }
+
+        self.rate_limiter.acquire().await?;
+
+        let exists = retry::with_retry(&self.config.retry_config, || async {
+            self.crud.exists(sobject, id).await
+        })
+        .await?;
+
+        self.record_cache.set_exists(sobject, id, exists).await;
+        Ok(exists)
+    }
+
+    /// Insert a new record
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::Serialize;
+    /// #[derive(Serialize)]
+    /// struct NewAccount {
+    ///     #[serde(rename = "Name")]
+    ///     name: String,
+    ///     #[serde(rename = "Industry")]
+    ///     industry: String,
+    /// }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// let account = NewAccount {
+    ///     name: "Acme Corporation".to_string(),
+    ///     industry: "Technology".to_string(),
+    /// };
+    ///
+    /// let response = client.insert("Account", &account).await?;
+    /// println!("Created account with ID: {}", response.id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, data))]
+    pub async fn insert<T: Serialize>(&self, sobject: &str, data: &T) -> SfResult<InsertResponse> {
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            self.crud.insert(sobject, data).await
+        })
+        .await
+    }
+
+    /// Create a file record (e.g. ContentVersion) with binary content
+    ///
+    /// Sends a multipart request with `metadata` as the `entity_content`
+    /// JSON part and the file as the binary part. Content can be in-memory
+    /// bytes, which are retried like any other request, or streamed from an
+    /// `AsyncRead` with [`FileContent::from_reader`], which is sent once
+    /// without buffering the file.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, FileContent, SfError};
+    /// # use serde_json::json;
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let file = tokio::fs::File::open("invoice.pdf").await.unwrap();
+    ///
+    /// let created = client
+    ///     .upload_file(
+    ///         "ContentVersion",
+    ///         &json!({"Title": "Invoice", "PathOnClient": "invoice.pdf"}),
+    ///         FileContent::from_reader(file),
+    ///         "invoice.pdf",
+    ///         "application/pdf",
+    ///     )
+    ///     .await?;
+    /// println!("Created ContentVersion {}", created.id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, metadata, content))]
+    pub async fn upload_file<T: Serialize>(
+        &self,
+        sobject: &str,
+        metadata: &T,
+        content: impl Into<FileContent>,
+        filename: &str,
+        content_type: &str,
+    ) -> SfResult<InsertResponse> {
+        self.rate_limiter.acquire().await?;
+
+        match content.into() {
+            FileContent::Bytes(bytes) => {
+                retry::with_retry(&self.config.retry_config, || async {
+                    self.crud
+                        .upload(
+                            sobject,
+                            metadata,
+                            FileContent::Bytes(bytes.clone()),
+                            filename,
+                            content_type,
+                        )
+                        .await
+                })
+                .await
+            }
+            content => {
+                self.crud
+                    .upload(sobject, metadata, content, filename, content_type)
+                    .await
+            }
+        }
+    }
+
+    /// Download the content of a blob field, e.g. ContentVersion `VersionData`
+    ///
+    /// Loads the whole file into memory; use
+    /// [`download_blob_to`](Self::download_blob_to) for large files.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let blob = client
+    ///     .download_blob("ContentVersion", "068xx0000001", "VersionData")
+    ///     .await?;
+    /// println!("{} bytes of {:?}", blob.data.len(), blob.metadata.content_type);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn download_blob(&self, sobject: &str, id: &str, blob_field: &str) -> SfResult<Blob> {
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            self.crud.download_blob(sobject, id, blob_field).await
+        })
+        .await
+    }
+
+    /// Stream the content of a blob field into `writer`
+    ///
+    /// The body is copied chunk by chunk without buffering the whole file.
+    /// Only the request itself is retried; a failure after bytes have been
+    /// written is returned as-is.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let mut file = tokio::fs::File::create("invoice.pdf").await?;
+    /// client
+    ///     .download_blob_to("ContentVersion", "068xx0000001", "VersionData", &mut file)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, writer))]
+    pub async fn download_blob_to<W>(
+        &self,
+        sobject: &str,
+        id: &str,
+        blob_field: &str,
+        writer: &mut W,
+    ) -> SfResult<BlobMetadata>
+    where
+        W: tokio::io::AsyncWrite + Unpin + ?Sized,
+    {
+        self.rate_limiter.acquire().await?;
+
+        let (response, metadata) = retry::with_retry(&self.config.retry_config, || async {
+            self.crud.blob_response(sobject, id, blob_field).await
+        })
+        .await?;
+
+        let written = crud::copy_body(response, writer).await?;
+        info!("Downloaded {} bytes from {} {}", written, sobject, id);
+
+        Ok(metadata)
+    }
+
+    /// Attach a small file to a record as a base64-encoded Attachment
+    ///
+    /// Simpler than [`upload_file`](Self::upload_file) but the whole body is
+    /// base64-encoded into the JSON request, so keep it for small files.
+    #[instrument(skip(self, content), fields(size = content.len()))]
+    pub async fn upload_attachment(
+        &self,
+        parent_id: &str,
+        filename: &str,
+        content: &[u8],
+        content_type: &str,
+    ) -> SfResult<InsertResponse> {
+        use base64::Engine;
+
+        let attachment = serde_json::json!({
+            "ParentId": parent_id,
+            "Name": filename,
+            "ContentType": content_type,
+            "Body": base64::engine::general_purpose::STANDARD.encode(content),
+        });
+        self.insert("Attachment", &attachment).await
+    }
+
+    /// Update an existing record
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::Serialize;
+    /// #[derive(Serialize)]
+    /// struct AccountUpdate {
+    ///     #[serde(rename = "Name")]
+    ///     name: String,
+    /// }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// let update = AccountUpdate {
+    ///     name: "Acme Corp (Updated)".to_string(),
+    /// };
+    ///
+    /// client.update("Account", "001xx000003DGbX", &update).await?;
+    /// println!("Account updated successfully");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, data))]
+    pub async fn update<T: Serialize>(&self, sobject: &str, id: &str, data: &T) -> SfResult<()> {
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            self.crud.update(sobject, id, data).await
+        })
+        .await?;
+
+        // Invalidate cache for this record
+        self.query_cache.invalidate_sobject(sobject).await;
+        self.record_cache.invalidate(sobject, id).await;
+
+        Ok(())
+    }
+
+    /// Update a record only if it has not changed since a given time
+    ///
+    /// For optimistic concurrency, pass the `LastModifiedDate` you read the
+    /// record at; a concurrent change fails the update with
+    /// `SfError::PreconditionFailed` instead of being overwritten.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError, UpdateOptions};
+    /// # use serde_json::json;
+    /// # async fn example(last_modified: chrono::DateTime<chrono::Utc>) -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let options = UpdateOptions::new().if_unmodified_since(last_modified);
+    ///
+    /// match client
+    ///     .update_with("Account", "001xx000003DGbX", &json!({"Rating": "Hot"}), &options)
+    ///     .await
+    /// {
+    ///     Err(SfError::PreconditionFailed { .. }) => println!("Changed by someone else, re-read"),
+    ///     other => other?,
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, data))]
+    pub async fn update_with<T: Serialize>(
+        &self,
+        sobject: &str,
+        id: &str,
+        data: &T,
+        options: &UpdateOptions,
+    ) -> SfResult<()> {
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            self.crud.update_with(sobject, id, data, options).await
+        })
+        .await?;
+
+        // Invalidate cache for this record
+        self.query_cache.invalidate_sobject(sobject).await;
+        self.record_cache.invalidate(sobject, id).await;
+
+        Ok(())
+    }
+
+    /// Update a record, clearing `fields_to_null`
+    ///
+    /// Serde skips or keeps `None` fields depending on the struct, so this
+    /// injects an explicit JSON `null` for each listed field, like the SOAP
+    /// API's `fieldsToNull`. A listed field that `data` sets to a non-null
+    /// value fails with `SfError::Serialization` before anything is sent.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde_json::json;
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// client
+    ///     .update_with_nulls(
+    ///         "Account",
+    ///         "001xx000003DGbX",
+    ///         &json!({"Rating": "Hot"}),
+    ///         &["Fax", "Website"],
+    ///     )
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, data))]
+    pub async fn update_with_nulls<T: Serialize>(
+        &self,
+        sobject: &str,
+        id: &str,
+        data: &T,
+        fields_to_null: &[&str],
+    ) -> SfResult<()> {
+        let options = UpdateOptions::new().fields_to_null(fields_to_null.iter().copied());
+        self.update_with(sobject, id, data, &options).await
+    }
+
+    /// Delete a record
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// client.delete("Account", "001xx000003DGbX").await?;
+    /// println!("Account deleted successfully");
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn delete(&self, sobject: &str, id: &str) -> SfResult<()> {
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            self.crud.delete(sobject, id).await
+        })
+        .await?;
+
+        // Invalidate cache
+        self.query_cache.invalidate_sobject(sobject).await;
+        self.record_cache.invalidate(sobject, id).await;
+
+        Ok(())
+    }
+
+    /// Retrieve a record by external ID
+    ///
+    /// The value is URL-encoded. If it matches more than one record the
+    /// call fails with [`SfError::MultipleMatches`], listing their URLs.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::Deserialize;
+    /// #[derive(Deserialize)]
+    /// struct Account {
+    ///     #[serde(rename = "Id")]
+    ///     id: String,
+    /// }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// let account: Account = client
+    ///     .get_by_external_id("Account", "External_Id__c", "EXT-12345")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn get_by_external_id<T: DeserializeOwned>(
+        &self,
+        sobject: &str,
+        external_id_field: &str,
+        value: &str,
+    ) -> SfResult<T> {
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            self.crud
+                .get_by_external_id(sobject, external_id_field, value)
+                .await
+        })
+        .await
+    }
+
+    /// Delete a record by external ID, without looking up its Salesforce ID
+    ///
+    /// The value is URL-encoded, so IDs containing `/` or `+` are safe.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// client
+    ///     .delete_by_external_id("Account", "External_Id__c", "EXT-12345")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn delete_by_external_id(
+        &self,
+        sobject: &str,
+        external_id_field: &str,
+        value: &str,
+    ) -> SfResult<()> {
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            self.crud
+                .delete_by_external_id(sobject, external_id_field, value)
+                .await
+        })
+        .await?;
+
+        // Invalidate cache; the deleted record's ID is unknown here
+        self.query_cache.invalidate_sobject(sobject).await;
+        self.record_cache.invalidate_sobject(sobject).await;
+
+        Ok(())
+    }
+
+    /// Upsert a record (insert or update based on external ID)
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, UpsertBuilder, SfError};
+    /// # use serde::Serialize;
+    /// #[derive(Serialize)]
+    /// struct Account {
+    ///     #[serde(rename = "Name")]
+    ///     name: String,
+    /// }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// let account = Account {
+    ///     name: "Acme Corporation".to_string(),
+    /// };
+    ///
+    /// let upsert = UpsertBuilder::new("External_Id__c", "EXT-12345");
+    /// let response = client.upsert("Account", upsert, &account).await?;
+    /// println!("Upserted account with ID: {}", response.id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, data))]
+    pub async fn upsert<T: Serialize>(
+        &self,
+        sobject: &str,
+        builder: UpsertBuilder,
+        data: &T,
+    ) -> SfResult<InsertResponse> {
+        self.rate_limiter.acquire().await?;
+
+        let result = retry::with_retry(&self.config.retry_config, || async {
+            self.crud.upsert(sobject, builder.clone(), data).await
+        })
+        .await?;
+
+        // Invalidate cache
+        self.query_cache.invalidate_sobject(sobject).await;
+        self.record_cache.invalidate(sobject, &result.id).await;
+
+        Ok(result)
+    }
+
+    /// Upsert a record with per-call options
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{DuplicateRuleHeader, SalesforceClient, ClientConfig, InsertOptions, UpsertBuilder, SfError};
+    /// # use serde_json::json;
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let options =
+    ///     InsertOptions::new().duplicate_rule(DuplicateRuleHeader::new().allow_save(true));
+    /// let upsert = UpsertBuilder::new("External_Id__c", "EXT-12345");
+    /// client
+    ///     .upsert_with("Account", upsert, &json!({"Name": "Acme"}), &options)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, data))]
+    pub async fn upsert_with<T: Serialize>(
+        &self,
+        sobject: &str,
+        builder: UpsertBuilder,
+        data: &T,
+        options: &InsertOptions,
+    ) -> SfResult<InsertResponse> {
+        self.rate_limiter.acquire().await?;
+
+        let result = retry::with_retry(&self.config.retry_config, || async {
+            self.crud
+                .upsert_with(sobject, builder.clone(), data, options)
+                .await
+        })
+        .await?;
+
+        // Invalidate cache
+        self.query_cache.invalidate_sobject(sobject).await;
+        self.record_cache.invalidate(sobject, &result.id).await;
+
+        Ok(result)
+    }
+
+    /// Insert a new record with per-call options
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{AutoAssign, SalesforceClient, ClientConfig, InsertOptions, SfError};
+    /// # use serde_json::json;
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// // Route the new lead with the org's active assignment rule
+    /// let options = InsertOptions::new().auto_assign(AutoAssign::True);
+    /// client
+    ///     .insert_with("Lead", &json!({"LastName": "Smith", "Company": "Acme"}), &options)
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, data))]
+    pub async fn insert_with<T: Serialize>(
+        &self,
+        sobject: &str,
+        data: &T,
+        options: &InsertOptions,
+    ) -> SfResult<InsertResponse> {
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            self.crud.insert_with(sobject, data, options).await
+        })
+        .await
+    }
+
+    /// Insert a record and read back its stored representation
+    ///
+    /// Returns server-populated fields such as `CreatedDate`, formula fields
+    /// and auto-numbers. An insert failure is returned as an error; if only
+    /// the follow-up read fails, the created ID is still returned alongside
+    /// the fetch error so the record is not lost.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::{Deserialize, Serialize};
+    /// #[derive(Serialize)]
+    /// struct NewCase {
+    ///     #[serde(rename = "Subject")]
+    ///     subject: String,
+    /// }
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Case {
+    ///     #[serde(rename = "CaseNumber")]
+    ///     case_number: String,
+    /// }
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// let new_case = NewCase { subject: "Broken widget".to_string() };
+    /// let inserted = client.insert_and_fetch::<_, Case>("Case", &new_case).await?;
+    /// match inserted.record {
+    ///     Ok(case) => println!("Created case {}", case.case_number),
+    ///     Err(e) => println!("Created {} but could not read it back: {}", inserted.id, e),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, data))]
+    pub async fn insert_and_fetch<TIn, TOut>(
+        &self,
+        sobject: &str,
+        data: &TIn,
+    ) -> SfResult<InsertedRecord<TOut>>
+    where
+        TIn: Serialize,
+        TOut: DeserializeOwned,
+    {
+        self.insert_then_get(sobject, data, &[]).await
+    }
+
+    /// Insert a record and read back only the given fields
+    #[instrument(skip(self, data))]
+    pub async fn insert_and_fetch_fields<TIn, TOut>(
+        &self,
+        sobject: &str,
+        data: &TIn,
+        fields: &[&str],
+    ) -> SfResult<InsertedRecord<TOut>>
+    where
+        TIn: Serialize,
+        TOut: DeserializeOwned,
+    {
+        if fields.is_empty() {
+            return Err(SfError::InvalidQuery(
+                "insert_and_fetch_fields requires at least one field".to_string(),
+            ));
+        }
+
+        self.insert_then_get(sobject, data, fields).await
+    }
+
+    async fn insert_then_get<TIn, TOut>(
+        &self,
+        sobject: &str,
+        data: &TIn,
+        fields: &[&str],
+    ) -> SfResult<InsertedRecord<TOut>>
+    where
+        TIn: Serialize,
+        TOut: DeserializeOwned,
+    {
+        let id = self.insert(sobject, data).await?.id;
+
+        let record = async {
+            self.rate_limiter.acquire().await?;
+
+            retry::with_retry(&self.config.retry_config, || async {
+                self.crud.get(sobject, &id, fields).await
+            })
+            .await
+        }
+        .await;
+
+        if let Err(e) = &record {
+            warn!("Created {} {} but failed to fetch it: {}", sobject, id, e);
+        }
+        Ok(InsertedRecord { id, record })
+    }
+
+    /// Insert many records with the sObject Collections API
+    ///
+    /// Records are sent in chunks of 200, each chunk going through the rate
+    /// limiter and retry logic. The returned results line up with `records`
+    /// by index, so failed rows can be identified and retried. With
+    /// `all_or_none`, a failure rolls back the rest of its chunk; chunks that
+    /// were already committed stay committed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::Serialize;
+    /// #[derive(Serialize)]
+    /// struct NewContact {
+    ///     #[serde(rename = "LastName")]
+    ///     last_name: String,
+    /// }
+    /// # async fn example(contacts: Vec<NewContact>) -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// let results = client.insert_many("Contact", &contacts, false).await?;
+    /// for (contact, result) in contacts.iter().zip(&results) {
+    ///     if !result.success {
+    ///         println!("{} failed: {:?}", contact.last_name, result.errors);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, records), fields(count = records.len()))]
+    pub async fn insert_many<T: Serialize>(
+        &self,
+        sobject: &str,
+        records: &[T],
+        all_or_none: bool,
+    ) -> SfResult<Vec<SaveResult>> {
+        let options = IngestOptions::new().all_or_none(all_or_none);
+        self.insert_many_with(sobject, records, &options).await
+    }
+
+    /// Insert many records with chunking, concurrency and progress options
+    ///
+    /// Chunks go through the rate limiter and retry logic, with up to
+    /// `options.concurrency` in flight, and the progress callback runs after
+    /// each one. Results line up with `records` by index. Once the
+    /// cancellation token fires no new chunks are started, so the results
+    /// cover only a prefix of `records`; compare their lengths to tell.
+    ///
+    /// A chunk that fails outright (rather than with per-row errors) stops
+    /// new chunks from starting. If other chunks were already saved, the
+    /// error is [`SfError::PartialBatch`], carrying their results.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{IngestOptions, SalesforceClient, ClientConfig, SfError};
+    /// # async fn example(contacts: Vec<serde_json::Value>) -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let options = IngestOptions::new()
+    ///     .concurrency(4)
+    ///     .on_progress(|p| println!("{}/{} sent, {} failed", p.done, p.total, p.failures));
+    ///
+    /// let results = client.insert_many_with("Contact", &contacts, &options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, records, options), fields(count = records.len()))]
+    pub async fn insert_many_with<T: Serialize>(
+        &self,
+        sobject: &str,
+        records: &[T],
+        options: &IngestOptions,
+    ) -> SfResult<Vec<SaveResult>> {
+        let total = records.len();
+        let chunk_size = options.chunk_size.max(1);
+        let done = AtomicUsize::new(0);
+        let failures = AtomicUsize::new(0);
+        let chunk_failed = AtomicBool::new(false);
+
+        let outcomes: Vec<SfResult<Vec<SaveResult>>> =
+            futures::stream::iter(records.chunks(chunk_size))
+                .take_while(|_| {
+                    std::future::ready(
+                        !options.is_cancelled() && !chunk_failed.load(Ordering::SeqCst),
+                    )
+                })
+                .map(|chunk| async {
+                    let sent = async {
+                        self.rate_limiter.acquire().await?;
+
+                        retry::with_retry(&self.config.retry_config, || async {
+                            self.crud
+                                .insert_collection(sobject, chunk, options.all_or_none)
+                                .await
+                        })
+                        .await
+                    }
+                    .await;
+                    let Ok(results) = &sent else {
+                        chunk_failed.store(true, Ordering::SeqCst);
+                        return sent;
+                    };
+
+                    let failed = results.iter().filter(|r| !r.success).count();
+                    let progress = IngestProgress {
+                        done: done.fetch_add(chunk.len(), Ordering::SeqCst) + chunk.len(),
+                        total,
+                        failures: failures.fetch_add(failed, Ordering::SeqCst) + failed,
+                    };
+                    if let Some(on_progress) = &options.on_progress {
+                        on_progress(progress);
+                    }
+
+                    sent
+                })
+                .buffered(options.concurrency.max(1))
+                .collect()
+                .await;
+
+        // Keep the results of saved chunks next to the first chunk error
+        let mut slots: Vec<Option<SaveResult>> = Vec::with_capacity(total);
+        let mut error = None;
+        for (chunk, outcome) in records.chunks(chunk_size).zip(outcomes) {
+            match outcome {
+                Ok(chunk_results) => slots.extend(chunk_results.into_iter().map(Some)),
+                Err(e) => {
+                    slots.extend(chunk.iter().map(|_| None));
+                    error.get_or_insert(e);
+                }
+            }
+        }
+        if let Some(error) = error {
+            if slots.iter().all(Option::is_none) {
+                return Err(error);
+            }
+            slots.resize_with(total, || None);
+            return Err(SfError::PartialBatch {
+                results: slots,
+                source: Box::new(error),
+            });
+        }
+
+        let results: Vec<SaveResult> = slots.into_iter().flatten().collect();
+        if results.len() < total {
+            warn!(
+                "Insert of {} {} records cancelled after {}",
+                total,
+                sobject,
+                results.len()
+            );
+        }
+        Ok(results)
+    }
+
+    /// Upsert many records by external ID with the sObject Collections API
+    ///
+    /// Records are sent in chunks of 200 through the rate limiter and retry
+    /// logic. Each result's `created` flag tells inserts from updates.
+    /// Records without a value for `external_id_field` are rejected before
+    /// anything is sent, with the offending row index in the error.
+    ///
+    /// `all_or_none` applies to each chunk of 200: a failing row rolls back
+    /// its own chunk, but chunks already sent stay committed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde::Serialize;
+    /// #[derive(Serialize)]
+    /// struct Account {
+    ///     #[serde(rename = "External_Id__c")]
+    ///     external_id: String,
+    ///     #[serde(rename = "Name")]
+    ///     name: String,
+    /// }
+    /// # async fn example(accounts: Vec<Account>) -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// let results = client
+    ///     .upsert_many("Account", "External_Id__c", &accounts, false)
+    ///     .await?;
+    /// let created = results.iter().filter(|r| r.created == Some(true)).count();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, records), fields(count = records.len()))]
+    pub async fn upsert_many<T: Serialize>(
+        &self,
+        sobject: &str,
+        external_id_field: &str,
+        records: &[T],
+        all_or_none: bool,
+    ) -> SfResult<Vec<SaveResult>> {
+        // Validate every row up front so a bad row never leaves earlier
+        // chunks committed
+        let label = format!("external ID field {}", external_id_field);
+        require_field(records, external_id_field, &label)?;
+
+        let mut results = Vec::with_capacity(records.len());
+
+        let sent = async {
+            for chunk in records.chunks(crud::COLLECTION_BATCH_SIZE) {
+                self.rate_limiter.acquire().await?;
+
+                let chunk_results = retry::with_retry(&self.config.retry_config, || async {
+                    self.crud
+                        .upsert_collection(sobject, external_id_field, chunk, all_or_none)
+                        .await
+                })
+                .await?;
+                results.extend(chunk_results);
+            }
+            Ok::<_, SfError>(())
+        }
+        .await;
+
+        // Invalidate cache, even on failure: earlier chunks are committed
+        self.query_cache.invalidate_sobject(sobject).await;
+        self.record_cache.invalidate_sobject(sobject).await;
+
+        sent.map(|()| results)
+    }
+
+    /// Update many records with the sObject Collections API
+    ///
+    /// Every record needs an `Id`; records are sent in chunks of 200
+    /// through the rate limiter and retry logic. With `all_or_none`, pass
+    /// the results to [`BatchOutcome::from_results`] to find the row that
+    /// failed among the rolled-back ones.
+    ///
+    /// `all_or_none` applies to each chunk of 200: a failing row rolls back
+    /// its own chunk, but chunks already sent stay committed. Keep batches
+    /// to 200 records when they must succeed or fail as a whole.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{BatchOutcome, SalesforceClient, ClientConfig, SfError};
+    /// # use serde::Serialize;
+    /// #[derive(Serialize)]
+    /// struct Payment {
+    ///     #[serde(rename = "Id")]
+    ///     id: String,
+    ///     #[serde(rename = "Amount__c")]
+    ///     amount: f64,
+    /// }
+    /// # async fn example(payments: Vec<Payment>) -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// let results = client.update_many("Payment__c", &payments, true).await?;
+    /// let outcome = BatchOutcome::from_results(&results);
+    /// if let Some(index) = outcome.failed_index() {
+    ///     println!("payment {} failed: {:?}", payments[index].id, results[index].errors);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, records), fields(count = records.len()))]
+    pub async fn update_many<T: Serialize>(
+        &self,
+        sobject: &str,
+        records: &[T],
+        all_or_none: bool,
+    ) -> SfResult<Vec<SaveResult>> {
+        // Validate every row up front so a bad row never leaves earlier
+        // chunks committed
+        require_field(records, "Id", "Id")?;
+
+        let mut results = Vec::with_capacity(records.len());
+
+        let sent = async {
+            for chunk in records.chunks(crud::COLLECTION_BATCH_SIZE) {
+                self.rate_limiter.acquire().await?;
+
+                let chunk_results = retry::with_retry(&self.config.retry_config, || async {
+                    self.crud
+                        .update_collection(sobject, chunk, all_or_none)
+                        .await
+                })
+                .await?;
+                results.extend(chunk_results);
+            }
+            Ok::<_, SfError>(())
+        }
+        .await;
+
+        // Invalidate cache, even on failure: earlier chunks are committed
+        self.query_cache.invalidate_sobject(sobject).await;
+        self.record_cache.invalidate_sobject(sobject).await;
+
+        sent.map(|()| results)
+    }
+
+    /// Delete many records with the sObject Collections API
+    ///
+    /// IDs are sent in chunks of 200 through the rate limiter and retry
+    /// logic. Every result carries its record ID; use
+    /// [`SaveResult::is_already_deleted`] to tell records that were already
+    /// gone apart from real failures such as missing permissions.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example(ids: Vec<&str>) -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    ///
+    /// let results = client.delete_many("Contact", &ids, false).await?;
+    /// let retry: Vec<_> = results
+    ///     .iter()
+    ///     .filter(|r| !r.success && !r.is_already_deleted())
+    ///     .filter_map(|r| r.id.as_deref())
+    ///     .collect();
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, ids), fields(count = ids.len()))]
+    pub async fn delete_many(
+        &self,
+        sobject: &str,
+        ids: &[&str],
+        all_or_none: bool,
+    ) -> SfResult<Vec<SaveResult>> {
+        let mut results = Vec::with_capacity(ids.len());
+
+        let sent = async {
+            for chunk in ids.chunks(crud::COLLECTION_BATCH_SIZE) {
+                self.rate_limiter.acquire().await?;
+
+                let chunk_results = retry::with_retry(&self.config.retry_config, || async {
+                    self.crud
+                        .delete_collection(sobject, chunk, all_or_none)
+                        .await
+                })
+                .await?;
+                results.extend(chunk_results);
+            }
+            Ok::<_, SfError>(())
+        }
+        .await;
+
+        // Invalidate cache, even on failure: earlier chunks are committed
+        self.query_cache.invalidate_sobject(sobject).await;
+        self.record_cache.invalidate_sobject(sobject).await;
+
+        sent.map(|()| results)
+    }
+
+    /// Delete many records, re-attempting rows that hit row lock errors
+    ///
+    /// Runs [`delete_many`](Self::delete_many) without `allOrNone`, then
+    /// deletes again only the rows that failed with `UNABLE_TO_LOCK_ROW`,
+    /// waiting `initial_backoff` (doubling each time, up to `max_backoff`)
+    /// between passes. Rows that fail for any other reason are not retried.
+    /// Records that were already deleted count as deleted. Only a failure
+    /// of the first pass is an error; if a retry pass fails, the report is
+    /// returned with that pass's records in `failed`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{ResilientDeleteOptions, SalesforceClient, ClientConfig, SfError};
+    /// # async fn example(ids: Vec<&str>) -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let report = client
+    ///     .delete_many_resilient("Task", &ids, &ResilientDeleteOptions::new())
+    ///     .await?;
+    /// for failure in &report.failed {
+    ///     println!("{} not deleted: {:?}", failure.id, failure.error_codes());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, ids, options), fields(count = ids.len()))]
+    pub async fn delete_many_resilient(
+        &self,
+        sobject: &str,
+        ids: &[&str],
+        options: &ResilientDeleteOptions,
+    ) -> SfResult<DeleteReport> {
+        let mut report = DeleteReport::default();
+        let mut pending = ids.to_vec();
+        let mut backoff = options.initial_backoff.min(options.max_backoff);
+
+        while !pending.is_empty() {
+            report.passes += 1;
+            let last_pass = report.passes >= options.max_passes;
+
+            let results = match self.delete_many(sobject, &pending, false).await {
+                Ok(results) => results,
+                Err(e) if report.passes == 1 => return Err(e),
+                // Keep what earlier passes deleted
+                Err(e) => {
+                    warn!(
+                        "Retry pass over {} {} records failed: {}",
+                        pending.len(),
+                        sobject,
+                        e
+                    );
+                    let errors = e.salesforce_errors();
+                    report.failed.extend(pending.iter().map(|id| FailedDelete {
+                        id: id.to_string(),
+                        errors: errors.clone(),
+                    }));
+                    break;
+                }
+            };
+            let mut locked = Vec::new();
+            for (id, result) in pending.iter().zip(results) {
+                if result.success || result.is_already_deleted() {
+                    report.deleted += 1;
+                } else if !last_pass && result.errors.iter().any(|e| e.status_code.is_row_lock()) {
+                    locked.push(*id);
+                } else {
+                    report.failed.push(FailedDelete {
+                        id: id.to_string(),
+                        errors: result.errors,
+                    });
+                }
+            }
+
+            if !locked.is_empty() {
+                warn!(
+                    "{} {} records were locked, retrying in {:?}",
+                    locked.len(),
+                    sobject,
+                    backoff
+                );
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(options.max_backoff);
+            }
+            pending = locked;
+        }
+
+        info!(
+            "Deleted {}/{} {} records in {} passes",
+            report.deleted,
+            ids.len(),
+            sobject,
+            report.passes
+        );
+        Ok(report)
+    }
+
+    /// Insert parent records together with their children in one request
+    ///
+    /// Uses the Composite Tree API, which saves the whole request atomically
+    /// and is limited to 200 records across all levels. Record-level
+    /// failures are returned in the response rather than as an error.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError, TreeRecord};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let account = TreeRecord::new("Account", "acme")
+    ///     .field("Name", "Acme")
+    ///     .child(
+    ///         "Contacts",
+    ///         TreeRecord::new("Contact", "smith").field("LastName", "Smith"),
+    ///     );
+    ///
+    /// let response = client.insert_tree("Account", &[account]).await?;
+    /// println!("Contact created: {:?}", response.id("smith"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, records), fields(count = records.len()))]
+    pub async fn insert_tree(
+        &self,
+        sobject: &str,
+        records: &[TreeRecord],
+    ) -> SfResult<TreeResponse> {
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            self.crud.insert_tree(sobject, records).await
+        })
+        .await
+    }
+
+    /// List records of `sobject` deleted between `start` and `end`
+    ///
+    /// The window must be at most 30 days long. Use the response's
+    /// `latest_date_covered` as the next `start` for incremental syncs.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let end = chrono::Utc::now();
+    /// let start = end - chrono::Duration::days(1);
+    ///
+    /// let deleted = client.get_deleted("Account", start, end).await?;
+    /// for record in &deleted.deleted_records {
+    ///     println!("{} deleted at {}", record.id, record.deleted_date);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn get_deleted(
+        &self,
+        sobject: &str,
+        start: chrono::DateTime<chrono::Utc>,
+        end: chrono::DateTime<chrono::Utc>,
+    ) -> SfResult<DeletedRecords> {
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            self.crud.get_deleted(sobject, start, end).await
+        })
+        .await
+    }
+
+    /// List IDs of `sobject` records created or updated between `start` and `end`
+    ///
+    /// The window must be at most 30 days long.
+    #[instrument(skip(self))]
+    pub async fn get_updated(
+        &self,
+        sobject: &str,
+        start: chrono::DateTime<chrono::Utc>,
+        end: chrono::DateTime<chrono::Utc>,
+    ) -> SfResult<UpdatedRecords> {
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            self.crud.get_updated(sobject, start, end).await
+        })
+        .await
+    }
+
+    /// Start a composite request whose subrequest URLs use this client's API version
+    pub fn composite_request(&self) -> CompositeRequestBuilder {
+        CompositeRequestBuilder::for_api_version(&self.config.api_version)
+    }
+
+    /// Execute several subrequests in one Composite API call
+    ///
+    /// Returns one result per subrequest, looked up by reference ID. With
+    /// `all_or_none` unset, individual subrequests can fail while the call
+    /// as a whole succeeds, so check each result's status.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, CompositeRequestBuilder, SfError};
+    /// # use serde_json::json;
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let request = client
+    ///     .composite_request()
+    ///     .all_or_none(true)
+    ///     .create("newAccount", "Account", json!({"Name": "Acme"}))
+    ///     .create(
+    ///         "newContact",
+    ///         "Contact",
+    ///         json!({"LastName": "Smith", "AccountId": "@{newAccount.id}"}),
+    ///     );
+    ///
+    /// let response = client.composite(request).await?;
+    /// let contact = response.get("newContact").unwrap();
+    /// println!("Contact created with status {}", contact.status);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, request), fields(count = request.len()))]
+    pub async fn composite(&self, request: CompositeRequestBuilder) -> SfResult<CompositeResponse> {
+        self.rate_limiter.acquire().await?;
+
+        let response = retry::with_retry(&self.config.retry_config, || async {
+            self.crud.composite(&request).await
+        })
+        .await?;
+
+        if request.has_writes() {
+            // Invalidate cache
+            self.query_cache.clear().await;
+            self.record_cache.clear().await;
+        }
+
+        Ok(response)
+    }
+
+    /// Get the identity of the authenticated user and org
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let identity = client.identity().await?;
+    /// println!("Acting as {} in org {}", identity.username, identity.organization_id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn identity(&self) -> SfResult<UserIdentity> {
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            let url = format!("{}/services/oauth2/userinfo", self.config.base_url);
+
+            let response =
+                auth::send_authorized(self.token_provider.as_ref(), || self.http_client.get(&url))
+                    .await?;
+
+            let status = response.status();
+            if !status.is_success() {
+                let body = response.text().await.unwrap_or_default();
+                return Err(SfError::Api {
+                    status: status.as_u16(),
+                    body,
+                });
+            }
+
+            Ok(response.json().await?)
+        })
+        .await
+    }
+
+    /// Describe an SObject's fields, relationships and record types
+    ///
+    /// Results are cached for `CacheConfig::metadata_ttl` and are not
+    /// invalidated by writes; call [`clear_cache`](Self::clear_cache) after
+    /// changing the org's schema. Once the TTL passes, the cached describe
+    /// is revalidated with `If-Modified-Since`/`If-None-Match` and only
+    /// downloaded again if it changed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let describe = client.describe("Account").await?;
+    /// if let Some(field) = describe.field("Industry") {
+    ///     println!("Industry is a {} field", field.field_type);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn describe(&self, sobject: &str) -> SfResult<SObjectDescribe> {
+        let path = format!("/sobjects/{}/describe", sobject);
+        self.get_metadata(&format!("describe:{}", sobject), &path)
+            .await
+    }
+
+    /// List every SObject in the org
+    ///
+    /// Cached like [`describe`](Self::describe).
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let global = client.describe_global().await?;
+    /// for sobject in global.custom_objects() {
+    ///     println!("Custom object: {}", sobject.name);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn describe_global(&self) -> SfResult<DescribeGlobal> {
+        self.get_metadata("describe_global", "/sobjects/").await
+    }
+
+    /// Submit records for approval
+    ///
+    /// Results are returned in request order. A record that matches no
+    /// approval process, e.g. because it fails the entry criteria, fails the
+    /// whole call with [`SfError::Api`] carrying
+    /// [`ErrorCode::NoApplicableProcess`].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{ApprovalRequest, SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let results = client
+    ///     .submit_for_approval(&[
+    ///         ApprovalRequest::new("006xx000001a2bC").comments("Discount above 20%")
+    ///     ])
+    ///     .await?;
+    /// println!("Instance {:?} is {:?}", results[0].instance_id, results[0].instance_status);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, requests), fields(count = requests.len()))]
+    pub async fn submit_for_approval(
+        &self,
+        requests: &[ApprovalRequest],
+    ) -> SfResult<Vec<ApprovalResult>> {
+        self.rate_limiter.acquire().await?;
+
+        let results = retry::with_retry(&self.config.retry_config, || async {
+            self.crud.submit_for_approval(requests).await
+        })
+        .await?;
+
+        // Invalidate cache; approval steps can update fields of records of
+        // any object
+        self.query_cache.clear().await;
+        self.record_cache.clear().await;
+
+        Ok(results)
+    }
+
+    /// List the approval processes records can be submitted to, keyed by object
+    #[instrument(skip(self))]
+    pub async fn pending_approvals(&self) -> SfResult<HashMap<String, Vec<ApprovalProcess>>> {
+        self.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.config.retry_config, || async {
+            self.crud.approval_processes().await
+        })
+        .await
+    }
+
+    /// List the quick actions available on an object
+    ///
+    /// Cached like [`describe`](Self::describe).
+    #[instrument(skip(self))]
+    pub async fn list_quick_actions(&self, sobject: &str) -> SfResult<Vec<QuickAction>> {
+        let path = format!("/sobjects/{}/quickActions", sobject);
+        self.get_metadata(&format!("quick_actions:{}", sobject), &path)
+            .await
+    }
+
+    /// Invoke a standard or custom quick action
+    ///
+    /// `body` is sent as is; Salesforce expects the record fields under
+    /// `record` and, for actions run from a record, its ID as `contextId`.
+    /// Validation failures come back as [`SfError::Api`]; use
+    /// [`SfError::salesforce_errors`] to read them.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # use serde_json::json;
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let result = client
+    ///     .invoke_quick_action(
+    ///         "Account",
+    ///         "LogACall",
+    ///         &json!({
+    ///             "contextId": "001xx000003DGbX",
+    ///             "record": {"Subject": "Follow-up call"}
+    ///         }),
+    ///     )
+    ///     .await?;
+    /// println!("Logged call {:?}", result.id);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self, body))]
+    pub async fn invoke_quick_action<T: Serialize>(
+        &self,
+        sobject: &str,
+        action: &str,
+        body: &T,
+    ) -> SfResult<QuickActionResult> {
+        self.rate_limiter.acquire().await?;
+
+        let result = retry::with_retry(&self.config.retry_config, || async {
+            self.crud.invoke_quick_action(sobject, action, body).await
+        })
+        .await?;
+
+        // Invalidate cache
+        self.query_cache.clear().await;
+        self.record_cache.invalidate_sobject(sobject).await;
+
+        Ok(result)
+    }
+
+    /// Describe an SObject's page layouts and their record type assignments
+    ///
+    /// Cached like [`describe`](Self::describe).
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let layouts = client.describe_layouts("Account").await?;
+    /// if let Some(layout) = layouts.layout_for_record_type("012000000000000AAA") {
+    ///     println!("Fields: {:?}", layout.detail_fields());
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn describe_layouts(&self, sobject: &str) -> SfResult<DescribeLayouts> {
+        let path = format!("/sobjects/{}/describe/layouts", sobject);
+        self.get_metadata(&format!("layouts:{}", sobject), &path)
+            .await
+    }
+
+    /// Describe an SObject's compact layouts
+    ///
+    /// Cached like [`describe`](Self::describe).
+    #[instrument(skip(self))]
+    pub async fn describe_compact_layouts(
+        &self,
+        sobject: &str,
+    ) -> SfResult<DescribeCompactLayouts> {
+        let path = format!("/sobjects/{}/describe/compactLayouts", sobject);
+        self.get_metadata(&format!("compact_layouts:{}", sobject), &path)
+            .await
+    }
+
+    /// Picklist values of one field for a record type, from the UI API
+    ///
+    /// Unlike [`describe`](Self::describe), the values are filtered to the
+    /// record type and include dependencies on controlling fields. Cached
+    /// like `describe`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let industries = client
+    ///     .picklist_values("Account", "012000000000000AAA", "Industry")
+    ///     .await?;
+    /// for entry in &industries.values {
+    ///     println!("{} ({})", entry.label, entry.value);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self))]
+    pub async fn picklist_values(
+        &self,
+        sobject: &str,
+        record_type_id: &str,
+        field: &str,
+    ) -> SfResult<PicklistFieldValues> {
+        let path = format!(
+            "/ui-api/object-info/{}/picklist-values/{}/{}",
+            sobject, record_type_id, field
+        );
+        let cache_key = format!("picklist:{}:{}:{}", sobject, record_type_id, field);
+        self.get_metadata(&cache_key, &path).await
+    }
+
+    /// Picklist values of every picklist field for a record type
+    ///
+    /// Cached like [`describe`](Self::describe).
+    #[instrument(skip(self))]
+    pub async fn record_type_picklist_values(
+        &self,
+        sobject: &str,
+        record_type_id: &str,
+    ) -> SfResult<RecordTypePicklists> {
+        let path = format!(
+            "/ui-api/object-info/{}/picklist-values/{}",
+            sobject, record_type_id
+        );
+        let cache_key = format!("picklist:{}:{}", sobject, record_type_id);
+        self.get_metadata(&cache_key, &path).await
+    }
+
+    /// GET a metadata resource, going through the metadata cache
+    ///
+    /// `path` is relative to the REST API root, e.g. `/sobjects/`. A stale
+    /// entry cached with an `ETag` or `Last-Modified` is revalidated with a
+    /// conditional request, and kept without re-downloading on a 304.
+    async fn get_metadata<T>(&self, cache_key: &str, path: &str) -> SfResult<T>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        if let Some(cached) = self.metadata_cache.get::<T>(cache_key).await {
+            return Ok(cached);
+        }
+        let stale = self.metadata_cache.get_revalidatable(cache_key).await;
+
+        self.rate_limiter.acquire().await?;
+
+        let fetched = retry::with_retry(&self.config.retry_config, || async {
+            let url = format!("{}{}", self.config.api_url(), path);
+
+            let response = auth::send_authorized(self.token_provider.as_ref(), || {
+                let request = self.http_client.get(&url);
+                match &stale {
+                    Some(entry) => entry.validators.apply(request),
+                    None => request,
+                }
+            })
+            .await?;
+
+            let status = response.status();
+            if status == reqwest::StatusCode::NOT_MODIFIED {
+                return Ok(None);
+            }
+            let validators = cache::Validators::from_headers(response.headers());
+            let body = response.text().await?;
+            if !status.is_success() {
+                return Err(SfError::Api {
+                    status: status.as_u16(),
+                    body,
+                });
+            }
+
+            Ok(Some((serde_json::from_str::<T>(&body)?, validators)))
+        })
+        .await?;
+
+        let Some((metadata, validators)) = fetched else {
+            let Some(entry) = stale else {
+                return Err(SfError::Api {
+                    status: 304,
+                    body: String::new(),
+                });
+            };
+            return self.metadata_cache.revalidated(cache_key, entry).await;
+        };
+
+        if let Err(e) = self
+            .metadata_cache
+            .set_validated(cache_key, &metadata, validators)
+            .await
+        {
+            debug!("Failed to cache {}: {}", cache_key, e);
+        }
+        Ok(metadata)
+    }
+
+    /// Access the Tooling API (`ApexClass`, `ApexLog`, `EntityDefinition`, ...)
+    ///
+    /// The returned handle shares this client's authentication, retry and
+    /// rate limiting.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+    /// # async fn example() -> Result<(), SfError> {
+    /// # let config = ClientConfig::new("https://example.com", "token");
+    /// # let client = SalesforceClient::new(config);
+    /// let logs: Vec<serde_json::Value> = client
+    ///     .tooling()
+    ///     .query("SELECT Id, LogLength FROM ApexLog ORDER BY StartTime DESC LIMIT 10")
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn tooling(&self) -> ToolingClient<'_> {
+        ToolingClient::new(self)
+    }
+
+    // ========================================================================
+    // Utility Methods
+    // ========================================================================
+
+    /// Clear the query, metadata and record caches
+    pub async fn clear_cache(&self) {
+        self.query_cache.clear().await;
+        self.metadata_cache.clear().await;
+        self.record_cache.clear().await;
+        info!("Cache cleared");
+    }
+
+    /// Hit, miss and size statistics for the query cache
+    pub fn cache_stats(&self) -> CacheStats {
+        self.query_cache.stats()
+    }
+
+    /// Hit, miss and size statistics for the record cache used by `get`
+    /// and `exists`
+    pub fn record_cache_stats(&self) -> CacheStats {
+        self.record_cache.stats()
+    }
+
+    /// Reset the query and record cache counters
+    pub fn reset_cache_stats(&self) {
+        self.query_cache.stats_reset();
+        self.record_cache.stats_reset();
+    }
+
+    /// Get the current configuration
+    pub fn config(&self) -> &ClientConfig {
+        &self.config
+    }
+
+    /// Get rate limiter status
+    pub fn rate_limit_status(&self) -> rate_limit::RateLimitStatus {
+        self.rate_limiter.status()
+    }
+}
+
+/// Length of a query once URL-encoded as the `q` parameter
+fn encoded_query_length(soql: &str) -> usize {
+    url::form_urlencoded::byte_serialize(soql.as_bytes())
+        .map(str::len)
+        .sum()
+}
+
+/// Fail with the index of the first record without a value for `field`,
+/// calling the field `label` in the error
+fn require_field<T: Serialize>(records: &[T], field: &str, label: &str) -> SfResult<()> {
+    for (index, record) in records.iter().enumerate() {
+        let value = serde_json::to_value(record)?;
+        if value.get(field).map_or(true, |v| v.is_null()) {
+            return Err(SfError::Serialization(serde::ser::Error::custom(format!(
+                "record {} is missing {}",
+                index, label
+            ))));
+        }
+    }
+    Ok(())
+}
+
+fn query_too_long(length: usize) -> SfError {
+    SfError::InvalidQuery(format!(
+        "query too long ({} chars); consider query_in_chunks",
+        length
+    ))
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_client_config_builder() {
-        let config = ClientConfig::new("https://test.salesforce.com", "test_token")
+    fn test_client_config_builder() {
+        let config = ClientConfig::new("https://test.salesforce.com", "test_token")
+            .with_cache(CacheConfig::disabled())
+            .no_pagination();
+
+        assert_eq!(config.base_url, "https://test.salesforce.com");
+        assert!(!config.auto_paginate);
+        assert_eq!(config.environment, Environment::Production);
+    }
+
+    #[test]
+    fn test_client_config_environment() {
+        let config = ClientConfig::new("https://test.salesforce.com", "test_token").sandbox();
+        assert_eq!(config.environment, Environment::Sandbox);
+        assert_eq!(config.environment.auth_url(), "https://test.salesforce.com");
+
+        let custom = Environment::Custom("https://acme.my.salesforce.com".to_string());
+        let client = SalesforceClient::new(
+            ClientConfig::new("https://acme.my.salesforce.com", "test_token")
+                .with_environment(custom.clone()),
+        );
+        assert_eq!(client.config().environment, custom);
+    }
+
+    #[tokio::test]
+    async fn test_with_oauth_config_rejects_invalid_environment() {
+        let config = ClientConfig::new("", "").with_environment(Environment::Custom(
+            "http://insecure.example.com".to_string(),
+        ));
+        let result = SalesforceClient::with_oauth_config(
+            config,
+            OAuthCredentials::client_credentials("id", "secret"),
+        )
+        .await;
+
+        assert!(matches!(result, Err(SfError::Config(_))));
+    }
+
+    #[test]
+    fn test_client_creation() {
+        let config = ClientConfig::new("https://test.salesforce.com", "test_token");
+
+        let client = SalesforceClient::new(config);
+        assert_eq!(client.config.base_url, "https://test.salesforce.com");
+    }
+
+    fn static_client(server: &mockito::ServerGuard) -> SalesforceClient {
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::disabled())
+            .with_retry(RetryConfig::no_retry());
+        SalesforceClient::new(config)
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct Account {
+        #[serde(rename = "Id")]
+        id: String,
+        #[serde(rename = "Name")]
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn test_get_record() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/001xx000003DGbX")
+            .match_header("authorization", "Bearer test_token")
+            .with_body(
+                r#"{"attributes":{"type":"Account","url":"/services/data/v57.0/sobjects/Account/001xx000003DGbX"},"Id":"001xx000003DGbX","Name":"Acme"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let account: Account = static_client(&server)
+            .get("Account", "001xx000003DGbX")
+            .await
+            .unwrap();
+
+        assert_eq!(account.id, "001xx000003DGbX");
+        assert_eq!(account.name, "Acme");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_record_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/001missing")
+            .with_status(404)
+            .with_body(
+                r#"[{"errorCode":"NOT_FOUND","message":"The requested resource does not exist"}]"#,
+            )
+            .create_async()
+            .await;
+
+        let err = static_client(&server)
+            .get::<Account>("Account", "001missing")
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(err, SfError::NotFound { sobject, id } if sobject == "Account" && id == "001missing")
+        );
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_record_malformed_json() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock(
+                "GET",
+                "/services/data/v57.0/sobjects/Account/001xx000003DGbX",
+            )
+            .with_body(r#"{"Id":"001xx000003DGbX""#)
+            .create_async()
+            .await;
+
+        let err = static_client(&server)
+            .get::<Account>("Account", "001xx000003DGbX")
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, SfError::Serialization(_)));
+        mock.assert_async().await;
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct PartialAccount {
+        #[serde(rename = "Name")]
+        name: Option<String>,
+        #[serde(rename = "AnnualRevenue")]
+        annual_revenue: Option<f64>,
+        #[serde(rename = "Industry")]
+        industry: Option<String>,
+    }
+
+    #[tokio::test]
+    async fn test_get_fields() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/001xx000003DGbX")
+            .match_query(mockito::Matcher::Exact(
+                "fields=Name%2CAnnualRevenue".to_string(),
+            ))
+            .with_body(
+                r#"{"attributes":{"type":"Account"},"Name":"Acme","AnnualRevenue":1500000.0,"Id":"001xx000003DGbX"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let account: PartialAccount = client
+            .get_fields("Account", "001xx000003DGbX", &["Name", "AnnualRevenue"])
+            .await
+            .unwrap();
+
+        assert_eq!(account.name.as_deref(), Some("Acme"));
+        assert_eq!(account.annual_revenue, Some(1_500_000.0));
+        assert!(account.industry.is_none());
+        mock.assert_async().await;
+
+        let err = client
+            .get_fields::<PartialAccount>("Account", "001xx000003DGbX", &[])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::InvalidQuery(_)));
+    }
+
+    /// Answer a collections insert with one result per record, failing rows
+    /// whose `LastName` is empty
+    fn collection_results(request: &mockito::Request) -> Vec<u8> {
+        let body: serde_json::Value = serde_json::from_slice(request.body().unwrap()).unwrap();
+        let results: Vec<_> = body["records"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|record| {
+                assert_eq!(record["attributes"]["type"], "Contact");
+                let name = record["LastName"].as_str().unwrap();
+                if name.is_empty() {
+                    serde_json::json!({"success": false, "errors": [{"statusCode": "REQUIRED_FIELD_MISSING", "message": "Required fields are missing: [LastName]", "fields": ["LastName"]}]})
+                } else {
+                    serde_json::json!({"id": format!("003{}", name), "success": true, "errors": []})
+                }
+            })
+            .collect();
+        serde_json::to_vec(&results).unwrap()
+    }
+
+    #[derive(serde::Serialize)]
+    struct NewContact {
+        #[serde(rename = "LastName")]
+        last_name: String,
+    }
+
+    #[tokio::test]
+    async fn test_insert_many_chunks_and_aligns_results() {
+        let mut server = mockito::Server::new_async().await;
+        let full_chunk = server
+            .mock("POST", "/services/data/v57.0/composite/sobjects")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"allOrNone":false}"#.to_string(),
+            ))
+            .match_request(|request| {
+                let body: serde_json::Value =
+                    serde_json::from_slice(request.body().unwrap()).unwrap();
+                body["records"].as_array().unwrap().len() == 200
+            })
+            .with_body_from_request(collection_results)
+            .expect(1)
+            .create_async()
+            .await;
+        let remainder = server
+            .mock("POST", "/services/data/v57.0/composite/sobjects")
+            .match_request(|request| {
+                let body: serde_json::Value =
+                    serde_json::from_slice(request.body().unwrap()).unwrap();
+                body["records"].as_array().unwrap().len() == 1
+            })
+            .with_body_from_request(collection_results)
+            .expect(1)
+            .create_async()
+            .await;
+
+        // Rows 5 and 200 (the single record of the second chunk) fail
+        let contacts: Vec<_> = (0..201)
+            .map(|i| NewContact {
+                last_name: if i == 5 || i == 200 {
+                    String::new()
+                } else {
+                    i.to_string()
+                },
+            })
+            .collect();
+
+        let results = static_client(&server)
+            .insert_many("Contact", &contacts, false)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 201);
+        assert_eq!(results[0].id.as_deref(), Some("0030"));
+        assert_eq!(results[199].id.as_deref(), Some("003199"));
+        let failed: Vec<_> = results
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| !r.success)
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(failed, vec![5, 200]);
+        assert_eq!(results[5].errors[0].status_code, "REQUIRED_FIELD_MISSING");
+        assert!(results[5].id.is_none());
+
+        full_chunk.assert_async().await;
+        remainder.assert_async().await;
+    }
+
+    const TWO_RESULTS_ONE_FAILED: &str = r#"[
+        {"id":"003A","success":true,"errors":[]},
+        {"success":false,"errors":[{"statusCode":"REQUIRED_FIELD_MISSING","message":"Required fields are missing: [LastName]","fields":["LastName"]}]}
+    ]"#;
+
+    fn ten_contacts() -> Vec<NewContact> {
+        (0..10)
+            .map(|i| NewContact {
+                last_name: format!("Contact {}", i),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_insert_many_progress() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/data/v57.0/composite/sobjects")
+            .with_body(TWO_RESULTS_ONE_FAILED)
+            .expect(5)
+            .create_async()
+            .await;
+
+        let updates = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = updates.clone();
+        let options = IngestOptions::new()
+            .chunk_size(2)
+            .concurrency(2)
+            .on_progress(move |progress| seen.lock().unwrap().push(progress));
+
+        let results = static_client(&server)
+            .insert_many_with("Contact", &ten_contacts(), &options)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 10);
+        let updates = updates.lock().unwrap().clone();
+        assert_eq!(updates.len(), 5);
+        assert!(updates.iter().all(|p| p.total == 10));
+        let last = updates.iter().max_by_key(|p| p.done).unwrap();
+        assert_eq!((last.done, last.failures), (10, 5));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_insert_many_middle_chunk_failure() {
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("POST", "/services/data/v57.0/composite/sobjects")
+            .match_body(mockito::Matcher::Regex("Contact 0".to_string()))
+            .with_body(TWO_RESULTS_ONE_FAILED)
+            .expect(1)
+            .create_async()
+            .await;
+        let middle = server
+            .mock("POST", "/services/data/v57.0/composite/sobjects")
+            .match_body(mockito::Matcher::Regex("Contact 2".to_string()))
+            .with_status(500)
+            .with_body(r#"[{"errorCode":"UNKNOWN_EXCEPTION","message":"boom"}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let rest = server
+            .mock("POST", "/services/data/v57.0/composite/sobjects")
+            .match_body(mockito::Matcher::Regex("Contact [4-9]".to_string()))
+            .with_body(TWO_RESULTS_ONE_FAILED)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let options = IngestOptions::new().chunk_size(2);
+        let err = static_client(&server)
+            .insert_many_with("Contact", &ten_contacts(), &options)
+            .await
+            .unwrap_err();
+
+        // The saved first chunk is reported; later chunks are never sent
+        let SfError::PartialBatch { results, source } = err else {
+            panic!("expected PartialBatch, got {:?}", err);
+        };
+        assert_eq!(results.len(), 10);
+        assert_eq!(results[0].as_ref().unwrap().id.as_deref(), Some("003A"));
+        assert!(!results[1].as_ref().unwrap().success);
+        assert!(results[2..].iter().all(Option::is_none));
+        assert!(matches!(*source, SfError::Api { status: 500, .. }));
+
+        first.assert_async().await;
+        middle.assert_async().await;
+        rest.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_insert_many_cancellation() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/data/v57.0/composite/sobjects")
+            .with_body(TWO_RESULTS_ONE_FAILED)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let token = tokio_util::sync::CancellationToken::new();
+        let cancel = token.clone();
+        let options = IngestOptions::new()
+            .chunk_size(2)
+            .cancellation(token)
+            .on_progress(move |progress| {
+                if progress.done >= 4 {
+                    cancel.cancel();
+                }
+            });
+
+        let results = static_client(&server)
+            .insert_many_with("Contact", &ten_contacts(), &options)
+            .await
+            .unwrap();
+
+        // The two chunks sent before cancelling are reported; nothing after
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[2].id.as_deref(), Some("003A"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_insert_many_all_or_none_rollback() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/data/v57.0/composite/sobjects")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"allOrNone":true}"#.to_string(),
+            ))
+            .with_body(
+                r#"[
+                    {"id":null,"success":false,"errors":[{"statusCode":"ALL_OR_NONE_OPERATION_ROLLED_BACK","message":"Record rolled back because not all records were valid and the request was using AllOrNone header","fields":[]}]},
+                    {"success":false,"errors":[{"statusCode":"REQUIRED_FIELD_MISSING","message":"Required fields are missing: [LastName]","fields":["LastName"]}]}
+                ]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let contacts = vec![
+            NewContact {
+                last_name: "Doe".to_string(),
+            },
+            NewContact {
+                last_name: String::new(),
+            },
+        ];
+        let results = static_client(&server)
+            .insert_many("Contact", &contacts, true)
+            .await
+            .unwrap();
+
+        assert!(results.iter().all(|r| !r.success));
+        assert_eq!(
+            results[0].errors[0].status_code,
+            "ALL_OR_NONE_OPERATION_ROLLED_BACK"
+        );
+        assert_eq!(results[1].errors[0].fields, vec!["LastName"]);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_with_meta_after_paginated_query() {
+        let soql = "SELECT Id FROM Account";
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            .with_body(
+                r#"{"totalSize":2,"done":false,"nextRecordsUrl":"/services/data/v57.0/query/01gA-1","records":[{"Id":"001A"}]}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        let next = server
+            .mock("GET", "/services/data/v57.0/query/01gA-1")
+            .with_body(r#"{"totalSize":2,"done":true,"records":[{"Id":"001B"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        let records: Vec<serde_json::Value> = client.query(soql).await.unwrap();
+        assert_eq!(records.len(), 2);
+
+        // The cached full result isn't passed off as the first page
+        for _ in 0..2 {
+            let page = client
+                .query_with_meta::<serde_json::Value>(soql)
+                .await
+                .unwrap();
+            assert_eq!(page.records.len(), 1);
+            assert_eq!(page.total_size, Some(2));
+            assert!(page.has_more());
+        }
+
+        let records: Vec<serde_json::Value> = client.query(soql).await.unwrap();
+        assert_eq!(records.len(), 2);
+
+        first.assert_async().await;
+        next.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_with_meta_cached() {
+        let soql = "SELECT Id, Name FROM Account LIMIT 1";
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            .with_body(
+                r#"{"totalSize":12340,"done":false,"nextRecordsUrl":"/services/data/v57.0/query/01gA-2000",
+                    "records":[{"Id":"001A","Name":"Acme"}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry())
+            .no_pagination();
+        let client = SalesforceClient::new(config);
+
+        let fresh = client
+            .query_with_meta::<serde_json::Value>(soql)
+            .await
+            .unwrap();
+        let cached = client
+            .query_with_meta::<serde_json::Value>(soql)
+            .await
+            .unwrap();
+
+        for result in [fresh, cached] {
+            assert_eq!(result.total_size, Some(12340));
+            assert!(!result.done);
+            assert!(result.has_more());
+            assert_eq!(
+                result.next_records_url.as_deref(),
+                Some("/services/data/v57.0/query/01gA-2000")
+            );
+            assert_eq!(result.records[0]["Name"], "Acme");
+        }
+
+        // Without auto-pagination query() shares the cache entry
+        let records: Vec<serde_json::Value> = client.query(soql).await.unwrap();
+        assert_eq!(records.len(), 1);
+
+        mock.assert_async().await;
+    }
+
+    async fn mock_two_task_pages(server: &mut mockito::Server, soql: &str) -> [mockito::Mock; 2] {
+        let first = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            .with_body(
+                r#"{"totalSize":3,"done":false,"nextRecordsUrl":"/services/data/v57.0/query/01gA-2","records":[{"Id":"00T1"},{"Id":"00T2"}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/services/data/v57.0/query/01gA-2")
+            .with_body(r#"{"totalSize":3,"done":true,"records":[{"Id":"00T3"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        [first, second]
+    }
+
+    #[tokio::test]
+    async fn test_query_auto_paginates() {
+        let soql = "SELECT Id FROM Task";
+        let mut server = mockito::Server::new_async().await;
+        let mocks = mock_two_task_pages(&mut server, soql).await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        let records: Vec<serde_json::Value> = client.query(soql).await.unwrap();
+        assert_eq!(task_ids(&records), ["00T1", "00T2", "00T3"]);
+
+        // The complete result is cached
+        let records: Vec<serde_json::Value> = client.query(soql).await.unwrap();
+        assert_eq!(records.len(), 3);
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_without_auto_pagination() {
+        let soql = "SELECT Id FROM Task";
+        let mut server = mockito::Server::new_async().await;
+        let [first, second] = mock_two_task_pages(&mut server, soql).await;
+        let second = second.expect(0);
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::disabled())
+            .with_retry(RetryConfig::no_retry())
+            .no_pagination();
+        let client = SalesforceClient::new(config);
+
+        let records: Vec<serde_json::Value> = client.query(soql).await.unwrap();
+        assert_eq!(task_ids(&records), ["00T1", "00T2"]);
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_auto_pagination_max_records() {
+        let soql = "SELECT Id FROM Task";
+        let mut server = mockito::Server::new_async().await;
+        let [first, _second] = mock_two_task_pages(&mut server, soql).await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::disabled())
+            .with_retry(RetryConfig::no_retry())
+            .with_max_records(1);
+        let client = SalesforceClient::new(config);
+
+        let err = client.query::<serde_json::Value>(soql).await.unwrap_err();
+        assert!(matches!(
+            err,
+            SfError::ResultTooLarge {
+                fetched: 2,
+                limit: 1
+            }
+        ));
+        first.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_stream_is_lazy() {
+        let soql = "SELECT Id FROM Task";
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            .with_body(
+                r#"{"totalSize":3,"done":false,"nextRecordsUrl":"/services/data/v57.0/query/01gA-2","records":[{"Id":"00T1"},{"Id":"00T2"}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/services/data/v57.0/query/01gA-2")
+            .with_body(r#"{"totalSize":3,"done":true,"records":[{"Id":"00T3"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let stream = client.query_stream::<serde_json::Value>(soql);
+        futures::pin_mut!(stream);
+
+        assert!(!first.matched_async().await);
+
+        assert_eq!(stream.try_next().await.unwrap().unwrap()["Id"], "00T1");
+        assert_eq!(stream.try_next().await.unwrap().unwrap()["Id"], "00T2");
+        assert!(!second.matched_async().await);
+
+        assert_eq!(stream.try_next().await.unwrap().unwrap()["Id"], "00T3");
+        assert!(stream.try_next().await.unwrap().is_none());
+
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    /// Three pages of two, two and one Task
+    async fn mock_three_task_pages(
+        server: &mut mockito::Server,
+        soql: &str,
+        expected: [usize; 3],
+    ) -> Vec<mockito::Mock> {
+        let pages = [
+            (
+                "/services/data/v57.0/query",
+                r#"{"totalSize":5,"done":false,"nextRecordsUrl":"/services/data/v57.0/query/01gA-2","records":[{"Id":"00T1"},{"Id":"00T2"}]}"#,
+            ),
+            (
+                "/services/data/v57.0/query/01gA-2",
+                r#"{"totalSize":5,"done":false,"nextRecordsUrl":"/services/data/v57.0/query/01gA-4","records":[{"Id":"00T3"},{"Id":"00T4"}]}"#,
+            ),
+            (
+                "/services/data/v57.0/query/01gA-4",
+                r#"{"totalSize":5,"done":true,"records":[{"Id":"00T5"}]}"#,
+            ),
+        ];
+
+        let mut mocks = Vec::new();
+        for ((path, body), expected) in pages.into_iter().zip(expected) {
+            let mock = server.mock("GET", path);
+            let mock = if path.ends_with("/query") {
+                mock.match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            } else {
+                mock
+            };
+            mocks.push(mock.with_body(body).expect(expected).create_async().await);
+        }
+        mocks
+    }
+
+    fn task_ids(records: &[serde_json::Value]) -> Vec<&str> {
+        records.iter().map(|r| r["Id"].as_str().unwrap()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_query_all_with_limit_on_page_boundary() {
+        let soql = "SELECT Id FROM Task";
+        let mut server = mockito::Server::new_async().await;
+        let mocks = mock_three_task_pages(&mut server, soql, [1, 1, 0]).await;
+
+        let client = static_client(&server);
+        let records = client
+            .query_all_with::<serde_json::Value>(soql, QueryOptions::new().limit(4))
+            .await
+            .unwrap();
+        assert_eq!(task_ids(&records), ["00T1", "00T2", "00T3", "00T4"]);
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_all_with_limit_mid_page() {
+        let soql = "SELECT Id FROM Task";
+        let mut server = mockito::Server::new_async().await;
+        let mocks = mock_three_task_pages(&mut server, soql, [1, 1, 0]).await;
+
+        let client = static_client(&server);
+        let records = client
+            .query_all_with::<serde_json::Value>(soql, QueryOptions::new().limit(3))
+            .await
+            .unwrap();
+        assert_eq!(task_ids(&records), ["00T1", "00T2", "00T3"]);
+
+        let records = client
+            .query_all_with::<serde_json::Value>(soql, QueryOptions::new().limit(0))
+            .await
+            .unwrap();
+        assert!(records.is_empty());
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_paginated_returns_single_page() {
+        let soql = "SELECT Id FROM Task";
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            .with_body(r#"{"totalSize":2,"done":true,"records":[{"Id":"00T1"},{"Id":"00T2"}]}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let mut pages = client
+            .query_paginated::<serde_json::Value>(soql)
+            .await
+            .unwrap();
+        assert_eq!(
+            task_ids(&pages.next().await.unwrap().unwrap()),
+            ["00T1", "00T2"]
+        );
+        assert!(pages.next().await.unwrap().is_none());
+
+        let records = client.query_all::<serde_json::Value>(soql).await.unwrap();
+        assert_eq!(task_ids(&records), ["00T1", "00T2"]);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_all_max_records() {
+        let soql = "SELECT Id FROM Task";
+        let mut server = mockito::Server::new_async().await;
+        let mocks = mock_three_task_pages(&mut server, soql, [2, 2, 0]).await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::disabled())
+            .with_retry(RetryConfig::no_retry())
+            .with_max_records(3);
+        let client = SalesforceClient::new(config);
+
+        let err = client
+            .query_all::<serde_json::Value>(soql)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SfError::ResultTooLarge {
+                fetched: 4,
+                limit: 3
+            }
+        ));
+        assert!(err.to_string().contains("query_stream or query_paginated"));
+
+        let err = client
+            .query_paginated::<serde_json::Value>(soql)
+            .await
+            .unwrap()
+            .collect_all()
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::ResultTooLarge { fetched: 4, .. }));
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_all_with_max_records() {
+        let soql = "SELECT Id FROM Task";
+        let mut server = mockito::Server::new_async().await;
+        let mocks = mock_three_task_pages(&mut server, soql, [2, 1, 1]).await;
+
+        let client = static_client(&server);
+        let err = client
+            .query_all_with::<serde_json::Value>(soql, QueryOptions::new().max_records(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            SfError::ResultTooLarge {
+                fetched: 2,
+                limit: 1
+            }
+        ));
+
+        // Exactly at the cap is fine
+        let records = client
+            .query_all_with::<serde_json::Value>(soql, QueryOptions::new().max_records(5))
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 5);
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_paginated_with_limit() {
+        let soql = "SELECT Id FROM Task";
+        let mut server = mockito::Server::new_async().await;
+        let mocks = mock_three_task_pages(&mut server, soql, [1, 1, 0]).await;
+
+        let client = static_client(&server);
+        let mut pages = client
+            .query_paginated_with::<serde_json::Value>(soql, &QueryOptions::new().limit(3))
+            .await
+            .unwrap();
+        assert_eq!(
+            task_ids(&pages.next().await.unwrap().unwrap()),
+            ["00T1", "00T2"]
+        );
+        assert_eq!(task_ids(&pages.next().await.unwrap().unwrap()), ["00T3"]);
+        assert!(pages.next().await.unwrap().is_none());
+
+        let mut pages = client
+            .query_paginated_with::<serde_json::Value>(soql, &QueryOptions::new().limit(0))
+            .await
+            .unwrap();
+        assert!(pages.next().await.unwrap().is_none());
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_in_chunks() {
+        let mut server = mockito::Server::new_async().await;
+        let chunks = [
+            (
+                "'001A', '001B'",
+                r#"{"totalSize":2,"done":true,"records":[{"Id":"003A","Name":"Ann"},{"Id":"003B","Name":"Bob"}]}"#,
+            ),
+            (
+                "'001C', '001D'",
+                r#"{"totalSize":2,"done":true,"records":[{"Id":"003B","Name":"Bob"},{"Id":"003C","Name":"Cy"}]}"#,
+            ),
+            (
+                "'001E'",
+                r#"{"totalSize":1,"done":true,"records":[{"Id":"003D","Name":"Di"}]}"#,
+            ),
+        ];
+        let mut mocks = Vec::new();
+        for (ids, body) in chunks {
+            let soql = format!(
+                "SELECT Id, Name FROM Contact WHERE IsDeleted = false AND AccountId IN ({})",
+                ids
+            );
+            mocks.push(
+                server
+                    .mock("GET", "/services/data/v57.0/query")
+                    .match_query(mockito::Matcher::UrlEncoded("q".into(), soql))
+                    .with_body(body)
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let client = static_client(&server);
+        let builder = QueryBuilder::select(&["Id", "Name"])
+            .from("Contact")
+            .where_clause("IsDeleted = false");
+        let contacts: Vec<serde_json::Value> = client
+            .query_in_chunks_with(
+                builder,
+                "AccountId",
+                ["001A", "001B", "001C", "001D", "001E"],
+                2,
+                2,
+            )
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = contacts.iter().map(|c| c["Id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["003A", "003B", "003C", "003D"]);
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_in_chunks_with_or_clause() {
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = Vec::new();
+        for ids in ["'001A', '001B'", "'001C'"] {
+            let soql = format!(
+                "SELECT Id FROM Contact WHERE (Email = null OR Phone = null) AND AccountId IN ({})",
+                ids
+            );
+            mocks.push(
+                server
+                    .mock("GET", "/services/data/v57.0/query")
+                    .match_query(mockito::Matcher::UrlEncoded("q".into(), soql))
+                    .with_body(r#"{"totalSize":0,"done":true,"records":[]}"#)
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let client = static_client(&server);
+        let builder = QueryBuilder::select(&["Id"])
+            .from("Contact")
+            .where_clause("Email = null OR Phone = null");
+        let contacts: Vec<serde_json::Value> = client
+            .query_in_chunks_with(builder, "AccountId", ["001A", "001B", "001C"], 2, 1)
+            .await
+            .unwrap();
+
+        assert!(contacts.is_empty());
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_in_chunks_empty_ids() {
+        let mut server = mockito::Server::new_async().await;
+        let query = server
+            .mock("GET", mockito::Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let ids: Vec<String> = Vec::new();
+        let contacts: Vec<serde_json::Value> = client
+            .query_in_chunks(
+                QueryBuilder::select(&["Id"]).from("Contact"),
+                "AccountId",
+                ids,
+                100,
+            )
+            .await
+            .unwrap();
+
+        assert!(contacts.is_empty());
+        query.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_stream_error_mid_stream() {
+        let soql = "SELECT Id FROM Task";
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            .with_body(
+                r#"{"totalSize":3,"done":false,"nextRecordsUrl":"/services/data/v57.0/query/01gA-2","records":[{"Id":"00T1"}]}"#,
+            )
+            .create_async()
+            .await;
+        let failed = server
+            .mock("GET", "/services/data/v57.0/query/01gA-2")
+            .with_status(400)
+            .with_body(
+                r#"[{"errorCode":"INVALID_QUERY_LOCATOR","message":"invalid query locator"}]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let stream = client.query_stream::<serde_json::Value>(soql);
+        futures::pin_mut!(stream);
+
+        assert!(stream.try_next().await.unwrap().is_some());
+        let err = stream.try_next().await.unwrap_err();
+        assert!(matches!(err, SfError::Api { status: 400, .. }));
+        assert!(stream.next().await.is_none());
+
+        first.assert_async().await;
+        failed.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_all_records() {
+        #[derive(Debug, Clone, PartialEq, serde::Deserialize, serde::Serialize)]
+        struct AuditRow {
+            #[serde(rename = "Id")]
+            id: String,
+            #[serde(rename = "IsDeleted")]
+            is_deleted: bool,
+        }
+
+        let soql = "SELECT Id, IsDeleted FROM Account";
+        let mut server = mockito::Server::new_async().await;
+        let live = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            .with_body(
+                r#"{"totalSize":1,"done":true,"records":[{"attributes":{"type":"Account"},"Id":"001A","IsDeleted":false}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let all = server
+            .mock("GET", "/services/data/v57.0/queryAll")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            .with_body(
+                r#"{"totalSize":2,"done":true,"records":[
+                    {"attributes":{"type":"Account"},"Id":"001A","IsDeleted":false},
+                    {"attributes":{"type":"Account"},"Id":"001B","IsDeleted":true}
+                ]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        // Each endpoint is fetched once and cached under its own key
+        for _ in 0..2 {
+            let rows: Vec<AuditRow> = client.query(soql).await.unwrap();
+            assert_eq!(rows.len(), 1);
+
+            let rows: Vec<AuditRow> = client.query_all_records(soql).await.unwrap();
+            assert_eq!(rows.len(), 2);
+            assert_eq!(
+                rows[1],
+                AuditRow {
+                    id: "001B".to_string(),
+                    is_deleted: true
+                }
+            );
+        }
+
+        live.assert_async().await;
+        all.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_paginated_include_deleted() {
+        let soql = "SELECT Id FROM Task WHERE IsArchived = true";
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("GET", "/services/data/v57.0/queryAll")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            .with_body(
+                r#"{"totalSize":2,"done":false,"nextRecordsUrl":"/services/data/v57.0/queryAll/01gA-2000","records":[{"Id":"00TA"}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let next = server
+            .mock("GET", "/services/data/v57.0/queryAll/01gA-2000")
+            .with_body(r#"{"totalSize":2,"done":true,"records":[{"Id":"00TB"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let mut pages = client
+            .query_paginated_with::<serde_json::Value>(soql, &QueryOptions::new().include_deleted())
+            .await
+            .unwrap();
+
+        while pages.next().await.unwrap().is_some() {}
+
+        first.assert_async().await;
+        next.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_explain() {
+        let soql = "SELECT Id FROM Account WHERE Industry = 'Energy'";
+        let mut server = mockito::Server::new_async().await;
+        let plan = server
+            .mock("GET", "/services/data/v57.0/query/")
+            .match_query(mockito::Matcher::UrlEncoded("explain".into(), soql.into()))
+            .with_body_from_file(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/explain_plan.json"
+            ))
+            .expect(2)
+            .create_async()
+            .await;
+        let report = server
+            .mock("GET", "/services/data/v57.0/query/")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "explain".into(),
+                "00OA0000000aBcD".into(),
+            ))
+            .with_body(
+                r#"{"plans":[{"cardinality":12,"fields":[],"leadingOperationType":"Other","notes":[],"relativeCost":0.5,"sobjectCardinality":40,"sobjectType":"Opportunity"}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        // Caching is on, but plans always come from the server
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        for _ in 0..2 {
+            let explain = client.explain(soql).await.unwrap();
+            assert_eq!(explain.plans.len(), 2);
+            assert_eq!(explain.best().unwrap().leading_operation_type, "Index");
+        }
+
+        let explain = client.explain("00OA0000000aBcD").await.unwrap();
+        assert_eq!(explain.plans[0].sobject_type, "Opportunity");
+
+        plan.assert_async().await;
+        report.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_execute_builder_shares_cache() {
+        let builder = QueryBuilder::select(&["Id", "Name"])
+            .from("Account")
+            .where_clause("Industry = 'Energy'")
+            .limit(2);
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "q".into(),
+                "SELECT Id, Name FROM Account WHERE Industry = 'Energy' LIMIT 2".into(),
+            ))
+            .with_body(
+                r#"{"totalSize":2,"done":true,"records":[{"Id":"001A","Name":"Acme"},{"Id":"001B","Name":"Globex"}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        let executed: Vec<serde_json::Value> = client.execute(builder.clone()).await.unwrap();
+        let queried: Vec<serde_json::Value> = client.query(builder.build()).await.unwrap();
+
+        assert_eq!(executed, queried);
+        assert_eq!(executed[1]["Name"], "Globex");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_run_typed_query_is_cached() {
+        #[derive(Debug, Clone, PartialEq, serde::Deserialize, Serialize)]
+        struct Account {
+            #[serde(rename = "Id")]
+            id: String,
+        }
+
+        let query: Query<Account> = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .limit(1)
+            .try_build_typed()
+            .unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "q".into(),
+                "SELECT Id FROM Account LIMIT 1".into(),
+            ))
+            .with_body(r#"{"totalSize":1,"done":true,"records":[{"Id":"001A"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        let first = client.run(&query).await.unwrap();
+        let second = client.run(&query.clone()).await.unwrap();
+
+        assert_eq!(first, second);
+        assert_eq!(first[0].id, "001A");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_map_duplicates() {
+        #[derive(Debug, serde::Deserialize)]
+        struct Account {
+            #[serde(rename = "Id")]
+            id: String,
+            #[serde(rename = "Industry")]
+            industry: String,
+        }
+
+        impl HasId for Account {
+            fn id(&self) -> &str {
+                &self.id
+            }
+        }
+
+        let soql = "SELECT Id, Industry FROM Account";
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            .with_body(
+                r#"{"totalSize":3,"done":false,"nextRecordsUrl":"/services/data/v57.0/query/01gA-2","records":[{"Id":"001A","Industry":"Energy"},{"Id":"001B","Industry":"Media"}]}"#,
+            )
+            .expect(3)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/services/data/v57.0/query/01gA-2")
+            .with_body(
+                r#"{"totalSize":3,"done":true,"records":[{"Id":"001C","Industry":"Energy"}]}"#,
+            )
+            .expect(3)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+
+        let by_id = client.query_by_id::<Account>(soql).await.unwrap();
+        assert_eq!(by_id.len(), 3);
+        assert_eq!(by_id["001B"].industry, "Media");
+
+        let by_industry = client
+            .query_map(soql, |a: &Account| a.industry.clone())
+            .await
+            .unwrap();
+        assert_eq!(by_industry.len(), 2);
+        assert_eq!(by_industry["Energy"].id, "001C");
+
+        let result = client
+            .query_map_with(soql, |a: &Account| a.industry.clone(), DuplicateKeys::Error)
+            .await;
+        assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_oversized_query_fails_locally() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", mockito::Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let ids: Vec<String> = (0..1200).map(|i| format!("001{:015}", i)).collect();
+        let soql = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .where_cond(Condition::in_ids("Id", &ids))
+            .build();
+
+        let client = static_client(&server);
+        let expected = format!(
+            "query too long ({} chars); consider query_in_chunks",
+            encoded_query_length(&soql)
+        );
+
+        match client.query::<serde_json::Value>(&soql).await {
+            Err(SfError::InvalidQuery(message)) => assert_eq!(message, expected),
+            other => panic!("expected InvalidQuery, got {:?}", other),
+        }
+        assert!(matches!(
+            client.query_paginated::<serde_json::Value>(&soql).await,
+            Err(SfError::InvalidQuery(_))
+        ));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_uri_too_long_response() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::Any)
+            .with_status(414)
+            .with_header("content-type", "text/html")
+            .with_body("<html><body>414 Request-URI Too Large</body></html>")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::disabled())
+            .with_retry(RetryConfig::no_retry())
+            .with_max_query_length(usize::MAX);
+        let client = SalesforceClient::new(config);
+
+        let result = client
+            .query::<serde_json::Value>("SELECT Id FROM Account")
+            .await;
+        match result {
+            Err(SfError::InvalidQuery(message)) => {
+                assert!(message.starts_with("query too long"), "{}", message)
+            }
+            other => panic!("expected InvalidQuery, got {:?}", other),
+        }
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_pages_by_id() {
+        let mut server = mockito::Server::new_async().await;
+        let pages = [
+            (
+                "SELECT Id, Name FROM Account WHERE Industry = 'Tech' ORDER BY Id LIMIT 2",
+                r#"[{"Id":"001A","Name":"a"},{"Id":"001B","Name":"b"}]"#,
+            ),
+            (
+                "SELECT Id, Name FROM Account WHERE Industry = 'Tech' AND Id > '001B' ORDER BY Id LIMIT 2",
+                r#"[{"Id":"001C","Name":"c"},{"Id":"001D","Name":"d"}]"#,
+            ),
+            (
+                "SELECT Id, Name FROM Account WHERE Industry = 'Tech' AND Id > '001D' ORDER BY Id LIMIT 2",
+                r#"[{"Id":"001E","Name":"e"}]"#,
+            ),
+        ];
+        let mut mocks = Vec::new();
+        for (soql, records) in pages {
+            mocks.push(
+                server
+                    .mock("GET", "/services/data/v57.0/query")
+                    .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+                    .with_body(format!(
+                        r#"{{"totalSize":0,"done":true,"records":{}}}"#,
+                        records
+                    ))
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        #[derive(serde::Deserialize)]
+        struct Account {
+            #[serde(rename = "Id")]
+            id: String,
+        }
+
+        let client = static_client(&server);
+        let builder = QueryBuilder::select(&["Id", "Name"])
+            .from("Account")
+            .where_cond(Condition::eq("Industry", "Tech"));
+        let mut keyset = client.query_pages_by_id::<Account>(builder, 2).unwrap();
+        assert_eq!(keyset.next_soql().unwrap(), pages[0].0);
+
+        let mut batches = Vec::new();
+        while let Some(batch) = keyset.next().await.unwrap() {
+            batches.push(batch.into_iter().map(|a| a.id).collect::<Vec<_>>());
+        }
+        assert_eq!(
+            batches,
+            vec![vec!["001A", "001B"], vec!["001C", "001D"], vec!["001E"],]
+        );
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_query_pages_by_id_with_or_clause() {
+        let mut server = mockito::Server::new_async().await;
+        let pages = [
+            (
+                "SELECT Id FROM Account WHERE Industry = 'Tech' OR Industry = 'Media' ORDER BY Id LIMIT 2",
+                r#"[{"Id":"001A"},{"Id":"001B"}]"#,
+            ),
+            (
+                "SELECT Id FROM Account WHERE (Industry = 'Tech' OR Industry = 'Media') AND Id > '001B' ORDER BY Id LIMIT 2",
+                r#"[{"Id":"001C"},{"Id":"001D"}]"#,
+            ),
+            (
+                "SELECT Id FROM Account WHERE (Industry = 'Tech' OR Industry = 'Media') AND Id > '001D' ORDER BY Id LIMIT 2",
+                r#"[]"#,
+            ),
+        ];
+        let mut mocks = Vec::new();
+        for (soql, records) in pages {
+            mocks.push(
+                server
+                    .mock("GET", "/services/data/v57.0/query")
+                    .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+                    .with_body(format!(
+                        r#"{{"totalSize":0,"done":true,"records":{}}}"#,
+                        records
+                    ))
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let client = static_client(&server);
+        let builder = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .where_clause("Industry = 'Tech' OR Industry = 'Media'");
+        let keyset = client
+            .query_pages_by_id::<serde_json::Value>(builder, 2)
+            .unwrap();
+
+        let records = keyset.collect_all().await.unwrap();
+        assert_eq!(task_ids(&records), ["001A", "001B", "001C", "001D"]);
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[test]
+    fn test_query_pages_by_id_validation() {
+        let client = SalesforceClient::new(ClientConfig::new("https://example.com", "token"));
+        let message = |builder, page_size| match client
+            .query_pages_by_id::<serde_json::Value>(builder, page_size)
+        {
+            Err(SfError::InvalidQuery(message)) => message,
+            Err(e) => panic!("expected InvalidQuery, got {:?}", e),
+            Ok(_) => panic!("expected InvalidQuery"),
+        };
+
+        assert_eq!(
+            message(QueryBuilder::select(&["Name"]).from("Account"), 100),
+            "Paging by ID requires Id in the SELECT list"
+        );
+        assert_eq!(
+            message(
+                QueryBuilder::select(&["Id"])
+                    .from("Account")
+                    .order_by("Name"),
+                100
+            ),
+            "Paging by ID orders by Id and cannot also ORDER BY Name"
+        );
+        assert!(message(
+            QueryBuilder::select(&["Id"]).from("Account").offset(10),
+            100
+        )
+        .contains("LIMIT or OFFSET"));
+        assert_eq!(
+            message(QueryBuilder::select(&["Id"]).from("Account"), 0),
+            "Page size 0 must be between 1 and 2000"
+        );
+        assert!(client
+            .query_pages_by_id::<serde_json::Value>(
+                QueryBuilder::select(&["id"])
+                    .from("Account")
+                    .order_by_asc("Id"),
+                2000
+            )
+            .is_ok());
+
+        // FIELDS(ALL) is checked with the page size as its LIMIT
+        assert_eq!(
+            message(
+                QueryBuilder::select_fields(FieldsSpec::All).from("Account"),
+                500
+            ),
+            "Paging by ID with FIELDS(ALL) requires a page size of at most 200"
+        );
+        let pages = client
+            .query_pages_by_id::<serde_json::Value>(
+                QueryBuilder::select_fields(FieldsSpec::All).from("Account"),
+                200,
+            )
+            .unwrap();
+        assert_eq!(
+            pages.next_soql().unwrap(),
+            "SELECT FIELDS(ALL) FROM Account ORDER BY Id LIMIT 200"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_invalid_builder() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", mockito::Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let result = client
+            .execute::<serde_json::Value>(QueryBuilder::select(&["Id"]).from("Account").limit(0))
+            .await;
+
+        assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_aggregate() {
+        let soql =
+            "SELECT Industry, COUNT(Id) cnt, SUM(AnnualRevenue) FROM Account GROUP BY Industry";
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            .with_body(
+                r#"{"totalSize":2,"done":true,"records":[
+                    {"attributes":{"type":"AggregateResult"},"Industry":"Energy","cnt":12,"expr0":4.2E7},
+                    {"attributes":{"type":"AggregateResult"},"Industry":null,"cnt":3,"expr0":null}
+                ]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let rows = client.query_aggregate(soql).await.unwrap();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].get_str("Industry"), Some("Energy"));
+        assert_eq!(rows[0].get_i64("cnt"), Some(12));
+        assert_eq!(rows[0].expr_f64(0), Some(4.2e7));
+        assert!(rows[1].is_null("Industry"));
+        assert_eq!(rows[1].get_i64("cnt"), Some(3));
+
+        mock.assert_async().await;
+    }
+
+    #[test]
+    fn test_api_version_validation() {
+        let config = ClientConfig::new("https://example.com", "token");
+        assert_eq!(config.api_version, DEFAULT_API_VERSION);
+        assert_eq!(config.api_url(), "https://example.com/services/data/v57.0");
+
+        let config = config.with_api_version("61.0").unwrap();
+        assert_eq!(config.api_version, "v61.0");
+
+        for invalid in ["", "v61", "61", "v61.10", "vv61.0", "v6a.0", "latest"] {
+            let err = ClientConfig::new("https://example.com", "token")
+                .with_api_version(invalid)
+                .unwrap_err();
+            assert!(matches!(err, SfError::Config(_)), "{}", invalid);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_api_version_urls() {
+        let mut server = mockito::Server::new_async().await;
+        let query = server
+            .mock("GET", "/services/data/v61.0/query")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "q".into(),
+                "SELECT Id FROM Account".into(),
+            ))
+            .with_body(
+                r#"{"totalSize":2,"done":false,"nextRecordsUrl":"/services/data/v61.0/query/01gA-1","records":[{"Id":"001A"}]}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        let next = server
+            .mock("GET", "/services/data/v61.0/query/01gA-1")
+            .with_body(r#"{"totalSize":2,"done":true,"records":[{"Id":"001B"}]}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let insert = server
+            .mock("POST", "/services/data/v61.0/sobjects/Account")
+            .with_status(201)
+            .with_body(r#"{"id":"001NEW","success":true,"errors":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let upsert = server
+            .mock(
+                "PATCH",
+                "/services/data/v61.0/sobjects/Account/External_Id__c/EXT-1",
+            )
+            .with_body(r#"{"id":"001A","success":true,"errors":[],"created":false}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let describe = server
+            .mock("GET", "/services/data/v61.0/sobjects/")
+            .with_body(r#"{"encoding":"UTF-8","maxBatchSize":200,"sobjects":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::disabled())
+            .with_retry(RetryConfig::no_retry())
+            .with_api_version("v61.0")
+            .unwrap();
+        let client = SalesforceClient::new(config);
+
+        let records: Vec<serde_json::Value> = client.query("SELECT Id FROM Account").await.unwrap();
+        assert_eq!(records.len(), 2);
+        let stream = client.query_stream::<serde_json::Value>("SELECT Id FROM Account");
+        futures::pin_mut!(stream);
+        while stream.try_next().await.unwrap().is_some() {}
+
+        client
+            .insert("Account", &serde_json::json!({"Name": "Acme"}))
+            .await
+            .unwrap();
+        client
+            .upsert(
+                "Account",
+                UpsertBuilder::new("External_Id__c", "EXT-1"),
+                &serde_json::json!({"Name": "Acme"}),
+            )
+            .await
+            .unwrap();
+        client.describe_global().await.unwrap();
+
+        let request =
+            client
+                .composite_request()
+                .create("newAccount", "Account", serde_json::json!({}));
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["compositeRequest"][0]["url"],
+            "/services/data/v61.0/sobjects/Account"
+        );
+
+        query.assert_async().await;
+        next.assert_async().await;
+        insert.assert_async().await;
+        upsert.assert_async().await;
+        describe.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_count_field() {
+        let mut server = mockito::Server::new_async().await;
+        let counted = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "q".into(),
+                "SELECT COUNT(Email) FROM Contact".into(),
+            ))
+            .with_body(
+                r#"{"totalSize":1,"done":true,"records":[{"attributes":{"type":"AggregateResult"},"expr0":87}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let empty = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "q".into(),
+                "SELECT COUNT(Email) FROM Contact WHERE Name = 'Nobody'".into(),
+            ))
+            .with_body(r#"{"totalSize":0,"done":true,"records":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+
+        let count = client
+            .count(CountQueryBuilder::count_from("Contact").count_field("Email"))
+            .await
+            .unwrap();
+        assert_eq!(count, 87);
+
+        let count = client
+            .count(
+                CountQueryBuilder::count_from("Contact")
+                    .count_field("Email")
+                    .where_clause("Name = 'Nobody'"),
+            )
+            .await
+            .unwrap();
+        assert_eq!(count, 0);
+
+        counted.assert_async().await;
+        empty.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_count() {
+        let mut server = mockito::Server::new_async().await;
+        let count = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "q".into(),
+                "SELECT COUNT() FROM Account WHERE Industry = 'Energy'".into(),
+            ))
+            .with_body_from_file(concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/tests/fixtures/count_query.json"
+            ))
+            .expect(2)
+            .create_async()
+            .await;
+        let records = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "q".into(),
+                "SELECT Id FROM Account".into(),
+            ))
+            .with_body(r#"{"totalSize":1,"done":true,"records":[{"Id":"001A"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+
+        assert_eq!(
+            client
+                .query_count("SELECT COUNT() FROM Account WHERE Industry = 'Energy'")
+                .await
+                .unwrap(),
+            1532
+        );
+        assert_eq!(
+            client
+                .count(CountQueryBuilder::count_from("Account").where_clause("Industry = 'Energy'"))
+                .await
+                .unwrap(),
+            1532
+        );
+
+        let err = client
+            .query_count("SELECT Id FROM Account")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::InvalidQuery(_)));
+
+        count.assert_async().await;
+        records.assert_async().await;
+    }
+
+    fn rollback_fixture() -> String {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/collection_rollback.json"
+        );
+        std::fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn test_batch_outcome_from_rollback() {
+        let results: Vec<SaveResult> = serde_json::from_str(&rollback_fixture()).unwrap();
+        let outcome = BatchOutcome::from_results(&results);
+
+        assert_eq!(outcome.succeeded, 0);
+        assert_eq!(outcome.rolled_back, 2);
+        assert_eq!(outcome.failed_indices, vec![1]);
+        assert_eq!(outcome.failed_index(), Some(1));
+        assert!(!outcome.is_success());
+        assert!(results[0].is_rolled_back() && !results[1].is_rolled_back());
+
+        let saved: Vec<SaveResult> =
+            serde_json::from_str(r#"[{"id":"a01A","success":true,"errors":[]}]"#).unwrap();
+        let outcome = BatchOutcome::from_results(&saved);
+        assert_eq!(outcome.succeeded, 1);
+        assert!(outcome.is_success() && outcome.failed_index().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_update_many_all_or_none() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("PATCH", "/services/data/v57.0/composite/sobjects")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"allOrNone":true,"records":[{"attributes":{"type":"Payment__c"},"Id":"a01A"}]}"#
+                    .to_string(),
+            ))
+            .with_body(rollback_fixture())
+            .expect(1)
+            .create_async()
+            .await;
+
+        let payments = vec![
+            serde_json::json!({"Id": "a01A", "Amount__c": 10}),
+            serde_json::json!({"Id": "a01B", "Amount__c": -5}),
+            serde_json::json!({"Id": "a01C", "Amount__c": 7}),
+        ];
+        let client = static_client(&server);
+        let results = client
+            .update_many("Payment__c", &payments, true)
+            .await
+            .unwrap();
+
+        let outcome = BatchOutcome::from_results(&results);
+        assert_eq!(outcome.failed_index(), Some(1));
+        assert_eq!(outcome.rolled_back, 2);
+        assert_eq!(results[1].errors[0].fields, vec!["Amount__c"]);
+        mock.assert_async().await;
+
+        // Rows without an Id are rejected before anything is sent
+        let err = client
+            .update_many("Payment__c", &[serde_json::json!({"Amount__c": 1})], true)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("record 0 is missing Id"));
+    }
+
+    /// Hands out a new token on every call, like a store rotated externally
+    struct RotatingToken(std::sync::atomic::AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl auth::TokenProvider for RotatingToken {
+        async fn bearer(&self) -> SfResult<String> {
+            let n = self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(format!("token-{}", n))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_crud_requests_use_current_token() {
+        let mut server = mockito::Server::new_async().await;
+        let insert = server
+            .mock("POST", "/services/data/v57.0/sobjects/Account")
+            .match_header("authorization", "Bearer token-0")
+            .with_status(201)
+            .with_body(r#"{"id":"001A","success":true,"errors":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let update = server
+            .mock("PATCH", "/services/data/v57.0/sobjects/Account/001A")
+            .match_header("authorization", "Bearer token-1")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+        let delete = server
+            .mock("DELETE", "/services/data/v57.0/sobjects/Account/001A")
+            .match_header("authorization", "Bearer token-2")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "unused")
+            .with_cache(CacheConfig::disabled())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::with_token_provider(
+            config,
+            Arc::new(RotatingToken(std::sync::atomic::AtomicUsize::new(0))),
+        );
+
+        let account = serde_json::json!({"Name": "Acme"});
+        let created = client.insert("Account", &account).await.unwrap();
+        client
+            .update("Account", &created.id, &account)
+            .await
+            .unwrap();
+        client.delete("Account", &created.id).await.unwrap();
+
+        insert.assert_async().await;
+        update.assert_async().await;
+        delete.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_exists() {
+        let mut server = mockito::Server::new_async().await;
+        let found = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/001A")
+            .match_query(mockito::Matcher::UrlEncoded("fields".into(), "Id".into()))
+            .with_body(r#"{"attributes":{"type":"Account"},"Id":"001A"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let missing = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/001B")
+            .match_query(mockito::Matcher::Any)
+            .with_status(404)
+            .with_body(
+                r#"[{"errorCode":"NOT_FOUND","message":"The requested resource does not exist"}]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let forbidden = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/001C")
+            .match_query(mockito::Matcher::Any)
+            .with_status(403)
+            .with_body(r#"[{"errorCode":"INSUFFICIENT_ACCESS","message":"insufficient access rights on object id"}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("DELETE", "/services/data/v57.0/sobjects/Account/001A")
+            .with_status(204)
+            .create_async()
+            .await;
+
+        // Caching enabled, so repeat checks are answered locally
+        let config =
+            ClientConfig::new(server.url(), "test_token").with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        assert!(client.exists("Account", "001A").await.unwrap());
+        assert!(client.exists("Account", "001A").await.unwrap());
+        assert!(!client.exists("Account", "001B").await.unwrap());
+        assert!(!client.exists("Account", "001B").await.unwrap());
+
+        let err = client.exists("Account", "001C").await.unwrap_err();
+        assert!(matches!(err, SfError::Api { status: 403, .. }));
+
+        // Deleting clears the cached answer
+        client.delete("Account", "001A").await.unwrap();
+        assert!(client.exists("Account", "001A").await.unwrap());
+
+        found.assert_async().await;
+        missing.assert_async().await;
+        forbidden.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_invalidates_only_queries_on_that_sobject() {
+        let mut server = mockito::Server::new_async().await;
+        let soql = "SELECT Id FROM Account";
+        let query = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            .with_body(r#"{"totalSize":1,"done":true,"records":[{"Id":"001A"}]}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        server
+            .mock(
+                "PATCH",
+                mockito::Matcher::Regex("^/services/data/v57.0/sobjects/".into()),
+            )
+            .with_status(204)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+        let data = serde_json::json!({"Name": "Changed"});
+
+        let _: Vec<serde_json::Value> = client.query(soql).await.unwrap();
+
+        // A Contact update leaves the Account query cached
+        client.update("Contact", "003A", &data).await.unwrap();
+        let _: Vec<serde_json::Value> = client.query(soql).await.unwrap();
+
+        // An Account update drops it
+        client.update("Account", "001A", &data).await.unwrap();
+        let _: Vec<serde_json::Value> = client.query(soql).await.unwrap();
+
+        query.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_with_policy() {
+        let mut server = mockito::Server::new_async().await;
+        let soql = "SELECT Id FROM Account";
+        let query = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), soql.into()))
+            .with_body(r#"{"totalSize":1,"done":true,"records":[{"Id":"001A"}]}"#)
+            .expect(4)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+        let query_with = |policy| client.query_with_policy::<serde_json::Value>(soql, policy);
+
+        // CacheOnly never calls Salesforce
+        let err = query_with(CachePolicy::CacheOnly).await.unwrap_err();
+        assert!(matches!(err, SfError::NotCached));
+
+        // Bypass doesn't populate the cache
+        query_with(CachePolicy::Bypass).await.unwrap();
+        let err = query_with(CachePolicy::CacheOnly).await.unwrap_err();
+        assert!(matches!(err, SfError::NotCached));
+
+        // Refresh does, so later reads are served from it
+        query_with(CachePolicy::Refresh).await.unwrap();
+        let cached = query_with(CachePolicy::CacheOnly).await.unwrap();
+        assert_eq!(cached[0]["Id"], "001A");
+        query_with(CachePolicy::Default).await.unwrap();
+
+        // Refresh and Bypass always query, even with a cached result
+        query_with(CachePolicy::Refresh).await.unwrap();
+        query_with(CachePolicy::Bypass).await.unwrap();
+
+        query.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_query_with_skip_empty_policy() {
+        let mut server = mockito::Server::new_async().await;
+        let empty_soql = "SELECT Id FROM Task WHERE Status = 'New'";
+        let one_soql = "SELECT Id FROM Task LIMIT 1";
+        let empty = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), empty_soql.into()))
+            .with_body(r#"{"totalSize":0,"done":true,"records":[]}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let one = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::UrlEncoded("q".into(), one_soql.into()))
+            .with_body(r#"{"totalSize":1,"done":true,"records":[{"Id":"00T1"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        for soql in [empty_soql, empty_soql, one_soql, one_soql] {
+            client
+                .query_with_policy::<serde_json::Value>(soql, CachePolicy::SkipEmpty)
+                .await
+                .unwrap();
+        }
+
+        empty.assert_async().await;
+        one.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_stale_while_revalidate() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        let mut server = mockito::Server::new_async().await;
+        let soql = "SELECT Id, Name FROM Account";
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let query = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::Any)
+            .with_body_from_request(move |_| {
+                // The second response is broken, so that refresh fails
+                match counter.fetch_add(1, Ordering::SeqCst) + 1 {
+                    2 => b"not json".to_vec(),
+                    call => format!(
+                        r#"{{"totalSize":1,"done":true,"records":[{{"Id":"001A","Name":"v{}"}}]}}"#,
+                        call
+                    )
+                    .into_bytes(),
+                }
+            })
+            .expect(3)
+            .create_async()
+            .await;
+
+        let cache = CacheConfig::new()
+            .ttl(Duration::from_secs(60))
+            .stale_while_revalidate(Duration::from_millis(200));
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(cache)
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+        let name = |records: Vec<serde_json::Value>| records[0]["Name"].clone();
+
+        assert_eq!(name(client.query(soql).await.unwrap()), "v1");
+
+        // Stale reads return at once; only one refresh starts, and it fails
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(name(client.query(soql).await.unwrap()), "v1");
+        assert_eq!(name(client.query(soql).await.unwrap()), "v1");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // The entry is still stale, so the next read refreshes it again
+        assert_eq!(name(client.query(soql).await.unwrap()), "v1");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(name(client.query(soql).await.unwrap()), "v3");
+
+        query.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_many_invalidates_cached_get() {
+        let mut server = mockito::Server::new_async().await;
+        let get = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/001A")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"attributes":{"type":"Account"},"Id":"001A","Name":"Acme"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let update = server
+            .mock("PATCH", "/services/data/v57.0/composite/sobjects")
+            .with_body(r#"[{"id":"001A","success":true,"errors":[]}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        let _: serde_json::Value = client.get("Account", "001A").await.unwrap();
+        let _: serde_json::Value = client.get("Account", "001A").await.unwrap();
+        client
+            .update_many(
+                "Account",
+                &[serde_json::json!({"Id": "001A", "Name": "Acme Corp"})],
+                false,
+            )
+            .await
+            .unwrap();
+        let _: serde_json::Value = client.get("Account", "001A").await.unwrap();
+
+        get.assert_async().await;
+        update.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_many_failure_invalidates_cache() {
+        let mut server = mockito::Server::new_async().await;
+        let get = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/001A")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"attributes":{"type":"Account"},"Id":"001A","Name":"Acme"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let first = server
+            .mock("PATCH", "/services/data/v57.0/composite/sobjects")
+            .match_body(mockito::Matcher::Regex(r#""001A""#.to_string()))
+            .with_body_from_request(|request| {
+                let body: serde_json::Value =
+                    serde_json::from_slice(request.body().unwrap()).unwrap();
+                let results: Vec<_> = body["records"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|record| serde_json::json!({"id": record["Id"], "success": true, "errors": []}))
+                    .collect();
+                serde_json::to_vec(&results).unwrap()
+            })
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("PATCH", "/services/data/v57.0/composite/sobjects")
+            .match_body(mockito::Matcher::Regex(r#""001LAST""#.to_string()))
+            .with_status(500)
+            .with_body(r#"[{"errorCode":"UNKNOWN_EXCEPTION","message":"boom"}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        let mut records: Vec<_> = (0..200)
+            .map(|i| serde_json::json!({"Id": if i == 0 { "001A".to_string() } else { format!("001X{}", i) }}))
+            .collect();
+        records.push(serde_json::json!({"Id": "001LAST"}));
+
+        let _: serde_json::Value = client.get("Account", "001A").await.unwrap();
+        let err = client
+            .update_many("Account", &records, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::Api { status: 500, .. }));
+
+        // The first chunk was committed, so the cached record is gone
+        let _: serde_json::Value = client.get("Account", "001A").await.unwrap();
+
+        get.assert_async().await;
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_stale_while_revalidate_skip_empty() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Duration;
+
+        let mut server = mockito::Server::new_async().await;
+        let soql = "SELECT Id FROM Task WHERE Status = 'New'";
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let query = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::Any)
+            .with_body_from_request(move |_| {
+                // Only the first poll finds work
+                if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                    br#"{"totalSize":1,"done":true,"records":[{"Id":"00T1"}]}"#.to_vec()
+                } else {
+                    br#"{"totalSize":0,"done":true,"records":[]}"#.to_vec()
+                }
+            })
+            .expect(3)
+            .create_async()
+            .await;
+
+        let cache = CacheConfig::new()
+            .ttl(Duration::from_secs(60))
+            .stale_while_revalidate(Duration::from_millis(200));
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(cache)
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+        let poll = || client.query_with_policy::<serde_json::Value>(soql, CachePolicy::SkipEmpty);
+
+        assert_eq!(poll().await.unwrap().len(), 1);
+
+        // The stale read starts a refresh; its empty result isn't cached,
+        // and the outdated entry is dropped
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(poll().await.unwrap().len(), 1);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+        assert!(poll().await.unwrap().is_empty());
+
+        query.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_uses_record_cache() {
+        let mut server = mockito::Server::new_async().await;
+        let get = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/001A")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"attributes":{"type":"Account"},"Id":"001A","Name":"Acme"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let update = server
+            .mock("PATCH", "/services/data/v57.0/sobjects/Account/001A")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::disabled())
+            .with_record_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        // The second get is served from the cache
+        let account: serde_json::Value = client.get("Account", "001A").await.unwrap();
+        assert_eq!(account["Name"], "Acme");
+        let account: serde_json::Value = client.get("Account", "001A").await.unwrap();
+        assert_eq!(account["Name"], "Acme");
+
+        // Updating the record drops its cache entry
+        client
+            .update("Account", "001A", &serde_json::json!({"Name": "Acme Corp"}))
+            .await
+            .unwrap();
+        let _: serde_json::Value = client.get("Account", "001A").await.unwrap();
+
+        get.assert_async().await;
+        update.assert_async().await;
+
+        let stats = client.record_cache_stats();
+        assert_eq!((stats.hits, stats.misses, stats.inserts), (1, 2, 2));
+        assert_eq!(stats.invalidations, 1);
+
+        client.reset_cache_stats();
+        assert_eq!(client.record_cache_stats().hits, 0);
+        assert_eq!(client.cache_stats().hit_ratio(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_get_by_external_id() {
+        let mut server = mockito::Server::new_async().await;
+        let found = server
+            .mock(
+                "GET",
+                "/services/data/v57.0/sobjects/Account/External_Id__c/EXT%2F1",
+            )
+            .with_body(r#"{"attributes":{"type":"Account"},"Id":"001xx0000001","Name":"Acme"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let missing = server
+            .mock(
+                "GET",
+                "/services/data/v57.0/sobjects/Account/External_Id__c/EXT-404",
+            )
+            .with_status(404)
+            .expect(1)
+            .create_async()
+            .await;
+        let ambiguous = server
+            .mock(
+                "GET",
+                "/services/data/v57.0/sobjects/Account/External_Id__c/EXT-DUP",
+            )
+            .with_status(300)
+            .with_body(
+                r#"["/services/data/v57.0/sobjects/Account/001xx0000001","/services/data/v57.0/sobjects/Account/001xx0000002"]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let account: serde_json::Value = client
+            .get_by_external_id("Account", "External_Id__c", "EXT/1")
+            .await
+            .unwrap();
+        assert_eq!(account["Id"], "001xx0000001");
+
+        let err = client
+            .get_by_external_id::<serde_json::Value>("Account", "External_Id__c", "EXT-404")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::NotFound { ref id, .. } if id == "External_Id__c=EXT-404"));
+
+        let err = client
+            .get_by_external_id::<serde_json::Value>("Account", "External_Id__c", "EXT-DUP")
+            .await
+            .unwrap_err();
+        match err {
+            SfError::MultipleMatches {
+                external_id,
+                records,
+                ..
+            } => {
+                assert_eq!(external_id, "External_Id__c=EXT-DUP");
+                assert_eq!(records.len(), 2);
+                assert!(records[1].ends_with("/001xx0000002"));
+            }
+            other => panic!("expected MultipleMatches, got {:?}", other),
+        }
+
+        found.assert_async().await;
+        missing.assert_async().await;
+        ambiguous.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_by_external_id() {
+        let mut server = mockito::Server::new_async().await;
+        let deleted = server
+            .mock(
+                "DELETE",
+                "/services/data/v57.0/sobjects/Account/External_Id__c/INV%2F2024%2B01",
+            )
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+        let missing = server
+            .mock(
+                "DELETE",
+                "/services/data/v57.0/sobjects/Account/External_Id__c/EXT-404",
+            )
+            .with_status(404)
+            .with_body(r#"[{"errorCode":"NOT_FOUND","message":"Provided external ID field does not exist or is not accessible: EXT-404"}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        client
+            .delete_by_external_id("Account", "External_Id__c", "INV/2024+01")
+            .await
+            .unwrap();
+
+        let err = client
+            .delete_by_external_id("Account", "External_Id__c", "EXT-404")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::NotFound { .. }));
+        assert!(err.to_string().contains("External_Id__c=EXT-404"));
+
+        deleted.assert_async().await;
+        missing.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_many_url_and_chunking() {
+        let ids: Vec<String> = (0..250).map(|i| format!("003xx{:05}", i)).collect();
+        let id_refs: Vec<&str> = ids.iter().map(String::as_str).collect();
+
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = Vec::new();
+        for chunk in id_refs.chunks(200) {
+            let body: Vec<_> = chunk
+                .iter()
+                .map(|id| serde_json::json!({"id": id, "success": true, "errors": []}))
+                .collect();
+            mocks.push(
+                server
+                    .mock("DELETE", "/services/data/v57.0/composite/sobjects")
+                    .match_query(mockito::Matcher::AllOf(vec![
+                        mockito::Matcher::UrlEncoded("ids".into(), chunk.join(",")),
+                        mockito::Matcher::UrlEncoded("allOrNone".into(), "true".into()),
+                    ]))
+                    .with_body(serde_json::to_string(&body).unwrap())
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let results = static_client(&server)
+            .delete_many("Contact", &id_refs, true)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 250);
+        assert!(results.iter().all(|r| r.success));
+        assert_eq!(results[249].id.as_deref(), Some("003xx00249"));
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_many_failure_invalidates_cache() {
+        let ids: Vec<String> = (0..201).map(|i| format!("003xx{:05}", i)).collect();
+        let id_refs: Vec<&str> = ids.iter().map(String::as_str).collect();
+
+        let mut server = mockito::Server::new_async().await;
+        let get = server
+            .mock("GET", "/services/data/v57.0/sobjects/Contact/003xx00000")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"attributes":{"type":"Contact"},"Id":"003xx00000"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let missing = server
+            .mock("GET", "/services/data/v57.0/sobjects/Contact/003xx00000")
+            .match_query(mockito::Matcher::Any)
+            .with_status(404)
+            .with_body(
+                r#"[{"errorCode":"NOT_FOUND","message":"The requested resource does not exist"}]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let rows: Vec<_> = id_refs[..200].iter().map(|id| (*id, None)).collect();
+        let first = server
+            .mock("DELETE", "/services/data/v57.0/composite/sobjects")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "ids".into(),
+                id_refs[..200].join(","),
+            ))
+            .with_body(delete_results(&rows))
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("DELETE", "/services/data/v57.0/composite/sobjects")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "ids".into(),
+                "003xx00200".into(),
+            ))
+            .with_status(500)
+            .with_body(r#"[{"errorCode":"UNKNOWN_EXCEPTION","message":"boom"}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        let _: serde_json::Value = client.get("Contact", "003xx00000").await.unwrap();
+        let err = client
+            .delete_many("Contact", &id_refs, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::Api { status: 500, .. }));
+
+        // The first chunk was deleted, so the cached record is gone
+        let err = client
+            .get::<serde_json::Value>("Contact", "003xx00000")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::NotFound { .. }));
+
+        get.assert_async().await;
+        missing.assert_async().await;
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    fn delete_results(rows: &[(&str, Option<&str>)]) -> String {
+        let body: Vec<_> = rows
+            .iter()
+            .map(|(id, code)| match code {
+                None => serde_json::json!({"id": id, "success": true, "errors": []}),
+                Some(code) => serde_json::json!({
+                    "id": id,
+                    "success": false,
+                    "errors": [{"statusCode": code, "message": "failed", "fields": []}]
+                }),
+            })
+            .collect();
+        serde_json::to_string(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_delete_many_resilient() {
+        let mut server = mockito::Server::new_async().await;
+        let ids_are = |ids: &str| {
+            mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("ids".into(), ids.into()),
+                mockito::Matcher::UrlEncoded("allOrNone".into(), "false".into()),
+            ])
+        };
+        let first = server
+            .mock("DELETE", "/services/data/v57.0/composite/sobjects")
+            .match_query(ids_are("00TA,00TB,00TC,00TD"))
+            .with_body(delete_results(&[
+                ("00TA", None),
+                ("00TB", Some("UNABLE_TO_LOCK_ROW")),
+                ("00TC", Some("INSUFFICIENT_ACCESS_OR_READONLY")),
+                ("00TD", Some("UNABLE_TO_LOCK_ROW")),
+            ]))
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("DELETE", "/services/data/v57.0/composite/sobjects")
+            .match_query(ids_are("00TB,00TD"))
+            .with_body(delete_results(&[
+                ("00TB", None),
+                ("00TD", Some("UNABLE_TO_LOCK_ROW")),
+            ]))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let options = ResilientDeleteOptions::new()
+            .max_passes(2)
+            .initial_backoff(std::time::Duration::from_millis(1));
+        let report = static_client(&server)
+            .delete_many_resilient("Task", &["00TA", "00TB", "00TC", "00TD"], &options)
+            .await
+            .unwrap();
+
+        assert_eq!(report.deleted, 2);
+        assert_eq!(report.passes, 2);
+        assert!(!report.is_success());
+
+        // Other errors are never retried; locks are reported after the last pass
+        let failed: Vec<_> = report
+            .failed
+            .iter()
+            .map(|f| (f.id.as_str(), f.error_codes()))
+            .collect();
+        assert_eq!(
+            failed,
+            vec![
+                ("00TC", vec![ErrorCode::InsufficientAccessOrReadonly]),
+                ("00TD", vec![ErrorCode::UnableToLockRow]),
+            ]
+        );
+
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_many_resilient_failed_retry_pass() {
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("DELETE", "/services/data/v57.0/composite/sobjects")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "ids".into(),
+                "00TA,00TB".into(),
+            ))
+            .with_body(delete_results(&[
+                ("00TA", None),
+                ("00TB", Some("UNABLE_TO_LOCK_ROW")),
+            ]))
+            .expect(1)
+            .create_async()
+            .await;
+        let retry = server
+            .mock("DELETE", "/services/data/v57.0/composite/sobjects")
+            .match_query(mockito::Matcher::UrlEncoded("ids".into(), "00TB".into()))
+            .with_status(500)
+            .with_body(r#"[{"errorCode":"UNKNOWN_EXCEPTION","message":"boom"}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        // The hour-long initial backoff is capped
+        let options = ResilientDeleteOptions::new()
+            .initial_backoff(std::time::Duration::from_secs(3600))
+            .max_backoff(std::time::Duration::from_millis(1));
+        let report = static_client(&server)
+            .delete_many_resilient("Task", &["00TA", "00TB"], &options)
+            .await
+            .unwrap();
+
+        assert_eq!(report.deleted, 1);
+        assert_eq!(report.passes, 2);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].id, "00TB");
+        assert_eq!(
+            report.failed[0].error_codes(),
+            vec![ErrorCode::Other("UNKNOWN_EXCEPTION".to_string())]
+        );
+
+        first.assert_async().await;
+        retry.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_many_partial_failure() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("DELETE", "/services/data/v57.0/composite/sobjects")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"[
+                    {"id":"003A","success":true,"errors":[]},
+                    {"success":false,"errors":[{"statusCode":"ENTITY_IS_DELETED","message":"entity is deleted","fields":[]}]},
+                    {"id":"003C","success":false,"errors":[{"statusCode":"INSUFFICIENT_ACCESS_OR_READONLY","message":"insufficient access rights on object id","fields":[]}]}
+                ]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let results = static_client(&server)
+            .delete_many("Contact", &["003A", "003B", "003C"], false)
+            .await
+            .unwrap();
+
+        assert!(results[0].success);
+        assert_eq!(results[1].id.as_deref(), Some("003B"));
+        assert!(results[1].is_already_deleted());
+        assert!(!results[2].is_already_deleted());
+        assert!(results[2].has_error("INSUFFICIENT_ACCESS_OR_READONLY"));
+        mock.assert_async().await;
+    }
+
+    #[derive(serde::Serialize)]
+    struct ExternalAccount {
+        #[serde(rename = "External_Id__c")]
+        external_id: Option<String>,
+        #[serde(rename = "Name")]
+        name: String,
+    }
+
+    fn external_accounts(count: usize) -> Vec<ExternalAccount> {
+        (0..count)
+            .map(|i| ExternalAccount {
+                external_id: Some(format!("EXT-{}", i)),
+                name: format!("Account {}", i),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_upsert_many_created_and_chunking() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock(
+                "PATCH",
+                "/services/data/v57.0/composite/sobjects/Account/External_Id__c",
+            )
+            .with_body_from_request(|request| {
+                let body: serde_json::Value =
+                    serde_json::from_slice(request.body().unwrap()).unwrap();
+                let results: Vec<_> = body["records"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|record| {
+                        assert_eq!(record["attributes"]["type"], "Account");
+                        let ext = record["External_Id__c"].as_str().unwrap();
+                        let n: usize = ext.trim_start_matches("EXT-").parse().unwrap();
+                        // Even rows already exist and are updated
+                        serde_json::json!({"id": format!("001{}", n), "success": true, "errors": [], "created": n % 2 == 1})
+                    })
+                    .collect();
+                serde_json::to_vec(&results).unwrap()
+            })
+            .expect(2)
+            .create_async()
+            .await;
+
+        let results = static_client(&server)
+            .upsert_many("Account", "External_Id__c", &external_accounts(201), false)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 201);
+        assert_eq!(results[0].created, Some(false));
+        assert_eq!(results[1].created, Some(true));
+        assert_eq!(results[200].id.as_deref(), Some("001200"));
+        assert_eq!(
+            results.iter().filter(|r| r.created == Some(true)).count(),
+            100
+        );
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_upsert_many_failure_invalidates_cache() {
+        let mut server = mockito::Server::new_async().await;
+        let path = "/services/data/v57.0/composite/sobjects/Account/External_Id__c";
+        let get = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/001A")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"attributes":{"type":"Account"},"Id":"001A","Name":"Acme"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let first = server
+            .mock("PATCH", path)
+            .match_body(mockito::Matcher::Regex(r#""EXT-0""#.to_string()))
+            .with_body_from_request(|request| {
+                let body: serde_json::Value =
+                    serde_json::from_slice(request.body().unwrap()).unwrap();
+                let results: Vec<_> = body["records"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|_| serde_json::json!({"id": "001A", "success": true, "errors": []}))
+                    .collect();
+                serde_json::to_vec(&results).unwrap()
+            })
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("PATCH", path)
+            .match_body(mockito::Matcher::Regex(r#""EXT-200""#.to_string()))
+            .with_status(500)
+            .with_body(r#"[{"errorCode":"UNKNOWN_EXCEPTION","message":"boom"}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        let _: serde_json::Value = client.get("Account", "001A").await.unwrap();
+        let err = client
+            .upsert_many("Account", "External_Id__c", &external_accounts(201), false)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::Api { status: 500, .. }));
+
+        // The first chunk was committed, so the cached record is gone
+        let _: serde_json::Value = client.get("Account", "001A").await.unwrap();
+
+        get.assert_async().await;
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_upsert_many_rejects_missing_external_id() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock(
+                "PATCH",
+                "/services/data/v57.0/composite/sobjects/Account/External_Id__c",
+            )
+            .expect(0)
+            .create_async()
+            .await;
+
+        // The bad row is in the second chunk; nothing may be sent
+        let mut accounts = external_accounts(250);
+        accounts[230].external_id = None;
+
+        let err = static_client(&server)
+            .upsert_many("Account", "External_Id__c", &accounts, false)
+            .await
+            .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("record 230 is missing external ID field External_Id__c"));
+        mock.assert_async().await;
+    }
+
+    #[cfg(feature = "bulk-api")]
+    #[tokio::test]
+    async fn test_bulk_query_polls_and_pages() {
+        #[derive(Debug, serde::Deserialize)]
+        struct BulkAccount {
+            #[serde(rename = "Id")]
+            id: String,
+            #[serde(rename = "Name")]
+            name: String,
+            #[serde(rename = "AnnualRevenue")]
+            annual_revenue: Option<f64>,
+        }
+
+        let mut server = mockito::Server::new_async().await;
+        let create = server
+            .mock("POST", "/services/data/v57.0/jobs/query")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "operation": "query",
+                "query": "SELECT Id, Name, AnnualRevenue FROM Account"
+            })))
+            .with_body(r#"{"id":"750xx0000001","state":"UploadComplete","object":"Account"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let in_progress = server
+            .mock("GET", "/services/data/v57.0/jobs/query/750xx0000001")
+            .with_body(r#"{"id":"750xx0000001","state":"InProgress","numberRecordsProcessed":0}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let complete = server
+            .mock("GET", "/services/data/v57.0/jobs/query/750xx0000001")
+            .with_body(r#"{"id":"750xx0000001","state":"JobComplete","numberRecordsProcessed":3}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let first_page = server
+            .mock("GET", "/services/data/v57.0/jobs/query/750xx0000001/results")
+            .match_query(mockito::Matcher::Exact("maxRecords=2".to_string()))
+            .with_header("Sforce-Locator", "MTAwMDA")
+            .with_body("\"Id\",\"Name\",\"AnnualRevenue\"\n\"001A\",\"Acme, Inc.\",\"1000000\"\n\"001B\",\"Globex\",\"\"\n")
+            .expect(1)
+            .create_async()
+            .await;
+        let second_page = server
+            .mock(
+                "GET",
+                "/services/data/v57.0/jobs/query/750xx0000001/results",
+            )
+            .match_query(mockito::Matcher::Exact(
+                "locator=MTAwMDA&maxRecords=2".to_string(),
+            ))
+            .with_header("Sforce-Locator", "null")
+            .with_body("\"Id\",\"Name\",\"AnnualRevenue\"\n\"001C\",\"Initech\",\"250.5\"\n")
+            .expect(1)
+            .create_async()
+            .await;
+
+        let options = BulkQueryOptions::default()
+            .with_poll_interval(std::time::Duration::from_millis(10))
+            .with_page_size(2);
+        let mut pages = static_client(&server)
+            .bulk_query_with_options::<BulkAccount>(
+                "SELECT Id, Name, AnnualRevenue FROM Account",
+                options,
+            )
+            .await
+            .unwrap();
+        assert_eq!(pages.job().records_processed, 3);
+
+        let page = pages.next().await.unwrap().unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].name, "Acme, Inc.");
+        assert_eq!(page[0].annual_revenue, Some(1_000_000.0));
+        assert_eq!(page[1].annual_revenue, None);
+
+        let rest = pages.collect_all().await.unwrap();
+        assert_eq!(rest.len(), 1);
+        assert_eq!(rest[0].id, "001C");
+        assert_eq!(rest[0].annual_revenue, Some(250.5));
+
+        for mock in [create, in_progress, complete, first_page, second_page] {
+            mock.assert_async().await;
+        }
+    }
+
+    #[cfg(feature = "bulk-api")]
+    #[tokio::test]
+    async fn test_bulk_query_records_and_prefetch() {
+        #[derive(serde::Deserialize)]
+        struct BulkId {
+            #[serde(rename = "Id")]
+            id: String,
+        }
+
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = vec![
+            server
+                .mock("POST", "/services/data/v57.0/jobs/query")
+                .with_body(
+                    r#"{"id":"750xx0000002","state":"JobComplete","numberRecordsProcessed":3}"#,
+                )
+                .expect(2)
+                .create_async()
+                .await,
+        ];
+        let pages = [
+            (mockito::Matcher::Missing, "L2", "001A"),
+            (
+                mockito::Matcher::UrlEncoded("locator".into(), "L2".into()),
+                "L3",
+                "001B",
+            ),
+            (
+                mockito::Matcher::UrlEncoded("locator".into(), "L3".into()),
+                "null",
+                "001C",
+            ),
+        ];
+        for (query, locator, id) in pages {
+            mocks.push(
+                server
+                    .mock(
+                        "GET",
+                        "/services/data/v57.0/jobs/query/750xx0000002/results",
+                    )
+                    .match_query(query)
+                    .with_header("Sforce-Locator", locator)
+                    .with_body(format!("\"Id\"\n\"{}\"\n", id))
+                    .expect(2)
+                    .create_async()
+                    .await,
+            );
+        }
+
+        let client = static_client(&server);
+        let soql = "SELECT Id FROM Account";
+
+        let records: Vec<BulkId> = client
+            .bulk_query(soql)
+            .await
+            .unwrap()
+            .records()
+            .try_collect()
+            .await
+            .unwrap();
+        let ids: Vec<_> = records.into_iter().map(|r| r.id).collect();
+        assert_eq!(ids, ["001A", "001B", "001C"]);
+
+        let mut prefetched = client
+            .bulk_query::<BulkId>(soql)
+            .await
+            .unwrap()
+            .with_prefetch(2);
+        let mut ids = Vec::new();
+        while let Some(batch) = prefetched.next().await.unwrap() {
+            ids.extend(batch.into_iter().map(|r| r.id));
+        }
+        assert_eq!(ids, ["001A", "001B", "001C"]);
+
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[cfg(feature = "bulk-api")]
+    #[tokio::test]
+    async fn test_bulk_query_failed_job() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/services/data/v57.0/jobs/query")
+            .with_body(
+                r#"{"id":"750xx0000002","state":"Failed","errorMessage":"INVALID_FIELD: No such column 'Nope'"}"#,
+            )
+            .create_async()
+            .await;
+
+        let err = match static_client(&server)
+            .bulk_query::<serde_json::Value>("SELECT Nope FROM Account")
+            .await
+        {
+            Ok(_) => panic!("expected failed job"),
+            Err(err) => err,
+        };
+        assert!(matches!(err, SfError::InvalidQuery(msg) if msg.contains("No such column 'Nope'")));
+    }
+
+    #[tokio::test]
+    async fn test_describe_is_cached() {
+        let mut server = mockito::Server::new_async().await;
+        let fixture = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/account_describe.json"
+        ))
+        .unwrap();
+        let mock = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/describe")
+            .with_body(fixture)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new().metadata_ttl(std::time::Duration::from_secs(60)))
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        let first = client.describe("Account").await.unwrap();
+        let second = client.describe("Account").await.unwrap();
+
+        assert_eq!(first.name, "Account");
+        assert_eq!(second.fields.len(), first.fields.len());
+        assert_eq!(second.field("Type").unwrap().picklist_values.len(), 3);
+        mock.assert_async().await;
+    }
+
+    /// Client whose describe results go stale after a second
+    fn revalidating_client(server: &mockito::ServerGuard) -> SalesforceClient {
+        let cache = CacheConfig::new().metadata_ttl(std::time::Duration::from_secs(1));
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(cache)
+            .with_retry(RetryConfig::no_retry());
+        SalesforceClient::new(config)
+    }
+
+    fn account_describe_fixture() -> String {
+        std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/account_describe.json"
+        ))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_describe_revalidated_not_modified() {
+        let mut server = mockito::Server::new_async().await;
+        let path = "/services/data/v57.0/sobjects/Account/describe";
+        let last_modified = "Wed, 14 Oct 2026 09:00:00 GMT";
+        let download = server
+            .mock("GET", path)
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_header("etag", "\"v1\"")
+            .with_header("last-modified", last_modified)
+            .with_body(account_describe_fixture())
+            .expect(1)
+            .create_async()
+            .await;
+        let not_modified = server
+            .mock("GET", path)
+            .match_header("if-none-match", "\"v1\"")
+            .match_header("if-modified-since", last_modified)
+            .with_status(304)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = revalidating_client(&server);
+        assert_eq!(client.describe("Account").await.unwrap().label, "Account");
+
+        // Stale, so revalidated; the 304 keeps the cached body fresh
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        assert_eq!(client.describe("Account").await.unwrap().label, "Account");
+        assert_eq!(client.describe("Account").await.unwrap().label, "Account");
+
+        download.assert_async().await;
+        not_modified.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_describe_revalidated_changed() {
+        let mut server = mockito::Server::new_async().await;
+        let path = "/services/data/v57.0/sobjects/Account/describe";
+        let fixture = account_describe_fixture();
+        let download = server
+            .mock("GET", path)
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_header("etag", "\"v1\"")
+            .with_body(&fixture)
+            .expect(1)
+            .create_async()
+            .await;
+        let changed = server
+            .mock("GET", path)
+            .match_header("if-none-match", "\"v1\"")
+            .with_header("etag", "\"v2\"")
+            .with_body(fixture.replacen(r#""label": "Account""#, r#""label": "Company""#, 1))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = revalidating_client(&server);
+        assert_eq!(client.describe("Account").await.unwrap().label, "Account");
+
+        // A 200 to the conditional request replaces the cached describe
+        tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+        assert_eq!(client.describe("Account").await.unwrap().label, "Company");
+        assert_eq!(client.describe("Account").await.unwrap().label, "Company");
+
+        download.assert_async().await;
+        changed.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_describe_global() {
+        let mut server = mockito::Server::new_async().await;
+        let fixture = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/fixtures/describe_global.json"
+        ))
+        .unwrap();
+        let mock = server
+            .mock("GET", "/services/data/v57.0/sobjects/")
+            .with_body(fixture)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let global = static_client(&server).describe_global().await.unwrap();
+
+        assert_eq!(global.max_batch_size, 200);
+        assert_eq!(global.queryable_objects().count(), 4);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_upsert_by_id() {
+        let mut server = mockito::Server::new_async().await;
+        let create = server
+            .mock("POST", "/services/data/v57.0/sobjects/Account/Id")
+            .match_body(mockito::Matcher::Json(serde_json::json!({"Name": "Acme"})))
+            .with_status(201)
+            .with_body(r#"{"id":"001NEW","success":true,"errors":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let update = server
+            .mock("PATCH", "/services/data/v57.0/sobjects/Account/Id/001A")
+            .match_body(mockito::Matcher::Json(serde_json::json!({"Name": "Acme"})))
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+        let missing = server
+            .mock("PATCH", "/services/data/v57.0/sobjects/Account/Id/001GONE")
+            .with_status(404)
+            .with_body(
+                r#"[{"errorCode":"NOT_FOUND","message":"The requested resource does not exist"}]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let data = serde_json::json!({"Name": "Acme"});
+
+        let created = client
+            .upsert("Account", UpsertBuilder::create_by_id(), &data)
+            .await
+            .unwrap();
+        assert_eq!(created.id, "001NEW");
+        assert!(created.created);
+
+        let updated = client
+            .upsert("Account", UpsertBuilder::by_id("001A"), &data)
+            .await
+            .unwrap();
+        assert_eq!(updated.id, "001A");
+        assert!(updated.success && !updated.created);
+
+        let err = client
+            .upsert("Account", UpsertBuilder::by_id("001GONE"), &data)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::NotFound { ref id, .. } if id == "001GONE"));
+
+        create.assert_async().await;
+        update.assert_async().await;
+        missing.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_upsert_conflicts() {
+        let mut server = mockito::Server::new_async().await;
+        let ambiguous = server
+            .mock("PATCH", "/services/data/v57.0/sobjects/Account/Ext__c/E1")
+            .with_status(300)
+            .with_body(
+                r#"["/services/data/v57.0/sobjects/Account/001A","/services/data/v57.0/sobjects/Account/001B"]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let conflict = server
+            .mock("PATCH", "/services/data/v57.0/sobjects/Account/Id/001A")
+            .with_status(409)
+            .with_body(r#"[{"errorCode":"UNABLE_TO_LOCK_ROW","message":"unable to obtain exclusive access to this record"}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let any = server
+            .mock("POST", mockito::Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let data = serde_json::json!({"Name": "Acme"});
+
+        let err = client
+            .upsert("Account", UpsertBuilder::new("Ext__c", "E1"), &data)
+            .await
+            .unwrap_err();
+        match err {
+            SfError::MultipleMatches {
+                external_id,
+                records,
+                ..
+            } => {
+                assert_eq!(external_id, "Ext__c=E1");
+                assert_eq!(records.len(), 2);
+            }
+            other => panic!("expected MultipleMatches, got {:?}", other),
+        }
+
+        let err = client
+            .upsert("Account", UpsertBuilder::by_id("001A"), &data)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, SfError::Api { status: 409, ref body } if body.contains("UNABLE_TO_LOCK_ROW"))
+        );
+
+        // An empty ID is rejected rather than turned into a create
+        let err = client
+            .upsert("Account", UpsertBuilder::by_id(""), &data)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::InvalidQuery(_)));
+
+        ambiguous.assert_async().await;
+        conflict.assert_async().await;
+        any.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_with_nulls() {
+        #[derive(Serialize)]
+        struct AccountUpdate {
+            #[serde(rename = "Rating")]
+            rating: String,
+            #[serde(rename = "Fax", skip_serializing_if = "Option::is_none")]
+            fax: Option<String>,
+            #[serde(rename = "Website")]
+            website: Option<String>,
+        }
+
+        let mut server = mockito::Server::new_async().await;
+        let update = server
+            .mock("PATCH", "/services/data/v57.0/sobjects/Account/001A")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "Rating": "Hot",
+                "Fax": null,
+                "Website": null,
+                "Description": null
+            })))
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let data = AccountUpdate {
+            rating: "Hot".to_string(),
+            fax: None,
+            website: None,
+        };
+        client
+            .update_with_nulls("Account", "001A", &data, &["Fax", "Website", "Description"])
+            .await
+            .unwrap();
+
+        let conflict = AccountUpdate {
+            rating: "Hot".to_string(),
+            fax: Some("555-0100".to_string()),
+            website: None,
+        };
+        let err = client
+            .update_with_nulls("Account", "001A", &conflict, &["Fax"])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::Serialization(_)));
+        assert!(err.to_string().contains("Fax"));
+
+        update.assert_async().await;
+    }
+
+    #[test]
+    fn test_approval_request_serialization() {
+        let body = serde_json::to_value(
+            ApprovalRequest::new("006A")
+                .comments("Discount above 20%")
+                .next_approver_ids(["005A"])
+                .process_definition("Opportunity_Discount")
+                .skip_entry_criteria(true),
+        )
+        .unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "actionType": "Submit",
+                "contextId": "006A",
+                "comments": "Discount above 20%",
+                "nextApproverIds": ["005A"],
+                "processDefinitionNameOrId": "Opportunity_Discount",
+                "skipEntryCriteria": true
+            })
+        );
+
+        let body = serde_json::to_value(ApprovalRequest::new("006A")).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"actionType": "Submit", "contextId": "006A"})
+        );
+    }
+
+    #[test]
+    fn test_approval_result_parsing() {
+        let results: Vec<ApprovalResult> = serde_json::from_str(
+            r#"[
+                {"actorIds":["005A"],"entityId":"006A","errors":null,"instanceId":"04gA",
+                 "instanceStatus":"Pending","newWorkitemIds":["04iA"],"success":true},
+                {"actorIds":null,"entityId":"006B","instanceId":null,"instanceStatus":null,"newWorkitemIds":null,
+                 "success":false,"errors":[{"statusCode":"NO_APPLICABLE_PROCESS","message":"No applicable approval process was found.","fields":[]}]}
+            ]"#,
+        )
+        .unwrap();
+
+        assert!(results[0].success);
+        assert_eq!(results[0].instance_status.as_deref(), Some("Pending"));
+        assert_eq!(results[0].actor_ids, vec!["005A"]);
+        assert!(results[0].errors.is_empty());
+
+        assert!(!results[1].success);
+        assert!(results[1].actor_ids.is_empty());
+        assert_eq!(
+            results[1].errors[0].status_code,
+            ErrorCode::NoApplicableProcess
+        );
+    }
+
+    #[tokio::test]
+    async fn test_submit_for_approval() {
+        let mut server = mockito::Server::new_async().await;
+        let submit = server
+            .mock("POST", "/services/data/v57.0/process/approvals/")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "requests": [{"actionType": "Submit", "contextId": "006A", "comments": "Please approve"}]
+            })))
+            .with_body(
+                r#"[{"actorIds":["005A"],"entityId":"006A","errors":null,"instanceId":"04gA","instanceStatus":"Pending","newWorkitemIds":["04iA"],"success":true}]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let rejected = server
+            .mock("POST", "/services/data/v57.0/process/approvals/")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"requests":[{"contextId":"006B"}]}"#.to_string(),
+            ))
+            .with_status(400)
+            .with_body(
+                r#"[{"errorCode":"NO_APPLICABLE_PROCESS","message":"No applicable approval process was found."}]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let list = server
+            .mock("GET", "/services/data/v57.0/process/approvals/")
+            .with_body(
+                r#"{"approvals":{"Opportunity":[{"description":null,"id":"04aA","name":"Opportunity_Discount","object":"Opportunity","sortOrder":1}]}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+
+        let results = client
+            .submit_for_approval(&[ApprovalRequest::new("006A").comments("Please approve")])
+            .await
+            .unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].instance_id.as_deref(), Some("04gA"));
+        assert_eq!(results[0].new_workitem_ids, vec!["04iA"]);
+
+        let err = client
+            .submit_for_approval(&[ApprovalRequest::new("006B")])
+            .await
+            .unwrap_err();
+        assert_eq!(err.error_codes(), vec![ErrorCode::NoApplicableProcess]);
+
+        let processes = client.pending_approvals().await.unwrap();
+        assert_eq!(processes["Opportunity"][0].name, "Opportunity_Discount");
+        assert_eq!(processes["Opportunity"][0].sort_order, Some(1));
+
+        submit.assert_async().await;
+        rejected.assert_async().await;
+        list.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_quick_actions_and_approvals_invalidate_records() {
+        let mut server = mockito::Server::new_async().await;
+        let account = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/001A")
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"attributes":{"type":"Account"},"Id":"001A","Rating":"Cold"}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        let opportunity = server
+            .mock("GET", "/services/data/v57.0/sobjects/Opportunity/006A")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"{"attributes":{"type":"Opportunity"},"Id":"006A","StageName":"Prospecting"}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        server
+            .mock(
+                "POST",
+                "/services/data/v57.0/sobjects/Account/quickActions/Account.Warm_Up",
+            )
+            .with_body(
+                r#"{"contextId":"001A","created":false,"errors":[],"id":"001A","success":true}"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/services/data/v57.0/process/approvals/")
+            .with_body(r#"[{"actorIds":["005A"],"entityId":"006A","errors":null,"instanceId":"04gA","instanceStatus":"Pending","newWorkitemIds":["04iA"],"success":true}]"#)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "test_token")
+            .with_cache(CacheConfig::new())
+            .with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        let _: serde_json::Value = client.get("Account", "001A").await.unwrap();
+        let _: serde_json::Value = client.get("Opportunity", "006A").await.unwrap();
+
+        client
+            .invoke_quick_action(
+                "Account",
+                "Account.Warm_Up",
+                &serde_json::json!({"contextId": "001A", "record": {"Rating": "Warm"}}),
+            )
+            .await
+            .unwrap();
+        let _: serde_json::Value = client.get("Account", "001A").await.unwrap();
+
+        client
+            .submit_for_approval(&[ApprovalRequest::new("006A")])
+            .await
+            .unwrap();
+        let _: serde_json::Value = client.get("Opportunity", "006A").await.unwrap();
+
+        account.assert_async().await;
+        opportunity.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_quick_actions() {
+        let mut server = mockito::Server::new_async().await;
+        let list = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/quickActions")
+            .with_body(
+                r#"[
+                    {"actionEnumOrId":"LogACall","label":"Log a Call","name":"LogACall","type":"LogACall",
+                     "urls":{"quickAction":"/services/data/v57.0/sobjects/Account/quickActions/LogACall"}},
+                    {"actionEnumOrId":"09DA0000000aBcD","label":"New Opportunity","name":"Account.New_Opportunity","type":"Create",
+                     "urls":{"quickAction":"/services/data/v57.0/sobjects/Account/quickActions/Account.New_Opportunity"}}
+                ]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let invoke = server
+            .mock(
+                "POST",
+                "/services/data/v57.0/sobjects/Account/quickActions/Account.New_Opportunity",
+            )
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "contextId": "001A",
+                "record": {"Name": "Renewal", "StageName": "Prospecting"}
+            })))
+            .with_body(
+                r#"{"contextId":"001A","created":true,"errors":[],"feedItemId":null,"id":"006A","success":true}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let invalid = server
+            .mock("POST", "/services/data/v57.0/sobjects/Account/quickActions/LogACall")
+            .with_status(400)
+            .with_body(
+                r#"[{"errorCode":"REQUIRED_FIELD_MISSING","message":"Required fields are missing: [Subject]","fields":["Subject"]}]"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+
+        let actions = client.list_quick_actions("Account").await.unwrap();
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[1].action_type, "Create");
+        assert_eq!(actions[1].label, "New Opportunity");
+
+        let result = client
+            .invoke_quick_action(
+                "Account",
+                "Account.New_Opportunity",
+                &serde_json::json!({
+                    "contextId": "001A",
+                    "record": {"Name": "Renewal", "StageName": "Prospecting"}
+                }),
+            )
+            .await
+            .unwrap();
+        assert!(result.success && result.created);
+        assert_eq!(result.id.as_deref(), Some("006A"));
+
+        let err = client
+            .invoke_quick_action(
+                "Account",
+                "LogACall",
+                &serde_json::json!({"contextId": "001A", "record": {}}),
+            )
+            .await
+            .unwrap_err();
+        let errors = err.salesforce_errors();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].status_code, ErrorCode::RequiredFieldMissing);
+        assert_eq!(errors[0].fields, vec!["Subject"]);
+
+        list.assert_async().await;
+        invoke.assert_async().await;
+        invalid.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_describe_layouts() {
+        let fixture = |name: &str| {
+            std::fs::read_to_string(format!(
+                "{}/tests/fixtures/{}",
+                env!("CARGO_MANIFEST_DIR"),
+                name
+            ))
+            .unwrap()
+        };
+        let mut server = mockito::Server::new_async().await;
+        let layouts_mock = server
+            .mock(
+                "GET",
+                "/services/data/v57.0/sobjects/Account/describe/layouts",
+            )
+            .with_body(fixture("account_layouts.json"))
+            .expect(1)
+            .create_async()
+            .await;
+        let compact_mock = server
+            .mock(
+                "GET",
+                "/services/data/v57.0/sobjects/Account/describe/compactLayouts",
+            )
+            .with_body(fixture("account_compact_layouts.json"))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config =
+            ClientConfig::new(server.url(), "test_token").with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        for _ in 0..2 {
+            let layouts = client.describe_layouts("Account").await.unwrap();
+            assert_eq!(layouts.layouts.len(), 2);
+            let compact = client.describe_compact_layouts("Account").await.unwrap();
+            assert_eq!(compact.record_type_compact_layout_mappings.len(), 2);
+        }
+
+        layouts_mock.assert_async().await;
+        compact_mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_picklist_values() {
+        let mut server = mockito::Server::new_async().await;
+        let all_fields = server
+            .mock(
+                "GET",
+                "/services/data/v57.0/ui-api/object-info/Account/picklist-values/012000000000000AAA",
+            )
+            .with_body(
+                std::fs::read_to_string(concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/tests/fixtures/picklist_values.json"
+                ))
+                .unwrap(),
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let one_field = server
+            .mock(
+                "GET",
+                "/services/data/v57.0/ui-api/object-info/Account/picklist-values/012000000000000AAA/Rating",
+            )
+            .with_body(
+                r#"{"controllerValues":{},"defaultValue":{"attributes":null,"label":"Warm","validFor":[],"value":"Warm"},"values":[{"attributes":null,"label":"Hot","validFor":[],"value":"Hot"},{"attributes":null,"label":"Warm","validFor":[],"value":"Warm"}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config =
+            ClientConfig::new(server.url(), "test_token").with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::new(config);
+
+        for _ in 0..2 {
+            let picklists = client
+                .record_type_picklist_values("Account", "012000000000000AAA")
+                .await
+                .unwrap();
+            assert!(picklists.field("Sub_Industry__c").unwrap().is_dependent());
+
+            let rating = client
+                .picklist_values("Account", "012000000000000AAA", "Rating")
+                .await
+                .unwrap();
+            assert_eq!(rating.values.len(), 2);
+            assert!(rating.is_default("Warm"));
+        }
+
+        // Second round was served from the metadata cache
+        all_fields.assert_async().await;
+        one_field.assert_async().await;
+    }
+
+    /// Split a multipart body into (headers, content) per part
+    fn multipart_parts(request: &mockito::Request) -> Vec<(String, Vec<u8>)> {
+        let content_type = request.header("content-type")[0]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let boundary = content_type
+            .strip_prefix("multipart/form-data; boundary=")
+            .unwrap();
+        let body = request.body().unwrap();
+        let delimiter = format!("--{}", boundary).into_bytes();
+
+        let mut parts = Vec::new();
+        let mut rest = &body[..];
+        while let Some(start) = find(rest, &delimiter) {
+            rest = &rest[start + delimiter.len()..];
+            if rest.starts_with(b"--") {
+                break;
+            }
+            let end = find(rest, &delimiter).unwrap();
+            let part = &rest[2..end - 2]; // Strip the CRLFs around the part
+            let split = find(part, b"\r\n\r\n").unwrap();
+            parts.push((
+                String::from_utf8(part[..split].to_vec()).unwrap(),
+                part[split + 4..].to_vec(),
+            ));
+        }
+        parts
+    }
+
+    fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    }
+
+    fn assert_version_data_multipart(request: &mockito::Request, content: &[u8]) -> bool {
+        let parts = multipart_parts(request);
+        assert_eq!(parts.len(), 2);
+
+        let (headers, entity) = &parts[0];
+        assert!(headers.contains(r#"name="entity_content""#));
+        assert!(headers.contains("content-type: application/json"));
+        let entity: serde_json::Value = serde_json::from_slice(entity).unwrap();
+        assert_eq!(entity["PathOnClient"], "report.pdf");
+
+        let (headers, data) = &parts[1];
+        assert!(headers.contains(r#"name="VersionData"; filename="report.pdf""#));
+        assert!(headers.contains("content-type: application/pdf"));
+        assert_eq!(data, content);
+        true
+    }
+
+    #[tokio::test]
+    async fn test_upload_file_multipart() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/data/v57.0/sobjects/ContentVersion")
+            .match_request(|request| {
+                assert_version_data_multipart(request, b"%PDF-1.7\r\n--not-a-boundary\r\n%%EOF")
+            })
+            .with_status(201)
+            .with_body(r#"{"id":"068xx0000001","success":true,"errors":[]}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let metadata = serde_json::json!({"Title": "Report", "PathOnClient": "report.pdf"});
+        let content: &'static [u8] = b"%PDF-1.7\r\n--not-a-boundary\r\n%%EOF";
+
+        let created = client
+            .upload_file(
+                "ContentVersion",
+                &metadata,
+                content,
+                "report.pdf",
+                "application/pdf",
+            )
+            .await
+            .unwrap();
+        assert_eq!(created.id, "068xx0000001");
+
+        // The same request streamed from a reader
+        let created = client
+            .upload_file(
+                "ContentVersion",
+                &metadata,
+                FileContent::from_reader(std::io::Cursor::new(content)),
+                "report.pdf",
+                "application/pdf",
+            )
+            .await
+            .unwrap();
+        assert_eq!(created.id, "068xx0000001");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_upload_attachment_base64() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/data/v57.0/sobjects/Attachment")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "ParentId": "001xx0000001",
+                "Name": "notes.txt",
+                "ContentType": "text/plain",
+                "Body": "aGVsbG8gd29ybGQ="
+            })))
+            .with_status(201)
+            .with_body(r#"{"id":"00Pxx0000001","success":true,"errors":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let created = static_client(&server)
+            .upload_attachment("001xx0000001", "notes.txt", b"hello world", "text/plain")
+            .await
+            .unwrap();
+
+        assert_eq!(created.id, "00Pxx0000001");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_blob_streams_chunks() {
+        let content: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+        let expected = content.clone();
+
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock(
+                "GET",
+                "/services/data/v57.0/sobjects/ContentVersion/068xx0000001/VersionData",
+            )
+            .with_header("content-type", "application/pdf")
+            .with_chunked_body(move |writer| {
+                for chunk in content.chunks(10_000) {
+                    writer.write_all(chunk)?;
+                }
+                Ok(())
+            })
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+
+        let mut written = Vec::new();
+        let metadata = client
+            .download_blob_to(
+                "ContentVersion",
+                "068xx0000001",
+                "VersionData",
+                &mut written,
+            )
+            .await
+            .unwrap();
+        assert_eq!(metadata.content_type.as_deref(), Some("application/pdf"));
+        assert_eq!(written, expected);
+
+        let blob = client
+            .download_blob("ContentVersion", "068xx0000001", "VersionData")
+            .await
+            .unwrap();
+        assert_eq!(blob.data.as_ref(), expected.as_slice());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_download_blob_not_found() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock(
+                "GET",
+                "/services/data/v57.0/sobjects/Attachment/00Pxx0000404/Body",
+            )
+            .with_status(404)
+            .with_body(
+                r#"[{"errorCode":"NOT_FOUND","message":"The requested resource does not exist"}]"#,
+            )
+            .create_async()
+            .await;
+
+        let err = static_client(&server)
+            .download_blob("Attachment", "00Pxx0000404", "Body")
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, SfError::NotFound { sobject, id } if sobject == "Attachment" && id == "00Pxx0000404")
+        );
+    }
+
+    #[derive(Debug, serde::Deserialize)]
+    struct StoredCase {
+        #[serde(rename = "Id")]
+        id: String,
+        #[serde(rename = "CaseNumber")]
+        case_number: String,
+    }
+
+    #[tokio::test]
+    async fn test_insert_and_fetch() {
+        let mut server = mockito::Server::new_async().await;
+        let insert = server
+            .mock("POST", "/services/data/v57.0/sobjects/Case")
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"Subject": "Broken"}),
+            ))
+            .with_status(201)
+            .with_body(r#"{"id":"500xx0000001","success":true,"errors":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let fetch = server
+            .mock("GET", "/services/data/v57.0/sobjects/Case/500xx0000001")
+            .match_query(mockito::Matcher::Exact(
+                "fields=Id%2CCaseNumber".to_string(),
+            ))
+            .with_body(
+                r#"{"attributes":{"type":"Case"},"Id":"500xx0000001","CaseNumber":"00001026"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let inserted = static_client(&server)
+            .insert_and_fetch_fields::<_, StoredCase>(
+                "Case",
+                &serde_json::json!({"Subject": "Broken"}),
+                &["Id", "CaseNumber"],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(inserted.id, "500xx0000001");
+        let case = inserted.into_record().unwrap();
+        assert_eq!(case.id, "500xx0000001");
+        assert_eq!(case.case_number, "00001026");
+        insert.assert_async().await;
+        fetch.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_insert_and_fetch_keeps_id_when_fetch_fails() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/services/data/v57.0/sobjects/Case")
+            .with_status(201)
+            .with_body(r#"{"id":"500xx0000002","success":true,"errors":[]}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/services/data/v57.0/sobjects/Case/500xx0000002")
+            .with_status(500)
+            .with_body(r#"[{"errorCode":"UNKNOWN_EXCEPTION","message":"boom"}]"#)
+            .create_async()
+            .await;
+
+        let inserted = static_client(&server)
+            .insert_and_fetch::<_, StoredCase>("Case", &serde_json::json!({"Subject": "Broken"}))
+            .await
+            .unwrap();
+
+        assert_eq!(inserted.id, "500xx0000002");
+        assert!(matches!(
+            inserted.record,
+            Err(SfError::Api { status: 500, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_conditional_update() {
+        let mut server = mockito::Server::new_async().await;
+        let updated = server
+            .mock(
+                "PATCH",
+                "/services/data/v57.0/sobjects/Account/001xx0000001",
+            )
+            .match_header("if-unmodified-since", "Wed, 01 May 2024 09:05:03 GMT")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+        let stale = server
+            .mock("PATCH", "/services/data/v57.0/sobjects/Account/001xx0000002")
+            .match_header("if-unmodified-since", mockito::Matcher::Any)
+            .with_status(412)
+            .with_body(r#"[{"errorCode":"PRECONDITION_FAILED","message":"The requested resource has been modified."}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let unconditional = server
+            .mock(
+                "PATCH",
+                "/services/data/v57.0/sobjects/Account/001xx0000003",
+            )
+            .match_header("if-unmodified-since", mockito::Matcher::Missing)
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let options = UpdateOptions::new().if_unmodified_since(utc("2024-05-01T09:05:03Z"));
+        let data = serde_json::json!({"Rating": "Hot"});
+
+        client
+            .update_with("Account", "001xx0000001", &data, &options)
+            .await
+            .unwrap();
+
+        let err = client
+            .update_with("Account", "001xx0000002", &data, &options)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, SfError::PreconditionFailed { sobject, id } if sobject == "Account" && id == "001xx0000002")
+        );
+
+        client
+            .update("Account", "001xx0000003", &data)
+            .await
+            .unwrap();
+
+        updated.assert_async().await;
+        stale.assert_async().await;
+        unconditional.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_auto_assign_header() {
+        let mut server = mockito::Server::new_async().await;
+        let mut mocks = Vec::new();
+        for value in ["TRUE", "FALSE", "01Qxx0000004C92"] {
+            mocks.push(
+                server
+                    .mock("POST", "/services/data/v57.0/sobjects/Lead")
+                    .match_header("sforce-auto-assign", value)
+                    .with_status(201)
+                    .with_body(r#"{"id":"00Qxx0000001","success":true,"errors":[]}"#)
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+        let default = server
+            .mock("POST", "/services/data/v57.0/sobjects/Lead")
+            .match_header("sforce-auto-assign", mockito::Matcher::Missing)
+            .with_status(201)
+            .with_body(r#"{"id":"00Qxx0000001","success":true,"errors":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let update = server
+            .mock("PATCH", "/services/data/v57.0/sobjects/Case/500xx0000001")
+            .match_header("sforce-auto-assign", "FALSE")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let lead = serde_json::json!({"LastName": "Smith", "Company": "Acme"});
+        for auto_assign in [
+            AutoAssign::True,
+            AutoAssign::False,
+            AutoAssign::RuleId("01Qxx0000004C92".to_string()),
+        ] {
+            let options = InsertOptions::new().auto_assign(auto_assign);
+            client.insert_with("Lead", &lead, &options).await.unwrap();
+        }
+        client.insert("Lead", &lead).await.unwrap();
+
+        let options = UpdateOptions::new().auto_assign(AutoAssign::False);
+        client
+            .update_with(
+                "Case",
+                "500xx0000001",
+                &serde_json::json!({"Status": "New"}),
+                &options,
+            )
+            .await
+            .unwrap();
+
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+        default.assert_async().await;
+        update.assert_async().await;
+    }
+
+    const DUPLICATES_DETECTED: &str = r#"[{
+        "duplicateResult": {
+            "allowSave": true,
+            "duplicateRule": "Standard_Account_Duplicate_Rule",
+            "duplicateRuleEntityType": "Account",
+            "errorMessage": "You're creating a duplicate record.",
+            "matchResults": [{
+                "entityType": "Account",
+                "errors": [],
+                "matchEngine": "FuzzyMatchEngine",
+                "matchRecords": [
+                    {"additionalInformation": [], "fieldDiffs": [], "matchConfidence": 98.5,
+                     "record": {"attributes": {"type": "Account"}, "Id": "001xx0000001", "Name": "Acme Inc"}},
+                    {"additionalInformation": [], "fieldDiffs": [], "matchConfidence": 87.0,
+                     "record": {"attributes": {"type": "Account"}, "Id": "001xx0000002", "Name": "ACME"}}
+                ],
+                "rule": "Standard_Account_Match_Rule_v1_0",
+                "size": 2,
+                "success": true
+            }]
+        },
+        "errorCode": "DUPLICATES_DETECTED",
+        "fields": [],
+        "message": "You're creating a duplicate record."
+    }]"#;
+
+    /// Log output captured by a thread-local subscriber
+    #[derive(Clone, Default)]
+    struct CapturedLogs(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl CapturedLogs {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_truncate_fields() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let mut server = mockito::Server::new_async().await;
+        let insert = server
+            .mock("POST", "/services/data/v57.0/sobjects/Account")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "Name": "Acm",
+                "Description": "Crème",
+                "Site": "HQ",
+                "NumberOfEmployees": 12345
+            })))
+            .with_status(201)
+            .with_body(r#"{"id":"001xx0000001","success":true,"errors":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let update = server
+            .mock(
+                "PATCH",
+                "/services/data/v57.0/sobjects/Account/001xx0000001",
+            )
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"Description": "日本"}),
+            ))
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+        let upsert = server
+            .mock(
+                "PATCH",
+                "/services/data/v57.0/sobjects/Account/External_Id__c/EXT-1",
+            )
+            .match_body(mockito::Matcher::Json(
+                serde_json::json!({"Description": "🦀🦀"}),
+            ))
+            .with_status(200)
+            .with_body(r#"{"id":"001xx0000001","success":true,"errors":[],"created":false}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let options = InsertOptions::new().truncate_fields([
+            ("Name", 3),
+            ("Description", 5),
+            ("Site", 10),
+            ("NumberOfEmployees", 2),
+        ]);
+        client
+            .insert_with(
+                "Account",
+                &serde_json::json!({
+                    "Name": "Acme",
+                    "Description": "Crème brûlée",
+                    "Site": "HQ",
+                    "NumberOfEmployees": 12345
+                }),
+                &options,
+            )
+            .await
+            .unwrap();
+
+        let output = logs.contents();
+        assert!(output.contains("WARN"));
+        assert!(output.contains("Truncated fields to their maximum length: Description, Name"));
+
+        client
+            .update_with(
+                "Account",
+                "001xx0000001",
+                &serde_json::json!({"Description": "日本語"}),
+                &UpdateOptions::new().truncate_fields([("Description", 2)]),
+            )
+            .await
+            .unwrap();
+        client
+            .upsert_with(
+                "Account",
+                UpsertBuilder::new("External_Id__c", "EXT-1"),
+                &serde_json::json!({"Description": "🦀🦀🦀"}),
+                &InsertOptions::new().truncate_fields([("Description", 2)]),
+            )
+            .await
+            .unwrap();
+
+        insert.assert_async().await;
+        update.assert_async().await;
+        upsert.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_tooling_urls() {
+        let mut server = mockito::Server::new_async().await;
+        let first_page = server
+            .mock("GET", "/services/data/v57.0/tooling/query")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "q".into(),
+                "SELECT Id, Name FROM ApexClass".into(),
+            ))
+            .with_body(
+                r#"{"totalSize":3,"done":false,"nextRecordsUrl":"/services/data/v57.0/tooling/query/01gxx-2000","records":[{"Id":"01pA","Name":"Alpha"},{"Id":"01pB","Name":"Beta"}]}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+        let next_page = server
+            .mock("GET", "/services/data/v57.0/tooling/query/01gxx-2000")
+            .with_body(r#"{"totalSize":3,"done":true,"records":[{"Id":"01pC","Name":"Gamma"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let get = server
+            .mock(
+                "GET",
+                "/services/data/v57.0/tooling/sobjects/ApexClass/01pA",
+            )
+            .with_body(r#"{"Id":"01pA","Name":"Alpha"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let insert = server
+            .mock("POST", "/services/data/v57.0/tooling/sobjects/TraceFlag")
+            .with_status(201)
+            .with_body(r#"{"id":"7tfA","success":true,"errors":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let delete = server
+            .mock(
+                "DELETE",
+                "/services/data/v57.0/tooling/sobjects/TraceFlag/7tfA",
+            )
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+        // The regular data API is served by the same transport
+        let data_get = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/001A")
+            .with_body(r#"{"Id":"001A","Name":"Acme"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let tooling = client.tooling();
+
+        let classes: Vec<Account> = tooling
+            .query("SELECT Id, Name FROM ApexClass")
+            .await
+            .unwrap();
+        assert_eq!(classes.len(), 2);
+        assert_eq!(classes[1].name, "Beta");
+
+        let mut pages = tooling
+            .query_paginated::<Account>("SELECT Id, Name FROM ApexClass")
+            .await
+            .unwrap();
+        assert_eq!(pages.next().await.unwrap().unwrap().len(), 2);
+        let page = pages.next().await.unwrap().unwrap();
+        assert_eq!(page[0].name, "Gamma");
+        assert!(pages.next().await.unwrap().is_none());
+
+        let class: Account = tooling.get("ApexClass", "01pA").await.unwrap();
+        assert_eq!(class.id, "01pA");
+        let flag = tooling
+            .insert(
+                "TraceFlag",
+                &serde_json::json!({"LogType": "DEVELOPER_LOG"}),
+            )
+            .await
+            .unwrap();
+        tooling.delete("TraceFlag", &flag.id).await.unwrap();
+
+        let account: Account = client.get("Account", "001A").await.unwrap();
+        assert_eq!(account.name, "Acme");
+
+        for mock in [first_page, next_page, get, insert, delete, data_get] {
+            mock.assert_async().await;
+        }
+    }
+
+    #[test]
+    fn test_duplicate_rule_header_value() {
+        assert_eq!(DuplicateRuleHeader::new().header_value(), "");
+        assert_eq!(
+            DuplicateRuleHeader::new().allow_save(true).header_value(),
+            "allowSave=true"
+        );
+        assert_eq!(
+            DuplicateRuleHeader::new()
+                .allow_save(true)
+                .include_record_details(false)
+                .run_as_current_user(true)
+                .header_value(),
+            "allowSave=true, includeRecordDetails=false, runAsCurrentUser=true"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_rule_header_and_error() {
+        let mut server = mockito::Server::new_async().await;
+        let blocked = server
+            .mock("POST", "/services/data/v57.0/sobjects/Account")
+            .match_header("sforce-duplicate-rule-header", mockito::Matcher::Missing)
+            .with_status(400)
+            .with_body(DUPLICATES_DETECTED)
+            .expect(1)
+            .create_async()
+            .await;
+        let allowed = server
+            .mock(
+                "PATCH",
+                "/services/data/v57.0/sobjects/Account/External_Id__c/EXT-1",
+            )
+            .match_header(
+                "sforce-duplicate-rule-header",
+                "allowSave=true, includeRecordDetails=true",
+            )
+            .with_status(201)
+            .with_body(r#"{"id":"001xx0000003","success":true,"errors":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = static_client(&server);
+        let account = serde_json::json!({"Name": "Acme"});
+
+        let err = client.insert("Account", &account).await.unwrap_err();
+        let duplicates = err.duplicate_result().unwrap();
+        assert!(duplicates.allow_save);
+        assert_eq!(duplicates.duplicate_rule, "Standard_Account_Duplicate_Rule");
+        let candidates: Vec<_> = duplicates.candidates().collect();
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].id(), Some("001xx0000001"));
+        assert_eq!(candidates[1].match_confidence, 87.0);
+
+        let options = InsertOptions::new().duplicate_rule(
+            DuplicateRuleHeader::new()
+                .allow_save(true)
+                .include_record_details(true),
+        );
+        let created = client
+            .upsert_with(
+                "Account",
+                UpsertBuilder::new("External_Id__c", "EXT-1"),
+                &account,
+                &options,
+            )
+            .await
+            .unwrap();
+        assert_eq!(created.id, "001xx0000003");
+
+        assert!(SfError::Api {
+            status: 400,
+            body: "[]".to_string()
+        }
+        .duplicate_result()
+        .is_none());
+        blocked.assert_async().await;
+        allowed.assert_async().await;
+    }
+
+    fn utc(value: &str) -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&chrono::Utc)
+    }
+
+    #[tokio::test]
+    async fn test_get_deleted() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/services/data/v57.0/sobjects/Account/deleted/")
+            .match_query(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::UrlEncoded("start".into(), "2024-05-01T00:00:00Z".into()),
+                mockito::Matcher::UrlEncoded("end".into(), "2024-05-02T00:00:00Z".into()),
+            ]))
+            .with_body(
+                r#"{"deletedRecords":[{"id":"001xx0000001","deletedDate":"2024-05-01T15:57:00.000+0000"}],
+                    "earliestDateAvailable":"2024-04-03T00:00:00.000+0000",
+                    "latestDateCovered":"2024-05-01T23:59:00.000+0000"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let deleted = static_client(&server)
+            .get_deleted(
+                "Account",
+                utc("2024-05-01T00:00:00Z"),
+                utc("2024-05-02T00:00:00Z"),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(deleted.deleted_records.len(), 1);
+        assert_eq!(deleted.deleted_records[0].id, "001xx0000001");
+        assert_eq!(
+            deleted.deleted_records[0].deleted_date,
+            utc("2024-05-01T15:57:00Z")
+        );
+        assert_eq!(deleted.latest_date_covered, utc("2024-05-01T23:59:00Z"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_updated() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/services/data/v57.0/sobjects/Contact/updated/")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"{"ids":["003xx0000001","003xx0000002"],"latestDateCovered":"2024-05-01T23:59:00.000+0000"}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let updated = static_client(&server)
+            .get_updated(
+                "Contact",
+                utc("2024-05-01T00:00:00Z"),
+                utc("2024-05-02T00:00:00Z"),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(updated.ids, vec!["003xx0000001", "003xx0000002"]);
+        assert_eq!(updated.latest_date_covered, utc("2024-05-01T23:59:00Z"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_replication_window_validation() {
+        let server = mockito::Server::new_async().await;
+        let client = static_client(&server);
+        let start = utc("2024-05-01T00:00:00Z");
+
+        let inverted = client.get_updated("Account", start, utc("2024-04-30T00:00:00Z"));
+        assert!(
+            matches!(inverted.await, Err(SfError::InvalidQuery(msg)) if msg.contains("must be after"))
+        );
+
+        let empty = client.get_deleted("Account", start, start);
+        assert!(matches!(empty.await, Err(SfError::InvalidQuery(_))));
+
+        let too_long = client.get_deleted("Account", start, utc("2024-05-31T00:00:01Z"));
+        assert!(
+            matches!(too_long.await, Err(SfError::InvalidQuery(msg)) if msg.contains("exceeds 30 days"))
+        );
+    }
+
+    fn account_tree() -> TreeRecord {
+        TreeRecord::new("Account", "acme")
+            .field("Name", "Acme")
+            .child(
+                "Contacts",
+                TreeRecord::new("Contact", "smith").field("LastName", "Smith"),
+            )
+            .child(
+                "Contacts",
+                TreeRecord::new("Contact", "jones")
+                    .field("LastName", "Jones")
+                    .field("Email", "not-an-email"),
+            )
+    }
+
+    #[tokio::test]
+    async fn test_insert_tree_two_levels() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/data/v57.0/composite/tree/Account")
+            .match_body(mockito::Matcher::Json(serde_json::json!({
+                "records": [{
+                    "attributes": {"type": "Account", "referenceId": "acme"},
+                    "Name": "Acme",
+                    "Contacts": {"records": [
+                        {"attributes": {"type": "Contact", "referenceId": "smith"}, "LastName": "Smith"},
+                        {"attributes": {"type": "Contact", "referenceId": "jones"}, "LastName": "Jones", "Email": "not-an-email"}
+                    ]}
+                }]
+            })))
+            .with_status(201)
+            .with_body(
+                r#"{"hasErrors":false,"results":[
+                    {"referenceId":"acme","id":"001xx0000001"},
+                    {"referenceId":"smith","id":"003xx0000001"},
+                    {"referenceId":"jones","id":"003xx0000002"}
+                ]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let response = static_client(&server)
+            .insert_tree("Account", &[account_tree()])
+            .await
+            .unwrap();
+
+        assert!(!response.has_errors);
+        assert_eq!(response.id("acme"), Some("001xx0000001"));
+        assert_eq!(response.id("jones"), Some("003xx0000002"));
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_insert_tree_child_error() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/data/v57.0/composite/tree/Account")
+            .with_status(400)
+            .with_body(
+                r#"{"hasErrors":true,"results":[{"referenceId":"jones","errors":[
+                    {"statusCode":"INVALID_EMAIL_ADDRESS","message":"Email: invalid email address: not-an-email","fields":["Email"]}
+                ]}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let response = static_client(&server)
+            .insert_tree("Account", &[account_tree()])
+            .await
+            .unwrap();
+
+        assert!(response.has_errors);
+        assert_eq!(response.id("acme"), None);
+        let failed = response.get("jones").unwrap();
+        assert_eq!(failed.errors[0].status_code, "INVALID_EMAIL_ADDRESS");
+        assert_eq!(failed.errors[0].fields, vec!["Email".to_string()]);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_insert_tree_record_limit() {
+        let server = mockito::Server::new_async().await;
+
+        let account = (0..200).fold(TreeRecord::new("Account", "acme"), |account, i| {
+            account.child("Contacts", TreeRecord::new("Contact", format!("c{}", i)))
+        });
+        assert_eq!(account.record_count(), 201);
+
+        let err = static_client(&server)
+            .insert_tree("Account", &[account])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::InvalidQuery(msg) if msg.contains("201 records")));
+    }
+
+    fn account_and_contact() -> CompositeRequestBuilder {
+        CompositeRequestBuilder::for_api_version(DEFAULT_API_VERSION)
+            .all_or_none(true)
+            .create("newAccount", "Account", serde_json::json!({"Name": "Acme"}))
+            .create(
+                "newContact",
+                "Contact",
+                serde_json::json!({"LastName": "Smith", "AccountId": "@{newAccount.id}"}),
+            )
+    }
+
+    #[test]
+    fn test_composite_payload() {
+        let payload = serde_json::to_value(account_and_contact()).unwrap();
+
+        assert_eq!(
+            payload,
+            serde_json::json!({
+                "allOrNone": true,
+                "compositeRequest": [
+                    {
+                        "method": "POST",
+                        "url": "/services/data/v57.0/sobjects/Account",
+                        "referenceId": "newAccount",
+                        "body": {"Name": "Acme"}
+                    },
+                    {
+                        "method": "POST",
+                        "url": "/services/data/v57.0/sobjects/Contact",
+                        "referenceId": "newContact",
+                        "body": {"LastName": "Smith", "AccountId": "@{newAccount.id}"}
+                    }
+                ]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_composite_validation() {
+        let server = mockito::Server::new_async().await;
+        let client = static_client(&server);
+
+        let err = client
+            .composite(client.composite_request())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SfError::InvalidQuery(_)));
+
+        let too_many = (0..26).fold(client.composite_request(), |request, i| {
+            request.get(format!("ref{}", i), "/services/data/v57.0/limits")
+        });
+        let err = client.composite(too_many).await.unwrap_err();
+        assert!(matches!(err, SfError::InvalidQuery(msg) if msg.contains("limit is 25")));
+
+        let duplicate = client
+            .composite_request()
+            .get("same", "/services/data/v57.0/limits")
+            .get("same", "/services/data/v57.0/limits");
+        let err = client.composite(duplicate).await.unwrap_err();
+        assert!(matches!(err, SfError::InvalidQuery(msg) if msg.contains("'same'")));
+    }
+
+    #[tokio::test]
+    async fn test_composite_reference_chaining_and_mixed_status() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/data/v57.0/composite")
+            .match_body(mockito::Matcher::PartialJsonString(
+                r#"{"compositeRequest":[{"referenceId":"newAccount"},{"referenceId":"newContact","body":{"AccountId":"@{newAccount.id}"}},{"referenceId":"badUpdate","url":"/services/data/v57.0/sobjects/Contact/@{newContact.id}"}]}"#
+                    .to_string(),
+            ))
+            .with_status(200)
+            .with_body(
+                r#"{"compositeResponse":[
+                    {"body":{"id":"001xx0000001","success":true,"errors":[]},"httpHeaders":{"Location":"/services/data/v57.0/sobjects/Account/001xx0000001"},"httpStatusCode":201,"referenceId":"newAccount"},
+                    {"body":{"id":"003xx0000001","success":true,"errors":[]},"httpHeaders":{},"httpStatusCode":201,"referenceId":"newContact"},
+                    {"body":[{"errorCode":"INVALID_FIELD","message":"No such column 'Nope' on sobject of type Contact"}],"httpHeaders":{},"httpStatusCode":400,"referenceId":"badUpdate"}
+                ]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+
+        let request = account_and_contact().all_or_none(false).update(
+            "badUpdate",
+            "Contact",
+            "@{newContact.id}",
+            serde_json::json!({"Nope": 1}),
+        );
+        let response = static_client(&server).composite(request).await.unwrap();
+
+        assert!(response.has_errors());
+        let account = response.get("newAccount").unwrap();
+        assert!(account.is_success());
+        assert_eq!(
+            account.headers.get("Location").map(String::as_str),
+            Some("/services/data/v57.0/sobjects/Account/001xx0000001")
+        );
+        let contact: InsertResponse = response.get("newContact").unwrap().body_as().unwrap();
+        assert_eq!(contact.id, "003xx0000001");
+
+        let failed = response.get("badUpdate").unwrap();
+        assert_eq!(failed.status, 400);
+        assert_eq!(failed.errors()[0].status_code, "INVALID_FIELD");
+        assert!(response.get("missing").is_none());
+        mock.assert_async().await;
+    }
+
+    fn token_body(server_url: &str, access_token: &str) -> String {
+        format!(
+            r#"{{"access_token":"{}","instance_url":"{}","token_type":"Bearer","expires_in":7200}}"#,
+            access_token, server_url
+        )
+    }
+
+    #[tokio::test]
+    async fn test_oauth_client_refreshes_token_mid_session() {
+        let mut server = mockito::Server::new_async().await;
+        let first_token = server
+            .mock("POST", "/services/oauth2/token")
+            .with_status(200)
+            .with_body(token_body(&server.url(), "first"))
+            .expect(1)
+            .create_async()
+            .await;
+        let second_token = server
+            .mock("POST", "/services/oauth2/token")
+            .with_status(200)
+            .with_body(token_body(&server.url(), "second"))
+            .expect(1)
+            .create_async()
+            .await;
+        let first_query = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::Any)
+            .match_header("authorization", "Bearer first")
+            .with_status(200)
+            .with_body(r#"{"totalSize":0,"done":true,"records":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let second_query = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::Any)
+            .match_header("authorization", "Bearer second")
+            .with_status(200)
+            .with_body(r#"{"totalSize":0,"done":true,"records":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let manager = Arc::new(
+            TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+                .with_auth_url_unchecked(server.url()),
+        );
+        let config = ClientConfig::new("", "").with_cache(CacheConfig::disabled());
+        let client = SalesforceClient::with_token_manager(config, manager.clone())
+            .await
+            .unwrap();
+        assert_eq!(client.config().base_url, server.url());
+
+        let _: Vec<serde_json::Value> = client.query("SELECT Id FROM Account").await.unwrap();
+
+        // Simulate session expiry; the same client picks up a new token
+        manager.invalidate().await;
+        let _: Vec<serde_json::Value> = client.query("SELECT Id FROM Account").await.unwrap();
+
+        first_token.assert_async().await;
+        second_token.assert_async().await;
+        first_query.assert_async().await;
+        second_query.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_crud_uses_token_manager() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/services/oauth2/token")
+            .with_status(200)
+            .with_body(token_body(&server.url(), "crud_token"))
+            .create_async()
+            .await;
+        let delete = server
+            .mock("DELETE", "/services/data/v57.0/sobjects/Account/001xx")
+            .match_header("authorization", "Bearer crud_token")
+            .with_status(204)
+            .create_async()
+            .await;
+
+        let manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+            .with_auth_url_unchecked(server.url());
+        let config = ClientConfig::new("", "").with_retry(RetryConfig::no_retry());
+        let client = SalesforceClient::with_token_manager(config, manager)
+            .await
+            .unwrap();
+
+        client.delete("Account", "001xx").await.unwrap();
+        delete.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_identity() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/services/oauth2/userinfo")
+            .match_header("authorization", "Bearer test_token")
+            .with_body(
+                r#"{"user_id":"005xx","organization_id":"00Dxx","preferred_username":"jane@acme.com","name":"Jane Doe","urls":{"rest":"https://acme.my.salesforce.com/services/data/v{version}/"}}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = SalesforceClient::new(ClientConfig::new(server.url(), "test_token"));
+        let identity = client.identity().await.unwrap();
+
+        assert_eq!(identity.organization_id, "00Dxx");
+        assert_eq!(identity.display_name, "Jane Doe");
+        assert!(identity.email.is_none());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_custom_http_client_is_used() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::Any)
+            .match_header("x-egress", "corp")
+            .with_body(r#"{"totalSize":0,"done":true,"records":[]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            "x-egress",
+            reqwest::header::HeaderValue::from_static("corp"),
+        );
+        let http_client = reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap();
+
+        let config = ClientConfig::new(server.url(), "token")
             .with_cache(CacheConfig::disabled())
-            .no_pagination();
+            .with_http_client(http_client);
+        let client = SalesforceClient::new(config);
 
-        assert_eq!(config.base_url, "https://test.salesforce.com");
-        assert!(!config.auto_paginate);
+        let records: Vec<serde_json::Value> = client.query("SELECT Id FROM Account").await.unwrap();
+        assert!(records.is_empty());
+        mock.assert_async().await;
     }
 
     #[test]
-    fn test_client_creation() {
-        let config = ClientConfig::new("https://test.salesforce.com", "test_token");
+    fn test_env_proxy_flag() {
+        assert!(ClientConfig::new("", "").env_proxy);
+        assert!(!ClientConfig::new("", "").with_env_proxy(false).env_proxy);
+    }
+
+    const INVALID_SESSION: &str =
+        r#"[{"message":"Session expired or invalid","errorCode":"INVALID_SESSION_ID"}]"#;
+
+    async fn oauth_client(server: &mockito::ServerGuard) -> SalesforceClient {
+        let manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+            .with_auth_url_unchecked(server.url());
+        let config = ClientConfig::new("", "")
+            .with_cache(CacheConfig::disabled())
+            .with_retry(RetryConfig::no_retry());
+        SalesforceClient::with_token_manager(config, manager)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_query_refreshes_session_on_401() {
+        let mut server = mockito::Server::new_async().await;
+        let tokens = [
+            server
+                .mock("POST", "/services/oauth2/token")
+                .with_body(token_body(&server.url(), "revoked"))
+                .expect(1)
+                .create_async()
+                .await,
+            server
+                .mock("POST", "/services/oauth2/token")
+                .with_body(token_body(&server.url(), "fresh"))
+                .expect(1)
+                .create_async()
+                .await,
+        ];
+        let rejected = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::Any)
+            .match_header("authorization", "Bearer revoked")
+            .with_status(401)
+            .with_body(INVALID_SESSION)
+            .expect(1)
+            .create_async()
+            .await;
+        let accepted = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::Any)
+            .match_header("authorization", "Bearer fresh")
+            .with_body(r#"{"totalSize":1,"done":true,"records":[{"Id":"001xx"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = oauth_client(&server).await;
+        let records: Vec<serde_json::Value> = client.query("SELECT Id FROM Account").await.unwrap();
+
+        assert_eq!(records.len(), 1);
+        rejected.assert_async().await;
+        accepted.assert_async().await;
+        for token in tokens {
+            token.assert_async().await;
+        }
+    }
 
+    #[tokio::test]
+    async fn test_update_refreshes_session_on_401() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/services/oauth2/token")
+            .with_body(token_body(&server.url(), "revoked"))
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/services/oauth2/token")
+            .with_body(token_body(&server.url(), "fresh"))
+            .create_async()
+            .await;
+        server
+            .mock("PATCH", "/services/data/v57.0/sobjects/Account/001xx")
+            .match_header("authorization", "Bearer revoked")
+            .with_status(401)
+            .with_body(INVALID_SESSION)
+            .create_async()
+            .await;
+        let accepted = server
+            .mock("PATCH", "/services/data/v57.0/sobjects/Account/001xx")
+            .match_header("authorization", "Bearer fresh")
+            .with_status(204)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = oauth_client(&server).await;
+        client
+            .update("Account", "001xx", &serde_json::json!({"Name": "Acme"}))
+            .await
+            .unwrap();
+
+        accepted.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_persistent_401_surfaces_auth_error() {
+        let mut server = mockito::Server::new_async().await;
+        let token = server
+            .mock("POST", "/services/oauth2/token")
+            .with_body(token_body(&server.url(), "revoked"))
+            .expect(2)
+            .create_async()
+            .await;
+        let rejected = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::Any)
+            .with_status(401)
+            .with_body(INVALID_SESSION)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = oauth_client(&server).await;
+        let err = client
+            .query::<serde_json::Value>("SELECT Id FROM Account")
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, SfError::Auth(msg) if msg.contains("INVALID_SESSION_ID")));
+        token.assert_async().await;
+        rejected.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_other_401_does_not_refresh() {
+        let mut server = mockito::Server::new_async().await;
+        let token = server
+            .mock("POST", "/services/oauth2/token")
+            .with_body(token_body(&server.url(), "valid"))
+            .expect(1)
+            .create_async()
+            .await;
+        let rejected = server
+            .mock("GET", "/services/data/v57.0/query")
+            .match_query(mockito::Matcher::Any)
+            .with_status(401)
+            .with_body(r#"[{"message":"Access from this IP is restricted","errorCode":"LOGIN_MUST_USE_SECURITY_TOKEN"}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = oauth_client(&server).await;
+        let err = client
+            .query::<serde_json::Value>("SELECT Id FROM Account")
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(err, SfError::Api { status: 401, ref body } if body.contains("IP is restricted"))
+        );
+        token.assert_async().await;
+        rejected.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_static_token_401_is_not_retried() {
+        let mut server = mockito::Server::new_async().await;
+        let rejected = server
+            .mock("DELETE", "/services/data/v57.0/sobjects/Account/001xx")
+            .with_status(401)
+            .with_body(INVALID_SESSION)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let config = ClientConfig::new(server.url(), "static").with_retry(RetryConfig::no_retry());
         let client = SalesforceClient::new(config);
-        assert_eq!(client.config.base_url, "https://test.salesforce.com");
+        let err = client.delete("Account", "001xx").await.unwrap_err();
+
+        assert!(matches!(err, SfError::Api { status: 401, .. }));
+        rejected.assert_async().await;
     }
 }
@@ -3,14 +3,23 @@
 //! Salesforce limits query results to 2000 records per request.
 //! This module handles automatic pagination transparently.
 
+use crate::auth::{self, TokenProvider};
 use crate::error::{SfError, SfResult};
+use crate::query_builder::{Complete, QueryBuilder};
+use crate::soql::{Condition, SoqlValue};
+use crate::{retry, SalesforceClient};
+use async_trait::async_trait;
+use futures::{Stream, TryStreamExt};
 use serde::de::DeserializeOwned;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
 use tracing::{debug, info};
 
-/// Response from Salesforce query with pagination info
-#[derive(Debug, Deserialize)]
-pub(crate) struct QueryResponse<T> {
+/// One page of query results with pagination info
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct QueryResult<T> {
     /// The list of records returned by the query
     pub records: Vec<T>,
 
@@ -19,17 +28,15 @@ pub(crate) struct QueryResponse<T> {
 
     /// Total number of records (optional, may not always be present)
     #[serde(rename = "totalSize")]
-    #[allow(dead_code)]
     pub total_size: Option<i32>,
 
     /// URL for fetching next batch of records
-    #[serde(rename = "nextRecordsUrl")]
+    #[serde(rename = "nextRecordsUrl", default)]
     pub next_records_url: Option<String>,
 }
 
-impl<T> QueryResponse<T> {
+impl<T> QueryResult<T> {
     /// Check if there are more records to fetch
-    #[allow(dead_code)]
     pub fn has_more(&self) -> bool {
         !self.done && self.next_records_url.is_some()
     }
@@ -50,37 +57,177 @@ impl<T> QueryResponse<T> {
 pub struct PaginatedQuery<T> {
     client: reqwest::Client,
     base_url: String,
-    access_token: String,
+    token_provider: Arc<dyn TokenProvider>,
+    first_page: Option<Vec<T>>,
     next_url: Option<String>,
+    remaining: Option<usize>,
+    max_records: Option<usize>,
     finished: bool,
+    prefetch: Option<Prefetch<T>>,
     _phantom: std::marker::PhantomData<T>,
 }
 
+/// A sequence of result pages fetched one request at a time
+///
+/// Implemented by REST query pagination ([`PaginatedQuery`], following
+/// `nextRecordsUrl`) and Bulk API result paging (`BulkQuery`, with the
+/// `bulk-api` feature, following `Sforce-Locator`), which share their record
+/// streaming and prefetching through it.
+#[async_trait]
+pub trait PageSource<T>: Send {
+    /// Fetch the next page, or `None` once every page has been returned
+    async fn next_page(&mut self) -> SfResult<Option<Vec<T>>>;
+}
+
+/// Stream the records of a page source one at a time, fetching the next page
+/// only once the current one is used up
+pub(crate) fn records<T, S>(source: S) -> impl Stream<Item = SfResult<T>>
+where
+    T: Send,
+    S: PageSource<T>,
+{
+    futures::stream::try_unfold(source, |mut source| async move {
+        SfResult::Ok(source.next_page().await?.map(|batch| (batch, source)))
+    })
+    .map_ok(|batch| futures::stream::iter(batch.into_iter().map(Ok)))
+    .try_flatten()
+}
+
+/// Pages fetched ahead by a background task
+pub(crate) struct Prefetch<T> {
+    receiver: mpsc::Receiver<SfResult<Vec<T>>>,
+    task: tokio::task::JoinHandle<()>,
+}
+
+impl<T: Send + 'static> Prefetch<T> {
+    /// Start fetching up to `pages` pages of `source` ahead of the caller
+    pub(crate) fn spawn<S>(mut source: S, pages: usize) -> Self
+    where
+        S: PageSource<T> + 'static,
+    {
+        let (sender, receiver) = mpsc::channel(pages.max(1));
+
+        let task = tokio::spawn(async move {
+            // Reserve buffer space first so at most `pages` are fetched ahead
+            while let Ok(permit) = sender.reserve().await {
+                match source.next_page().await {
+                    Ok(Some(batch)) => permit.send(Ok(batch)),
+                    Ok(None) => break,
+                    Err(e) => {
+                        permit.send(Err(e));
+                        break;
+                    }
+                }
+            }
+        });
+
+        Self { receiver, task }
+    }
+}
+
+impl<T> Prefetch<T> {
+    /// The next prefetched page; `None` after the last page or an error
+    pub(crate) async fn recv(&mut self) -> SfResult<Option<Vec<T>>> {
+        self.receiver.recv().await.transpose()
+    }
+}
+
+#[async_trait]
+impl<T: Send> PageSource<T> for Prefetch<T> {
+    async fn next_page(&mut self) -> SfResult<Option<Vec<T>>> {
+        self.recv().await
+    }
+}
+
+impl<T> Drop for Prefetch<T> {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
 impl<T: DeserializeOwned> PaginatedQuery<T> {
-    /// Create a new paginated query iterator
+    /// Create a paginated query iterator from the first page's records and
+    /// the `nextRecordsUrl` of the page after it
     pub(crate) fn new(
         client: reqwest::Client,
         base_url: String,
-        access_token: String,
-        initial_url: Option<String>,
+        token_provider: Arc<dyn TokenProvider>,
+        first_page: Vec<T>,
+        next_url: Option<String>,
     ) -> Self {
-        let finished = initial_url.is_none();
+        let first_page = Some(first_page).filter(|records| !records.is_empty());
+        let finished = first_page.is_none() && next_url.is_none();
         Self {
             client,
             base_url,
-            access_token,
-            next_url: initial_url,
+            token_provider,
+            first_page,
+            next_url,
+            remaining: None,
+            max_records: None,
             finished,
+            prefetch: None,
             _phantom: std::marker::PhantomData,
         }
     }
 
+    /// Fail [`collect_all`](Self::collect_all) once more than `max_records`
+    /// records are gathered
+    pub(crate) fn with_max_records(mut self, max_records: Option<usize>) -> Self {
+        self.max_records = max_records;
+        self
+    }
+
+    /// Stop after `limit` records, truncating the page that reaches it
+    pub(crate) fn with_limit(mut self, limit: Option<usize>) -> Self {
+        self.remaining = limit;
+        self.finished |= limit == Some(0);
+        self
+    }
+
     /// Fetch the next page of results
     pub async fn next(&mut self) -> SfResult<Option<Vec<T>>> {
+        let Some(prefetch) = self.prefetch.as_mut() else {
+            return self.fetch_page().await;
+        };
+
+        let page = prefetch.recv().await;
+        if !matches!(page, Ok(Some(_))) {
+            self.finished = true;
+        }
+        page
+    }
+
+    async fn fetch_page(&mut self) -> SfResult<Option<Vec<T>>> {
         if self.finished {
             return Ok(None);
         }
 
+        let mut records = match self.first_page.take() {
+            Some(records) => {
+                self.finished = self.next_url.is_none();
+                records
+            }
+            None => match self.request_page().await? {
+                Some(records) => records,
+                None => return Ok(None),
+            },
+        };
+
+        if let Some(remaining) = self.remaining.as_mut() {
+            records.truncate(*remaining);
+            *remaining -= records.len();
+            if *remaining == 0 {
+                self.finished = true;
+                info!("Record limit reached, stopping pagination");
+            }
+        }
+
+        Ok(Some(records))
+    }
+
+    /// Request the page at `next_url`
+    async fn request_page(&mut self) -> SfResult<Option<Vec<T>>> {
         let url = match &self.next_url {
             Some(path) => {
                 // nextRecordsUrl is a relative path, prepend base URL
@@ -98,12 +245,8 @@ impl<T: DeserializeOwned> PaginatedQuery<T> {
 
         debug!("Fetching paginated results from: {}", url);
 
-        let response = self
-            .client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", self.access_token))
-            .send()
-            .await?;
+        let response =
+            auth::send_authorized(self.token_provider.as_ref(), || self.client.get(&url)).await?;
 
         let status = response.status();
         if !status.is_success() {
@@ -114,7 +257,7 @@ impl<T: DeserializeOwned> PaginatedQuery<T> {
             });
         }
 
-        let query_response: QueryResponse<T> = response.json().await?;
+        let query_response: QueryResult<T> = response.json().await?;
 
         if query_response.done {
             self.finished = true;
@@ -132,11 +275,16 @@ impl<T: DeserializeOwned> PaginatedQuery<T> {
     ///
     /// **Warning:** This loads all results into memory. For very large
     /// result sets (>100k records), consider processing pages individually.
+    /// With a `max_records` cap on the client or query options, fails with
+    /// [`SfError::ResultTooLarge`] once the cap is passed.
+    ///
+    /// Use [`with_prefetch`](Self::with_prefetch) to overlap page requests.
     pub async fn collect_all(mut self) -> SfResult<Vec<T>> {
         let mut all_records = Vec::new();
 
         while let Some(batch) = self.next().await? {
             all_records.extend(batch);
+            check_max_records(all_records.len(), self.max_records)?;
         }
 
         info!(
@@ -147,6 +295,309 @@ impl<T: DeserializeOwned> PaginatedQuery<T> {
     }
 }
 
+/// Iterator over query results paged by record ID
+///
+/// Each page re-runs the query with `ORDER BY Id LIMIT page_size`, adding
+/// `Id > last_id` after the first page, so paging is not bounded by the
+/// 2000-row OFFSET limit. Created by
+/// [`SalesforceClient::query_pages_by_id`](crate::SalesforceClient::query_pages_by_id).
+///
+/// ```ignore
+/// let builder = QueryBuilder::select(&["Id", "Name"]).from("Account");
+/// let mut pages = client.query_pages_by_id::<Account>(builder, 500)?;
+///
+/// while let Some(batch) = pages.next().await? {
+///     println!("Got {} accounts", batch.len());
+/// }
+/// ```
+pub struct KeysetQuery<'a, T> {
+    client: &'a SalesforceClient,
+    builder: QueryBuilder<Complete>,
+    page_size: u32,
+    last_id: Option<String>,
+    finished: bool,
+    _phantom: std::marker::PhantomData<T>,
+}
+
+impl<'a, T: DeserializeOwned> KeysetQuery<'a, T> {
+    pub(crate) fn new(
+        client: &'a SalesforceClient,
+        builder: QueryBuilder<Complete>,
+        page_size: u32,
+    ) -> SfResult<Self> {
+        if !(1..=2000).contains(&page_size) {
+            return Err(SfError::InvalidQuery(format!(
+                "Page size {} must be between 1 and 2000",
+                page_size
+            )));
+        }
+        builder.validate_keyset(page_size)?;
+
+        Ok(Self {
+            client,
+            builder,
+            page_size,
+            last_id: None,
+            finished: false,
+            _phantom: std::marker::PhantomData,
+        })
+    }
+
+    /// SOQL for the next page
+    pub fn next_soql(&self) -> SfResult<String> {
+        let mut builder = self.builder.clone();
+        if let Some(last_id) = &self.last_id {
+            builder = builder.and_cond(Condition::gt("Id", SoqlValue::id(last_id.as_str())));
+        }
+        builder.order_by("Id").limit(self.page_size).try_build()
+    }
+
+    /// Fetch the next page of results
+    pub async fn next(&mut self) -> SfResult<Option<Vec<T>>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let soql = self.next_soql()?;
+        let url = self.client.query_url(QueryEndpoint::Query);
+        debug!("Fetching keyset page after {:?}", self.last_id);
+
+        self.client.rate_limiter.acquire().await?;
+        let records = retry::with_retry(&self.client.config.retry_config, || async {
+            self.client
+                .execute_query_at::<serde_json::Value>(&url, &soql)
+                .await
+        })
+        .await?;
+
+        if records.len() < self.page_size as usize {
+            self.finished = true;
+            info!("Keyset pagination complete");
+        }
+
+        let last_id = records
+            .last()
+            .and_then(|record| record.get("Id"))
+            .and_then(serde_json::Value::as_str);
+        match last_id {
+            Some(id) => self.last_id = Some(id.to_string()),
+            None => {
+                self.finished = true;
+                if records.is_empty() {
+                    return Ok(None);
+                }
+            }
+        }
+
+        let records = records
+            .into_iter()
+            .map(serde_json::from_value)
+            .collect::<Result<Vec<T>, _>>()?;
+        Ok(Some(records))
+    }
+
+    /// Collect all remaining pages into a single vector
+    ///
+    /// **Warning:** This loads all results into memory.
+    pub async fn collect_all(mut self) -> SfResult<Vec<T>> {
+        let mut all_records = Vec::new();
+
+        while let Some(batch) = self.next().await? {
+            all_records.extend(batch);
+        }
+
+        info!(
+            "Collected {} total records across all keyset pages",
+            all_records.len()
+        );
+        Ok(all_records)
+    }
+}
+
+impl<T: DeserializeOwned + Serialize> PaginatedQuery<T> {
+    /// Write the remaining records to `writer` as newline-delimited JSON
+    ///
+    /// Records are written as each page arrives instead of being collected
+    /// first. Returns the number of records written. A record that fails to
+    /// serialize is reported with its zero-based page and row index.
+    ///
+    /// ```ignore
+    /// let file = tokio::fs::File::create("accounts.ndjson").await?;
+    /// let written = client
+    ///     .query_paginated::<Account>("SELECT Id, Name FROM Account")
+    ///     .await?
+    ///     .write_ndjson(file)
+    ///     .await?;
+    /// ```
+    pub async fn write_ndjson<W>(mut self, mut writer: W) -> SfResult<usize>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut written = 0;
+        let mut line = Vec::new();
+
+        for page in 0.. {
+            let Some(batch) = self.next().await? else {
+                break;
+            };
+            for (row, record) in batch.iter().enumerate() {
+                line.clear();
+                serde_json::to_writer(&mut line, record).map_err(|e| record_error(page, row, e))?;
+                line.push(b'\n');
+                writer.write_all(&line).await?;
+                written += 1;
+            }
+        }
+
+        writer.flush().await?;
+        info!("Wrote {} records as NDJSON", written);
+        Ok(written)
+    }
+
+    /// Write the remaining records to `writer` as CSV, with a header row
+    ///
+    /// Like [`write_ndjson`](Self::write_ndjson); the header is taken from
+    /// the first record's field names, so `T` should be a flat struct.
+    /// Enabled with the `csv-export` feature.
+    #[cfg(feature = "csv-export")]
+    pub async fn write_csv<W>(mut self, mut writer: W) -> SfResult<usize>
+    where
+        W: AsyncWrite + Unpin,
+    {
+        let mut written = 0;
+
+        for page in 0.. {
+            let Some(batch) = self.next().await? else {
+                break;
+            };
+
+            // Each page is encoded separately; only the first record written
+            // gets a header row
+            let mut csv_writer = csv::WriterBuilder::new()
+                .has_headers(written == 0)
+                .from_writer(Vec::new());
+            for (row, record) in batch.iter().enumerate() {
+                csv_writer
+                    .serialize(record)
+                    .map_err(|e| record_error(page, row, e))?;
+                written += 1;
+            }
+
+            let encoded = csv_writer
+                .into_inner()
+                .map_err(|e| SfError::Io(e.into_error()))?;
+            writer.write_all(&encoded).await?;
+        }
+
+        writer.flush().await?;
+        info!("Wrote {} records as CSV", written);
+        Ok(written)
+    }
+}
+
+/// Serialization error for one record of an export
+fn record_error(page: usize, row: usize, error: impl std::fmt::Display) -> SfError {
+    SfError::Serialization(serde::ser::Error::custom(format!(
+        "Failed to serialize row {} of page {}: {}",
+        row, page, error
+    )))
+}
+
+#[async_trait]
+impl<T: DeserializeOwned + Send> PageSource<T> for PaginatedQuery<T> {
+    async fn next_page(&mut self) -> SfResult<Option<Vec<T>>> {
+        self.next().await
+    }
+}
+
+impl<T: DeserializeOwned + Send> PaginatedQuery<T> {
+    /// Stream the remaining records one at a time
+    ///
+    /// Batches are flattened, and the next page is requested only once the
+    /// current one is used up (or earlier, with
+    /// [`with_prefetch`](Self::with_prefetch)). An error ends the stream
+    /// after it is yielded.
+    ///
+    /// ```ignore
+    /// use futures::TryStreamExt;
+    ///
+    /// let records = client
+    ///     .query_paginated::<Account>("SELECT Id FROM Account")
+    ///     .await?
+    ///     .records();
+    /// futures::pin_mut!(records);
+    /// while let Some(account) = records.try_next().await? {
+    ///     println!("{:?}", account);
+    /// }
+    /// ```
+    pub fn records(self) -> impl Stream<Item = SfResult<T>> {
+        records(self)
+    }
+}
+
+impl<T: DeserializeOwned + Send + 'static> PaginatedQuery<T> {
+    /// Fetch up to `pages` pages ahead in the background
+    ///
+    /// While the caller works on one batch, the following pages are already
+    /// being requested, hiding network latency for slow per-record
+    /// processing. Pages still arrive in order, and an error is returned when
+    /// the page it replaced would have been. Dropping the query cancels any
+    /// request in flight. Must be called within a Tokio runtime; a `pages` of
+    /// 0 leaves fetching on demand.
+    ///
+    /// ```ignore
+    /// let mut pages = client
+    ///     .query_paginated::<Account>("SELECT Id FROM Account")
+    ///     .await?
+    ///     .with_prefetch(2);
+    ///
+    /// while let Some(batch) = pages.next().await? {
+    ///     expensive_processing(batch);
+    /// }
+    /// ```
+    pub fn with_prefetch(mut self, pages: usize) -> Self {
+        if pages == 0 || self.finished || self.prefetch.is_some() {
+            return self;
+        }
+
+        let fetcher = PaginatedQuery::<T> {
+            client: self.client.clone(),
+            base_url: self.base_url.clone(),
+            token_provider: self.token_provider.clone(),
+            first_page: self.first_page.take(),
+            next_url: self.next_url.take(),
+            remaining: self.remaining.take(),
+            max_records: None,
+            finished: false,
+            prefetch: None,
+            _phantom: std::marker::PhantomData,
+        };
+        self.prefetch = Some(Prefetch::spawn(fetcher, pages));
+        self
+    }
+}
+
+/// REST resource a SOQL query runs against
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum QueryEndpoint {
+    /// `/query`, live records only
+    #[default]
+    Query,
+
+    /// `/queryAll`, also returning deleted and archived records
+    QueryAll,
+}
+
+impl QueryEndpoint {
+    /// Resource path segment, e.g. `queryAll`
+    pub fn path(&self) -> &'static str {
+        match self {
+            QueryEndpoint::Query => "query",
+            QueryEndpoint::QueryAll => "queryAll",
+        }
+    }
+}
+
 /// Builder for query options
 #[derive(Debug, Clone)]
 pub struct QueryOptions {
@@ -158,6 +609,13 @@ pub struct QueryOptions {
 
     /// Enable automatic pagination
     pub auto_paginate: bool,
+
+    /// Include deleted and archived records, via `/queryAll`
+    pub include_deleted: bool,
+
+    /// Most records to gather into memory before failing with
+    /// [`SfError::ResultTooLarge`] (None = the client's setting)
+    pub max_records: Option<usize>,
 }
 
 impl Default for QueryOptions {
@@ -166,6 +624,8 @@ impl Default for QueryOptions {
             limit: None,
             batch_size: 2000,
             auto_paginate: true,
+            include_deleted: false,
+            max_records: None,
         }
     }
 }
@@ -193,6 +653,72 @@ impl QueryOptions {
         self.auto_paginate = false;
         self
     }
+
+    /// Include deleted and archived records (`IsDeleted = true`, archived
+    /// Tasks and Events)
+    pub fn include_deleted(mut self) -> Self {
+        self.include_deleted = true;
+        self
+    }
+
+    /// Fail with [`SfError::ResultTooLarge`] rather than gather more than
+    /// `max_records` records into memory
+    ///
+    /// Unlike [`limit`](Self::limit), which quietly stops early, this guards
+    /// against unexpectedly large results.
+    pub fn max_records(mut self, max_records: usize) -> Self {
+        self.max_records = Some(max_records);
+        self
+    }
+
+    /// Resource these options query against
+    pub fn endpoint(&self) -> QueryEndpoint {
+        if self.include_deleted {
+            QueryEndpoint::QueryAll
+        } else {
+            QueryEndpoint::Query
+        }
+    }
+}
+
+/// Fail once more than `max_records` records have been fetched
+pub(crate) fn check_max_records(fetched: usize, max_records: Option<usize>) -> SfResult<()> {
+    match max_records {
+        Some(limit) if fetched > limit => Err(SfError::ResultTooLarge { fetched, limit }),
+        _ => Ok(()),
+    }
+}
+
+/// Records with a Salesforce ID, for [`SalesforceClient::query_by_id`](crate::SalesforceClient::query_by_id)
+///
+/// # Example
+/// ```
+/// use salesforce_client::HasId;
+///
+/// struct Account {
+///     id: String,
+/// }
+///
+/// impl HasId for Account {
+///     fn id(&self) -> &str {
+///         &self.id
+///     }
+/// }
+/// ```
+pub trait HasId {
+    /// The record's `Id`
+    fn id(&self) -> &str;
+}
+
+/// How keyed query results treat two records with the same key
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Keep the record seen last
+    #[default]
+    LastWins,
+
+    /// Fail with [`SfError::InvalidQuery`](crate::SfError::InvalidQuery)
+    Error,
 }
 
 #[cfg(test)]
@@ -206,6 +732,363 @@ mod tests {
         assert_eq!(opts.limit, Some(1000));
         assert_eq!(opts.batch_size, 500);
         assert!(opts.auto_paginate);
+        assert_eq!(opts.endpoint(), QueryEndpoint::Query);
+        assert_eq!(opts.include_deleted().endpoint(), QueryEndpoint::QueryAll);
+    }
+
+    #[tokio::test]
+    async fn test_next_page_refreshes_session_on_401() {
+        use crate::auth::{OAuthCredentials, TokenManager};
+
+        let mut server = mockito::Server::new_async().await;
+        let token_body = |token: &str| {
+            format!(
+                r#"{{"access_token":"{}","instance_url":"https://acme.my.salesforce.com","token_type":"Bearer"}}"#,
+                token
+            )
+        };
+        server
+            .mock("POST", "/services/oauth2/token")
+            .with_body(token_body("revoked"))
+            .expect(1)
+            .create_async()
+            .await;
+        server
+            .mock("POST", "/services/oauth2/token")
+            .with_body(token_body("fresh"))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/services/data/v57.0/query/01gxx-2000")
+            .match_header("authorization", "Bearer revoked")
+            .with_status(401)
+            .with_body(
+                r#"[{"message":"Session expired or invalid","errorCode":"INVALID_SESSION_ID"}]"#,
+            )
+            .create_async()
+            .await;
+        let page = server
+            .mock("GET", "/services/data/v57.0/query/01gxx-2000")
+            .match_header("authorization", "Bearer fresh")
+            .with_body(r#"{"totalSize":2001,"done":true,"records":[{"Id":"001xx"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+            .with_auth_url_unchecked(server.url());
+        let mut pages = PaginatedQuery::<serde_json::Value>::new(
+            reqwest::Client::new(),
+            server.url(),
+            Arc::new(manager),
+            Vec::new(),
+            Some("/services/data/v57.0/query/01gxx-2000".to_string()),
+        );
+
+        let batch = pages.next().await.unwrap().unwrap();
+        assert_eq!(batch.len(), 1);
+        assert!(pages.next().await.unwrap().is_none());
+        page.assert_async().await;
+    }
+
+    /// Five chained pages of one record each, `00T1` to `00T5`
+    async fn mock_five_pages(server: &mut mockito::Server) -> Vec<mockito::Mock> {
+        let mut mocks = Vec::new();
+        for page in 1..=5 {
+            let body = if page == 5 {
+                r#"{"totalSize":5,"done":true,"records":[{"Id":"00T5"}]}"#.to_string()
+            } else {
+                format!(
+                    r#"{{"totalSize":5,"done":false,"nextRecordsUrl":"/query/01gA-{}","records":[{{"Id":"00T{}"}}]}}"#,
+                    page + 1,
+                    page
+                )
+            };
+            mocks.push(
+                server
+                    .mock("GET", format!("/query/01gA-{}", page).as_str())
+                    .with_body(body)
+                    .expect(1)
+                    .create_async()
+                    .await,
+            );
+        }
+        mocks
+    }
+
+    fn five_pages(server: &mockito::Server) -> PaginatedQuery<serde_json::Value> {
+        PaginatedQuery::new(
+            reqwest::Client::new(),
+            server.url(),
+            Arc::new(crate::auth::StaticToken("token".to_string())),
+            Vec::new(),
+            Some("/query/01gA-1".to_string()),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_preserves_order() {
+        let mut server = mockito::Server::new_async().await;
+        let mocks = mock_five_pages(&mut server).await;
+
+        let mut pages = five_pages(&server).with_prefetch(2);
+        let mut ids = Vec::new();
+        while let Some(batch) = pages.next().await.unwrap() {
+            ids.extend(
+                batch
+                    .into_iter()
+                    .map(|r| r["Id"].as_str().unwrap().to_string()),
+            );
+        }
+
+        assert_eq!(ids, ["00T1", "00T2", "00T3", "00T4", "00T5"]);
+        assert!(pages.next().await.unwrap().is_none());
+        for mock in mocks {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_prefetch_fetches_ahead_while_batch_is_processed() {
+        let mut server = mockito::Server::new_async().await;
+        let mocks = mock_five_pages(&mut server).await;
+
+        let mut pages = five_pages(&server).with_prefetch(2);
+        let first = pages.next().await.unwrap().unwrap();
+        assert_eq!(first[0]["Id"], "00T1");
+
+        // Pages 2 and 3 arrive while the caller is busy with page 1
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_secs(5);
+        while !mocks[2].matched_async().await {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "page 3 was not prefetched"
+            );
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(mocks[1].matched_async().await);
+
+        // The buffer is full, so page 4 waits for the caller
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(!mocks[3].matched_async().await);
+
+        // Dropping the query stops further fetching
+        drop(pages);
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert!(!mocks[3].matched_async().await);
+        assert!(!mocks[4].matched_async().await);
+    }
+
+    #[tokio::test]
+    async fn test_records_flattens_pages() {
+        let mut server = mockito::Server::new_async().await;
+        let mocks = mock_five_pages(&mut server).await;
+
+        let records: Vec<_> = five_pages(&server).records().try_collect().await.unwrap();
+        let ids: Vec<_> = records.iter().map(|r| r["Id"].as_str().unwrap()).collect();
+        assert_eq!(ids, ["00T1", "00T2", "00T3", "00T4", "00T5"]);
+
+        let records: Vec<_> = five_pages(&server)
+            .with_prefetch(2)
+            .records()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(records.len(), 5);
+        assert_eq!(records[4]["Id"], "00T5");
+        for mock in mocks {
+            mock.expect(2).assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_records_error_position() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/query/01gA-1")
+            .with_body(
+                r#"{"totalSize":4,"done":false,"nextRecordsUrl":"/query/01gA-2","records":[{"Id":"00T1"},{"Id":"00T2"}]}"#,
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/query/01gA-2")
+            .with_status(400)
+            .with_body(r#"[{"errorCode":"INVALID_QUERY_LOCATOR","message":"invalid"}]"#)
+            .create_async()
+            .await;
+
+        let records = five_pages(&server).records();
+        futures::pin_mut!(records);
+        assert_eq!(records.try_next().await.unwrap().unwrap()["Id"], "00T1");
+        assert_eq!(records.try_next().await.unwrap().unwrap()["Id"], "00T2");
+        assert!(matches!(
+            records.try_next().await,
+            Err(SfError::Api { status: 400, .. })
+        ));
+        assert!(records.try_next().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_each_page_fetches_current_token() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        /// Hands out a new token on every request, like a rotating session
+        struct RotatingToken(AtomicUsize);
+
+        #[async_trait]
+        impl TokenProvider for RotatingToken {
+            async fn bearer(&self) -> SfResult<String> {
+                Ok(format!("token-{}", self.0.fetch_add(1, Ordering::SeqCst)))
+            }
+        }
+
+        let mut server = mockito::Server::new_async().await;
+        let first = server
+            .mock("GET", "/query/01gA-1")
+            .match_header("authorization", "Bearer token-0")
+            .with_body(
+                r#"{"totalSize":2,"done":false,"nextRecordsUrl":"/query/01gA-2","records":[{"Id":"00T1"}]}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/query/01gA-2")
+            .match_header("authorization", "Bearer token-1")
+            .with_body(r#"{"totalSize":2,"done":true,"records":[{"Id":"00T2"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let pages = PaginatedQuery::<serde_json::Value>::new(
+            reqwest::Client::new(),
+            server.url(),
+            Arc::new(RotatingToken(AtomicUsize::new(0))),
+            Vec::new(),
+            Some("/query/01gA-1".to_string()),
+        );
+        let records = pages.collect_all().await.unwrap();
+        assert_eq!(records.len(), 2);
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    /// Three chained pages holding two, one and two Accounts
+    async fn mock_account_pages(server: &mut mockito::Server) -> Vec<mockito::Mock> {
+        let pages = [
+            (
+                "/query/01gB-1",
+                r#"{"totalSize":5,"done":false,"nextRecordsUrl":"/query/01gB-2","records":[{"Id":"001A","Name":"Acme"},{"Id":"001B","Name":"Globex, Inc."}]}"#,
+            ),
+            (
+                "/query/01gB-2",
+                r#"{"totalSize":5,"done":false,"nextRecordsUrl":"/query/01gB-3","records":[{"Id":"001C","Name":"Initech"}]}"#,
+            ),
+            (
+                "/query/01gB-3",
+                r#"{"totalSize":5,"done":true,"records":[{"Id":"001D","Name":"bad"},{"Id":"001E","Name":"Umbrella"}]}"#,
+            ),
+        ];
+
+        let mut mocks = Vec::new();
+        for (path, body) in pages {
+            mocks.push(
+                server
+                    .mock("GET", path)
+                    .with_body(body)
+                    .create_async()
+                    .await,
+            );
+        }
+        mocks
+    }
+
+    fn account_pages<T: DeserializeOwned>(server: &mockito::Server) -> PaginatedQuery<T> {
+        PaginatedQuery::new(
+            reqwest::Client::new(),
+            server.url(),
+            Arc::new(crate::auth::StaticToken("token".to_string())),
+            Vec::new(),
+            Some("/query/01gB-1".to_string()),
+        )
+    }
+
+    #[derive(Deserialize, Serialize)]
+    struct Account {
+        #[serde(rename = "Id")]
+        id: String,
+        #[serde(rename = "Name")]
+        name: String,
+    }
+
+    #[tokio::test]
+    async fn test_write_ndjson() {
+        let mut server = mockito::Server::new_async().await;
+        let _mocks = mock_account_pages(&mut server).await;
+
+        let mut buffer = Vec::new();
+        let written = account_pages::<Account>(&server)
+            .write_ndjson(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(written, 5);
+
+        let output = String::from_utf8(buffer).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert_eq!(lines[1], r#"{"Id":"001B","Name":"Globex, Inc."}"#);
+        assert_eq!(lines[4], r#"{"Id":"001E","Name":"Umbrella"}"#);
+    }
+
+    #[tokio::test]
+    async fn test_write_ndjson_reports_failing_row() {
+        #[derive(Deserialize, Serialize)]
+        struct Strict {
+            #[serde(rename = "Name", serialize_with = "reject_bad")]
+            name: String,
+        }
+
+        fn reject_bad<S: serde::Serializer>(name: &str, serializer: S) -> Result<S::Ok, S::Error> {
+            if name == "bad" {
+                return Err(serde::ser::Error::custom("name rejected"));
+            }
+            serializer.serialize_str(name)
+        }
+
+        let mut server = mockito::Server::new_async().await;
+        let _mocks = mock_account_pages(&mut server).await;
+
+        let mut buffer = Vec::new();
+        let err = account_pages::<Strict>(&server)
+            .write_ndjson(&mut buffer)
+            .await
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("row 0 of page 2: name rejected"),
+            "{}",
+            err
+        );
+        // Rows before the failure were already written
+        assert_eq!(String::from_utf8(buffer).unwrap().lines().count(), 3);
+    }
+
+    #[cfg(feature = "csv-export")]
+    #[tokio::test]
+    async fn test_write_csv() {
+        let mut server = mockito::Server::new_async().await;
+        let _mocks = mock_account_pages(&mut server).await;
+
+        let mut buffer = Vec::new();
+        let written = account_pages::<Account>(&server)
+            .write_csv(&mut buffer)
+            .await
+            .unwrap();
+        assert_eq!(written, 5);
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "Id,Name\n001A,Acme\n001B,\"Globex, Inc.\"\n001C,Initech\n001D,bad\n001E,Umbrella\n"
+        );
     }
 
     #[test]
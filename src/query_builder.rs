@@ -2,6 +2,8 @@
 //!
 //! Provides a fluent API for constructing SOQL queries with compile-time guarantees.
 
+use crate::error::{SfError, SfResult};
+use crate::soql::{Condition, ConditionGroup, FieldRef};
 use std::marker::PhantomData;
 
 /// Type-safe SOQL query builder
@@ -21,15 +23,152 @@ use std::marker::PhantomData;
 /// ```
 #[derive(Debug, Clone)]
 pub struct QueryBuilder<State = NeedsFrom> {
-    fields: Vec<String>,
+    exprs: Vec<String>,
+    fields: Vec<FieldRef>,
     from: Option<String>,
-    where_clauses: Vec<String>,
+    where_clauses: Vec<Condition>,
     order_by: Option<String>,
     limit: Option<u32>,
     offset: Option<u32>,
+    security_modes: Vec<SecurityMode>,
+    fields_spec: Option<FieldsSpec>,
+    group_by: Option<GroupBy>,
+    having_clauses: Vec<Condition>,
+    subqueries: Vec<SubqueryBuilder>,
+    typeofs: Vec<TypeofBuilder>,
+    data_categories: Vec<(String, Selector, String)>,
+    scopes: Vec<Scope>,
     _state: PhantomData<State>,
 }
 
+/// Record set a `USING SCOPE` clause limits the query to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// Records owned by the running user
+    Mine,
+
+    /// Records owned by the user's account or opportunity team
+    Team,
+
+    /// All records the user can see
+    Everything,
+
+    /// Records delegated to the running user
+    Delegated,
+
+    /// Records owned by the user or their queues and groups
+    MineAndMyGroups,
+}
+
+impl Scope {
+    fn keyword(self) -> &'static str {
+        match self {
+            Scope::Mine => "mine",
+            Scope::Team => "team",
+            Scope::Everything => "everything",
+            Scope::Delegated => "delegated",
+            Scope::MineAndMyGroups => "mine_and_my_groups",
+        }
+    }
+}
+
+/// How a `WITH DATA CATEGORY` filter matches the category hierarchy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selector {
+    /// The category itself
+    At,
+
+    /// The category and its parents
+    Above,
+
+    /// The category and its children
+    Below,
+
+    /// The category, its parents and its children
+    AboveOrBelow,
+}
+
+impl Selector {
+    fn keyword(self) -> &'static str {
+        match self {
+            Selector::At => "AT",
+            Selector::Above => "ABOVE",
+            Selector::Below => "BELOW",
+            Selector::AboveOrBelow => "ABOVE_OR_BELOW",
+        }
+    }
+}
+
+/// GROUP BY form and its fields
+#[derive(Debug, Clone)]
+enum GroupBy {
+    Plain(Vec<String>),
+    Rollup(Vec<String>),
+    Cube(Vec<String>),
+}
+
+impl GroupBy {
+    fn render(&self) -> String {
+        match self {
+            GroupBy::Plain(fields) => fields.join(", "),
+            GroupBy::Rollup(fields) => format!("ROLLUP({})", fields.join(", ")),
+            GroupBy::Cube(fields) => format!("CUBE({})", fields.join(", ")),
+        }
+    }
+}
+
+/// Field set selected with the SOQL `FIELDS()` function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldsSpec {
+    /// `FIELDS(ALL)`: every field; requires a LIMIT of at most 200
+    All,
+
+    /// `FIELDS(STANDARD)`: standard fields only
+    Standard,
+
+    /// `FIELDS(CUSTOM)`: custom fields only; requires a LIMIT of at most 200
+    Custom,
+}
+
+impl FieldsSpec {
+    fn keyword(self) -> &'static str {
+        match self {
+            FieldsSpec::All => "FIELDS(ALL)",
+            FieldsSpec::Standard => "FIELDS(STANDARD)",
+            FieldsSpec::Custom => "FIELDS(CUSTOM)",
+        }
+    }
+
+    /// Whether Salesforce requires a bounded LIMIT for this field set
+    fn requires_limit(self) -> bool {
+        !matches!(self, FieldsSpec::Standard)
+    }
+}
+
+/// Largest LIMIT Salesforce accepts with `FIELDS(ALL)` or `FIELDS(CUSTOM)`
+const FIELDS_MAX_LIMIT: u32 = 200;
+
+/// Largest OFFSET Salesforce accepts
+const MAX_OFFSET: u32 = 2000;
+
+/// Access mode applied with a `WITH` clause
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SecurityMode {
+    SecurityEnforced,
+    UserMode,
+    SystemMode,
+}
+
+impl SecurityMode {
+    fn keyword(self) -> &'static str {
+        match self {
+            SecurityMode::SecurityEnforced => "SECURITY_ENFORCED",
+            SecurityMode::UserMode => "USER_MODE",
+            SecurityMode::SystemMode => "SYSTEM_MODE",
+        }
+    }
+}
+
 // Type states for compile-time query validation
 #[derive(Debug, Clone)]
 pub struct NeedsFrom;
@@ -39,43 +178,299 @@ pub struct Complete;
 
 impl QueryBuilder<NeedsFrom> {
     /// Start building a query with SELECT fields
-    pub fn select(fields: &[&str]) -> Self {
+    ///
+    /// Fields are names such as `"Id"` or
+    /// [`FieldPath`](crate::soql::FieldPath)s to parent fields.
+    pub fn select<I>(fields: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<FieldRef>,
+    {
         Self {
-            fields: fields.iter().map(|s| s.to_string()).collect(),
+            exprs: Vec::new(),
+            fields: fields.into_iter().map(Into::into).collect(),
             from: None,
             where_clauses: Vec::new(),
             order_by: None,
             limit: None,
             offset: None,
+            security_modes: Vec::new(),
+            fields_spec: None,
+            group_by: None,
+            having_clauses: Vec::new(),
+            subqueries: Vec::new(),
+            typeofs: Vec::new(),
+            data_categories: Vec::new(),
+            scopes: Vec::new(),
             _state: PhantomData,
         }
     }
 
+    /// Start building a query with SELECT expressions and optional aliases
+    ///
+    /// ```
+    /// use salesforce_client::QueryBuilder;
+    ///
+    /// let query = QueryBuilder::select_exprs(&[("Industry", None), ("COUNT(Id)", Some("cnt"))])
+    ///     .from("Account")
+    ///     .group_by(&["Industry"])
+    ///     .build();
+    ///
+    /// assert_eq!(query, "SELECT Industry, COUNT(Id) cnt FROM Account GROUP BY Industry");
+    /// ```
+    pub fn select_exprs(exprs: &[(&str, Option<&str>)]) -> Self {
+        let mut builder = Self::select(Vec::<FieldRef>::new());
+        builder.exprs = exprs
+            .iter()
+            .map(|(expr, alias)| match alias {
+                Some(alias) => format!("{} {}", expr, alias),
+                None => expr.to_string(),
+            })
+            .collect();
+        builder
+    }
+
+    /// Start building a query selecting a `FIELDS()` set
+    ///
+    /// ```
+    /// use salesforce_client::{FieldsSpec, QueryBuilder};
+    ///
+    /// let query = QueryBuilder::select_fields(FieldsSpec::Standard)
+    ///     .add_fields(&["My_Custom__c"])
+    ///     .from("Account")
+    ///     .build();
+    ///
+    /// assert_eq!(query, "SELECT FIELDS(STANDARD), My_Custom__c FROM Account");
+    /// ```
+    pub fn select_fields(spec: FieldsSpec) -> Self {
+        let mut builder = Self::select(Vec::<FieldRef>::new());
+        builder.fields_spec = Some(spec);
+        builder
+    }
+
     /// Specify the FROM clause (required)
     pub fn from(mut self, sobject: impl Into<String>) -> QueryBuilder<Complete> {
         self.from = Some(sobject.into());
         QueryBuilder {
+            exprs: self.exprs,
             fields: self.fields,
             from: self.from,
             where_clauses: self.where_clauses,
             order_by: self.order_by,
             limit: self.limit,
             offset: self.offset,
+            security_modes: self.security_modes,
+            fields_spec: self.fields_spec,
+            group_by: self.group_by,
+            having_clauses: self.having_clauses,
+            subqueries: self.subqueries,
+            typeofs: self.typeofs,
+            data_categories: self.data_categories,
+            scopes: self.scopes,
             _state: PhantomData,
         }
     }
 }
 
+impl<State> QueryBuilder<State> {
+    /// Select additional fields
+    pub fn add_fields<I>(mut self, fields: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<FieldRef>,
+    {
+        self.fields.extend(fields.into_iter().map(Into::into));
+        self
+    }
+
+    /// The SELECT list, including any `FIELDS()` set, TYPEOF clauses and
+    /// subqueries
+    fn select_list(&self) -> String {
+        self.fields_spec
+            .map(|spec| spec.keyword().to_string())
+            .into_iter()
+            .chain(self.exprs.iter().cloned())
+            .chain(self.fields.iter().map(ToString::to_string))
+            .chain(self.typeofs.iter().map(TypeofBuilder::render))
+            .chain(self.subqueries.iter().map(SubqueryBuilder::render))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
 impl QueryBuilder<Complete> {
     /// Add a WHERE clause
+    ///
+    /// The condition is used as-is. Escape any values spliced into it with
+    /// [`soql::escape_string`](crate::soql::escape_string),
+    /// [`soql::quote`](crate::soql::quote) or
+    /// [`soql::escape_like_pattern`](crate::soql::escape_like_pattern), or
+    /// use [`where_cond`](Self::where_cond), which escapes values itself.
     pub fn where_clause(mut self, condition: impl Into<String>) -> Self {
-        self.where_clauses.push(condition.into());
+        self.where_clauses.push(Condition::raw(condition));
         self
     }
 
     /// Add an AND condition to WHERE clause
+    ///
+    /// Used as-is, like [`where_clause`](Self::where_clause).
     pub fn and(mut self, condition: impl Into<String>) -> Self {
-        self.where_clauses.push(condition.into());
+        self.where_clauses.push(Condition::raw(condition));
+        self
+    }
+
+    /// Add a typed WHERE condition
+    pub fn where_cond(mut self, condition: Condition) -> Self {
+        self.where_clauses.push(condition);
+        self
+    }
+
+    /// Add a typed AND condition to WHERE clause
+    pub fn and_cond(self, condition: Condition) -> Self {
+        self.where_cond(condition)
+    }
+
+    /// Add a parenthesized group of OR/AND conditions to WHERE clause
+    pub fn where_group(self, group: ConditionGroup) -> Self {
+        self.where_cond(group.into())
+    }
+
+    /// Select child records with a relationship subquery
+    ///
+    /// ```
+    /// use salesforce_client::{QueryBuilder, SubqueryBuilder};
+    ///
+    /// let query = QueryBuilder::select(&["Id", "Name"])
+    ///     .from("Account")
+    ///     .with_subquery(SubqueryBuilder::new("Contacts", &["Id", "Email"]))
+    ///     .build();
+    ///
+    /// assert_eq!(query, "SELECT Id, Name, (SELECT Id, Email FROM Contacts) FROM Account");
+    /// ```
+    pub fn with_subquery(mut self, subquery: SubqueryBuilder) -> Self {
+        self.subqueries.push(subquery);
+        self
+    }
+
+    /// Select fields of a polymorphic relationship with a TYPEOF clause
+    pub fn with_typeof(mut self, typeof_clause: TypeofBuilder) -> Self {
+        self.typeofs.push(typeof_clause);
+        self
+    }
+
+    /// Add a semi-join condition, `field IN (SELECT ...)`
+    pub fn where_in_subquery(
+        self,
+        field: impl Into<String>,
+        query: QueryBuilder<Complete>,
+    ) -> Self {
+        self.where_cond(Condition::in_subquery(field, query))
+    }
+
+    /// Limit the query to a record scope with `USING SCOPE`
+    ///
+    /// ```
+    /// use salesforce_client::{QueryBuilder, Scope};
+    ///
+    /// let query = QueryBuilder::select(&["Id"])
+    ///     .from("Case")
+    ///     .using_scope(Scope::Mine)
+    ///     .where_clause("IsClosed = false")
+    ///     .build();
+    ///
+    /// assert_eq!(query, "SELECT Id FROM Case USING SCOPE mine WHERE IsClosed = false");
+    /// ```
+    pub fn using_scope(mut self, scope: Scope) -> Self {
+        if !self.scopes.contains(&scope) {
+            self.scopes.push(scope);
+        }
+        self
+    }
+
+    /// Enforce field- and object-level security with `WITH SECURITY_ENFORCED`
+    pub fn with_security_enforced(self) -> Self {
+        self.with_security_mode(SecurityMode::SecurityEnforced)
+    }
+
+    /// Run the query with the running user's permissions (`WITH USER_MODE`)
+    pub fn with_user_mode(self) -> Self {
+        self.with_security_mode(SecurityMode::UserMode)
+    }
+
+    /// Run the query in system context (`WITH SYSTEM_MODE`)
+    pub fn with_system_mode(self) -> Self {
+        self.with_security_mode(SecurityMode::SystemMode)
+    }
+
+    fn with_security_mode(mut self, mode: SecurityMode) -> Self {
+        if !self.security_modes.contains(&mode) {
+            self.security_modes.push(mode);
+        }
+        self
+    }
+
+    /// Filter Knowledge articles or questions by data category
+    ///
+    /// Repeated calls add filters joined with AND.
+    ///
+    /// ```
+    /// use salesforce_client::{QueryBuilder, Selector};
+    ///
+    /// let query = QueryBuilder::select(&["Id", "Title"])
+    ///     .from("KnowledgeArticleVersion")
+    ///     .where_clause("PublishStatus = 'Online'")
+    ///     .with_data_category("Geography__c", Selector::Above, "usa__c")
+    ///     .build();
+    ///
+    /// assert_eq!(
+    ///     query,
+    ///     "SELECT Id, Title FROM KnowledgeArticleVersion WHERE PublishStatus = 'Online' WITH DATA CATEGORY Geography__c ABOVE usa__c"
+    /// );
+    /// ```
+    pub fn with_data_category(
+        mut self,
+        group: impl Into<String>,
+        selector: Selector,
+        category: impl Into<String>,
+    ) -> Self {
+        self.data_categories
+            .push((group.into(), selector, category.into()));
+        self
+    }
+
+    /// Add a GROUP BY clause
+    pub fn group_by(mut self, fields: &[&str]) -> Self {
+        self.group_by = Some(GroupBy::Plain(to_strings(fields)));
+        self
+    }
+
+    /// Add a GROUP BY ROLLUP clause, adding subtotal rows
+    pub fn group_by_rollup(mut self, fields: &[&str]) -> Self {
+        self.group_by = Some(GroupBy::Rollup(to_strings(fields)));
+        self
+    }
+
+    /// Add a GROUP BY CUBE clause, adding subtotals for every field combination
+    pub fn group_by_cube(mut self, fields: &[&str]) -> Self {
+        self.group_by = Some(GroupBy::Cube(to_strings(fields)));
+        self
+    }
+
+    /// Add a HAVING clause filtering grouped rows
+    pub fn having(mut self, condition: impl Into<String>) -> Self {
+        self.having_clauses.push(Condition::raw(condition));
+        self
+    }
+
+    /// Add an AND condition to HAVING clause
+    pub fn and_having(mut self, condition: impl Into<String>) -> Self {
+        self.having_clauses.push(Condition::raw(condition));
+        self
+    }
+
+    /// Add a typed HAVING condition, e.g. `Condition::gt("COUNT(Id)", 10)`
+    pub fn having_cond(mut self, condition: Condition) -> Self {
+        self.having_clauses.push(condition);
         self
     }
 
@@ -109,22 +504,277 @@ impl QueryBuilder<Complete> {
         self
     }
 
+    /// Build the final SOQL query string, checking that the clauses combine
+    /// into a valid query
+    ///
+    /// Fails with [`SfError::InvalidQuery`] when:
+    /// - nothing is selected, a field is selected twice, a field name
+    ///   contains whitespace or quotes, or a field path is invalid
+    /// - LIMIT is 0 or OFFSET is over 2000
+    /// - more than one security mode or `USING SCOPE` is set
+    /// - `FIELDS(ALL)` or `FIELDS(CUSTOM)` is selected without a LIMIT of at
+    ///   most 200
+    /// - OFFSET is combined with GROUP BY ROLLUP
+    /// - HAVING is used without GROUP BY
+    /// - a typed condition is invalid, such as an empty IN list or an empty
+    ///   condition group
+    /// - a subquery is nested inside another subquery
+    /// - a TYPEOF clause has no WHEN branch, or is combined with GROUP BY
+    pub fn try_build(self) -> SfResult<String> {
+        self.validate()?;
+        Ok(self.build())
+    }
+
+    /// Check a query used as a semi-join subquery
+    pub(crate) fn validate_subquery(&self) -> SfResult<()> {
+        if !self.subqueries.is_empty() || self.where_clauses.iter().any(Condition::is_subquery) {
+            return Err(SfError::InvalidQuery(format!(
+                "Subqueries cannot be nested inside the {} subquery",
+                self.from.as_deref().unwrap_or_default()
+            )));
+        }
+
+        self.validate()
+    }
+
+    /// Check a query paged by ID, which must select `Id` and leave the
+    /// ORDER BY, LIMIT and OFFSET to the pager
+    ///
+    /// The rest is checked as sent, with `page_size` as its LIMIT.
+    pub(crate) fn validate_keyset(&self, page_size: u32) -> SfResult<()> {
+        let selects_id = self
+            .fields
+            .iter()
+            .any(|f| f.as_str().eq_ignore_ascii_case("Id"))
+            || matches!(
+                self.fields_spec,
+                Some(FieldsSpec::All | FieldsSpec::Standard)
+            );
+        if !selects_id {
+            return Err(SfError::InvalidQuery(
+                "Paging by ID requires Id in the SELECT list".to_string(),
+            ));
+        }
+
+        if let Some(order) = self.order_by.as_deref().filter(|order| {
+            !order.eq_ignore_ascii_case("Id") && !order.eq_ignore_ascii_case("Id ASC")
+        }) {
+            return Err(SfError::InvalidQuery(format!(
+                "Paging by ID orders by Id and cannot also ORDER BY {}",
+                order
+            )));
+        }
+
+        if self.limit.is_some() || self.offset.is_some() {
+            return Err(SfError::InvalidQuery(
+                "Paging by ID sets its own LIMIT and cannot be combined with LIMIT or OFFSET"
+                    .to_string(),
+            ));
+        }
+
+        if self.group_by.is_some() {
+            return Err(SfError::InvalidQuery(
+                "Paging by ID cannot be combined with GROUP BY".to_string(),
+            ));
+        }
+
+        if let Some(spec) = self.fields_spec.filter(|spec| spec.requires_limit()) {
+            if page_size > FIELDS_MAX_LIMIT {
+                return Err(SfError::InvalidQuery(format!(
+                    "Paging by ID with {} requires a page size of at most {}",
+                    spec.keyword(),
+                    FIELDS_MAX_LIMIT
+                )));
+            }
+        }
+
+        let mut paged = self.clone();
+        paged.limit = Some(page_size);
+        paged.validate()
+    }
+
+    fn validate(&self) -> SfResult<()> {
+        self.validate_select()?;
+
+        if self.limit == Some(0) {
+            return Err(SfError::InvalidQuery(
+                "LIMIT must be at least 1".to_string(),
+            ));
+        }
+
+        if let Some(offset) = self.offset.filter(|offset| *offset > MAX_OFFSET) {
+            return Err(SfError::InvalidQuery(format!(
+                "OFFSET {} exceeds the maximum of {}",
+                offset, MAX_OFFSET
+            )));
+        }
+
+        for typeof_clause in &self.typeofs {
+            if typeof_clause.branches.is_empty() {
+                return Err(SfError::InvalidQuery(format!(
+                    "TYPEOF {} requires at least one WHEN branch",
+                    typeof_clause.field
+                )));
+            }
+        }
+
+        if !self.typeofs.is_empty() && self.group_by.is_some() {
+            return Err(SfError::InvalidQuery(
+                "TYPEOF cannot be used with GROUP BY".to_string(),
+            ));
+        }
+
+        for condition in self.where_clauses.iter().chain(&self.having_clauses) {
+            condition.validate()?;
+        }
+
+        if !self.having_clauses.is_empty() && self.group_by.is_none() {
+            return Err(SfError::InvalidQuery(
+                "HAVING requires a GROUP BY clause".to_string(),
+            ));
+        }
+
+        if matches!(self.group_by, Some(GroupBy::Rollup(_))) && self.offset.is_some() {
+            return Err(SfError::InvalidQuery(
+                "OFFSET cannot be used with GROUP BY ROLLUP".to_string(),
+            ));
+        }
+
+        if let Some(spec) = self.fields_spec.filter(|spec| spec.requires_limit()) {
+            if !self.limit.is_some_and(|limit| limit <= FIELDS_MAX_LIMIT) {
+                return Err(SfError::InvalidQuery(format!(
+                    "{} requires a LIMIT of at most {}",
+                    spec.keyword(),
+                    FIELDS_MAX_LIMIT
+                )));
+            }
+        }
+
+        if self.security_modes.len() > 1 {
+            let modes: Vec<_> = self.security_modes.iter().map(|m| m.keyword()).collect();
+            return Err(SfError::InvalidQuery(format!(
+                "Only one security mode can be used per query, got {}",
+                modes.join(", ")
+            )));
+        }
+
+        if self.scopes.len() > 1 {
+            let scopes: Vec<_> = self.scopes.iter().map(|s| s.keyword()).collect();
+            return Err(SfError::InvalidQuery(format!(
+                "Only one USING SCOPE can be used per query, got {}",
+                scopes.join(", ")
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn validate_select(&self) -> SfResult<()> {
+        if self.exprs.is_empty()
+            && self.fields.is_empty()
+            && self.fields_spec.is_none()
+            && self.typeofs.is_empty()
+            && self.subqueries.is_empty()
+        {
+            return Err(SfError::InvalidQuery("SELECT list is empty".to_string()));
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for field_ref in &self.fields {
+            field_ref.validate()?;
+
+            let field = field_ref.as_str();
+            if field.is_empty()
+                || field
+                    .chars()
+                    .any(|c| c.is_whitespace() || matches!(c, '\'' | '"'))
+            {
+                return Err(SfError::InvalidQuery(format!(
+                    "Invalid field name {:?}: names cannot be empty or contain whitespace or quotes",
+                    field
+                )));
+            }
+
+            // Field names are case-insensitive
+            if !seen.insert(field.to_ascii_lowercase()) {
+                return Err(SfError::InvalidQuery(format!(
+                    "Field {} is selected more than once",
+                    field
+                )));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Build a [`Query`] tied to the record type it deserializes into
+    ///
+    /// Like [`build`](Self::build), no validation is done; see
+    /// [`try_build_typed`](Self::try_build_typed).
+    pub fn build_typed<T>(self) -> Query<T> {
+        Query::new(self.build())
+    }
+
+    /// Validate and build a [`Query`] tied to its record type
+    pub fn try_build_typed<T>(self) -> SfResult<Query<T>> {
+        self.try_build().map(Query::new)
+    }
+
     /// Build the final SOQL query string
+    ///
+    /// No validation is done; use [`try_build`](Self::try_build) to catch
+    /// clause combinations Salesforce would reject.
     pub fn build(self) -> String {
+        self.render()
+    }
+
+    pub(crate) fn render(&self) -> String {
         let mut query = format!(
             "SELECT {} FROM {}",
-            self.fields.join(", "),
-            self.from.unwrap() // Safe because Complete state guarantees from is set
+            self.select_list(),
+            self.from.as_deref().unwrap() // Safe because Complete state guarantees from is set
         );
 
+        for scope in &self.scopes {
+            query.push_str(" USING SCOPE ");
+            query.push_str(scope.keyword());
+        }
+
         if !self.where_clauses.is_empty() {
             query.push_str(" WHERE ");
-            query.push_str(&self.where_clauses.join(" AND "));
+            query.push_str(&join_conditions(&self.where_clauses));
+        }
+
+        for mode in &self.security_modes {
+            query.push_str(" WITH ");
+            query.push_str(mode.keyword());
+        }
+
+        if !self.data_categories.is_empty() {
+            let filters: Vec<_> = self
+                .data_categories
+                .iter()
+                .map(|(group, selector, category)| {
+                    format!("{} {} {}", group, selector.keyword(), category)
+                })
+                .collect();
+            query.push_str(" WITH DATA CATEGORY ");
+            query.push_str(&filters.join(" AND "));
+        }
+
+        if let Some(group_by) = &self.group_by {
+            query.push_str(" GROUP BY ");
+            query.push_str(&group_by.render());
         }
 
-        if let Some(order) = self.order_by {
+        if !self.having_clauses.is_empty() {
+            query.push_str(" HAVING ");
+            query.push_str(&join_conditions(&self.having_clauses));
+        }
+
+        if let Some(order) = &self.order_by {
             query.push_str(" ORDER BY ");
-            query.push_str(&order);
+            query.push_str(order);
         }
 
         if let Some(limit) = self.limit {
@@ -139,9 +789,25 @@ impl QueryBuilder<Complete> {
     }
 }
 
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|s| s.to_string()).collect()
+}
+
+fn join_conditions(conditions: &[Condition]) -> String {
+    match conditions {
+        [condition] => condition.to_string(),
+        _ => conditions
+            .iter()
+            .map(Condition::to_operand)
+            .collect::<Vec<_>>()
+            .join(" AND "),
+    }
+}
+
 /// Fluent API for building COUNT queries
 pub struct CountQueryBuilder {
     from: String,
+    count_field: Option<String>,
     where_clauses: Vec<String>,
 }
 
@@ -150,10 +816,24 @@ impl CountQueryBuilder {
     pub fn count_from(sobject: impl Into<String>) -> Self {
         Self {
             from: sobject.into(),
+            count_field: None,
             where_clauses: Vec::new(),
         }
     }
 
+    /// Count non-null values of a field with `COUNT(field)` instead of `COUNT()`
+    ///
+    /// `COUNT(field)` comes back as an aggregate row rather than `totalSize`.
+    pub fn count_field(mut self, field: impl Into<String>) -> Self {
+        self.count_field = Some(field.into());
+        self
+    }
+
+    /// Whether this counts a field, returning an aggregate row
+    pub(crate) fn is_field_count(&self) -> bool {
+        self.count_field.is_some()
+    }
+
     /// Add a WHERE clause
     pub fn where_clause(mut self, condition: impl Into<String>) -> Self {
         self.where_clauses.push(condition.into());
@@ -162,7 +842,11 @@ impl CountQueryBuilder {
 
     /// Build the query
     pub fn build(self) -> String {
-        let mut query = format!("SELECT COUNT() FROM {}", self.from);
+        let mut query = format!(
+            "SELECT COUNT({}) FROM {}",
+            self.count_field.as_deref().unwrap_or_default(),
+            self.from
+        );
 
         if !self.where_clauses.is_empty() {
             query.push_str(" WHERE ");
@@ -173,7 +857,86 @@ impl CountQueryBuilder {
     }
 }
 
+/// A SOQL query paired with the record type it returns
+///
+/// Run it with [`SalesforceClient::run`](crate::SalesforceClient::run).
+/// Queries are cheap to clone and can be kept and reused; equal queries
+/// share client cache entries.
+///
+/// # Example
+/// ```
+/// use salesforce_client::{Query, QueryBuilder};
+///
+/// struct Account;
+///
+/// let query: Query<Account> = QueryBuilder::select(&["Id", "Name"])
+///     .from("Account")
+///     .build_typed();
+///
+/// assert_eq!(query.to_string(), "SELECT Id, Name FROM Account");
+/// ```
+pub struct Query<T> {
+    soql: String,
+    _record: PhantomData<fn() -> T>,
+}
+
+impl<T> Query<T> {
+    /// Wrap a hand-written SOQL string
+    pub fn new(soql: impl Into<String>) -> Self {
+        Self {
+            soql: soql.into(),
+            _record: PhantomData,
+        }
+    }
+
+    /// The SOQL text
+    pub fn as_str(&self) -> &str {
+        &self.soql
+    }
+}
+
+// Manual impls so `T` itself needn't implement these traits
+
+impl<T> Clone for Query<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.soql.clone())
+    }
+}
+
+impl<T> std::fmt::Debug for Query<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Query").field(&self.soql).finish()
+    }
+}
+
+impl<T> std::fmt::Display for Query<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.soql)
+    }
+}
+
+impl<T> PartialEq for Query<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.soql == other.soql
+    }
+}
+
+impl<T> Eq for Query<T> {}
+
+impl<T> std::hash::Hash for Query<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.soql.hash(state);
+    }
+}
+
+impl<T> AsRef<str> for Query<T> {
+    fn as_ref(&self) -> &str {
+        &self.soql
+    }
+}
+
 /// Helper for building subqueries
+#[derive(Debug, Clone)]
 pub struct SubqueryBuilder {
     fields: Vec<String>,
     relationship: String,
@@ -186,7 +949,7 @@ impl SubqueryBuilder {
     /// Create a new subquery builder
     pub fn new(relationship: impl Into<String>, fields: &[&str]) -> Self {
         Self {
-            fields: fields.iter().map(|s| s.to_string()).collect(),
+            fields: to_strings(fields),
             relationship: relationship.into(),
             where_clauses: Vec::new(),
             order_by: None,
@@ -214,6 +977,10 @@ impl SubqueryBuilder {
 
     /// Build the subquery string (for use in parent query)
     pub fn build(self) -> String {
+        self.render()
+    }
+
+    fn render(&self) -> String {
         let mut query = format!(
             "(SELECT {} FROM {}",
             self.fields.join(", "),
@@ -225,9 +992,9 @@ impl SubqueryBuilder {
             query.push_str(&self.where_clauses.join(" AND "));
         }
 
-        if let Some(order) = self.order_by {
+        if let Some(order) = &self.order_by {
             query.push_str(" ORDER BY ");
-            query.push_str(&order);
+            query.push_str(order);
         }
 
         if let Some(limit) = self.limit {
@@ -239,6 +1006,73 @@ impl SubqueryBuilder {
     }
 }
 
+/// Builder for `TYPEOF` clauses on polymorphic relationship fields
+///
+/// # Example
+/// ```
+/// use salesforce_client::{QueryBuilder, TypeofBuilder};
+///
+/// let query = QueryBuilder::select(&["Id"])
+///     .from("Task")
+///     .with_typeof(
+///         TypeofBuilder::new("What")
+///             .when("Account", &["Name"])
+///             .when("Opportunity", &["Amount"])
+///             .else_fields(&["Id"]),
+///     )
+///     .build();
+///
+/// assert_eq!(
+///     query,
+///     "SELECT Id, TYPEOF What WHEN Account THEN Name WHEN Opportunity THEN Amount ELSE Id END FROM Task"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct TypeofBuilder {
+    field: String,
+    branches: Vec<(String, Vec<String>)>,
+    else_fields: Vec<String>,
+}
+
+impl TypeofBuilder {
+    /// Start a TYPEOF clause for a polymorphic relationship, e.g. `What`
+    pub fn new(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            branches: Vec::new(),
+            else_fields: Vec::new(),
+        }
+    }
+
+    /// Fields to select when the related record is of `sobject` type
+    pub fn when(mut self, sobject: impl Into<String>, fields: &[&str]) -> Self {
+        self.branches.push((sobject.into(), to_strings(fields)));
+        self
+    }
+
+    /// Fields to select for any other type
+    pub fn else_fields(mut self, fields: &[&str]) -> Self {
+        self.else_fields = to_strings(fields);
+        self
+    }
+
+    fn render(&self) -> String {
+        let mut clause = format!("TYPEOF {}", self.field);
+
+        for (sobject, fields) in &self.branches {
+            clause.push_str(&format!(" WHEN {} THEN {}", sobject, fields.join(", ")));
+        }
+
+        if !self.else_fields.is_empty() {
+            clause.push_str(" ELSE ");
+            clause.push_str(&self.else_fields.join(", "));
+        }
+
+        clause.push_str(" END");
+        clause
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -307,6 +1141,519 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_security_mode_placement() {
+        let query = QueryBuilder::select(&["Id", "Name"])
+            .from("Account")
+            .with_security_enforced()
+            .build();
+        assert_eq!(query, "SELECT Id, Name FROM Account WITH SECURITY_ENFORCED");
+
+        let query = QueryBuilder::select(&["Id", "Name"])
+            .from("Account")
+            .order_by("Name")
+            .with_user_mode()
+            .where_clause("Industry = 'Technology'")
+            .limit(10)
+            .try_build()
+            .unwrap();
+        assert_eq!(
+            query,
+            "SELECT Id, Name FROM Account WHERE Industry = 'Technology' WITH USER_MODE ORDER BY Name LIMIT 10"
+        );
+
+        let query = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .with_system_mode()
+            .order_by_desc("CreatedDate")
+            .try_build()
+            .unwrap();
+        assert_eq!(
+            query,
+            "SELECT Id FROM Account WITH SYSTEM_MODE ORDER BY CreatedDate DESC"
+        );
+    }
+
+    #[test]
+    fn test_using_scope() {
+        let query = QueryBuilder::select(&["Id", "Subject"])
+            .from("Case")
+            .where_clause("IsClosed = false")
+            .order_by("CreatedDate")
+            .using_scope(Scope::Team)
+            .try_build()
+            .unwrap();
+        assert_eq!(
+            query,
+            "SELECT Id, Subject FROM Case USING SCOPE team WHERE IsClosed = false ORDER BY CreatedDate"
+        );
+
+        let query = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .using_scope(Scope::MineAndMyGroups)
+            .order_by("Name")
+            .build();
+        assert_eq!(
+            query,
+            "SELECT Id FROM Account USING SCOPE mine_and_my_groups ORDER BY Name"
+        );
+    }
+
+    #[test]
+    fn test_conflicting_scopes() {
+        let message = invalid_query_message(
+            QueryBuilder::select(&["Id"])
+                .from("Account")
+                .using_scope(Scope::Mine)
+                .using_scope(Scope::Delegated)
+                .try_build(),
+        );
+        assert_eq!(
+            message,
+            "Only one USING SCOPE can be used per query, got mine, delegated"
+        );
+
+        let query = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .using_scope(Scope::Everything)
+            .using_scope(Scope::Everything)
+            .try_build()
+            .unwrap();
+        assert_eq!(query, "SELECT Id FROM Account USING SCOPE everything");
+    }
+
+    #[test]
+    fn test_conflicting_security_modes() {
+        let result = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .with_security_enforced()
+            .with_user_mode()
+            .try_build();
+        assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+
+        // Repeating the same mode is not a conflict
+        let query = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .with_user_mode()
+            .with_user_mode()
+            .try_build()
+            .unwrap();
+        assert_eq!(query, "SELECT Id FROM Account WITH USER_MODE");
+    }
+
+    #[test]
+    fn test_fields_spec() {
+        let query = QueryBuilder::select_fields(FieldsSpec::All)
+            .from("Account")
+            .limit(200)
+            .try_build()
+            .unwrap();
+        assert_eq!(query, "SELECT FIELDS(ALL) FROM Account LIMIT 200");
+
+        let query = QueryBuilder::select_fields(FieldsSpec::Standard)
+            .add_fields(&["My_Custom__c"])
+            .from("Account")
+            .try_build()
+            .unwrap();
+        assert_eq!(query, "SELECT FIELDS(STANDARD), My_Custom__c FROM Account");
+
+        let query = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .add_fields(&["Name", "Owner.Name"])
+            .build();
+        assert_eq!(query, "SELECT Id, Name, Owner.Name FROM Account");
+    }
+
+    #[test]
+    fn test_fields_spec_requires_limit() {
+        let missing = QueryBuilder::select_fields(FieldsSpec::Custom)
+            .from("Account")
+            .try_build();
+        assert!(matches!(missing, Err(SfError::InvalidQuery(_))));
+
+        let too_large = QueryBuilder::select_fields(FieldsSpec::All)
+            .from("Account")
+            .limit(201)
+            .try_build();
+        assert!(matches!(too_large, Err(SfError::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn test_group_by_forms() {
+        let base = || {
+            QueryBuilder::select_exprs(&[
+                ("Industry", None),
+                ("Type", None),
+                ("COUNT(Id)", Some("cnt")),
+            ])
+            .from("Account")
+            .where_clause("IsDeleted = false")
+        };
+
+        assert_eq!(
+            base().group_by(&["Industry", "Type"]).order_by("Industry").limit(5).build(),
+            "SELECT Industry, Type, COUNT(Id) cnt FROM Account WHERE IsDeleted = false GROUP BY Industry, Type ORDER BY Industry LIMIT 5"
+        );
+        assert_eq!(
+            base().group_by_rollup(&["Industry", "Type"]).build(),
+            "SELECT Industry, Type, COUNT(Id) cnt FROM Account WHERE IsDeleted = false GROUP BY ROLLUP(Industry, Type)"
+        );
+        assert_eq!(
+            base().with_user_mode().group_by_cube(&["Industry", "Type"]).offset(10).try_build().unwrap(),
+            "SELECT Industry, Type, COUNT(Id) cnt FROM Account WHERE IsDeleted = false WITH USER_MODE GROUP BY CUBE(Industry, Type) OFFSET 10"
+        );
+    }
+
+    #[test]
+    fn test_rollup_rejects_offset() {
+        let result = QueryBuilder::select_exprs(&[("Industry", None), ("COUNT(Id)", None)])
+            .from("Account")
+            .group_by_rollup(&["Industry"])
+            .offset(10)
+            .try_build();
+        assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn test_having_clause_ordering() {
+        let query = QueryBuilder::select_exprs(&[("Industry", None), ("COUNT(Id)", Some("cnt"))])
+            .from("Account")
+            .limit(10)
+            .order_by_desc("COUNT(Id)")
+            .having("COUNT(Id) > 10")
+            .and_having("SUM(AnnualRevenue) > 1000000")
+            .group_by(&["Industry"])
+            .where_clause("Industry != null")
+            .try_build()
+            .unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT Industry, COUNT(Id) cnt FROM Account WHERE Industry != null GROUP BY Industry HAVING COUNT(Id) > 10 AND SUM(AnnualRevenue) > 1000000 ORDER BY COUNT(Id) DESC LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn test_having_requires_group_by() {
+        let result = QueryBuilder::select(&["COUNT(Id)"])
+            .from("Account")
+            .having("COUNT(Id) > 10")
+            .try_build();
+        assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn test_raw_or_parenthesized_when_joined() {
+        let query = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .where_clause("Industry = 'Tech' OR Industry = 'Media'")
+            .and_cond(Condition::gt("AnnualRevenue", 1_000_000))
+            .build();
+        assert_eq!(
+            query,
+            "SELECT Id FROM Account WHERE (Industry = 'Tech' OR Industry = 'Media') AND AnnualRevenue > 1000000"
+        );
+
+        // ORs inside literals or parentheses don't need wrapping
+        let query = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .where_clause("Name = 'Salt OR Pepper'")
+            .where_clause("(Type = 'A' or Type = 'B')")
+            .where_clause("Orders__c > 0")
+            .build();
+        assert_eq!(
+            query,
+            "SELECT Id FROM Account WHERE Name = 'Salt OR Pepper' AND (Type = 'A' or Type = 'B') AND Orders__c > 0"
+        );
+
+        // A lone raw clause is left as written
+        let query = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .where_clause("Industry = 'Tech' OR Industry = 'Media'")
+            .build();
+        assert!(query.ends_with("WHERE Industry = 'Tech' OR Industry = 'Media'"));
+    }
+
+    #[test]
+    fn test_typed_conditions() {
+        let query = QueryBuilder::select(&["Id", "Name"])
+            .from("Account")
+            .where_clause("IsDeleted = false")
+            .and_cond(Condition::eq("Name", r"Smith\'s"))
+            .and_cond(Condition::in_list("Industry", ["Energy", "Media"]))
+            .try_build()
+            .unwrap();
+
+        assert_eq!(
+            query,
+            r"SELECT Id, Name FROM Account WHERE IsDeleted = false AND Name = 'Smith\\\'s' AND Industry IN ('Energy', 'Media')"
+        );
+
+        let query = QueryBuilder::select_exprs(&[("Industry", None), ("COUNT(Id)", None)])
+            .from("Account")
+            .group_by(&["Industry"])
+            .having_cond(Condition::gt("COUNT(Id)", 10))
+            .build();
+        assert_eq!(
+            query,
+            "SELECT Industry, COUNT(Id) FROM Account GROUP BY Industry HAVING COUNT(Id) > 10"
+        );
+    }
+
+    #[test]
+    fn test_empty_in_list_fails_try_build() {
+        let ids: Vec<&str> = Vec::new();
+        let result = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .where_cond(Condition::in_list("Id", ids))
+            .try_build();
+        assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn test_subqueries() {
+        let query = QueryBuilder::select(&["Id", "Name"])
+            .from("Account")
+            .with_subquery(SubqueryBuilder::new("Contacts", &["Id", "Email"]).limit(5))
+            .with_subquery(
+                SubqueryBuilder::new("Opportunities", &["Id", "Amount"])
+                    .where_clause("IsClosed = false"),
+            )
+            .where_in_subquery(
+                "Id",
+                QueryBuilder::select(&["AccountId"])
+                    .from("Case")
+                    .where_clause("Status = 'Escalated'"),
+            )
+            .try_build()
+            .unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT Id, Name, (SELECT Id, Email FROM Contacts LIMIT 5), (SELECT Id, Amount FROM Opportunities WHERE IsClosed = false) FROM Account WHERE Id IN (SELECT AccountId FROM Case WHERE Status = 'Escalated')"
+        );
+    }
+
+    #[test]
+    fn test_nested_subquery_rejected() {
+        let inner = QueryBuilder::select(&["AccountId"])
+            .from("Contact")
+            .where_in_subquery(
+                "Id",
+                QueryBuilder::select(&["ContactId"]).from("CaseContactRole"),
+            );
+        let result = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .where_in_subquery("Id", inner)
+            .try_build();
+        assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+
+        let inner = QueryBuilder::select(&["AccountId"])
+            .from("Contact")
+            .with_subquery(SubqueryBuilder::new("Cases", &["Id"]));
+        let result = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .where_in_subquery("Id", inner)
+            .try_build();
+        assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn test_typeof_clause() {
+        let query = QueryBuilder::select(&["Id", "Subject"])
+            .from("Event")
+            .with_typeof(
+                TypeofBuilder::new("Who")
+                    .when("Contact", &["FirstName", "LastName"])
+                    .when("Lead", &["Company"]),
+            )
+            .where_clause("IsChild = false")
+            .try_build()
+            .unwrap();
+
+        assert_eq!(
+            query,
+            "SELECT Id, Subject, TYPEOF Who WHEN Contact THEN FirstName, LastName WHEN Lead THEN Company END FROM Event WHERE IsChild = false"
+        );
+    }
+
+    #[test]
+    fn test_typeof_validation() {
+        let no_branches = QueryBuilder::select(&["Id"])
+            .from("Task")
+            .with_typeof(TypeofBuilder::new("What").else_fields(&["Id"]))
+            .try_build();
+        assert!(matches!(no_branches, Err(SfError::InvalidQuery(_))));
+
+        let grouped = QueryBuilder::select(&["Subject"])
+            .from("Task")
+            .with_typeof(TypeofBuilder::new("What").when("Account", &["Name"]))
+            .group_by(&["Subject"])
+            .try_build();
+        assert!(matches!(grouped, Err(SfError::InvalidQuery(_))));
+    }
+
+    fn invalid_query_message(result: SfResult<String>) -> String {
+        match result {
+            Err(SfError::InvalidQuery(message)) => message,
+            other => panic!("expected InvalidQuery, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_try_build_select_validation() {
+        assert_eq!(
+            invalid_query_message(
+                QueryBuilder::select(Vec::<&str>::new())
+                    .from("Account")
+                    .try_build()
+            ),
+            "SELECT list is empty"
+        );
+        assert_eq!(
+            invalid_query_message(
+                QueryBuilder::select(&["Id", "Name", "id"])
+                    .from("Account")
+                    .try_build()
+            ),
+            "Field id is selected more than once"
+        );
+        assert_eq!(
+            invalid_query_message(
+                QueryBuilder::select(&["Id", "Name FROM User"])
+                    .from("Account")
+                    .try_build()
+            ),
+            r#"Invalid field name "Name FROM User": names cannot be empty or contain whitespace or quotes"#
+        );
+        assert_eq!(
+            invalid_query_message(QueryBuilder::select(&["Na'me"]).from("Account").try_build()),
+            r#"Invalid field name "Na'me": names cannot be empty or contain whitespace or quotes"#
+        );
+
+        // Aliased expressions may contain spaces
+        assert!(QueryBuilder::select_exprs(&[("COUNT(Id)", Some("cnt"))])
+            .from("Account")
+            .try_build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_try_build_limit_and_offset() {
+        assert_eq!(
+            invalid_query_message(
+                QueryBuilder::select(&["Id"])
+                    .from("Account")
+                    .limit(0)
+                    .try_build()
+            ),
+            "LIMIT must be at least 1"
+        );
+        assert_eq!(
+            invalid_query_message(
+                QueryBuilder::select(&["Id"])
+                    .from("Account")
+                    .offset(2001)
+                    .try_build()
+            ),
+            "OFFSET 2001 exceeds the maximum of 2000"
+        );
+
+        let query = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .offset(2000)
+            .try_build()
+            .unwrap();
+        assert_eq!(query, "SELECT Id FROM Account OFFSET 2000");
+
+        // build() stays unvalidated
+        assert_eq!(
+            QueryBuilder::select(Vec::<&str>::new())
+                .from("Account")
+                .limit(0)
+                .build(),
+            "SELECT  FROM Account LIMIT 0"
+        );
+    }
+
+    #[test]
+    fn test_data_category_filters() {
+        let query = QueryBuilder::select(&["Id", "Title"])
+            .from("FAQ__kav")
+            .with_data_category("Geography__c", Selector::At, "europe__c")
+            .order_by("Title")
+            .build();
+        assert_eq!(
+            query,
+            "SELECT Id, Title FROM FAQ__kav WITH DATA CATEGORY Geography__c AT europe__c ORDER BY Title"
+        );
+
+        let query = QueryBuilder::select_exprs(&[("Language", None), ("COUNT(Id)", None)])
+            .from("KnowledgeArticleVersion")
+            .group_by(&["Language"])
+            .with_data_category("Product__c", Selector::AboveOrBelow, "mobile__c")
+            .where_clause("PublishStatus = 'Online'")
+            .with_data_category("Geography__c", Selector::Below, "usa__c")
+            .with_security_enforced()
+            .limit(10)
+            .try_build()
+            .unwrap();
+        assert_eq!(
+            query,
+            "SELECT Language, COUNT(Id) FROM KnowledgeArticleVersion WHERE PublishStatus = 'Online' WITH SECURITY_ENFORCED WITH DATA CATEGORY Product__c ABOVE_OR_BELOW mobile__c AND Geography__c BELOW usa__c GROUP BY Language LIMIT 10"
+        );
+    }
+
+    #[test]
+    fn test_typed_query() {
+        struct Account;
+
+        let query = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .limit(5)
+            .build_typed::<Account>();
+        assert_eq!(query.as_str(), "SELECT Id FROM Account LIMIT 5");
+        assert_eq!(
+            query.clone(),
+            Query::<Account>::new("SELECT Id FROM Account LIMIT 5")
+        );
+
+        let invalid = QueryBuilder::select(&["Id"])
+            .from("Account")
+            .limit(0)
+            .try_build_typed::<Account>();
+        assert!(matches!(invalid, Err(SfError::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn test_field_paths_in_select() {
+        use crate::soql::FieldPath;
+
+        let query = QueryBuilder::select([
+            FieldRef::from("Id"),
+            FieldPath::new("Account").field("Name").into(),
+        ])
+        .from("Contact")
+        .add_fields([FieldPath::new("ReportsTo").child("Owner").field("Email")])
+        .order_by(FieldPath::new("Account").field("Name"))
+        .try_build()
+        .unwrap();
+        assert_eq!(
+            query,
+            "SELECT Id, Account.Name, ReportsTo.Owner.Email FROM Contact ORDER BY Account.Name"
+        );
+
+        let too_deep = FieldPath::new("A")
+            .child("B")
+            .child("C")
+            .child("D")
+            .child("E")
+            .child("F")
+            .field("Name");
+        let result = QueryBuilder::select([too_deep]).from("Contact").try_build();
+        assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+    }
+
     #[test]
     fn test_count_query() {
         let query = CountQueryBuilder::count_from("Account")
@@ -317,6 +1664,15 @@ mod tests {
             query,
             "SELECT COUNT() FROM Account WHERE AnnualRevenue > 1000000"
         );
+
+        let query = CountQueryBuilder::count_from("Contact")
+            .count_field("Email")
+            .where_clause("AccountId != null")
+            .build();
+        assert_eq!(
+            query,
+            "SELECT COUNT(Email) FROM Contact WHERE AccountId != null"
+        );
     }
 
     #[test]
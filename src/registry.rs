@@ -0,0 +1,242 @@
+//! Multi-org client registry
+//!
+//! Keeps one [`SalesforceClient`] per org, each with its own token manager,
+//! cache and rate limiter, created lazily on first use.
+
+use crate::auth::OAuthCredentials;
+use crate::error::SfResult;
+use crate::{ClientConfig, SalesforceClient};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+use tracing::info;
+
+type Slot = Arc<OnceCell<SalesforceClient>>;
+
+/// Registry of clients keyed by an org identifier
+///
+/// Cloning the registry is cheap and all clones share the same clients.
+/// Concurrent first use of a key performs the OAuth login only once; the
+/// other callers wait for it and receive the same client. If construction
+/// fails the key stays empty and the next call tries again.
+///
+/// # Example
+/// ```no_run
+/// use salesforce_client::{ClientConfig, ClientRegistry, OAuthCredentials};
+///
+/// # async fn example() -> Result<(), salesforce_client::SfError> {
+/// let registry = ClientRegistry::new(ClientConfig::new("", ""));
+///
+/// let acme = registry
+///     .get_or_create("acme", OAuthCredentials::client_credentials("id", "secret"))
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct ClientRegistry {
+    config: ClientConfig,
+    clients: Arc<Mutex<HashMap<String, Slot>>>,
+}
+
+impl ClientRegistry {
+    /// Create a registry whose clients are built from `config`
+    ///
+    /// `config.base_url` is replaced by each org's instance URL.
+    pub fn new(config: ClientConfig) -> Self {
+        Self {
+            config,
+            clients: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Get the client for `key`, logging in with `credentials` on first use
+    pub async fn get_or_create(
+        &self,
+        key: &str,
+        credentials: OAuthCredentials,
+    ) -> SfResult<SalesforceClient> {
+        let config = self.config.clone();
+        self.get_or_create_with(key, || {
+            SalesforceClient::with_oauth_config(config, credentials)
+        })
+        .await
+    }
+
+    /// Get the client for `key`, building it with `create` on first use
+    ///
+    /// Use this when clients need a custom `TokenManager` (token store,
+    /// auth URL, ...).
+    pub async fn get_or_create_with<F, Fut>(
+        &self,
+        key: &str,
+        create: F,
+    ) -> SfResult<SalesforceClient>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = SfResult<SalesforceClient>>,
+    {
+        let slot = self.slot(key);
+
+        let client = slot
+            .get_or_try_init(|| async {
+                info!("Creating Salesforce client for org {}", key);
+                create().await
+            })
+            .await?;
+
+        Ok(client.clone())
+    }
+
+    /// Get the client for `key` if it has already been created
+    pub fn get(&self, key: &str) -> Option<SalesforceClient> {
+        let clients = self.lock();
+        clients.get(key).and_then(|slot| slot.get().cloned())
+    }
+
+    /// Remove the client for `key`, returning it if it existed
+    ///
+    /// The next `get_or_create` for the key logs in again.
+    pub fn remove(&self, key: &str) -> Option<SalesforceClient> {
+        let mut clients = self.lock();
+        clients.remove(key).and_then(|slot| slot.get().cloned())
+    }
+
+    /// Keys of all clients that have been created
+    pub fn keys(&self) -> Vec<String> {
+        let clients = self.lock();
+        clients
+            .iter()
+            .filter(|(_, slot)| slot.initialized())
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
+
+    /// Number of clients that have been created
+    pub fn len(&self) -> usize {
+        let clients = self.lock();
+        clients.values().filter(|slot| slot.initialized()).count()
+    }
+
+    /// Whether no clients have been created yet
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn slot(&self, key: &str) -> Slot {
+        let mut clients = self.lock();
+        clients.entry(key.to_string()).or_default().clone()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Slot>> {
+        // The map is only touched in short non-panicking sections
+        self.clients
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CacheConfig, RetryConfig, TokenManager};
+
+    fn assert_send_sync<T: Send + Sync + Clone>() {}
+
+    async fn create_client(auth_url: String) -> SfResult<SalesforceClient> {
+        let manager = TokenManager::new(OAuthCredentials::client_credentials("id", "secret"))
+            .with_auth_url_unchecked(auth_url);
+        let config = ClientConfig::new("", "")
+            .with_cache(CacheConfig::disabled())
+            .with_retry(RetryConfig::no_retry());
+        SalesforceClient::with_token_manager(config, manager).await
+    }
+
+    fn token_body(server_url: &str) -> String {
+        format!(
+            r#"{{"access_token":"token","instance_url":"{}","token_type":"Bearer"}}"#,
+            server_url
+        )
+    }
+
+    #[test]
+    fn test_registry_is_send_sync_clone() {
+        assert_send_sync::<ClientRegistry>();
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_first_use_fetches_once() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/services/oauth2/token")
+            .with_body(token_body(&server.url()))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let registry = ClientRegistry::new(ClientConfig::new("", ""));
+        let tasks: Vec<_> = (0..2)
+            .map(|_| {
+                let registry = registry.clone();
+                let auth_url = server.url();
+                tokio::spawn(async move {
+                    registry
+                        .get_or_create_with("acme", || async move {
+                            // Give the other task time to arrive while we log in
+                            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                            create_client(auth_url).await
+                        })
+                        .await
+                })
+            })
+            .collect();
+
+        for task in tasks {
+            let client = task.await.unwrap().unwrap();
+            assert_eq!(client.config().base_url, server.url());
+        }
+
+        assert_eq!(registry.len(), 1);
+        assert_eq!(registry.keys(), vec!["acme".to_string()]);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_failed_creation_is_retried() {
+        let mut server = mockito::Server::new_async().await;
+        let failure = server
+            .mock("POST", "/services/oauth2/token")
+            .with_status(400)
+            .with_body(r#"{"error":"invalid_client"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let registry = ClientRegistry::new(ClientConfig::new("", ""));
+        assert!(registry
+            .get_or_create_with("acme", || create_client(server.url()))
+            .await
+            .is_err());
+        assert!(registry.get("acme").is_none());
+        assert!(registry.is_empty());
+        failure.assert_async().await;
+
+        let success = server
+            .mock("POST", "/services/oauth2/token")
+            .with_body(token_body(&server.url()))
+            .expect(1)
+            .create_async()
+            .await;
+
+        registry
+            .get_or_create_with("acme", || create_client(server.url()))
+            .await
+            .unwrap();
+        assert!(registry.get("acme").is_some());
+        success.assert_async().await;
+
+        assert!(registry.remove("acme").is_some());
+        assert!(registry.is_empty());
+    }
+}
@@ -2,7 +2,7 @@
 //!
 //! Automatically retries failed requests with intelligent backoff strategies.
 
-use crate::error::{SfError, SfResult};
+use crate::error::{ErrorCode, SfError, SfResult};
 // Retry logic implementation without backoff crate due to lifetime issues
 use std::time::Duration;
 use tracing::{debug, warn};
@@ -83,6 +83,9 @@ pub(crate) fn is_retryable(error: &SfError) -> bool {
         // Timeout is retryable
         SfError::Timeout { .. } => true,
 
+        // Row locks clear once the other transaction commits
+        SfError::Api { .. } if error.error_codes().iter().any(ErrorCode::is_row_lock) => true,
+
         // API errors: only retry on specific status codes
         SfError::Api { status, .. } => {
             matches!(
@@ -198,6 +201,18 @@ mod tests {
             status: 400,
             body: "Bad Request".to_string()
         }));
+        assert!(!is_retryable(&SfError::Api {
+            status: 400,
+            body: r#"[{"errorCode":"REQUIRED_FIELD_MISSING","message":"Required fields are missing: [Name]"}]"#
+                .to_string()
+        }));
+
+        // Row lock errors come back as 400 but are transient
+        assert!(is_retryable(&SfError::Api {
+            status: 400,
+            body: r#"[{"errorCode":"UNABLE_TO_LOCK_ROW","message":"unable to obtain exclusive access to this record"}]"#
+                .to_string()
+        }));
     }
 
     #[tokio::test]
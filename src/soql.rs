@@ -0,0 +1,1124 @@
+//! Typed SOQL values and conditions
+//!
+//! [`Condition`] builds WHERE expressions whose values are rendered as
+//! [`SoqlValue`]s, so strings are quoted and escaped and dates are written
+//! in the literal formats SOQL expects. [`DateLiteral`] covers relative
+//! date tokens such as `LAST_N_DAYS:30`.
+//!
+//! For queries still written as strings, [`escape_string`], [`quote`] and
+//! [`escape_like_pattern`] make user input safe to splice into a literal.
+
+use crate::error::{SfError, SfResult};
+use crate::query_builder::{Complete, QueryBuilder};
+use chrono::{DateTime, NaiveDate, Utc};
+use std::fmt;
+
+/// A literal value in a SOQL expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum SoqlValue {
+    /// Text, rendered quoted and escaped
+    String(String),
+
+    /// Record ID, rendered quoted
+    Id(String),
+
+    /// Whole number
+    Integer(i64),
+
+    /// Decimal number
+    Decimal(f64),
+
+    /// `true` / `false`
+    Boolean(bool),
+
+    /// Date, rendered unquoted as `YYYY-MM-DD`
+    Date(NaiveDate),
+
+    /// Date and time, rendered unquoted as `YYYY-MM-DDThh:mm:ssZ`
+    DateTime(DateTime<Utc>),
+
+    /// Relative date such as `TODAY` or `LAST_N_DAYS:30`, rendered unquoted
+    DateLiteral(DateLiteral),
+
+    /// `null`
+    Null,
+}
+
+impl SoqlValue {
+    /// A record ID value
+    pub fn id(id: impl Into<String>) -> Self {
+        SoqlValue::Id(id.into())
+    }
+
+    fn validate(&self) -> SfResult<()> {
+        match self {
+            SoqlValue::DateLiteral(literal) => literal.validate(),
+            // NaN and infinities have no SOQL literal
+            SoqlValue::Decimal(n) if !n.is_finite() => Err(SfError::InvalidQuery(format!(
+                "{} is not a valid SOQL number",
+                n
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for SoqlValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SoqlValue::String(s) | SoqlValue::Id(s) => f.write_str(&quote(s)),
+            SoqlValue::Integer(n) => write!(f, "{}", n),
+            SoqlValue::Decimal(n) => write!(f, "{}", n),
+            SoqlValue::Boolean(b) => write!(f, "{}", b),
+            SoqlValue::Date(date) => write!(f, "{}", date.format("%Y-%m-%d")),
+            SoqlValue::DateTime(datetime) => {
+                write!(f, "{}", datetime.format("%Y-%m-%dT%H:%M:%SZ"))
+            }
+            SoqlValue::DateLiteral(literal) => write!(f, "{}", literal),
+            SoqlValue::Null => f.write_str("null"),
+        }
+    }
+}
+
+impl From<&str> for SoqlValue {
+    fn from(value: &str) -> Self {
+        SoqlValue::String(value.to_string())
+    }
+}
+
+impl From<String> for SoqlValue {
+    fn from(value: String) -> Self {
+        SoqlValue::String(value)
+    }
+}
+
+impl From<&String> for SoqlValue {
+    fn from(value: &String) -> Self {
+        SoqlValue::String(value.clone())
+    }
+}
+
+impl From<i32> for SoqlValue {
+    fn from(value: i32) -> Self {
+        SoqlValue::Integer(value.into())
+    }
+}
+
+impl From<i64> for SoqlValue {
+    fn from(value: i64) -> Self {
+        SoqlValue::Integer(value)
+    }
+}
+
+impl From<u32> for SoqlValue {
+    fn from(value: u32) -> Self {
+        SoqlValue::Integer(value.into())
+    }
+}
+
+impl From<f64> for SoqlValue {
+    fn from(value: f64) -> Self {
+        SoqlValue::Decimal(value)
+    }
+}
+
+impl From<bool> for SoqlValue {
+    fn from(value: bool) -> Self {
+        SoqlValue::Boolean(value)
+    }
+}
+
+impl From<NaiveDate> for SoqlValue {
+    fn from(value: NaiveDate) -> Self {
+        SoqlValue::Date(value)
+    }
+}
+
+impl From<DateTime<Utc>> for SoqlValue {
+    fn from(value: DateTime<Utc>) -> Self {
+        SoqlValue::DateTime(value)
+    }
+}
+
+impl From<DateLiteral> for SoqlValue {
+    fn from(value: DateLiteral) -> Self {
+        SoqlValue::DateLiteral(value)
+    }
+}
+
+/// SOQL relative date literal
+///
+/// Parameterized variants take the `n` of e.g. `LAST_N_DAYS:n`, which must
+/// be at least 1.
+///
+/// # Example
+/// ```
+/// use salesforce_client::soql::DateLiteral;
+///
+/// assert_eq!(DateLiteral::LastNDays(30).to_string(), "LAST_N_DAYS:30");
+/// assert_eq!(DateLiteral::ThisFiscalYear.to_string(), "THIS_FISCAL_YEAR");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateLiteral {
+    Yesterday,
+    Today,
+    Tomorrow,
+    LastWeek,
+    ThisWeek,
+    NextWeek,
+    LastMonth,
+    ThisMonth,
+    NextMonth,
+    Last90Days,
+    Next90Days,
+    LastQuarter,
+    ThisQuarter,
+    NextQuarter,
+    LastYear,
+    ThisYear,
+    NextYear,
+    LastFiscalQuarter,
+    ThisFiscalQuarter,
+    NextFiscalQuarter,
+    LastFiscalYear,
+    ThisFiscalYear,
+    NextFiscalYear,
+    LastNDays(u32),
+    NextNDays(u32),
+    NDaysAgo(u32),
+    LastNWeeks(u32),
+    NextNWeeks(u32),
+    NWeeksAgo(u32),
+    LastNMonths(u32),
+    NextNMonths(u32),
+    NMonthsAgo(u32),
+    LastNQuarters(u32),
+    NextNQuarters(u32),
+    NQuartersAgo(u32),
+    LastNYears(u32),
+    NextNYears(u32),
+    NYearsAgo(u32),
+    LastNFiscalQuarters(u32),
+    NextNFiscalQuarters(u32),
+    NFiscalQuartersAgo(u32),
+    LastNFiscalYears(u32),
+    NextNFiscalYears(u32),
+    NFiscalYearsAgo(u32),
+}
+
+impl DateLiteral {
+    /// SOQL keyword, and `n` for parameterized literals
+    fn parts(self) -> (&'static str, Option<u32>) {
+        use DateLiteral::*;
+
+        match self {
+            Yesterday => ("YESTERDAY", None),
+            Today => ("TODAY", None),
+            Tomorrow => ("TOMORROW", None),
+            LastWeek => ("LAST_WEEK", None),
+            ThisWeek => ("THIS_WEEK", None),
+            NextWeek => ("NEXT_WEEK", None),
+            LastMonth => ("LAST_MONTH", None),
+            ThisMonth => ("THIS_MONTH", None),
+            NextMonth => ("NEXT_MONTH", None),
+            Last90Days => ("LAST_90_DAYS", None),
+            Next90Days => ("NEXT_90_DAYS", None),
+            LastQuarter => ("LAST_QUARTER", None),
+            ThisQuarter => ("THIS_QUARTER", None),
+            NextQuarter => ("NEXT_QUARTER", None),
+            LastYear => ("LAST_YEAR", None),
+            ThisYear => ("THIS_YEAR", None),
+            NextYear => ("NEXT_YEAR", None),
+            LastFiscalQuarter => ("LAST_FISCAL_QUARTER", None),
+            ThisFiscalQuarter => ("THIS_FISCAL_QUARTER", None),
+            NextFiscalQuarter => ("NEXT_FISCAL_QUARTER", None),
+            LastFiscalYear => ("LAST_FISCAL_YEAR", None),
+            ThisFiscalYear => ("THIS_FISCAL_YEAR", None),
+            NextFiscalYear => ("NEXT_FISCAL_YEAR", None),
+            LastNDays(n) => ("LAST_N_DAYS", Some(n)),
+            NextNDays(n) => ("NEXT_N_DAYS", Some(n)),
+            NDaysAgo(n) => ("N_DAYS_AGO", Some(n)),
+            LastNWeeks(n) => ("LAST_N_WEEKS", Some(n)),
+            NextNWeeks(n) => ("NEXT_N_WEEKS", Some(n)),
+            NWeeksAgo(n) => ("N_WEEKS_AGO", Some(n)),
+            LastNMonths(n) => ("LAST_N_MONTHS", Some(n)),
+            NextNMonths(n) => ("NEXT_N_MONTHS", Some(n)),
+            NMonthsAgo(n) => ("N_MONTHS_AGO", Some(n)),
+            LastNQuarters(n) => ("LAST_N_QUARTERS", Some(n)),
+            NextNQuarters(n) => ("NEXT_N_QUARTERS", Some(n)),
+            NQuartersAgo(n) => ("N_QUARTERS_AGO", Some(n)),
+            LastNYears(n) => ("LAST_N_YEARS", Some(n)),
+            NextNYears(n) => ("NEXT_N_YEARS", Some(n)),
+            NYearsAgo(n) => ("N_YEARS_AGO", Some(n)),
+            LastNFiscalQuarters(n) => ("LAST_N_FISCAL_QUARTERS", Some(n)),
+            NextNFiscalQuarters(n) => ("NEXT_N_FISCAL_QUARTERS", Some(n)),
+            NFiscalQuartersAgo(n) => ("N_FISCAL_QUARTERS_AGO", Some(n)),
+            LastNFiscalYears(n) => ("LAST_N_FISCAL_YEARS", Some(n)),
+            NextNFiscalYears(n) => ("NEXT_N_FISCAL_YEARS", Some(n)),
+            NFiscalYearsAgo(n) => ("N_FISCAL_YEARS_AGO", Some(n)),
+        }
+    }
+
+    fn validate(self) -> SfResult<()> {
+        match self.parts() {
+            (keyword, Some(0)) => Err(SfError::InvalidQuery(format!(
+                "{} requires n of at least 1",
+                keyword
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl fmt::Display for DateLiteral {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.parts() {
+            (keyword, Some(n)) => write!(f, "{}:{}", keyword, n),
+            (keyword, None) => f.write_str(keyword),
+        }
+    }
+}
+
+/// A WHERE condition with typed values
+///
+/// # Example
+/// ```
+/// use salesforce_client::{Condition, QueryBuilder};
+///
+/// let query = QueryBuilder::select(&["Id"])
+///     .from("Account")
+///     .where_cond(Condition::eq("Name", "O'Brien & Sons"))
+///     .and_cond(Condition::gte("NumberOfEmployees", 50))
+///     .build();
+///
+/// assert_eq!(
+///     query,
+///     r"SELECT Id FROM Account WHERE Name = 'O\'Brien & Sons' AND NumberOfEmployees >= 50"
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Condition {
+    expr: Expr,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Raw(String),
+    Compare {
+        field: String,
+        op: &'static str,
+        value: SoqlValue,
+    },
+    List {
+        field: String,
+        op: &'static str,
+        values: Vec<SoqlValue>,
+    },
+    SemiJoin {
+        field: String,
+        query: Box<QueryBuilder<Complete>>,
+    },
+    Group(ConditionGroup),
+}
+
+impl Condition {
+    fn compare(field: impl Into<String>, op: &'static str, value: impl Into<SoqlValue>) -> Self {
+        Self {
+            expr: Expr::Compare {
+                field: field.into(),
+                op,
+                value: value.into(),
+            },
+        }
+    }
+
+    fn list<V: Into<SoqlValue>>(
+        field: impl Into<String>,
+        op: &'static str,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        Self {
+            expr: Expr::List {
+                field: field.into(),
+                op,
+                values: values.into_iter().map(Into::into).collect(),
+            },
+        }
+    }
+
+    /// A condition written as raw SOQL, used as-is
+    pub fn raw(condition: impl Into<String>) -> Self {
+        Self {
+            expr: Expr::Raw(condition.into()),
+        }
+    }
+
+    /// `field = value`
+    pub fn eq(field: impl Into<String>, value: impl Into<SoqlValue>) -> Self {
+        Self::compare(field, "=", value)
+    }
+
+    /// `field != value`
+    pub fn ne(field: impl Into<String>, value: impl Into<SoqlValue>) -> Self {
+        Self::compare(field, "!=", value)
+    }
+
+    /// `field < value`
+    pub fn lt(field: impl Into<String>, value: impl Into<SoqlValue>) -> Self {
+        Self::compare(field, "<", value)
+    }
+
+    /// `field <= value`
+    pub fn lte(field: impl Into<String>, value: impl Into<SoqlValue>) -> Self {
+        Self::compare(field, "<=", value)
+    }
+
+    /// `field > value`
+    pub fn gt(field: impl Into<String>, value: impl Into<SoqlValue>) -> Self {
+        Self::compare(field, ">", value)
+    }
+
+    /// `field >= value`
+    pub fn gte(field: impl Into<String>, value: impl Into<SoqlValue>) -> Self {
+        Self::compare(field, ">=", value)
+    }
+
+    /// `field LIKE pattern`; `%` and `_` in the pattern act as wildcards
+    pub fn like(field: impl Into<String>, pattern: impl Into<String>) -> Self {
+        Self::compare(field, "LIKE", SoqlValue::String(pattern.into()))
+    }
+
+    /// `field = null`
+    pub fn is_null(field: impl Into<String>) -> Self {
+        Self::compare(field, "=", SoqlValue::Null)
+    }
+
+    /// `field != null`
+    pub fn not_null(field: impl Into<String>) -> Self {
+        Self::compare(field, "!=", SoqlValue::Null)
+    }
+
+    /// `field IN (values)`; an empty list fails [`QueryBuilder::try_build`](crate::QueryBuilder::try_build)
+    pub fn in_list<V: Into<SoqlValue>>(
+        field: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        Self::list(field, "IN", values)
+    }
+
+    /// `field IN (ids)` with each value quoted as a record ID
+    pub fn in_ids<V: Into<String>>(
+        field: impl Into<String>,
+        ids: impl IntoIterator<Item = V>,
+    ) -> Self {
+        Self::list(field, "IN", ids.into_iter().map(SoqlValue::id))
+    }
+
+    /// `field NOT IN (values)`
+    pub fn not_in<V: Into<SoqlValue>>(
+        field: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        Self::list(field, "NOT IN", values)
+    }
+
+    /// `field IN (SELECT ...)`, a semi-join on another object
+    pub fn in_subquery(field: impl Into<String>, query: QueryBuilder<Complete>) -> Self {
+        Self {
+            expr: Expr::SemiJoin {
+                field: field.into(),
+                query: Box::new(query),
+            },
+        }
+    }
+
+    /// `field INCLUDES (values)`, matching multi-select picklists containing any value
+    pub fn includes<V: Into<String>>(
+        field: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        Self::list(
+            field,
+            "INCLUDES",
+            values.into_iter().map(|v| SoqlValue::String(v.into())),
+        )
+    }
+
+    /// `field EXCLUDES (values)`
+    pub fn excludes<V: Into<String>>(
+        field: impl Into<String>,
+        values: impl IntoIterator<Item = V>,
+    ) -> Self {
+        Self::list(
+            field,
+            "EXCLUDES",
+            values.into_iter().map(|v| SoqlValue::String(v.into())),
+        )
+    }
+
+    /// Check the condition can be sent to Salesforce
+    pub(crate) fn validate(&self) -> SfResult<()> {
+        match &self.expr {
+            Expr::Raw(_) => Ok(()),
+            Expr::Compare { value, .. } => value.validate(),
+            Expr::List { field, op, values } if values.is_empty() => Err(SfError::InvalidQuery(
+                format!("{} {} requires at least one value", field, op),
+            )),
+            Expr::List { values, .. } => values.iter().try_for_each(SoqlValue::validate),
+            Expr::SemiJoin { query, .. } => query.validate_subquery(),
+            Expr::Group(group) => group.validate(),
+        }
+    }
+
+    /// The condition as one of several ANDed together
+    ///
+    /// Raw SOQL with a top-level OR is parenthesized, since AND binds
+    /// tighter: `A OR B` joined with `C` must render as `(A OR B) AND C`.
+    pub(crate) fn to_operand(&self) -> String {
+        match &self.expr {
+            Expr::Raw(condition) if has_top_level_or(condition) => format!("({})", condition),
+            _ => self.to_string(),
+        }
+    }
+
+    pub(crate) fn is_subquery(&self) -> bool {
+        match &self.expr {
+            Expr::SemiJoin { .. } => true,
+            Expr::Group(group) => group.members.iter().any(|(_, c)| c.is_subquery()),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Condition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.expr {
+            Expr::Raw(condition) => f.write_str(condition),
+            Expr::Compare { field, op, value } => write!(f, "{} {} {}", field, op, value),
+            Expr::List { field, op, values } => {
+                let values: Vec<_> = values.iter().map(ToString::to_string).collect();
+                write!(f, "{} {} ({})", field, op, values.join(", "))
+            }
+            Expr::SemiJoin { field, query } => write!(f, "{} IN ({})", field, query.render()),
+            Expr::Group(group) => write!(f, "{}", group),
+        }
+    }
+}
+
+/// Whether raw SOQL has an OR outside string literals and parentheses
+fn has_top_level_or(condition: &str) -> bool {
+    let mut depth = 0usize;
+    let mut word = String::new();
+    let mut chars = condition.chars();
+
+    while let Some(c) = chars.next() {
+        if c.is_alphanumeric() || c == '_' {
+            word.push(c);
+            continue;
+        }
+        if depth == 0 && word.eq_ignore_ascii_case("OR") {
+            return true;
+        }
+        word.clear();
+
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            '\'' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '\'' => break,
+                        _ => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    depth == 0 && word.eq_ignore_ascii_case("OR")
+}
+
+impl From<String> for Condition {
+    fn from(condition: String) -> Self {
+        Condition::raw(condition)
+    }
+}
+
+impl From<&str> for Condition {
+    fn from(condition: &str) -> Self {
+        Condition::raw(condition)
+    }
+}
+
+impl From<ConditionGroup> for Condition {
+    fn from(group: ConditionGroup) -> Self {
+        Self {
+            expr: Expr::Group(group),
+        }
+    }
+}
+
+/// Conditions joined with AND or OR, rendered in parentheses
+///
+/// A group uses a single connector; to mix AND and OR, nest a group, so
+/// precedence is always explicit. Groups nest anywhere a [`Condition`] is
+/// accepted.
+///
+/// # Example
+/// ```
+/// use salesforce_client::{Condition, ConditionGroup, QueryBuilder};
+///
+/// let query = QueryBuilder::select(&["Id"])
+///     .from("Account")
+///     .where_group(
+///         ConditionGroup::new()
+///             .or(Condition::eq("Industry", "Energy"))
+///             .or(Condition::eq("Industry", "Media")),
+///     )
+///     .and_cond(Condition::gt("AnnualRevenue", 1_000_000))
+///     .build();
+///
+/// assert_eq!(
+///     query,
+///     "SELECT Id FROM Account WHERE (Industry = 'Energy' OR Industry = 'Media') AND AnnualRevenue > 1000000"
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ConditionGroup {
+    members: Vec<(&'static str, Condition)>,
+}
+
+impl ConditionGroup {
+    /// Start an empty group
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a condition or nested group joined with OR
+    pub fn or(self, condition: impl Into<Condition>) -> Self {
+        self.push("OR", condition.into())
+    }
+
+    /// Add a condition or nested group joined with AND
+    pub fn and(self, condition: impl Into<Condition>) -> Self {
+        self.push("AND", condition.into())
+    }
+
+    fn push(mut self, connector: &'static str, condition: Condition) -> Self {
+        self.members.push((connector, condition));
+        self
+    }
+
+    fn validate(&self) -> SfResult<()> {
+        if self.members.is_empty() {
+            return Err(SfError::InvalidQuery(
+                "Condition group is empty".to_string(),
+            ));
+        }
+
+        // The first member's connector is never rendered
+        let mut connectors = self.members.iter().skip(1).map(|(connector, _)| connector);
+        if let Some(first) = connectors.next() {
+            if connectors.any(|connector| connector != first) {
+                return Err(SfError::InvalidQuery(format!(
+                    "Condition group {} mixes AND and OR; nest a group to make precedence explicit",
+                    self
+                )));
+            }
+        }
+
+        self.members
+            .iter()
+            .try_for_each(|(_, condition)| condition.validate())
+    }
+}
+
+impl fmt::Display for ConditionGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("(")?;
+        for (i, (connector, condition)) in self.members.iter().enumerate() {
+            if i > 0 {
+                write!(f, " {} ", connector)?;
+            }
+            if self.members.len() > 1 {
+                f.write_str(&condition.to_operand())?;
+            } else {
+                write!(f, "{}", condition)?;
+            }
+        }
+        f.write_str(")")
+    }
+}
+
+/// Most parent relationships a field path may traverse
+const MAX_RELATIONSHIP_DEPTH: usize = 5;
+
+/// Dotted path to a field on a parent record, e.g. `Account.Owner.Name`
+///
+/// # Example
+/// ```
+/// use salesforce_client::QueryBuilder;
+/// use salesforce_client::soql::FieldPath;
+///
+/// let owner_name = FieldPath::new("Account").child("Owner").field("Name");
+/// assert_eq!(owner_name.to_string(), "Account.Owner.Name");
+///
+/// let query = QueryBuilder::select([owner_name])
+///     .add_fields(&["Id"])
+///     .from("Contact")
+///     .try_build()
+///     .unwrap();
+/// assert_eq!(query, "SELECT Account.Owner.Name, Id FROM Contact");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldPath {
+    relationships: Vec<String>,
+    field: Option<String>,
+}
+
+impl FieldPath {
+    /// Start a path at a relationship of the queried object
+    pub fn new(relationship: impl Into<String>) -> Self {
+        Self {
+            relationships: vec![relationship.into()],
+            field: None,
+        }
+    }
+
+    /// Follow a relationship of the current parent
+    pub fn child(mut self, relationship: impl Into<String>) -> Self {
+        self.relationships.push(relationship.into());
+        self
+    }
+
+    /// End the path at a field of the current parent
+    pub fn field(mut self, field: impl Into<String>) -> Self {
+        self.field = Some(field.into());
+        self
+    }
+
+    /// Number of relationships traversed
+    pub fn depth(&self) -> usize {
+        self.relationships.len()
+    }
+
+    /// Check the path ends in a field, has no empty segments and stays
+    /// within SOQL's limit of 5 relationships
+    pub fn validate(&self) -> SfResult<()> {
+        let Some(field) = &self.field else {
+            return Err(SfError::InvalidQuery(format!(
+                "Field path {} does not end in a field",
+                self
+            )));
+        };
+
+        if field.is_empty() || self.relationships.iter().any(String::is_empty) {
+            return Err(SfError::InvalidQuery(format!(
+                "Field path {:?} has an empty segment",
+                self.to_string()
+            )));
+        }
+
+        if self.depth() > MAX_RELATIONSHIP_DEPTH {
+            return Err(SfError::InvalidQuery(format!(
+                "Field path {} traverses {} relationships; SOQL allows at most {}",
+                self,
+                self.depth(),
+                MAX_RELATIONSHIP_DEPTH
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for FieldPath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let segments: Vec<_> = self
+            .relationships
+            .iter()
+            .chain(&self.field)
+            .map(String::as_str)
+            .collect();
+        f.write_str(&segments.join("."))
+    }
+}
+
+impl From<FieldPath> for String {
+    fn from(path: FieldPath) -> Self {
+        path.to_string()
+    }
+}
+
+/// A field accepted by the query builder: a name or a [`FieldPath`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldRef {
+    name: String,
+    path: Option<FieldPath>,
+}
+
+impl FieldRef {
+    /// The field as written in SOQL
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn validate(&self) -> SfResult<()> {
+        self.path.as_ref().map_or(Ok(()), FieldPath::validate)
+    }
+}
+
+impl fmt::Display for FieldRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl From<&str> for FieldRef {
+    fn from(name: &str) -> Self {
+        name.to_string().into()
+    }
+}
+
+impl From<&&str> for FieldRef {
+    fn from(name: &&str) -> Self {
+        (*name).into()
+    }
+}
+
+impl From<String> for FieldRef {
+    fn from(name: String) -> Self {
+        Self { name, path: None }
+    }
+}
+
+impl From<&String> for FieldRef {
+    fn from(name: &String) -> Self {
+        name.clone().into()
+    }
+}
+
+impl From<FieldPath> for FieldRef {
+    fn from(path: FieldPath) -> Self {
+        Self {
+            name: path.to_string(),
+            path: Some(path),
+        }
+    }
+}
+
+impl From<&FieldPath> for FieldRef {
+    fn from(path: &FieldPath) -> Self {
+        path.clone().into()
+    }
+}
+
+/// Escape text for use inside a quoted SOQL string literal
+///
+/// Backslashes and single quotes are backslash-escaped, and newlines,
+/// carriage returns and tabs become `\n`, `\r` and `\t`.
+///
+/// ```
+/// use salesforce_client::soql::escape_string;
+///
+/// let name = "O'Brien";
+/// let soql = format!("SELECT Id FROM Contact WHERE LastName = '{}'", escape_string(name));
+/// assert_eq!(soql, r"SELECT Id FROM Contact WHERE LastName = 'O\'Brien'");
+/// ```
+pub fn escape_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | '\'' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Escape text and wrap it in single quotes, giving a SOQL string literal
+pub fn quote(value: &str) -> String {
+    format!("'{}'", escape_string(value))
+}
+
+/// Escape text for use inside a quoted LIKE pattern
+///
+/// Like [`escape_string`], and additionally escapes the `%` and `_`
+/// wildcards so they match literally. Add wildcards around the result:
+///
+/// ```
+/// use salesforce_client::soql::escape_like_pattern;
+///
+/// let soql = format!("SELECT Id FROM Account WHERE Name LIKE '{}%'", escape_like_pattern("100%_"));
+/// assert_eq!(soql, r"SELECT Id FROM Account WHERE Name LIKE '100\%\_%'");
+/// ```
+///
+/// The result is already escaped, so pass it to [`Condition::raw`] style
+/// string conditions rather than [`Condition::like`], which escapes its
+/// pattern itself.
+pub fn escape_like_pattern(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in escape_string(value).chars() {
+        if matches!(c, '%' | '_') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_escape_string() {
+        assert_eq!(escape_string("plain"), "plain");
+        assert_eq!(escape_string("O'Brien"), r"O\'Brien");
+        assert_eq!(escape_string(r"C:\temp"), r"C:\\temp");
+        assert_eq!(escape_string(r"\'"), r"\\\'");
+        assert_eq!(escape_string("line one\nline two"), r"line one\nline two");
+        assert_eq!(escape_string("a\r\tb"), r"a\r\tb");
+        assert_eq!(escape_string("Zoë's café ☕"), r"Zoë\'s café ☕");
+        assert_eq!(escape_string("100%_done"), "100%_done");
+        assert_eq!(escape_string(""), "");
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("Acme"), "'Acme'");
+        assert_eq!(quote("it's"), r"'it\'s'");
+        assert_eq!(quote("' OR Name != '"), r"'\' OR Name != \''");
+        assert_eq!(quote(""), "''");
+    }
+
+    #[test]
+    fn test_escape_like_pattern() {
+        assert_eq!(escape_like_pattern("100%"), r"100\%");
+        assert_eq!(escape_like_pattern("first_name"), r"first\_name");
+        assert_eq!(escape_like_pattern(r"a\b'c"), r"a\\b\'c");
+        assert_eq!(escape_like_pattern("naïve_%\n"), r"naïve\_\%\n");
+    }
+
+    #[test]
+    fn test_string_escaping() {
+        assert_eq!(
+            Condition::eq("Name", "O'Brien").to_string(),
+            r"Name = 'O\'Brien'"
+        );
+        assert_eq!(
+            Condition::eq("Path__c", r"C:\temp\'x'").to_string(),
+            r"Path__c = 'C:\\temp\\\'x\''"
+        );
+        assert_eq!(
+            Condition::like("Name", "Acme%").to_string(),
+            "Name LIKE 'Acme%'"
+        );
+    }
+
+    #[test]
+    fn test_value_rendering() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let datetime = Utc.with_ymd_and_hms(2024, 1, 15, 10, 30, 0).unwrap();
+
+        assert_eq!(
+            Condition::gt("CloseDate", date).to_string(),
+            "CloseDate > 2024-01-15"
+        );
+        assert_eq!(
+            Condition::lte("CreatedDate", datetime).to_string(),
+            "CreatedDate <= 2024-01-15T10:30:00Z"
+        );
+        assert_eq!(
+            Condition::eq("IsActive__c", true).to_string(),
+            "IsActive__c = true"
+        );
+        assert_eq!(Condition::ne("Amount", 2.5).to_string(), "Amount != 2.5");
+        assert_eq!(
+            Condition::is_null("ParentId").to_string(),
+            "ParentId = null"
+        );
+        assert_eq!(Condition::not_null("Email").to_string(), "Email != null");
+        assert_eq!(
+            Condition::in_list("Id", [SoqlValue::id("001A"), SoqlValue::id("001B")]).to_string(),
+            "Id IN ('001A', '001B')"
+        );
+        assert_eq!(
+            Condition::in_ids("AccountId", ["001A", "001'B"]).to_string(),
+            r"AccountId IN ('001A', '001\'B')"
+        );
+        assert_eq!(
+            Condition::not_in("NumberOfEmployees", [1, 2]).to_string(),
+            "NumberOfEmployees NOT IN (1, 2)"
+        );
+        assert_eq!(
+            Condition::includes("Regions__c", ["EMEA", "APAC"]).to_string(),
+            "Regions__c INCLUDES ('EMEA', 'APAC')"
+        );
+        assert_eq!(
+            Condition::excludes("Regions__c", ["NA"]).to_string(),
+            "Regions__c EXCLUDES ('NA')"
+        );
+    }
+
+    #[test]
+    fn test_date_literals() {
+        let in_query = |literal: DateLiteral| {
+            crate::QueryBuilder::select(&["Id"])
+                .from("Opportunity")
+                .where_cond(Condition::eq("CloseDate", literal))
+                .try_build()
+                .unwrap()
+        };
+
+        assert_eq!(
+            in_query(DateLiteral::LastNDays(30)),
+            "SELECT Id FROM Opportunity WHERE CloseDate = LAST_N_DAYS:30"
+        );
+        assert_eq!(
+            in_query(DateLiteral::ThisQuarter),
+            "SELECT Id FROM Opportunity WHERE CloseDate = THIS_QUARTER"
+        );
+        assert_eq!(DateLiteral::Yesterday.to_string(), "YESTERDAY");
+        assert_eq!(DateLiteral::LastNMonths(6).to_string(), "LAST_N_MONTHS:6");
+        assert_eq!(
+            DateLiteral::NFiscalYearsAgo(2).to_string(),
+            "N_FISCAL_YEARS_AGO:2"
+        );
+        assert_eq!(
+            Condition::in_list("CloseDate", [DateLiteral::Today, DateLiteral::Tomorrow])
+                .to_string(),
+            "CloseDate IN (TODAY, TOMORROW)"
+        );
+    }
+
+    #[test]
+    fn test_date_literal_rejects_zero() {
+        let result = Condition::gt("CreatedDate", DateLiteral::NextNDays(0)).validate();
+        assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+
+        let result = Condition::in_list("CreatedDate", [DateLiteral::LastNWeeks(0)]).validate();
+        assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn test_condition_groups() {
+        let query = crate::QueryBuilder::select(&["Id"])
+            .from("Account")
+            .where_group(
+                ConditionGroup::new()
+                    .or(Condition::eq("A", 1))
+                    .or(Condition::eq("B", 2)),
+            )
+            .and_cond(Condition::eq("C", 3))
+            .try_build()
+            .unwrap();
+        assert_eq!(
+            query,
+            "SELECT Id FROM Account WHERE (A = 1 OR B = 2) AND C = 3"
+        );
+
+        let nested = ConditionGroup::new()
+            .or(Condition::eq("Status", "Open"))
+            .or(ConditionGroup::new()
+                .and(Condition::eq("Status", "Closed"))
+                .and(
+                    ConditionGroup::new()
+                        .or(Condition::lt("Amount", 100))
+                        .or(Condition::is_null("Amount")),
+                ));
+        let query = crate::QueryBuilder::select(&["Id"])
+            .from("Opportunity")
+            .where_group(nested)
+            .try_build()
+            .unwrap();
+        assert_eq!(
+            query,
+            "SELECT Id FROM Opportunity WHERE (Status = 'Open' OR (Status = 'Closed' AND (Amount < 100 OR Amount = null)))"
+        );
+    }
+
+    #[test]
+    fn test_condition_group_validation() {
+        let empty = Condition::from(ConditionGroup::new()).validate();
+        assert!(matches!(empty, Err(SfError::InvalidQuery(_))));
+
+        let nested_empty =
+            Condition::from(ConditionGroup::new().or("A = 1").or(ConditionGroup::new()));
+        assert!(matches!(
+            nested_empty.validate(),
+            Err(SfError::InvalidQuery(_))
+        ));
+
+        let mixed = Condition::from(ConditionGroup::new().or("A = 1").or("B = 2").and("C = 3"));
+        assert!(matches!(mixed.validate(), Err(SfError::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn test_field_path_rendering() {
+        let path = FieldPath::new("Account").child("Owner").field("Name");
+        assert_eq!(path.to_string(), "Account.Owner.Name");
+        assert_eq!(path.depth(), 2);
+        assert!(path.validate().is_ok());
+
+        let field: FieldRef = path.into();
+        assert_eq!(field.as_str(), "Account.Owner.Name");
+        assert_eq!(FieldRef::from("Id").as_str(), "Id");
+    }
+
+    #[test]
+    fn test_field_path_validation() {
+        let five = FieldPath::new("A")
+            .child("B")
+            .child("C")
+            .child("D")
+            .child("E")
+            .field("Name");
+        assert!(five.validate().is_ok());
+
+        let six = five.clone().child("F");
+        assert!(matches!(six.validate(), Err(SfError::InvalidQuery(_))));
+
+        let empty = FieldPath::new("Account").child("").field("Name");
+        assert!(matches!(empty.validate(), Err(SfError::InvalidQuery(_))));
+
+        let no_field = FieldPath::new("Account").child("Owner");
+        assert!(matches!(no_field.validate(), Err(SfError::InvalidQuery(_))));
+    }
+
+    #[test]
+    fn test_empty_list_is_invalid() {
+        let empty: Vec<String> = Vec::new();
+        let result = Condition::in_list("Id", empty).validate();
+        assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+
+        assert!(Condition::in_list("Id", ["001A"]).validate().is_ok());
+    }
+
+    #[test]
+    fn test_non_finite_decimal_is_invalid() {
+        for value in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let result = crate::QueryBuilder::select(&["Id"])
+                .from("Opportunity")
+                .where_cond(Condition::gt("Amount", value))
+                .try_build();
+            assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+        }
+
+        let result = Condition::in_list("Amount", [1.5, f64::NAN]).validate();
+        assert!(matches!(result, Err(SfError::InvalidQuery(_))));
+
+        assert!(Condition::gt("Amount", 1.5).validate().is_ok());
+    }
+}
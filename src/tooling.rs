@@ -0,0 +1,121 @@
+//! Tooling API access
+//!
+//! Developer metadata such as `ApexClass`, `ApexLog` and `EntityDefinition`
+//! lives under `/services/data/vXX.X/tooling/` rather than the regular data
+//! API. [`ToolingClient`] offers the same query and record operations as
+//! [`SalesforceClient`], sharing its authentication, retry and rate limiting.
+//! Tooling query results are not cached.
+
+use crate::crud::{CrudOperations, InsertResponse};
+use crate::error::SfResult;
+use crate::pagination::PaginatedQuery;
+use crate::{retry, SalesforceClient};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::{info, instrument};
+
+/// Handle for Tooling API requests, from [`SalesforceClient::tooling`]
+///
+/// # Example
+/// ```no_run
+/// # use salesforce_client::{SalesforceClient, ClientConfig, SfError};
+/// # use serde::Deserialize;
+/// #[derive(Deserialize)]
+/// struct ApexClass {
+///     #[serde(rename = "Name")]
+///     name: String,
+/// }
+/// # async fn example() -> Result<(), SfError> {
+/// # let config = ClientConfig::new("https://example.com", "token");
+/// # let client = SalesforceClient::new(config);
+///
+/// let classes: Vec<ApexClass> = client
+///     .tooling()
+///     .query_all("SELECT Name FROM ApexClass")
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct ToolingClient<'a> {
+    client: &'a SalesforceClient,
+    crud: CrudOperations,
+}
+
+impl<'a> ToolingClient<'a> {
+    pub(crate) fn new(client: &'a SalesforceClient) -> Self {
+        Self {
+            client,
+            crud: client.crud.tooling(),
+        }
+    }
+
+    fn query_url(&self) -> String {
+        format!("{}/tooling/query", self.client.config.api_url())
+    }
+
+    /// Execute a Tooling API query, returning the first page of results
+    #[instrument(skip(self, soql))]
+    pub async fn query<T: DeserializeOwned>(&self, soql: impl AsRef<str>) -> SfResult<Vec<T>> {
+        let url = self.query_url();
+        self.client.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.client.config.retry_config, || async {
+            self.client.execute_query_at(&url, soql.as_ref()).await
+        })
+        .await
+    }
+
+    /// Execute a Tooling API query, following `nextRecordsUrl` to the end
+    #[instrument(skip(self, soql))]
+    pub async fn query_all<T: DeserializeOwned>(&self, soql: impl AsRef<str>) -> SfResult<Vec<T>> {
+        let records = self
+            .query_paginated(soql.as_ref())
+            .await?
+            .collect_all()
+            .await?;
+
+        info!("Collected {} total tooling records", records.len());
+        Ok(records)
+    }
+
+    /// Get a paginated iterator over a Tooling API query
+    pub async fn query_paginated<T: DeserializeOwned>(
+        &self,
+        soql: &str,
+    ) -> SfResult<PaginatedQuery<T>> {
+        self.client.paginate_at(&self.query_url(), soql).await
+    }
+
+    /// Retrieve a Tooling API record by ID
+    #[instrument(skip(self))]
+    pub async fn get<T: DeserializeOwned>(&self, sobject: &str, id: &str) -> SfResult<T> {
+        self.client.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.client.config.retry_config, || async {
+            self.crud.get(sobject, id, &[]).await
+        })
+        .await
+    }
+
+    /// Create a Tooling API record, e.g. an `ApexClass` or `TraceFlag`
+    #[instrument(skip(self, data))]
+    pub async fn insert<T: Serialize>(&self, sobject: &str, data: &T) -> SfResult<InsertResponse> {
+        self.client.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.client.config.retry_config, || async {
+            self.crud.insert(sobject, data).await
+        })
+        .await
+    }
+
+    /// Delete a Tooling API record
+    #[instrument(skip(self))]
+    pub async fn delete(&self, sobject: &str, id: &str) -> SfResult<()> {
+        self.client.rate_limiter.acquire().await?;
+
+        retry::with_retry(&self.client.config.retry_config, || async {
+            self.crud.delete(sobject, id).await
+        })
+        .await
+    }
+}